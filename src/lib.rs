@@ -1,11 +1,17 @@
+mod module;
 pub mod runtime;
 pub mod snapshot;
 mod worker;
 
 // Core API
+pub use runtime::bindings::ConsoleMessage;
 pub use runtime::stream_manager::{StreamChunk, StreamManager};
-pub use runtime::{Runtime, run_event_loop};
-pub use worker::Worker;
+pub use runtime::{
+    FetchClient, FetchClientConfig, FetchPolicy, FetchProgress, FetchRecorder, FetchReplayer,
+    FetchResponseMeta, RecordedFetch, Runtime, run_event_loop, run_event_loop_with_policy,
+};
+pub use snapshot::Snapshot;
+pub use worker::{Worker, WorkerOptions};
 
 // Re-export common types from openworkers-core
 pub use openworkers_core::{
@@ -0,0 +1,429 @@
+/// Rewrite module-style exports so the code can be evaluated as a classic script
+///
+/// The default export is captured as `globalThis.__defaultExport`: `export
+/// default <expression>` becomes an assignment, while named `function` and
+/// `class` declarations and `export { name as default }` keep their binding
+/// and are assigned at the end of the script. The `export` keyword is dropped
+/// from top-level declarations and other export clauses are removed. Only
+/// top-level code is rewritten, never strings, template literals, regular
+/// expressions or comments. Imports and re-exports (`export ... from`) are
+/// not supported. Scripts without exports are returned unchanged.
+pub(crate) fn module_to_script(code: &str) -> String {
+    const DECLARATIONS: [&str; 6] = ["const", "let", "var", "function", "async", "class"];
+
+    let tokens: Vec<Token> = Lexer::new(code)
+        .filter(|token| token.kind != TokenKind::Trivia)
+        .collect();
+    let text = |index: usize| tokens.get(index).map(|token| &code[token.start..token.end]);
+
+    let mut output = String::with_capacity(code.len());
+    let mut copied = 0;
+    let mut assignments = Vec::new();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let is_export = tokens[i].kind == TokenKind::Word
+            && tokens[i].depth == 0
+            && text(i) == Some("export")
+            && (i == 0 || text(i - 1) != Some("."));
+
+        if !is_export {
+            i += 1;
+            continue;
+        }
+
+        match text(i + 1) {
+            Some("default") => {
+                // Named function/class declarations keep their binding
+                let declaration = match (text(i + 2), text(i + 3)) {
+                    (Some("async"), Some("function")) => Some(i + 4),
+                    (Some("function"), _) | (Some("class"), _) => Some(i + 3),
+                    _ => None,
+                };
+                let name = declaration
+                    .map(|name| {
+                        if text(name) == Some("*") {
+                            name + 1
+                        } else {
+                            name
+                        }
+                    })
+                    .filter(|&name| {
+                        tokens.get(name).is_some_and(|t| t.kind == TokenKind::Word)
+                            && text(name) != Some("extends")
+                    });
+
+                output.push_str(&code[copied..tokens[i].start]);
+                match name {
+                    Some(name) => {
+                        assignments.push(format!(
+                            "globalThis.__defaultExport = {};",
+                            text(name).unwrap()
+                        ));
+                        copied = tokens[i + 2].start;
+                    }
+                    None => {
+                        output.push_str("globalThis.__defaultExport =");
+                        copied = tokens[i + 1].end;
+                    }
+                }
+                i += 2;
+            }
+            Some("{") => {
+                let Some(close) = (i + 2..tokens.len()).find(|&j| text(j) == Some("}")) else {
+                    break;
+                };
+
+                // `export { ... } from '...'` is left as-is
+                if text(close + 1) == Some("from") {
+                    i = close + 1;
+                    continue;
+                }
+
+                // Specifiers: `name` or `name as exported`
+                let mut specifier = Vec::new();
+                for j in i + 2..=close {
+                    if text(j) == Some(",") || j == close {
+                        if let [local, "as", "default"] = specifier[..] {
+                            assignments.push(format!("globalThis.__defaultExport = {};", local));
+                        }
+                        specifier.clear();
+                    } else if let Some(word) = text(j) {
+                        specifier.push(word);
+                    }
+                }
+
+                let end = if text(close + 1) == Some(";") {
+                    close + 1
+                } else {
+                    close
+                };
+                output.push_str(&code[copied..tokens[i].start]);
+                copied = tokens[end].end;
+                i = end + 1;
+            }
+            Some(keyword) if DECLARATIONS.contains(&keyword) => {
+                output.push_str(&code[copied..tokens[i].start]);
+                copied = tokens[i + 1].start;
+                i += 1;
+            }
+            _ => i += 1,
+        }
+    }
+
+    output.push_str(&code[copied..]);
+    for assignment in assignments {
+        output.push('\n');
+        output.push_str(&assignment);
+    }
+    output
+}
+
+/// Keywords after which `/` starts a regular expression rather than a division
+const REGEX_KEYWORDS: [&str; 14] = [
+    "return",
+    "typeof",
+    "instanceof",
+    "in",
+    "of",
+    "new",
+    "delete",
+    "void",
+    "throw",
+    "case",
+    "do",
+    "else",
+    "yield",
+    "await",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    /// Identifier, keyword or number
+    Word,
+    /// Single punctuation character
+    Punct,
+    /// String, template literal (or part of one) or regular expression
+    Literal,
+    /// Whitespace or comment
+    Trivia,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token {
+    kind: TokenKind,
+    start: usize,
+    end: usize,
+    /// Enclosing braces (blocks, objects and template substitutions)
+    depth: usize,
+}
+
+/// Minimal JavaScript lexer: just enough to tell code from strings,
+/// templates, regular expressions and comments, and to track brace depth
+struct Lexer<'a> {
+    src: &'a [u8],
+    code: &'a str,
+    pos: usize,
+    /// One entry per open brace: true for a template `${` substitution
+    braces: Vec<bool>,
+    /// Last non-trivia token
+    prev: Option<Token>,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(code: &'a str) -> Self {
+        Self {
+            src: code.as_bytes(),
+            code,
+            pos: 0,
+            braces: Vec::new(),
+            prev: None,
+        }
+    }
+
+    fn peek(&self, offset: usize) -> Option<u8> {
+        self.src.get(self.pos + offset).copied()
+    }
+
+    fn is_word_byte(byte: u8) -> bool {
+        byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'$' || byte >= 0x80
+    }
+
+    /// Whether a `/` here starts a regular expression
+    fn regex_allowed(&self) -> bool {
+        match self.prev {
+            None => true,
+            Some(token) => match token.kind {
+                TokenKind::Punct => !matches!(self.src[token.start], b')' | b']'),
+                TokenKind::Word => REGEX_KEYWORDS.contains(&&self.code[token.start..token.end]),
+                TokenKind::Literal | TokenKind::Trivia => false,
+            },
+        }
+    }
+
+    fn skip_string(&mut self, quote: u8) {
+        self.pos += 1;
+        while let Some(byte) = self.peek(0) {
+            match byte {
+                b'\\' => self.pos += 2,
+                b'\n' => return,
+                _ if byte == quote => {
+                    self.pos += 1;
+                    return;
+                }
+                _ => self.pos += 1,
+            }
+        }
+    }
+
+    /// Template characters up to the closing backtick or a `${`
+    fn skip_template(&mut self) {
+        while let Some(byte) = self.peek(0) {
+            match byte {
+                b'\\' => self.pos += 2,
+                b'`' => {
+                    self.pos += 1;
+                    return;
+                }
+                b'$' if self.peek(1) == Some(b'{') => {
+                    self.pos += 2;
+                    self.braces.push(true);
+                    return;
+                }
+                _ => self.pos += 1,
+            }
+        }
+    }
+
+    /// Regular expression body up to the closing `/`, then its flags
+    fn skip_regex(&mut self) {
+        self.pos += 1;
+        let mut in_class = false;
+        while let Some(byte) = self.peek(0) {
+            match byte {
+                b'\\' => self.pos += 2,
+                b'\n' => return,
+                b'/' if !in_class => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => {
+                    in_class = match byte {
+                        b'[' => true,
+                        b']' => false,
+                        _ => in_class,
+                    };
+                    self.pos += 1;
+                }
+            }
+        }
+        while self.peek(0).is_some_and(Self::is_word_byte) {
+            self.pos += 1;
+        }
+    }
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        let start = self.pos;
+        let depth = self.braces.len();
+        let byte = self.peek(0)?;
+
+        let kind = match (byte, self.peek(1)) {
+            (b' ' | b'\t' | b'\n' | b'\r', _) => {
+                while matches!(self.peek(0), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+                    self.pos += 1;
+                }
+                TokenKind::Trivia
+            }
+            (b'/', Some(b'/')) => {
+                while self.peek(0).is_some_and(|byte| byte != b'\n') {
+                    self.pos += 1;
+                }
+                TokenKind::Trivia
+            }
+            (b'/', Some(b'*')) => {
+                self.pos = self.code[start + 2..]
+                    .find("*/")
+                    .map_or(self.src.len(), |end| start + 2 + end + 2);
+                TokenKind::Trivia
+            }
+            (b'\'' | b'"', _) => {
+                self.skip_string(byte);
+                TokenKind::Literal
+            }
+            (b'`', _) => {
+                self.pos += 1;
+                self.skip_template();
+                TokenKind::Literal
+            }
+            (b'/', _) if self.regex_allowed() => {
+                self.skip_regex();
+                TokenKind::Literal
+            }
+            (b'{', _) => {
+                self.pos += 1;
+                self.braces.push(false);
+                TokenKind::Punct
+            }
+            (b'}', _) => {
+                self.pos += 1;
+                if self.braces.pop() == Some(true) {
+                    self.skip_template();
+                    TokenKind::Literal
+                } else {
+                    TokenKind::Punct
+                }
+            }
+            _ if Self::is_word_byte(byte) => {
+                while self.peek(0).is_some_and(Self::is_word_byte) {
+                    self.pos += 1;
+                }
+                TokenKind::Word
+            }
+            _ => {
+                self.pos += 1;
+                TokenKind::Punct
+            }
+        };
+
+        // Escapes and unterminated literals may step past the end
+        self.pos = self.pos.min(self.src.len());
+
+        let token = Token {
+            kind,
+            start,
+            end: self.pos,
+            depth,
+        };
+        if kind != TokenKind::Trivia {
+            self.prev = Some(token);
+        }
+        Some(token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_without_exports_is_unchanged() {
+        let code = "addEventListener('fetch', (e) => e.respondWith(new Response('ok')));";
+        assert_eq!(module_to_script(code), code);
+    }
+
+    #[test]
+    fn test_export_default_expression() {
+        assert_eq!(
+            module_to_script("export default { fetch() {} };"),
+            "globalThis.__defaultExport = { fetch() {} };"
+        );
+    }
+
+    #[test]
+    fn test_export_default_named_declarations() {
+        assert_eq!(
+            module_to_script("export default async function handler() {}"),
+            "async function handler() {}\nglobalThis.__defaultExport = handler;"
+        );
+        assert_eq!(
+            module_to_script("export default class Worker extends Base {}"),
+            "class Worker extends Base {}\nglobalThis.__defaultExport = Worker;"
+        );
+        assert_eq!(
+            module_to_script("export default class extends Base {}"),
+            "globalThis.__defaultExport = class extends Base {}"
+        );
+    }
+
+    #[test]
+    fn test_export_declarations_and_clauses() {
+        assert_eq!(
+            module_to_script("export const a = 1;\nconst b = 2;\nexport { b as default, a };"),
+            "const a = 1;\nconst b = 2;\n\nglobalThis.__defaultExport = b;"
+        );
+    }
+
+    #[test]
+    fn test_exports_in_strings_are_kept() {
+        let code = r#"const s = 'export default 1'; const d = "export { x }";"#;
+        assert_eq!(module_to_script(code), code);
+    }
+
+    #[test]
+    fn test_exports_in_templates_are_kept() {
+        let code = "const t = `export default ${ { a: `export const b` }.a } }`;";
+        assert_eq!(module_to_script(code), code);
+
+        assert_eq!(
+            module_to_script("const t = `${x}`;\nexport default t;"),
+            "const t = `${x}`;\nglobalThis.__defaultExport = t;"
+        );
+    }
+
+    #[test]
+    fn test_exports_in_regexes_are_kept() {
+        let code = "const r = /export default [/}]/g; const q = a / b / c;";
+        assert_eq!(module_to_script(code), code);
+
+        assert_eq!(
+            module_to_script("if (/}/.test(s)) {}\nexport default s;"),
+            "if (/}/.test(s)) {}\nglobalThis.__defaultExport = s;"
+        );
+    }
+
+    #[test]
+    fn test_exports_in_comments_are_kept() {
+        let code = "// export default 1\n/* export { a as default } */\nconst a = 1;";
+        assert_eq!(module_to_script(code), code);
+    }
+
+    #[test]
+    fn test_nested_and_member_exports_are_kept() {
+        let code = "function f() { const export_ = 1; return module.export; }\nobj.export = 1;";
+        assert_eq!(module_to_script(code), code);
+    }
+}
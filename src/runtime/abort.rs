@@ -0,0 +1,93 @@
+/// AbortController and AbortSignal implementation (WHATWG DOM spec)
+///
+/// AbortSignal is an EventTarget, so this runs after the event-target prelude.
+pub const ABORT_JS: &str = r#"
+    globalThis.AbortSignal = class AbortSignal extends EventTarget {
+        constructor() {
+            super();
+            this.aborted = false;
+            this.reason = undefined;
+            this.onabort = null;
+        }
+
+        throwIfAborted() {
+            if (this.aborted) {
+                throw this.reason;
+            }
+        }
+
+        // Internal: mark as aborted and notify listeners
+        _abort(reason) {
+            if (this.aborted) {
+                return;
+            }
+
+            this.aborted = true;
+            this.reason = reason === undefined ? AbortSignal._error('AbortError', 'This operation was aborted') : reason;
+
+            this._dispatchWithHandler(new Event('abort'));
+        }
+
+        static _error(name, message) {
+            const error = new Error(message);
+            error.name = name;
+            return error;
+        }
+
+        static abort(reason) {
+            const signal = new AbortSignal();
+            signal._abort(reason);
+            return signal;
+        }
+
+        static timeout(ms) {
+            ms = Number(ms);
+            if (!Number.isFinite(ms) || ms < 0) {
+                throw new TypeError('AbortSignal.timeout requires a non-negative number of milliseconds');
+            }
+
+            const signal = new AbortSignal();
+            setTimeout(() => signal._abort(AbortSignal._error('TimeoutError', 'The operation timed out')), ms);
+            return signal;
+        }
+
+        // Abort as soon as any of the signals aborts, with that signal's reason
+        static any(signals) {
+            signals = Array.from(signals);
+            for (const source of signals) {
+                if (!(source instanceof AbortSignal)) {
+                    throw new TypeError('AbortSignal.any requires an iterable of AbortSignal');
+                }
+            }
+
+            const signal = new AbortSignal();
+            const alreadyAborted = signals.find(source => source.aborted);
+            if (alreadyAborted) {
+                signal._abort(alreadyAborted.reason);
+                return signal;
+            }
+
+            const listeners = signals.map(source => {
+                const listener = () => {
+                    // Stop listening to the other sources
+                    signals.forEach((other, i) => other.removeEventListener('abort', listeners[i]));
+                    signal._abort(source.reason);
+                };
+                source.addEventListener('abort', listener, { once: true });
+                return listener;
+            });
+
+            return signal;
+        }
+    };
+
+    globalThis.AbortController = class AbortController {
+        constructor() {
+            this.signal = new AbortSignal();
+        }
+
+        abort(reason) {
+            this.signal._abort(reason);
+        }
+    };
+"#;
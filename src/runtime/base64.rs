@@ -1,18 +1,36 @@
-use rusty_jsc::JSContext;
+use super::typed_array::js_value_bytes_mut;
+use rusty_jsc::{JSContext, JSValue};
 
-/// Setup atob/btoa (Base64 encoding/decoding)
-pub fn setup_base64(context: &mut JSContext) {
-    let code = r#"
+/// atob/btoa implementation (Base64 encoding/decoding, HTML spec)
+pub const BASE64_JS: &str = r#"
         // Base64 encoding/decoding (atob/btoa)
         const BASE64_CHARS = 'ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/';
 
-        globalThis.btoa = function(str) {
-            const bytes = typeof str === 'string'
-                ? new TextEncoder().encode(str)
-                : new Uint8Array(str);
+        // DOMException-like error thrown for invalid input
+        const invalidCharacterError = (message) => {
+            const error = new Error(message);
+            error.name = 'InvalidCharacterError';
+            error.code = 5;
+            return error;
+        };
+
+        // btoa encodes a binary string: every code unit must fit in one byte
+        globalThis.btoa = function(data) {
+            const str = String(data);
+            const len = str.length;
+            const bytes = new Uint8Array(len);
+
+            for (let i = 0; i < len; i++) {
+                const code = str.charCodeAt(i);
+                if (code > 0xFF) {
+                    throw invalidCharacterError(
+                        "Failed to execute 'btoa': The string to be encoded contains characters outside of the Latin1 range."
+                    );
+                }
+                bytes[i] = code;
+            }
 
             let result = '';
-            const len = bytes.length;
 
             for (let i = 0; i < len; i += 3) {
                 const b1 = bytes[i];
@@ -28,43 +46,440 @@ pub fn setup_base64(context: &mut JSContext) {
             return result;
         };
 
-        globalThis.atob = function(base64) {
-            // Remove whitespace and validate
-            base64 = base64.replace(/\s/g, '');
+        // atob decodes to a binary string (forgiving-base64 decode)
+        globalThis.atob = function(data) {
+            const invalid = () => invalidCharacterError(
+                "Failed to execute 'atob': The string to be decoded is not correctly encoded."
+            );
 
-            const len = base64.length;
-            if (len % 4 !== 0) {
-                throw new Error('Invalid base64 string');
+            // Remove ASCII whitespace, then up to two '=' when padded to a full quantum
+            let base64 = String(data).replace(/[\t\n\f\r ]/g, '');
+            if (base64.length % 4 === 0) {
+                base64 = base64.replace(/={1,2}$/, '');
             }
 
-            // Calculate output length
-            let outputLen = (len / 4) * 3;
-            if (base64[len - 1] === '=') outputLen--;
-            if (base64[len - 2] === '=') outputLen--;
+            if (base64.length % 4 === 1 || !/^[A-Za-z0-9+/]*$/.test(base64)) {
+                throw invalid();
+            }
 
-            const bytes = new Uint8Array(outputLen);
-            let p = 0;
+            let result = '';
+            let buffer = 0;
+            let bits = 0;
 
-            for (let i = 0; i < len; i += 4) {
-                const c1 = BASE64_CHARS.indexOf(base64[i]);
-                const c2 = BASE64_CHARS.indexOf(base64[i + 1]);
-                const c3 = base64[i + 2] === '=' ? 0 : BASE64_CHARS.indexOf(base64[i + 2]);
-                const c4 = base64[i + 3] === '=' ? 0 : BASE64_CHARS.indexOf(base64[i + 3]);
+            for (let i = 0; i < base64.length; i++) {
+                buffer = (buffer << 6) | BASE64_CHARS.indexOf(base64[i]);
+                bits += 6;
 
-                if (c1 === -1 || c2 === -1 || (base64[i + 2] !== '=' && c3 === -1) || (base64[i + 3] !== '=' && c4 === -1)) {
-                    throw new Error('Invalid base64 character');
+                if (bits >= 8) {
+                    bits -= 8;
+                    result += String.fromCharCode((buffer >> bits) & 0xFF);
                 }
-
-                bytes[p++] = (c1 << 2) | (c2 >> 4);
-                if (base64[i + 2] !== '=') bytes[p++] = ((c2 & 15) << 4) | (c3 >> 2);
-                if (base64[i + 3] !== '=') bytes[p++] = ((c3 & 3) << 6) | c4;
             }
 
-            return new TextDecoder().decode(bytes);
+            return result;
         };
     "#;
 
+/// How `Uint8Array.fromBase64` treats a final chunk shorter than 4 characters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LastChunkHandling {
+    /// Accept a missing padding (default)
+    Loose,
+    /// Require padding and zero overflow bits
+    Strict,
+    /// Stop before a final unpadded chunk
+    StopBeforePartial,
+}
+
+impl std::str::FromStr for LastChunkHandling {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "loose" => Ok(LastChunkHandling::Loose),
+            "strict" => Ok(LastChunkHandling::Strict),
+            "stop-before-partial" => Ok(LastChunkHandling::StopBeforePartial),
+            _ => Err(format!("Invalid lastChunkHandling: {}", s)),
+        }
+    }
+}
+
+const STANDARD_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const URL_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Encode bytes as base64 (or base64url)
+pub fn encode_base64(bytes: &[u8], url: bool, omit_padding: bool) -> String {
+    let alphabet = if url { URL_ALPHABET } else { STANDARD_ALPHABET };
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let sextets = [
+            b[0] >> 2,
+            ((b[0] & 0x03) << 4) | (b[1] >> 4),
+            ((b[1] & 0x0F) << 2) | (b[2] >> 6),
+            b[2] & 0x3F,
+        ];
+
+        for (i, sextet) in sextets.iter().enumerate() {
+            if i <= chunk.len() {
+                out.push(alphabet[*sextet as usize] as char);
+            } else if !omit_padding {
+                out.push('=');
+            }
+        }
+    }
+
+    out
+}
+
+/// Decode base64 (or base64url), skipping ASCII whitespace
+pub fn decode_base64(
+    input: &str,
+    url: bool,
+    last_chunk: LastChunkHandling,
+) -> Result<Vec<u8>, String> {
+    let alphabet = if url { URL_ALPHABET } else { STANDARD_ALPHABET };
+    let is_whitespace = |c: u8| matches!(c, b'\t' | b'\n' | b'\x0C' | b'\r' | b' ');
+
+    let mut out = Vec::with_capacity(input.len() / 4 * 3);
+    let mut chunk = Vec::with_capacity(4);
+    let mut chars = input.bytes().filter(|c| !is_whitespace(*c)).peekable();
+
+    while let Some(c) = chars.next() {
+        if c == b'=' {
+            if chunk.len() < 2 {
+                return Err("Unexpected padding in base64 string".to_string());
+            }
+
+            // Two sextets need a second '='
+            if chunk.len() == 2 && chars.next_if_eq(&b'=').is_none() {
+                if last_chunk == LastChunkHandling::StopBeforePartial {
+                    return Ok(out);
+                }
+                return Err("Incomplete padding in base64 string".to_string());
+            }
+
+            if chars.next().is_some() {
+                return Err("Unexpected data after base64 padding".to_string());
+            }
+
+            decode_final_chunk(&chunk, last_chunk == LastChunkHandling::Strict, &mut out)?;
+            return Ok(out);
+        }
+
+        let sextet = alphabet
+            .iter()
+            .position(|&a| a == c)
+            .ok_or_else(|| "Invalid base64 character".to_string())?;
+        chunk.push(sextet as u8);
+
+        if chunk.len() == 4 {
+            out.push((chunk[0] << 2) | (chunk[1] >> 4));
+            out.push((chunk[1] << 4) | (chunk[2] >> 2));
+            out.push((chunk[2] << 6) | chunk[3]);
+            chunk.clear();
+        }
+    }
+
+    if !chunk.is_empty() {
+        match last_chunk {
+            LastChunkHandling::StopBeforePartial => {}
+            LastChunkHandling::Strict => {
+                return Err("Missing padding in base64 string".to_string());
+            }
+            LastChunkHandling::Loose => decode_final_chunk(&chunk, false, &mut out)?,
+        }
+    }
+
+    Ok(out)
+}
+
+/// Decode a final chunk of 2 or 3 sextets
+fn decode_final_chunk(chunk: &[u8], strict: bool, out: &mut Vec<u8>) -> Result<(), String> {
+    let overflow = match chunk.len() {
+        2 => {
+            out.push((chunk[0] << 2) | (chunk[1] >> 4));
+            chunk[1] & 0x0F
+        }
+        3 => {
+            out.push((chunk[0] << 2) | (chunk[1] >> 4));
+            out.push((chunk[1] << 4) | (chunk[2] >> 2));
+            chunk[2] & 0x03
+        }
+        _ => return Err("Incomplete base64 chunk".to_string()),
+    };
+
+    if strict && overflow != 0 {
+        return Err("Non-zero padding bits in base64 string".to_string());
+    }
+
+    Ok(())
+}
+
+/// Encode bytes as lowercase hex
+pub fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Decode a hex string (either case, even length)
+pub fn decode_hex(input: &str) -> Result<Vec<u8>, String> {
+    if input.len() % 2 != 0 {
+        return Err("Hex string must have an even length".to_string());
+    }
+
+    if !input.bytes().all(|c| c.is_ascii_hexdigit()) {
+        return Err("Invalid hex character".to_string());
+    }
+
+    Ok((0..input.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&input[i..i + 2], 16).unwrap())
+        .collect())
+}
+
+/// Create a Uint8Array holding a copy of `bytes`
+pub(crate) fn new_uint8_array(ctx: &JSContext, bytes: &[u8]) -> Result<JSValue, JSValue> {
+    let array = ctx.evaluate_script(&format!("new Uint8Array({})", bytes.len()), 1)?;
+    let buffer = unsafe { js_value_bytes_mut(ctx, &array)? };
+    buffer.copy_from_slice(bytes);
+    Ok(array)
+}
+
+/// Setup Uint8Array.fromBase64/fromHex and Uint8Array.prototype.toBase64/toHex
+/// (TC39 proposal-arraybuffer-base64) and the global bytesToBase64/base64ToBytes
+/// helpers, backed by native encoders
+pub fn setup_uint8array_encoding(context: &mut JSContext) {
+    // __nativeToBase64(bytes, url, omitPadding) -> string
+    let to_base64_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            let bytes = match args.first() {
+                Some(arg) => unsafe { js_value_bytes_mut(&ctx, arg)? },
+                None => return Err(JSValue::string(&ctx, "Expected a Uint8Array")),
+            };
+
+            let url = args.get(1).is_some_and(|arg| arg.to_bool(&ctx));
+            let omit_padding = args.get(2).is_some_and(|arg| arg.to_bool(&ctx));
+
+            let encoded = encode_base64(bytes, url, omit_padding);
+            Ok(JSValue::string(&ctx, encoded.as_str()))
+        }
+    );
+
+    // __nativeFromBase64(string, url, lastChunkHandling) -> Uint8Array
+    let from_base64_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            let input = match args.first().map(|arg| arg.to_js_string(&ctx)) {
+                Some(Ok(s)) => s.to_string(),
+                _ => return Err(JSValue::string(&ctx, "Expected a string")),
+            };
+
+            let url = args.get(1).is_some_and(|arg| arg.to_bool(&ctx));
+            let last_chunk = match args.get(2).map(|arg| arg.to_js_string(&ctx)) {
+                Some(Ok(s)) => match s.to_string().parse() {
+                    Ok(mode) => mode,
+                    Err(e) => return Err(JSValue::string(&ctx, e.as_str())),
+                },
+                _ => LastChunkHandling::Loose,
+            };
+
+            match decode_base64(&input, url, last_chunk) {
+                Ok(bytes) => new_uint8_array(&ctx, &bytes),
+                Err(e) => Err(JSValue::string(&ctx, e.as_str())),
+            }
+        }
+    );
+
+    // __nativeToHex(bytes) -> string
+    let to_hex_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            let bytes = match args.first() {
+                Some(arg) => unsafe { js_value_bytes_mut(&ctx, arg)? },
+                None => return Err(JSValue::string(&ctx, "Expected a Uint8Array")),
+            };
+
+            Ok(JSValue::string(&ctx, encode_hex(bytes).as_str()))
+        }
+    );
+
+    // __nativeFromHex(string) -> Uint8Array
+    let from_hex_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            let input = match args.first().map(|arg| arg.to_js_string(&ctx)) {
+                Some(Ok(s)) => s.to_string(),
+                _ => return Err(JSValue::string(&ctx, "Expected a string")),
+            };
+
+            match decode_hex(&input) {
+                Ok(bytes) => new_uint8_array(&ctx, &bytes),
+                Err(e) => Err(JSValue::string(&ctx, e.as_str())),
+            }
+        }
+    );
+
+    let mut global = context.get_global_object();
+    global
+        .set_property(context, "__nativeToBase64", to_base64_fn.into())
+        .unwrap();
+    global
+        .set_property(context, "__nativeFromBase64", from_base64_fn.into())
+        .unwrap();
+    global
+        .set_property(context, "__nativeToHex", to_hex_fn.into())
+        .unwrap();
+    global
+        .set_property(context, "__nativeFromHex", from_hex_fn.into())
+        .unwrap();
+
+    let code = r#"
+        (() => {
+            const getOption = (options, name, allowed, fallback) => {
+                if (options === undefined) return fallback;
+                if (options === null || typeof options !== 'object') {
+                    throw new TypeError('Options must be an object');
+                }
+                const value = options[name];
+                if (value === undefined) return fallback;
+                if (!allowed.includes(value)) {
+                    throw new TypeError(`Invalid ${name}: ${value}`);
+                }
+                return value;
+            };
+
+            const checkUint8Array = (value) => {
+                if (!(value instanceof Uint8Array)) {
+                    throw new TypeError('Receiver must be a Uint8Array');
+                }
+            };
+
+            const checkString = (value) => {
+                if (typeof value !== 'string') {
+                    throw new TypeError('Input must be a string');
+                }
+            };
+
+            // Native decode errors are thrown as SyntaxError
+            const decode = (fn, ...args) => {
+                try {
+                    return fn(...args);
+                } catch (e) {
+                    throw new SyntaxError(String(e));
+                }
+            };
+
+            const define = (target, name, value) => Object.defineProperty(target, name, {
+                value,
+                writable: true,
+                configurable: true,
+                enumerable: false
+            });
+
+            define(Uint8Array, 'fromBase64', function fromBase64(string, options) {
+                checkString(string);
+                const alphabet = getOption(options, 'alphabet', ['base64', 'base64url'], 'base64');
+                const lastChunkHandling = getOption(options, 'lastChunkHandling',
+                    ['loose', 'strict', 'stop-before-partial'], 'loose');
+                return decode(__nativeFromBase64, string, alphabet === 'base64url', lastChunkHandling);
+            });
+
+            define(Uint8Array, 'fromHex', function fromHex(string) {
+                checkString(string);
+                return decode(__nativeFromHex, string);
+            });
+
+            define(Uint8Array.prototype, 'toBase64', function toBase64(options) {
+                checkUint8Array(this);
+                const alphabet = getOption(options, 'alphabet', ['base64', 'base64url'], 'base64');
+                const omitPadding = options !== undefined && Boolean(options.omitPadding);
+                if (this.length === 0) return '';
+                return __nativeToBase64(this, alphabet === 'base64url', omitPadding);
+            });
+
+            define(Uint8Array.prototype, 'toHex', function toHex() {
+                checkUint8Array(this);
+                if (this.length === 0) return '';
+                return __nativeToHex(this);
+            });
+
+            // Binary-safe helpers (non-standard): { url: true } selects unpadded
+            // base64url, as used by JWTs
+            const toUint8Array = (data) => {
+                if (data instanceof ArrayBuffer) {
+                    return new Uint8Array(data);
+                }
+                if (ArrayBuffer.isView(data)) {
+                    return new Uint8Array(data.buffer, data.byteOffset, data.byteLength);
+                }
+                throw new TypeError('bytesToBase64 expects an ArrayBuffer or ArrayBufferView');
+            };
+
+            globalThis.bytesToBase64 = function bytesToBase64(bytes, options) {
+                const url = Boolean(options && options.url);
+                const view = toUint8Array(bytes);
+                if (view.length === 0) return '';
+                return __nativeToBase64(view, url, url);
+            };
+
+            globalThis.base64ToBytes = function base64ToBytes(string, options) {
+                checkString(string);
+                const url = Boolean(options && options.url);
+                return decode(__nativeFromBase64, string, url, 'loose');
+            };
+        })();
+    "#;
+
     context
         .evaluate_script(code, 1)
-        .expect("Failed to setup atob/btoa");
+        .expect("Failed to setup Uint8Array base64/hex methods");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_roundtrip_alphabets() {
+        let bytes = [0xFB, 0xFF, 0x00, 0x10, 0x7F];
+        assert_eq!(encode_base64(&bytes, false, false), "+/8AEH8=");
+        assert_eq!(encode_base64(&bytes, true, true), "-_8AEH8");
+
+        for (encoded, url) in [("+/8AEH8=", false), ("-_8AEH8", true)] {
+            let decoded = decode_base64(encoded, url, LastChunkHandling::Loose).unwrap();
+            assert_eq!(decoded, bytes);
+        }
+    }
+
+    #[test]
+    fn test_base64_last_chunk_handling() {
+        assert_eq!(
+            decode_base64("aGk", false, LastChunkHandling::Loose).unwrap(),
+            b"hi"
+        );
+        assert!(decode_base64("aGk", false, LastChunkHandling::Strict).is_err());
+        assert_eq!(
+            decode_base64("aGVsbG8", false, LastChunkHandling::StopBeforePartial).unwrap(),
+            b"hel"
+        );
+        // 'aGl=' leaves non-zero overflow bits
+        assert!(decode_base64("aGl=", false, LastChunkHandling::Strict).is_err());
+        assert!(decode_base64("a", false, LastChunkHandling::Loose).is_err());
+        assert!(decode_base64("aG=x", false, LastChunkHandling::Loose).is_err());
+    }
+
+    #[test]
+    fn test_hex() {
+        assert_eq!(encode_hex(&[0x00, 0xAB, 0xFF]), "00abff");
+        assert_eq!(decode_hex("00ABff").unwrap(), [0x00, 0xAB, 0xFF]);
+        assert!(decode_hex("abc").is_err());
+        assert!(decode_hex("+1").is_err());
+    }
 }
@@ -1,7 +1,10 @@
-use super::{CallbackId, SchedulerMessage, stream_manager::StreamId};
+use super::{
+    CallbackId, SchedulerMessage, stream_manager::StreamId, typed_array::js_value_bytes_mut,
+};
 use rusty_jsc::{JSContext, JSObject, JSValue};
 use rusty_jsc_macros::callback;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc;
 
@@ -12,8 +15,44 @@ pub struct TimerState {
     pub next_id: Arc<Mutex<CallbackId>>,
 }
 
+/// A console message emitted by JS
+#[derive(Debug, Clone)]
+pub struct ConsoleMessage {
+    pub level: log::Level,
+    pub message: String,
+}
+
+/// Object nesting shown by console before collapsing to `[Object]` / `[Array]`
+pub const DEFAULT_CONSOLE_MAX_DEPTH: usize = 10;
+
+/// Console message length (UTF-16 code units) before truncating with `…`
+pub const DEFAULT_CONSOLE_MAX_LENGTH: usize = 16 * 1024;
+
+/// Console configuration shared between the JS bindings and the worker
+#[derive(Clone, Default)]
+pub struct ConsoleState {
+    /// Maximum console messages per request (None = unlimited)
+    pub max_log_events: Option<usize>,
+    /// Object nesting shown per argument (None = `DEFAULT_CONSOLE_MAX_DEPTH`)
+    pub max_depth: Option<usize>,
+    /// Maximum message length (None = `DEFAULT_CONSOLE_MAX_LENGTH`)
+    pub max_length: Option<usize>,
+    /// Messages emitted in the current request (reset by the worker)
+    pub log_count: Arc<AtomicUsize>,
+    /// Optional channel receiving every emitted message
+    pub log_tx: Option<mpsc::UnboundedSender<ConsoleMessage>>,
+}
+
 /// Setup console bindings (log, info, warn, error, debug)
 pub fn setup_console(context: &mut JSContext) {
+    setup_console_with_state(context, ConsoleState::default());
+}
+
+/// Setup console bindings with a log cap and optional output channel
+pub fn setup_console_with_state(context: &mut JSContext, state: ConsoleState) {
+    let max_depth = state.max_depth.unwrap_or(DEFAULT_CONSOLE_MAX_DEPTH);
+    let max_length = state.max_length.unwrap_or(DEFAULT_CONSOLE_MAX_LENGTH);
+
     // Create native __console_log function that accepts level and message
     let console_log_fn = rusty_jsc::callback_closure!(
         context,
@@ -28,14 +67,42 @@ pub fn setup_console(context: &mut JSContext) {
                 .map(|s| s.to_string())
                 .unwrap_or_default();
 
+            let level = match level_num {
+                0 => log::Level::Error,
+                1 => log::Level::Warn,
+                _ => log::Level::Info,
+            };
+
+            // Enforce the per-request cap: drop past the limit, warn exactly once
+            let (level, msg) = match state.max_log_events {
+                Some(max) => {
+                    let count = state.log_count.fetch_add(1, Ordering::SeqCst);
+                    if count < max {
+                        (level, msg)
+                    } else if count == max {
+                        (log::Level::Warn, "log rate exceeded".to_string())
+                    } else {
+                        return Ok(JSValue::undefined(&ctx));
+                    }
+                }
+                None => (level, msg),
+            };
+
             // Print to stdout
-            let prefix = match level_num {
-                0 => "[ERROR]",
-                1 => "[WARN]",
+            let prefix = match level {
+                log::Level::Error => "[ERROR]",
+                log::Level::Warn => "[WARN]",
                 _ => "[LOG]",
             };
             println!("{} {}", prefix, msg);
 
+            if let Some(tx) = &state.log_tx {
+                let _ = tx.send(ConsoleMessage {
+                    level,
+                    message: msg,
+                });
+            }
+
             Ok(JSValue::undefined(&ctx))
         }
     );
@@ -46,30 +113,115 @@ pub fn setup_console(context: &mut JSContext) {
         .set_property(context, "__console_log", console_log_fn.into())
         .unwrap();
 
+    context
+        .evaluate_script(
+            &format!(
+                "globalThis.__consoleLimits = {{ maxDepth: {}, maxLength: {} }};",
+                max_depth, max_length
+            ),
+            1,
+        )
+        .unwrap();
+
     // Create console object via JS that calls __console_log with appropriate level
     let console_script = r#"
-        globalThis.console = {
-            log: function(...args) {
-                const msg = args.map(a => typeof a === 'object' ? JSON.stringify(a) : String(a)).join(' ');
-                __console_log(2, msg);
-            },
-            info: function(...args) {
-                const msg = args.map(a => typeof a === 'object' ? JSON.stringify(a) : String(a)).join(' ');
-                __console_log(2, msg);
-            },
-            warn: function(...args) {
-                const msg = args.map(a => typeof a === 'object' ? JSON.stringify(a) : String(a)).join(' ');
-                __console_log(1, msg);
-            },
-            error: function(...args) {
-                const msg = args.map(a => typeof a === 'object' ? JSON.stringify(a) : String(a)).join(' ');
-                __console_log(0, msg);
-            },
-            debug: function(...args) {
-                const msg = args.map(a => typeof a === 'object' ? JSON.stringify(a) : String(a)).join(' ');
-                __console_log(2, msg);
-            }
-        };
+        (function() {
+            const { maxDepth, maxLength } = globalThis.__consoleLimits;
+            delete globalThis.__consoleLimits;
+
+            // JSON with objects past maxDepth collapsed (which also bounds
+            // cycles), and no more values visited once maxLength is reached
+            const inspect = (value) => {
+                const depths = new WeakMap();
+                let budget = maxLength;
+
+                const json = JSON.stringify(value, function(key, v) {
+                    if (budget <= 0) {
+                        return undefined;
+                    }
+                    budget -= key.length + 1;
+
+                    if (typeof v === 'bigint') {
+                        v = v.toString() + 'n';
+                    }
+                    if (v !== null && typeof v === 'object') {
+                        const depth = depths.has(this) ? depths.get(this) + 1 : 0;
+                        if (depth > maxDepth) {
+                            return Array.isArray(v) ? '[Array]' : '[Object]';
+                        }
+                        depths.set(v, depth);
+                    } else {
+                        budget -= typeof v === 'string' ? v.length : 4;
+                    }
+                    return v;
+                });
+
+                return json === undefined ? String(value) : json;
+            };
+
+            // Errors keep their type, message and stack; other objects are inspected
+            const format = (args) => {
+                const message = args.map(a => {
+                    if (a instanceof Error) {
+                        const header = a.name + ': ' + a.message;
+                        return a.stack ? header + '\n' + a.stack : header;
+                    }
+                    return a !== null && typeof a === 'object' ? inspect(a) : String(a);
+                }).join(' ');
+
+                return message.length > maxLength ? message.slice(0, maxLength) + '\u2026' : message;
+            };
+
+            globalThis.console = {
+                log: (...args) => __console_log(2, format(args)),
+                info: (...args) => __console_log(2, format(args)),
+                warn: (...args) => __console_log(1, format(args)),
+                error: (...args) => __console_log(0, format(args)),
+                debug: (...args) => __console_log(2, format(args))
+            };
+        })();
+
+        // Per-label counters and timers
+        (function() {
+            const counts = new Map();
+            const timers = new Map();
+
+            console.count = function(label = 'default') {
+                label = String(label);
+                const count = (counts.get(label) || 0) + 1;
+                counts.set(label, count);
+                __console_log(2, label + ': ' + count);
+            };
+
+            console.countReset = function(label = 'default') {
+                label = String(label);
+                if (!counts.has(label)) {
+                    __console_log(1, "Count for '" + label + "' does not exist");
+                    return;
+                }
+                counts.set(label, 0);
+            };
+
+            console.time = function(label = 'default') {
+                label = String(label);
+                if (timers.has(label)) {
+                    __console_log(1, "Timer '" + label + "' already exists");
+                    return;
+                }
+                timers.set(label, performance.now());
+            };
+
+            console.timeEnd = function(label = 'default') {
+                label = String(label);
+                if (!timers.has(label)) {
+                    __console_log(1, "Timer '" + label + "' does not exist");
+                    return;
+                }
+                const elapsed = performance.now() - timers.get(label);
+                timers.delete(label);
+                __console_log(2, label + ': ' + elapsed.toFixed(3) + 'ms');
+            };
+        })();
     "#;
 
     context.evaluate_script(console_script, 1).unwrap();
@@ -92,10 +244,17 @@ fn queue_microtask_fn(
     };
 
     // Use Promise.resolve().then() to queue as microtask
-    // This is the standard web platform approach
+    // This is the standard web platform approach. Exceptions are reported
+    // (not turned into a silent rejection) and don't affect other microtasks.
     let script = r#"
         (function(callback) {
-            Promise.resolve().then(callback);
+            Promise.resolve().then(() => {
+                try {
+                    callback();
+                } catch (error) {
+                    reportError(error);
+                }
+            });
         })
     "#;
 
@@ -111,7 +270,7 @@ fn queue_microtask_fn(
     Ok(JSValue::undefined(&ctx))
 }
 
-/// Setup queueMicrotask binding
+/// Setup queueMicrotask and reportError bindings
 pub fn setup_microtask(context: &mut JSContext) {
     let microtask_fn = JSValue::callback(context, Some(queue_microtask_fn));
 
@@ -119,6 +278,30 @@ pub fn setup_microtask(context: &mut JSContext) {
     global
         .set_property(context, "queueMicrotask", microtask_fn)
         .unwrap();
+
+    // reportError(error): dispatch a cancelable global 'error' event (when the
+    // worker installed dispatchEvent), then log to console.error unless a
+    // listener called preventDefault()
+    let report_error_script = r#"
+        globalThis.reportError = function reportError(error) {
+            if (typeof dispatchEvent === 'function' && typeof Event === 'function') {
+                const event = new Event('error', { cancelable: true });
+                event.error = error;
+                event.message = error && error.message !== undefined ? String(error.message) : String(error);
+                if (!dispatchEvent(event)) {
+                    return;
+                }
+            }
+
+            if (typeof console !== 'undefined') {
+                console.error('Uncaught', error);
+            }
+        };
+    "#;
+
+    context
+        .evaluate_script(report_error_script, 1)
+        .expect("Failed to setup reportError");
 }
 
 /// Setup fetch API
@@ -127,7 +310,12 @@ pub fn setup_fetch(
     scheduler_tx: mpsc::UnboundedSender<SchedulerMessage>,
     callbacks: Arc<Mutex<HashMap<CallbackId, JSObject>>>,
     next_id: Arc<Mutex<CallbackId>>,
+    progress: Arc<Mutex<HashMap<CallbackId, JSObject>>>,
+    propagated_headers: PropagatedHeaders,
 ) {
+    let scheduler_tx_abort = scheduler_tx.clone();
+    let callbacks_abort = callbacks.clone();
+    let progress_abort = progress.clone();
     let scheduler_tx_clone = scheduler_tx;
     let callbacks_clone = callbacks;
     let next_id_clone = next_id;
@@ -153,16 +341,42 @@ pub fn setup_fetch(
                 None
             };
 
-            let request = match super::fetch::parse_fetch_options(&ctx, url, options_val) {
+            let redirect = match super::fetch::parse_redirect_mode(&ctx, options_val.as_ref()) {
+                Ok(mode) => mode,
+                Err(e) => return Err(JSValue::string(&ctx, e.as_str())),
+            };
+
+            let buffered = super::fetch::parse_buffer_option(&ctx, options_val.as_ref());
+
+            let onprogress = super::fetch::parse_progress_option(&ctx, options_val.as_ref());
+
+            let mut request = match super::fetch::parse_fetch_options(&ctx, url, options_val) {
                 Ok(req) => req,
                 Err(e) => return Err(JSValue::string(&ctx, e.as_str())),
             };
 
-            // Create a Promise and store resolve/reject callbacks
+            // Trace headers of the incoming request, unless set explicitly
+            for (name, value) in propagated_headers.lock().unwrap().iter() {
+                if !request
+                    .headers
+                    .keys()
+                    .any(|key| key.eq_ignore_ascii_case(name))
+                {
+                    request.headers.insert(name.clone(), value.clone());
+                }
+            }
+
+            // Create a Promise and store a settle callback: the event loop calls it
+            // with a Response on success or an error message string on failure
             let promise_script = r#"
                 new Promise((resolve, reject) => {
-                    globalThis.__fetchResolve = resolve;
-                    globalThis.__fetchReject = reject;
+                    globalThis.__fetchSettle = (result) => {
+                        if (typeof result === 'string') {
+                            reject(new TypeError(result));
+                        } else {
+                            resolve(result);
+                        }
+                    };
                 })
             "#;
 
@@ -171,20 +385,15 @@ pub fn setup_fetch(
                 Err(_) => return Err(JSValue::string(&ctx, "Failed to create Promise")),
             };
 
-            // Get resolve and reject callbacks
+            // Get settle callback
             let global = ctx.get_global_object();
 
-            let resolve_callback = global
-                .get_property(&ctx, "__fetchResolve")
+            let settle_callback = global
+                .get_property(&ctx, "__fetchSettle")
                 .and_then(|v| v.to_object(&ctx).ok())
-                .ok_or_else(|| JSValue::string(&ctx, "Failed to get resolve callback"))?;
+                .ok_or_else(|| JSValue::string(&ctx, "Failed to get settle callback"))?;
 
-            let _reject_callback = global
-                .get_property(&ctx, "__fetchReject")
-                .and_then(|v| v.to_object(&ctx).ok())
-                .ok_or_else(|| JSValue::string(&ctx, "Failed to get reject callback"))?;
-
-            // Generate callback ID for resolve
+            // Generate callback ID for the settle callback
             let callback_id = {
                 let mut next = next_id_clone.lock().unwrap();
                 let id = *next;
@@ -192,37 +401,124 @@ pub fn setup_fetch(
                 id
             };
 
-            // Store resolve callback (we'll call it with Response or Error)
+            // Store settle callback (called with Response or error message)
             {
                 let mut cbs = callbacks_clone.lock().unwrap();
-                cbs.insert(callback_id, resolve_callback);
-                // For reject, we could store it separately, but for now we'll use the same callback
+                cbs.insert(callback_id, settle_callback);
+            }
+
+            // Progress callbacks are keyed by the promise ID and dropped after
+            // the last report
+            let report_progress = onprogress.is_some();
+            if let Some(onprogress) = onprogress {
+                progress.lock().unwrap().insert(callback_id, onprogress);
             }
 
             log::debug!(
-                "fetch: scheduled streaming {} {} (promise_id: {})",
+                "fetch: scheduled {} {} {} (promise_id: {})",
+                if buffered { "buffered" } else { "streaming" },
                 request.method.as_str(),
                 request.url,
                 callback_id
             );
 
-            // Schedule the fetch with streaming
-            let _ = scheduler_tx_clone.send(SchedulerMessage::FetchStreaming(callback_id, request));
+            // Schedule the fetch (streaming unless a buffered body was requested)
+            let message = if buffered {
+                SchedulerMessage::FetchBuffered(callback_id, request, redirect, report_progress)
+            } else {
+                SchedulerMessage::FetchStreaming(callback_id, request, redirect, report_progress)
+            };
+            let _ = scheduler_tx_clone.send(message);
+
+            // Expose the promise ID so the JS wrapper can abort the fetch
+            if let Ok(mut promise_obj) = promise.to_object(&ctx) {
+                let _ = promise_obj.set_property(
+                    &ctx,
+                    "_fetchId",
+                    JSValue::number(&ctx, callback_id as f64),
+                );
+            }
 
             // Return the Promise
             Ok(promise)
         }
     );
 
+    // __abortFetch(promise_id): drop the settle callback and cancel the request
+    let abort_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            if let Some(Ok(id)) = args.first().map(|arg| arg.to_number(&ctx)) {
+                let promise_id = id as CallbackId;
+                callbacks_abort.lock().unwrap().remove(&promise_id);
+                progress_abort.lock().unwrap().remove(&promise_id);
+                let _ = scheduler_tx_abort.send(SchedulerMessage::FetchAbort(promise_id));
+            }
+
+            Ok(JSValue::undefined(&ctx))
+        }
+    );
+
     // Add native fetch to global object (as __nativeFetch)
     let mut global = context.get_global_object();
     global
         .set_property(context, "__nativeFetch", fetch_fn.into())
         .unwrap();
+    global
+        .set_property(context, "__abortFetch", abort_fn.into())
+        .unwrap();
 
     // Create JS wrapper that handles ReadableStream bodies
     let wrapper_code = r#"
-        globalThis.fetch = async function(url, options = {}) {
+        globalThis.fetch = async function fetch(url, options = {}) {
+            // fetch(request, init): send the Request's method, headers and body
+            // (init wins); its body is handed over, so a clone() keeps the other tee branch.
+            // Uploads are not streamed: OperationsHandlers take buffered bodies, so the
+            // body stream is collected below like any ReadableStream body
+            if (url instanceof Request) {
+                const request = url;
+                if (request.bodyUsed) {
+                    throw new TypeError('Cannot fetch a Request whose body has been consumed');
+                }
+                options = {
+                    method: request.method,
+                    headers: request.headers,
+                    body: request.body === null ? undefined : request.body,
+                    mode: request.mode,
+                    redirect: request.redirect,
+                    ...options
+                };
+                url = request.url;
+                if (request.body) {
+                    request.bodyUsed = true;
+                }
+            }
+
+            // Native fetch takes a plain object: flatten Headers and [name, value]
+            // pairs, comma-joining repeated headers (Set-Cookie included)
+            if (options && options.headers !== undefined && options.headers !== null) {
+                const headers = new Headers(options.headers);
+                const flat = {};
+                for (const [key, value] of headers._map) {
+                    flat[headers._names.get(key) || key] = value;
+                }
+                options = { ...options, headers: flat };
+            }
+
+            // Workers have no origin, so every fetch is cross-origin
+            const mode = options && options.mode !== undefined ? String(options.mode) : 'cors';
+            if (mode === 'same-origin') {
+                throw new TypeError("fetch to '" + url + "' is cross-origin, which mode 'same-origin' forbids");
+            }
+            if (mode === 'no-cors') {
+                return Response._opaque(await fetch(url, { ...options, mode: 'cors' }));
+            }
+
+            const signal = options ? options.signal : undefined;
+            if (signal && signal.aborted) {
+                throw signal.reason;
+            }
+
             // If body is a ReadableStream, consume it first
             if (options && options.body instanceof ReadableStream) {
                 console.warn('[fetch] ReadableStream body detected - buffering entire stream before sending');
@@ -244,17 +540,65 @@ pub fn setup_fetch(
                         combined.set(chunk, offset);
                         offset += chunk.length;
                     }
-                    // Convert to string for the native fetch
-                    options = {
-                        ...options,
-                        body: new TextDecoder().decode(combined)
-                    };
+                    options = { ...options, body: combined };
                 } else {
                     options = { ...options, body: undefined };
                 }
             }
 
-            return __nativeFetch(url, options);
+            // Native fetch reads binary bodies as Uint8Array bytes
+            if (options && options.body instanceof ArrayBuffer) {
+                options = { ...options, body: new Uint8Array(options.body) };
+            } else if (options && ArrayBuffer.isView(options.body) && !(options.body instanceof Uint8Array)) {
+                const view = options.body;
+                options = { ...options, body: new Uint8Array(view.buffer, view.byteOffset, view.byteLength) };
+            }
+
+            // Non-standard: onprogress({ type, loaded, total, lengthComputable })
+            if (options && options.onprogress !== undefined && options.onprogress !== null) {
+                const onprogress = options.onprogress;
+                if (typeof onprogress !== 'function') {
+                    throw new TypeError('fetch onprogress must be a function');
+                }
+                options = {
+                    ...options,
+                    onprogress: (type, loaded, total) => onprogress({
+                        type,
+                        loaded,
+                        total: total === null ? 0 : total,
+                        lengthComputable: total !== null
+                    })
+                };
+            }
+
+            if (signal && signal.aborted) {
+                throw signal.reason;
+            }
+
+            const pending = __nativeFetch(url, options);
+            if (!signal) {
+                return pending;
+            }
+
+            // Reject with the signal's reason (whatever the caller passed to abort())
+            return new Promise((resolve, reject) => {
+                const onAbort = () => {
+                    __abortFetch(pending._fetchId);
+                    reject(signal.reason);
+                };
+                signal.addEventListener('abort', onAbort, { once: true });
+
+                pending.then(
+                    (response) => {
+                        signal.removeEventListener('abort', onAbort);
+                        resolve(response);
+                    },
+                    (error) => {
+                        signal.removeEventListener('abort', onAbort);
+                        reject(error);
+                    }
+                );
+            });
         };
     "#;
 
@@ -263,6 +607,113 @@ pub fn setup_fetch(
         .expect("Failed to setup fetch wrapper");
 }
 
+/// Incoming request headers copied onto outbound fetches, set by the worker
+/// for each event
+pub type PropagatedHeaders = Arc<Mutex<Vec<(String, String)>>>;
+
+/// Subrequest accounting shared between the fetch binding and the worker
+#[derive(Clone, Default)]
+pub struct SubrequestState {
+    /// Maximum fetches per request (None = unlimited)
+    pub max_subrequests: Option<usize>,
+    /// Fetches issued in the current request (reset by the worker)
+    pub count: Arc<AtomicUsize>,
+}
+
+/// Cap the number of fetches per request
+///
+/// Wraps the global fetch: once the cap is reached, further calls reject
+/// with a TypeError without reaching the event loop.
+pub fn setup_subrequest_limit(context: &mut JSContext, state: SubrequestState) {
+    let Some(max) = state.max_subrequests else {
+        return;
+    };
+
+    // __acquireSubrequest() -> bool
+    let acquire_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, _args: &[JSValue]| {
+            let count = state.count.fetch_add(1, Ordering::SeqCst);
+            Ok(JSValue::boolean(&ctx, count < max))
+        }
+    );
+
+    let mut global = context.get_global_object();
+    global
+        .set_property(context, "__acquireSubrequest", acquire_fn.into())
+        .unwrap();
+
+    let wrapper_code = format!(
+        r#"
+        (function() {{
+            const fetchImpl = globalThis.fetch;
+            globalThis.fetch = function(...args) {{
+                if (!__acquireSubrequest()) {{
+                    return Promise.reject(new TypeError('Too many subrequests (limit: {})'));
+                }}
+                return fetchImpl.apply(this, args);
+            }};
+        }})();
+        "#,
+        max
+    );
+
+    context
+        .evaluate_script(&wrapper_code, 1)
+        .expect("Failed to setup subrequest limit");
+}
+
+/// Cap how deeply timers may schedule timers
+///
+/// A timer callback runs at its timer's nesting level; setTimeout,
+/// setInterval and setImmediate called from it create a timer one level
+/// deeper, and throw a RangeError past `max_timer_nesting`. Timers scheduled
+/// outside any timer callback are at level 1, so sibling timers never count.
+pub fn setup_timer_nesting_limit(context: &mut JSContext, max_timer_nesting: Option<usize>) {
+    let Some(max) = max_timer_nesting else {
+        return;
+    };
+
+    let wrapper_code = format!(
+        r#"
+        (function() {{
+            // Nesting level of the timer callback currently running (0 = none)
+            let level = 0;
+
+            const guard = function(schedule) {{
+                return function(callback, ...rest) {{
+                    const nested = level + 1;
+                    if (nested > {max}) {{
+                        throw new RangeError('Timer nesting limit exceeded (limit: {max})');
+                    }}
+                    if (typeof callback !== 'function') {{
+                        return schedule.call(this, callback, ...rest);
+                    }}
+
+                    return schedule.call(this, function(...args) {{
+                        const previous = level;
+                        level = nested;
+                        try {{
+                            return callback.apply(this, args);
+                        }} finally {{
+                            level = previous;
+                        }}
+                    }}, ...rest);
+                }};
+            }};
+
+            globalThis.setTimeout = guard(globalThis.setTimeout);
+            globalThis.setInterval = guard(globalThis.setInterval);
+            globalThis.setImmediate = guard(globalThis.setImmediate);
+        }})();
+        "#
+    );
+
+    context
+        .evaluate_script(&wrapper_code, 1)
+        .expect("Failed to setup timer nesting limit");
+}
+
 /// Setup timer bindings (setTimeout, setInterval, clearTimeout, clearInterval)
 pub fn setup_timer(
     context: &mut JSContext,
@@ -270,13 +721,20 @@ pub fn setup_timer(
     callbacks: Arc<Mutex<HashMap<CallbackId, JSObject>>>,
     next_id: Arc<Mutex<CallbackId>>,
     intervals: Arc<Mutex<std::collections::HashSet<CallbackId>>>,
+    timers: Arc<Mutex<std::collections::HashSet<CallbackId>>>,
 ) {
+    // Setup the registry holding extra timer arguments
+    context
+        .evaluate_script(TIMER_ARGS_JS, 1)
+        .expect("Failed to setup timer arguments");
+
     // Setup setTimeout
     setup_set_timeout(
         context,
         scheduler_tx.clone(),
         callbacks.clone(),
         next_id.clone(),
+        timers.clone(),
     );
 
     // Setup setInterval
@@ -286,10 +744,217 @@ pub fn setup_timer(
         callbacks.clone(),
         next_id.clone(),
         intervals,
+        timers.clone(),
+    );
+
+    // Setup setImmediate and clearImmediate
+    setup_set_immediate(
+        context,
+        scheduler_tx.clone(),
+        callbacks.clone(),
+        next_id.clone(),
+        timers.clone(),
     );
 
     // Setup clearTimeout and clearInterval (same implementation)
-    setup_clear_timer(context, scheduler_tx.clone());
+    setup_clear_timer(context, scheduler_tx.clone(), callbacks, timers);
+}
+
+/// Cancel a timer registered by setTimeout, setInterval or setImmediate
+///
+/// Fetch and stream callbacks share the same ID counter, so IDs that don't
+/// belong to a pending timer are ignored.
+fn cancel_timer(
+    ctx: &JSContext,
+    timer_id: CallbackId,
+    scheduler_tx: &mpsc::UnboundedSender<SchedulerMessage>,
+    callbacks: &Mutex<HashMap<CallbackId, JSObject>>,
+    timers: &Mutex<std::collections::HashSet<CallbackId>>,
+) -> bool {
+    if !timers.lock().unwrap().remove(&timer_id) {
+        return false;
+    }
+
+    // The callback message may already be queued, so drop the callback too
+    callbacks.lock().unwrap().remove(&timer_id);
+    drop_timer_args(ctx, timer_id);
+
+    let _ = scheduler_tx.send(SchedulerMessage::ClearTimer(timer_id));
+    true
+}
+
+/// Extra timer arguments, keyed by timer ID
+///
+/// Keeping them in a global Map leaves them reachable from JS (and so safe
+/// from GC) until the timer fires or is cleared.
+const TIMER_ARGS_JS: &str = r#"
+    globalThis.__timerArgs = new Map();
+
+    globalThis.__storeTimerArgs = (id, ...args) => {
+        __timerArgs.set(id, args);
+    };
+
+    globalThis.__dropTimerArgs = (id) => {
+        __timerArgs.delete(id);
+    };
+
+    globalThis.__runTimer = (callback, id, once) => {
+        const args = __timerArgs.get(id);
+        if (once) {
+            __timerArgs.delete(id);
+        }
+        return args ? callback(...args) : callback();
+    };
+"#;
+
+/// Call a global helper function by name
+fn call_global_helper(ctx: &JSContext, name: &str, args: &[JSValue]) -> Result<JSValue, JSValue> {
+    let helper = ctx
+        .get_global_object()
+        .get_property(ctx, name)
+        .and_then(|v| v.to_object(ctx).ok())
+        .ok_or_else(|| JSValue::string(ctx, format!("{} is not defined", name).as_str()))?;
+
+    helper.call_as_function(ctx, None, args)
+}
+
+/// Remember the extra arguments passed to a timer (nothing to store when empty)
+fn store_timer_args(ctx: &JSContext, callback_id: CallbackId, args: &[JSValue]) {
+    if args.is_empty() {
+        return;
+    }
+
+    let mut helper_args = vec![JSValue::number(ctx, callback_id as f64)];
+    helper_args.extend_from_slice(args);
+
+    if call_global_helper(ctx, "__storeTimerArgs", &helper_args).is_err() {
+        log::error!("Failed to store arguments for timer {}", callback_id);
+    }
+}
+
+/// Forget the arguments of a cleared timer
+pub(crate) fn drop_timer_args(ctx: &JSContext, callback_id: CallbackId) {
+    let _ = call_global_helper(
+        ctx,
+        "__dropTimerArgs",
+        &[JSValue::number(ctx, callback_id as f64)],
+    );
+}
+
+/// Invoke a timer callback with its stored arguments
+///
+/// One-shot timers (`once`) release their arguments; intervals keep them for
+/// the next tick.
+pub(crate) fn call_timer(
+    ctx: &JSContext,
+    callback_id: CallbackId,
+    callback: &JSObject,
+    once: bool,
+) -> Result<JSValue, JSValue> {
+    call_global_helper(
+        ctx,
+        "__runTimer",
+        &[
+            callback.clone().into(),
+            JSValue::number(ctx, callback_id as f64),
+            JSValue::boolean(ctx, once),
+        ],
+    )
+}
+
+/// Setup setImmediate/clearImmediate bindings
+///
+/// Immediates are zero-delay macrotasks: they run on the next callback pass,
+/// once the current task and its microtasks have completed.
+fn setup_set_immediate(
+    context: &mut JSContext,
+    scheduler_tx: mpsc::UnboundedSender<SchedulerMessage>,
+    callbacks: Arc<Mutex<HashMap<CallbackId, JSObject>>>,
+    next_id: Arc<Mutex<CallbackId>>,
+    timers: Arc<Mutex<std::collections::HashSet<CallbackId>>>,
+) {
+    let callbacks_clone = callbacks.clone();
+    let timers_clone = timers.clone();
+    let scheduler_tx_clone = scheduler_tx.clone();
+
+    // __setImmediate(callback, ...args) -> id
+    let set_immediate = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            let callback = match args.first().map(|arg| arg.to_object(&ctx)) {
+                Some(Ok(obj)) => obj,
+                _ => return Err(JSValue::string(&ctx, "First argument must be a function")),
+            };
+
+            let callback_id = {
+                let mut next = next_id.lock().unwrap();
+                let id = *next;
+                *next += 1;
+                id
+            };
+
+            {
+                let mut cbs = callbacks_clone.lock().unwrap();
+                cbs.insert(callback_id, callback);
+            }
+            timers_clone.lock().unwrap().insert(callback_id);
+
+            store_timer_args(&ctx, callback_id, args.get(1..).unwrap_or_default());
+
+            let _ = scheduler_tx_clone.send(SchedulerMessage::ScheduleImmediate(callback_id));
+
+            log::debug!("setImmediate: registered callback {}", callback_id);
+
+            Ok(JSValue::number(&ctx, callback_id as f64))
+        }
+    );
+
+    // clearImmediate(id)
+    let clear_immediate = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            if let Some(Ok(id)) = args.first().map(|arg| arg.to_number(&ctx))
+                && cancel_timer(&ctx, id as CallbackId, &scheduler_tx, &callbacks, &timers)
+            {
+                log::debug!("clearImmediate: cleared immediate {}", id);
+            }
+
+            Ok(JSValue::undefined(&ctx))
+        }
+    );
+
+    let mut global = context.get_global_object();
+    global
+        .set_property(context, "__setImmediate", set_immediate.into())
+        .unwrap();
+    global
+        .set_property(context, "clearImmediate", clear_immediate.into())
+        .unwrap();
+
+    let wrapper_code = r#"
+        globalThis.setImmediate = function(callback, ...args) {
+            if (typeof callback !== 'function') {
+                throw new TypeError('setImmediate requires a function');
+            }
+            return __setImmediate(callback, ...args);
+        };
+    "#;
+
+    context
+        .evaluate_script(wrapper_code, 1)
+        .expect("Failed to setup setImmediate");
+}
+
+/// Normalize a timer delay in milliseconds
+///
+/// Like browsers, NaN, negative and infinite delays (and anything beyond the
+/// 32-bit range) fire as soon as possible instead of wrapping or never firing.
+fn timer_delay(delay: f64) -> u64 {
+    if delay.is_finite() && delay > 0.0 && delay <= i32::MAX as f64 {
+        delay as u64
+    } else {
+        0
+    }
 }
 
 /// Setup setTimeout binding
@@ -298,6 +963,7 @@ fn setup_set_timeout(
     scheduler_tx: mpsc::UnboundedSender<SchedulerMessage>,
     callbacks: Arc<Mutex<HashMap<CallbackId, JSObject>>>,
     next_id: Arc<Mutex<CallbackId>>,
+    timers: Arc<Mutex<std::collections::HashSet<CallbackId>>>,
 ) {
     let callbacks_clone = callbacks;
     let next_id_clone = next_id;
@@ -319,7 +985,7 @@ fn setup_set_timeout(
 
             // Get the delay
             let delay = match args[1].to_number(&ctx) {
-                Ok(d) => d as u64,
+                Ok(d) => timer_delay(d),
                 Err(_) => return Err(JSValue::string(&ctx, "Second argument must be a number")),
             };
 
@@ -336,6 +1002,10 @@ fn setup_set_timeout(
                 let mut cbs = callbacks_clone.lock().unwrap();
                 cbs.insert(callback_id, callback);
             }
+            timers.lock().unwrap().insert(callback_id);
+
+            // Extra arguments are forwarded to the callback
+            store_timer_args(&ctx, callback_id, &args[2..]);
 
             // Schedule the timeout
             let _ = scheduler_tx_clone.send(SchedulerMessage::ScheduleTimeout(callback_id, delay));
@@ -365,6 +1035,7 @@ fn setup_set_interval(
     callbacks: Arc<Mutex<HashMap<CallbackId, JSObject>>>,
     next_id: Arc<Mutex<CallbackId>>,
     intervals: Arc<Mutex<std::collections::HashSet<CallbackId>>>,
+    timers: Arc<Mutex<std::collections::HashSet<CallbackId>>>,
 ) {
     let callbacks_clone = callbacks;
     let next_id_clone = next_id;
@@ -387,7 +1058,7 @@ fn setup_set_interval(
 
             // Get the interval
             let interval = match args[1].to_number(&ctx) {
-                Ok(d) => d as u64,
+                Ok(d) => timer_delay(d),
                 Err(_) => return Err(JSValue::string(&ctx, "Second argument must be a number")),
             };
 
@@ -410,6 +1081,10 @@ fn setup_set_interval(
                 let mut intervals = intervals_clone.lock().unwrap();
                 intervals.insert(callback_id);
             }
+            timers.lock().unwrap().insert(callback_id);
+
+            // Extra arguments are forwarded to every call
+            store_timer_args(&ctx, callback_id, &args[2..]);
 
             // Schedule the interval
             let _ =
@@ -437,8 +1112,12 @@ fn setup_set_interval(
 fn setup_clear_timer(
     context: &mut JSContext,
     scheduler_tx: mpsc::UnboundedSender<SchedulerMessage>,
+    callbacks: Arc<Mutex<HashMap<CallbackId, JSObject>>>,
+    timers: Arc<Mutex<std::collections::HashSet<CallbackId>>>,
 ) {
     let scheduler_tx_clone = scheduler_tx.clone();
+    let callbacks_clone = callbacks.clone();
+    let timers_clone = timers.clone();
 
     // Create clearTimeout function
     let clear_timeout = rusty_jsc::callback_closure!(
@@ -454,10 +1133,15 @@ fn setup_clear_timer(
                 Err(_) => return Ok(JSValue::undefined(&ctx)),
             };
 
-            // Send clear message
-            let _ = scheduler_tx_clone.send(SchedulerMessage::ClearTimer(timer_id));
-
-            log::debug!("clearTimeout: cleared timer {}", timer_id);
+            if cancel_timer(
+                &ctx,
+                timer_id,
+                &scheduler_tx_clone,
+                &callbacks_clone,
+                &timers_clone,
+            ) {
+                log::debug!("clearTimeout: cleared timer {}", timer_id);
+            }
 
             Ok(JSValue::undefined(&ctx))
         }
@@ -479,10 +1163,9 @@ fn setup_clear_timer(
                 Err(_) => return Ok(JSValue::undefined(&ctx)),
             };
 
-            // Send clear message
-            let _ = scheduler_tx_clone2.send(SchedulerMessage::ClearTimer(timer_id));
-
-            log::debug!("clearInterval: cleared timer {}", timer_id);
+            if cancel_timer(&ctx, timer_id, &scheduler_tx_clone2, &callbacks, &timers) {
+                log::debug!("clearInterval: cleared timer {}", timer_id);
+            }
 
             Ok(JSValue::undefined(&ctx))
         }
@@ -634,11 +1317,16 @@ pub fn setup_stream_ops(
 
 /// Setup response stream operations for streaming all responses
 /// __responseStreamCreate() - creates a stream for response body, returns stream ID
-/// __responseStreamWrite(stream_id, Uint8Array) - writes bytes to the stream
+/// __responseStreamWrite(stream_id, Uint8Array | string) - writes bytes to the stream
+/// __responseStreamReady(stream_id) - whether the stream buffer has room
+/// __responseStreamWhenReady(stream_id, callback) - calls back once the buffer has room
 /// __responseStreamEnd(stream_id) - signals end of stream
 pub fn setup_response_stream_ops(
     context: &mut JSContext,
     stream_manager: Arc<super::stream_manager::StreamManager>,
+    scheduler_tx: mpsc::UnboundedSender<SchedulerMessage>,
+    callbacks: Arc<Mutex<HashMap<CallbackId, JSObject>>>,
+    next_id: Arc<Mutex<CallbackId>>,
 ) {
     // __responseStreamCreate() -> stream_id
     let manager_clone = stream_manager.clone();
@@ -651,7 +1339,7 @@ pub fn setup_response_stream_ops(
         }
     );
 
-    // __responseStreamWrite(stream_id, Uint8Array) -> boolean
+    // __responseStreamWrite(stream_id, Uint8Array | string) -> boolean
     let manager_clone = stream_manager.clone();
     let write_stream = rusty_jsc::callback_closure!(
         context,
@@ -668,18 +1356,22 @@ pub fn setup_response_stream_ops(
                 Err(_) => return Err(JSValue::string(&ctx, "stream_id must be a number")),
             };
 
-            // Get the Uint8Array data
-            let data_obj = match args[1].to_object(&ctx) {
-                Ok(obj) => obj,
-                Err(_) => return Err(JSValue::string(&ctx, "data must be a Uint8Array")),
-            };
-
-            // Read bytes from the TypedArray
-            let bytes = unsafe {
-                match data_obj.get_typed_array_buffer(&ctx) {
-                    Ok(slice) => bytes::Bytes::copy_from_slice(slice),
-                    Err(_) => return Err(JSValue::string(&ctx, "Failed to read TypedArray")),
+            // Strings are taken as owned UTF-8; typed arrays are copied once out
+            // of the JS heap (the buffer is GC-owned and may be reused by the
+            // writer after this call). The Bytes is then moved, not copied, down
+            // to the response body. Oversized chunks are rejected before copying.
+            let bytes = if args[1].is_string(&ctx) {
+                match args[1].to_js_string(&ctx) {
+                    Ok(s) => bytes::Bytes::from(s.to_string()),
+                    Err(_) => return Err(JSValue::string(&ctx, "Failed to read string")),
                 }
+            } else {
+                let slice = unsafe { js_value_bytes_mut(&ctx, &args[1])? };
+                if let Err(e) = manager_clone.check_chunk_size(slice.len()) {
+                    log::warn!("__responseStreamWrite error: {}", e);
+                    return Ok(JSValue::boolean(&ctx, false));
+                }
+                bytes::Bytes::copy_from_slice(slice)
             };
 
             // Try to write the chunk (non-blocking)
@@ -695,6 +1387,60 @@ pub fn setup_response_stream_ops(
         }
     );
 
+    // __responseStreamReady(stream_id) -> boolean (false while the buffer is full)
+    let manager_clone = stream_manager.clone();
+    let ready_stream = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            let stream_id = match args.first().map(|arg| arg.to_number(&ctx)) {
+                Some(Ok(id)) => id as StreamId,
+                _ => return Err(JSValue::string(&ctx, "stream_id must be a number")),
+            };
+
+            Ok(JSValue::boolean(
+                &ctx,
+                manager_clone.has_capacity(stream_id),
+            ))
+        }
+    );
+
+    // __responseStreamWhenReady(stream_id, callback): the event loop calls back
+    // once the embedder has read from a full buffer
+    let when_ready = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            if args.len() < 2 {
+                return Err(JSValue::string(
+                    &ctx,
+                    "__responseStreamWhenReady requires stream_id and callback",
+                ));
+            }
+
+            let stream_id = match args[0].to_number(&ctx) {
+                Ok(id) => id as StreamId,
+                Err(_) => return Err(JSValue::string(&ctx, "stream_id must be a number")),
+            };
+
+            let callback = match args[1].to_object(&ctx) {
+                Ok(obj) => obj,
+                Err(_) => return Err(JSValue::string(&ctx, "callback must be a function")),
+            };
+
+            let callback_id = {
+                let mut next = next_id.lock().unwrap();
+                let id = *next;
+                *next += 1;
+                id
+            };
+
+            callbacks.lock().unwrap().insert(callback_id, callback);
+
+            let _ = scheduler_tx.send(SchedulerMessage::StreamWaitReady(callback_id, stream_id));
+
+            Ok(JSValue::undefined(&ctx))
+        }
+    );
+
     // __responseStreamEnd(stream_id)
     let manager_clone = stream_manager;
     let end_stream = rusty_jsc::callback_closure!(
@@ -729,6 +1475,12 @@ pub fn setup_response_stream_ops(
     global
         .set_property(context, "__responseStreamWrite", write_stream.into())
         .unwrap();
+    global
+        .set_property(context, "__responseStreamReady", ready_stream.into())
+        .unwrap();
+    global
+        .set_property(context, "__responseStreamWhenReady", when_ready.into())
+        .unwrap();
     global
         .set_property(context, "__responseStreamEnd", end_stream.into())
         .unwrap();
@@ -0,0 +1,79 @@
+/// Blob class implementation (W3C File API)
+pub const BLOB_JS: &str = r#"
+    globalThis.Blob = class Blob {
+        constructor(parts, options) {
+            parts = parts || [];
+            options = options || {};
+
+            const encoder = new TextEncoder();
+            const chunks = [];
+
+            for (const part of parts) {
+                if (part instanceof Blob) {
+                    chunks.push(part._bytes);
+                } else if (part instanceof ArrayBuffer) {
+                    chunks.push(new Uint8Array(part));
+                } else if (ArrayBuffer.isView(part)) {
+                    chunks.push(new Uint8Array(part.buffer, part.byteOffset, part.byteLength));
+                } else {
+                    chunks.push(encoder.encode(String(part)));
+                }
+            }
+
+            // Concatenate all parts into a single buffer
+            const totalLength = chunks.reduce((sum, chunk) => sum + chunk.length, 0);
+            this._bytes = new Uint8Array(totalLength);
+            let offset = 0;
+            for (const chunk of chunks) {
+                this._bytes.set(chunk, offset);
+                offset += chunk.length;
+            }
+
+            const type = options.type === undefined ? '' : String(options.type);
+            this.type = /^[\x20-\x7E]*$/.test(type) ? type.toLowerCase() : '';
+        }
+
+        get size() {
+            return this._bytes.length;
+        }
+
+        slice(start, end, contentType) {
+            const size = this._bytes.length;
+            const clamp = (value, fallback) => {
+                if (value === undefined) return fallback;
+                value = Math.trunc(Number(value)) || 0;
+                return value < 0 ? Math.max(size + value, 0) : Math.min(value, size);
+            };
+
+            const from = clamp(start, 0);
+            const to = clamp(end, size);
+            const blob = new Blob([], { type: contentType === undefined ? '' : contentType });
+            blob._bytes = this._bytes.slice(from, Math.max(from, to));
+            return blob;
+        }
+
+        async text() {
+            return new TextDecoder().decode(this._bytes);
+        }
+
+        async arrayBuffer() {
+            return this._bytes.slice().buffer;
+        }
+
+        async bytes() {
+            return this._bytes.slice();
+        }
+
+        stream() {
+            const bytes = this._bytes.slice();
+            return new ReadableStream({
+                start(controller) {
+                    if (bytes.length > 0) {
+                        controller.enqueue(bytes);
+                    }
+                    controller.close();
+                }
+            });
+        }
+    };
+"#;
@@ -0,0 +1,127 @@
+use rusty_jsc::JSContext;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Runtime clock shared by timers, Date and performance.now()
+///
+/// Time is measured with tokio's clock, so pausing or advancing tokio time
+/// (`tokio::time::pause` / `advance`) moves JS time and timers in lockstep.
+#[derive(Debug, Clone, Copy)]
+pub struct Clock {
+    /// Wall-clock time at creation (ms since UNIX epoch)
+    wall_origin_ms: f64,
+    /// Monotonic instant at creation
+    origin: tokio::time::Instant,
+}
+
+impl Clock {
+    pub fn new() -> Self {
+        let wall_origin_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs_f64() * 1000.0)
+            .unwrap_or(0.0);
+
+        Self {
+            wall_origin_ms,
+            origin: tokio::time::Instant::now(),
+        }
+    }
+
+    /// Milliseconds elapsed since the clock was created (performance.now)
+    pub fn elapsed_ms(&self) -> f64 {
+        self.origin.elapsed().as_secs_f64() * 1000.0
+    }
+
+    /// Current wall-clock time in ms since UNIX epoch (Date.now)
+    pub fn now_ms(&self) -> f64 {
+        self.wall_origin_ms + self.elapsed_ms()
+    }
+
+    /// Wall-clock time at creation (performance.timeOrigin)
+    pub fn time_origin_ms(&self) -> f64 {
+        self.wall_origin_ms
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Setup Date.now(), new Date() and performance.now() backed by the runtime clock
+pub fn setup_clock(context: &mut JSContext, clock: Clock) {
+    let date_now_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, _args: &[JSValue]| {
+            Ok(JSValue::number(&ctx, clock.now_ms().floor()))
+        }
+    );
+
+    let performance_now_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, _args: &[JSValue]| {
+            Ok(JSValue::number(&ctx, clock.elapsed_ms()))
+        }
+    );
+
+    let mut global = context.get_global_object();
+    global
+        .set_property(context, "__clockNow", date_now_fn.into())
+        .unwrap();
+    global
+        .set_property(context, "__performanceNow", performance_now_fn.into())
+        .unwrap();
+
+    let clock_script = format!(
+        r#"
+        // Zero-argument `new Date()` and `Date()` read the runtime clock too
+        globalThis.Date = (function(NativeDate) {{
+            function Date(...args) {{
+                if (!new.target) {{
+                    return new NativeDate(__clockNow()).toString();
+                }}
+                return Reflect.construct(NativeDate, args.length === 0 ? [__clockNow()] : args, new.target);
+            }}
+
+            Object.defineProperty(Date, 'length', {{ value: NativeDate.length }});
+            Object.defineProperty(Date, 'prototype', {{ value: NativeDate.prototype, writable: false }});
+            Object.defineProperty(NativeDate.prototype, 'constructor', {{ value: Date, writable: true, configurable: true }});
+            Date.parse = NativeDate.parse;
+            Date.UTC = NativeDate.UTC;
+            Date.now = function() {{
+                return __clockNow();
+            }};
+            return Date;
+        }})(Date);
+
+        globalThis.performance = {{
+            timeOrigin: {},
+            now: function() {{
+                return __performanceNow();
+            }}
+        }};
+    "#,
+        clock.time_origin_ms()
+    );
+
+    context
+        .evaluate_script(&clock_script, 1)
+        .expect("Failed to setup clock");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_clock_follows_tokio_time() {
+        let clock = Clock::new();
+        let start = clock.now_ms();
+
+        tokio::time::advance(Duration::from_millis(1500)).await;
+
+        assert_eq!(clock.elapsed_ms(), 1500.0);
+        assert!((clock.now_ms() - start - 1500.0).abs() < 1.0);
+    }
+}
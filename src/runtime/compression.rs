@@ -0,0 +1,220 @@
+use super::base64::new_uint8_array;
+use super::typed_array::js_value_to_bytes;
+use flate2::write::{DeflateDecoder, GzDecoder, ZlibDecoder};
+use rusty_jsc::{JSContext, JSValue};
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+/// A streaming decoder writing decompressed bytes into a buffer
+enum Decoder {
+    Gzip(GzDecoder<Vec<u8>>),
+    Deflate(ZlibDecoder<Vec<u8>>),
+    DeflateRaw(DeflateDecoder<Vec<u8>>),
+}
+
+impl Decoder {
+    /// Decoder for a DecompressionStream format
+    fn new(format: &str) -> Option<Self> {
+        match format {
+            "gzip" => Some(Decoder::Gzip(GzDecoder::new(Vec::new()))),
+            "deflate" => Some(Decoder::Deflate(ZlibDecoder::new(Vec::new()))),
+            "deflate-raw" => Some(Decoder::DeflateRaw(DeflateDecoder::new(Vec::new()))),
+            _ => None,
+        }
+    }
+
+    /// Feed compressed bytes, returning what has been decompressed so far
+    fn write(&mut self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+        let output = match self {
+            Decoder::Gzip(decoder) => {
+                decoder.write_all(data)?;
+                decoder.get_mut()
+            }
+            Decoder::Deflate(decoder) => {
+                decoder.write_all(data)?;
+                decoder.get_mut()
+            }
+            Decoder::DeflateRaw(decoder) => {
+                decoder.write_all(data)?;
+                decoder.get_mut()
+            }
+        };
+        Ok(std::mem::take(output))
+    }
+
+    /// End of input: returns the remaining decompressed bytes
+    fn finish(self) -> std::io::Result<Vec<u8>> {
+        match self {
+            Decoder::Gzip(decoder) => decoder.finish(),
+            Decoder::Deflate(decoder) => decoder.finish(),
+            Decoder::DeflateRaw(decoder) => decoder.finish(),
+        }
+    }
+}
+
+/// In-progress decompressions, referenced from JS by handle
+#[derive(Default)]
+struct Decoders {
+    next_id: u64,
+    decoders: HashMap<u64, Decoder>,
+}
+
+impl Decoders {
+    fn insert(&mut self, decoder: Decoder) -> u64 {
+        self.next_id += 1;
+        self.decoders.insert(self.next_id, decoder);
+        self.next_id
+    }
+}
+
+/// Setup DecompressionStream (gzip, deflate, deflate-raw) backed by flate2
+pub fn setup_compression(context: &mut JSContext) {
+    let decoders = Arc::new(Mutex::new(Decoders::default()));
+
+    // Create __nativeDecompressInit(format) -> handle
+    let decoders_init = decoders.clone();
+    let init_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            let format = match args.first().map(|arg| arg.to_js_string(&ctx)) {
+                Some(Ok(s)) => s.to_string(),
+                _ => return Err(JSValue::string(&ctx, "Format must be a string")),
+            };
+
+            let Some(decoder) = Decoder::new(&format) else {
+                return Err(JSValue::string(&ctx, "Unsupported compression format"));
+            };
+
+            let handle = decoders_init.lock().unwrap().insert(decoder);
+            Ok(JSValue::number(&ctx, handle as f64))
+        }
+    );
+
+    // Create __nativeDecompressWrite(handle, chunk) -> Uint8Array
+    let decoders_write = decoders.clone();
+    let write_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            if args.len() < 2 {
+                return Err(JSValue::string(
+                    &ctx,
+                    "decompressWrite requires handle and data",
+                ));
+            }
+
+            let handle = match args[0].to_number(&ctx) {
+                Ok(n) => n as u64,
+                Err(_) => return Err(JSValue::string(&ctx, "Invalid decompression handle")),
+            };
+
+            let data = js_value_to_bytes(&ctx, &args[1])?;
+
+            let mut decoders = decoders_write.lock().unwrap();
+            let Some(decoder) = decoders.decoders.get_mut(&handle) else {
+                return Err(JSValue::string(&ctx, "Unknown decompression handle"));
+            };
+
+            match decoder.write(&data) {
+                Ok(output) => new_uint8_array(&ctx, &output),
+                Err(e) => {
+                    // The stream errors: release the decoder
+                    decoders.decoders.remove(&handle);
+                    Err(JSValue::string(
+                        &ctx,
+                        format!("Invalid compressed data: {}", e).as_str(),
+                    ))
+                }
+            }
+        }
+    );
+
+    // Create __nativeDecompressFinish(handle) -> Uint8Array
+    let decoders_finish = decoders;
+    let finish_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            let handle = match args.first().map(|arg| arg.to_number(&ctx)) {
+                Some(Ok(n)) => n as u64,
+                _ => return Err(JSValue::string(&ctx, "Invalid decompression handle")),
+            };
+
+            let Some(decoder) = decoders_finish.lock().unwrap().decoders.remove(&handle) else {
+                return Err(JSValue::string(&ctx, "Unknown decompression handle"));
+            };
+
+            match decoder.finish() {
+                Ok(output) => new_uint8_array(&ctx, &output),
+                Err(e) => Err(JSValue::string(
+                    &ctx,
+                    format!("Invalid compressed data: {}", e).as_str(),
+                )),
+            }
+        }
+    );
+
+    let mut global = context.get_global_object();
+    global
+        .set_property(context, "__nativeDecompressInit", init_fn.into())
+        .unwrap();
+    global
+        .set_property(context, "__nativeDecompressWrite", write_fn.into())
+        .unwrap();
+    global
+        .set_property(context, "__nativeDecompressFinish", finish_fn.into())
+        .unwrap();
+
+    let code = r#"
+        // DecompressionStream - decompress a stream of byte chunks
+        globalThis.DecompressionStream = class DecompressionStream {
+            constructor(format) {
+                format = String(format);
+                if (!['gzip', 'deflate', 'deflate-raw'].includes(format)) {
+                    throw new TypeError(`Unsupported compression format: ${format}`);
+                }
+
+                // Native errors are strings: surface them as TypeErrors
+                const native = (fn) => {
+                    try {
+                        return fn();
+                    } catch (e) {
+                        throw new TypeError(String(e));
+                    }
+                };
+
+                // The decoder is released on flush or on invalid data
+                const handle = __nativeDecompressInit(format);
+                this._format = format;
+                this._transform = new TransformStream({
+                    transform(chunk, controller) {
+                        if (!(chunk instanceof ArrayBuffer) && !ArrayBuffer.isView(chunk)) {
+                            throw new TypeError('DecompressionStream chunks must be BufferSource');
+                        }
+                        const output = native(() => __nativeDecompressWrite(handle, chunk));
+                        if (output.length > 0) {
+                            controller.enqueue(output);
+                        }
+                    },
+                    flush(controller) {
+                        const output = native(() => __nativeDecompressFinish(handle));
+                        if (output.length > 0) {
+                            controller.enqueue(output);
+                        }
+                    }
+                });
+            }
+
+            get readable() {
+                return this._transform.readable;
+            }
+
+            get writable() {
+                return this._transform.writable;
+            }
+        };
+    "#;
+
+    context
+        .evaluate_script(code, 1)
+        .expect("Failed to setup DecompressionStream");
+}
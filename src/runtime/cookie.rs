@@ -0,0 +1,98 @@
+/// Cookie helpers (RFC 6265): parseCookies(header) and serializeCookie(name, value, options)
+pub const COOKIE_JS: &str = r#"
+    (function() {
+        // token (RFC 7230) for names, cookie-octet (RFC 6265) for values and attributes
+        const tokenRe = /^[!#$%&'*+\-.^_`|~0-9A-Za-z]+$/;
+        const octetRe = /^[\x21\x23-\x2B\x2D-\x3A\x3C-\x5B\x5D-\x7E]*$/;
+        const attributeRe = /^[\x20-\x3A\x3C-\x7E]*$/;
+        const sameSiteValues = { strict: 'Strict', lax: 'Lax', none: 'None' };
+
+        // Parse a Cookie header ("a=1; b=2") into a Map, first occurrence wins
+        globalThis.parseCookies = function parseCookies(header) {
+            const cookies = new Map();
+            if (header === null || header === undefined) {
+                return cookies;
+            }
+
+            for (const pair of String(header).split(';')) {
+                const eq = pair.indexOf('=');
+                if (eq === -1) {
+                    continue;
+                }
+
+                const name = pair.slice(0, eq).trim();
+                let value = pair.slice(eq + 1).trim();
+                if (value.length >= 2 && value[0] === '"' && value[value.length - 1] === '"') {
+                    value = value.slice(1, -1);
+                }
+
+                if (name && !cookies.has(name)) {
+                    cookies.set(name, value);
+                }
+            }
+
+            return cookies;
+        };
+
+        // Build a Set-Cookie value
+        // options: { path, domain, maxAge, expires, httpOnly, secure, sameSite }
+        globalThis.serializeCookie = function serializeCookie(name, value, options) {
+            name = String(name);
+            value = value === undefined ? '' : String(value);
+            options = options || {};
+
+            if (!tokenRe.test(name)) {
+                throw new TypeError('Invalid cookie name: ' + name);
+            }
+            if (!octetRe.test(value)) {
+                throw new TypeError('Invalid cookie value for ' + name);
+            }
+
+            let cookie = name + '=' + value;
+
+            if (options.maxAge !== undefined) {
+                const maxAge = Number(options.maxAge);
+                if (!Number.isFinite(maxAge)) {
+                    throw new TypeError('Invalid cookie maxAge: ' + options.maxAge);
+                }
+                cookie += '; Max-Age=' + Math.floor(maxAge);
+            }
+
+            for (const [option, attribute] of [['domain', 'Domain'], ['path', 'Path']]) {
+                if (options[option] === undefined) {
+                    continue;
+                }
+                const attributeValue = String(options[option]);
+                if (!attributeRe.test(attributeValue)) {
+                    throw new TypeError('Invalid cookie ' + option + ': ' + attributeValue);
+                }
+                cookie += '; ' + attribute + '=' + attributeValue;
+            }
+
+            if (options.expires !== undefined) {
+                const expires = options.expires instanceof Date ? options.expires : new Date(options.expires);
+                if (isNaN(expires.getTime())) {
+                    throw new TypeError('Invalid cookie expires: ' + options.expires);
+                }
+                cookie += '; Expires=' + expires.toUTCString();
+            }
+
+            if (options.httpOnly) {
+                cookie += '; HttpOnly';
+            }
+            if (options.secure) {
+                cookie += '; Secure';
+            }
+
+            if (options.sameSite !== undefined) {
+                const sameSite = sameSiteValues[String(options.sameSite).toLowerCase()];
+                if (!sameSite) {
+                    throw new TypeError('Invalid cookie sameSite: ' + options.sameSite);
+                }
+                cookie += '; SameSite=' + sameSite;
+            }
+
+            return cookie;
+        };
+    })();
+"#;
@@ -1,8 +1,49 @@
-use ring::{digest, hmac, rand, rsa, signature, signature::KeyPair};
-use rusty_jsc::JSContext;
+use super::typed_array::{js_value_bytes_mut, js_value_to_bytes};
+use ring::{aead, digest, hmac, rand, rsa, signature, signature::KeyPair};
+use rusty_jsc::{JSContext, JSValue};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+// The `rsa` crate (`rsa` alone is ring's module here)
+use ::rsa::pkcs1::{DecodeRsaPrivateKey, DecodeRsaPublicKey};
+use ::rsa::pkcs8::{DecodePrivateKey, DecodePublicKey};
+use ::rsa::traits::PublicKeyParts;
+use ::rsa::{Oaep, RsaPrivateKey, RsaPublicKey};
+
+/// Reproducible byte source for getRandomValues and randomUUID (see
+/// `Runtime::with_rng`)
+///
+/// SplitMix64: fine for tests, not cryptographically secure.
+pub(crate) struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn fill(&mut self, bytes: &mut [u8]) {
+        for chunk in bytes.chunks_mut(8) {
+            chunk.copy_from_slice(&self.next_u64().to_le_bytes()[..chunk.len()]);
+        }
+    }
+}
+
+/// Seeded RNG replacing the system one when set - shared with the runtime
+pub(crate) type SharedRng = Arc<Mutex<Option<SeededRng>>>;
 
 /// Setup crypto global object with getRandomValues, randomUUID, and subtle
-pub fn setup_crypto(context: &mut JSContext) {
+pub fn setup_crypto(context: &mut JSContext, seeded_rng: SharedRng) {
+    let seeded_rng_uuid = seeded_rng.clone();
+
     // Create __nativeGetRandomValues function
     let get_random_values_fn = rusty_jsc::callback_closure!(
         context,
@@ -14,18 +55,13 @@ pub fn setup_crypto(context: &mut JSContext) {
                 ));
             }
 
-            let array = match args[0].to_object(&ctx) {
-                Ok(obj) => obj,
-                Err(_) => return Err(JSValue::string(&ctx, "Argument must be a TypedArray")),
-            };
+            // Get the typed array's bytes and fill them with random bytes
+            let bytes = unsafe { js_value_bytes_mut(&ctx, &args[0])? };
 
-            // Get the typed array buffer and fill with random bytes
-            let bytes = unsafe {
-                match array.get_typed_array_buffer(&ctx) {
-                    Ok(slice) => slice,
-                    Err(_) => return Err(JSValue::string(&ctx, "Argument must be a TypedArray")),
-                }
-            };
+            if let Some(seeded) = seeded_rng.lock().unwrap().as_mut() {
+                seeded.fill(bytes);
+                return Ok(args[0].clone());
+            }
 
             // Fill with random bytes using ring
             let rng = rand::SystemRandom::new();
@@ -43,8 +79,15 @@ pub fn setup_crypto(context: &mut JSContext) {
     let random_uuid_fn = rusty_jsc::callback_closure!(
         context,
         move |ctx: JSContext, _func: JSObject, _this: JSObject, _args: &[JSValue]| {
-            let uuid = uuid::Uuid::new_v4().to_string();
-            Ok(JSValue::string(&ctx, uuid.as_str()))
+            let uuid = match seeded_rng_uuid.lock().unwrap().as_mut() {
+                Some(seeded) => {
+                    let mut bytes = [0u8; 16];
+                    seeded.fill(&mut bytes);
+                    uuid::Builder::from_random_bytes(bytes).into_uuid()
+                }
+                None => uuid::Uuid::new_v4(),
+            };
+            Ok(JSValue::string(&ctx, uuid.to_string().as_str()))
         }
     );
 
@@ -62,26 +105,13 @@ pub fn setup_crypto(context: &mut JSContext) {
                 Err(_) => return Err(JSValue::string(&ctx, "Algorithm must be a string")),
             };
 
-            // Get data as Uint8Array
-            let data_obj = match args[1].to_object(&ctx) {
-                Ok(obj) => obj,
-                Err(_) => return Err(JSValue::string(&ctx, "Data must be a Uint8Array")),
-            };
-
-            let data = unsafe {
-                match data_obj.get_typed_array_buffer(&ctx) {
-                    Ok(slice) => slice.to_vec(),
-                    Err(_) => return Err(JSValue::string(&ctx, "Data must be a Uint8Array")),
-                }
-            };
+            // Get data bytes
+            let data = js_value_to_bytes(&ctx, &args[1])?;
 
             // Select algorithm
-            let algorithm = match algo.as_str() {
-                "SHA-1" => &digest::SHA1_FOR_LEGACY_USE_ONLY,
-                "SHA-256" => &digest::SHA256,
-                "SHA-384" => &digest::SHA384,
-                "SHA-512" => &digest::SHA512,
-                _ => return Err(JSValue::string(&ctx, "Unsupported algorithm")),
+            let algorithm = match digest_algorithm(&algo) {
+                Some(algorithm) => algorithm,
+                None => return Err(JSValue::string(&ctx, "Unsupported algorithm")),
             };
 
             // Compute digest
@@ -118,30 +148,10 @@ pub fn setup_crypto(context: &mut JSContext) {
             };
 
             // Get key data
-            let key_obj = match args[1].to_object(&ctx) {
-                Ok(obj) => obj,
-                Err(_) => return Err(JSValue::string(&ctx, "Key must be a Uint8Array")),
-            };
-
-            let key_data = unsafe {
-                match key_obj.get_typed_array_buffer(&ctx) {
-                    Ok(slice) => slice.to_vec(),
-                    Err(_) => return Err(JSValue::string(&ctx, "Key must be a Uint8Array")),
-                }
-            };
+            let key_data = js_value_to_bytes(&ctx, &args[1])?;
 
             // Get data
-            let data_obj = match args[2].to_object(&ctx) {
-                Ok(obj) => obj,
-                Err(_) => return Err(JSValue::string(&ctx, "Data must be a Uint8Array")),
-            };
-
-            let data = unsafe {
-                match data_obj.get_typed_array_buffer(&ctx) {
-                    Ok(slice) => slice.to_vec(),
-                    Err(_) => return Err(JSValue::string(&ctx, "Data must be a Uint8Array")),
-                }
-            };
+            let data = js_value_to_bytes(&ctx, &args[2])?;
 
             // Select algorithm
             let algorithm = match algo.as_str() {
@@ -187,42 +197,18 @@ pub fn setup_crypto(context: &mut JSContext) {
             };
 
             // Get key data
-            let key_obj = match args[1].to_object(&ctx) {
-                Ok(obj) => obj,
-                Err(_) => return Ok(JSValue::boolean(&ctx, false)),
-            };
-
-            let key_data = unsafe {
-                match key_obj.get_typed_array_buffer(&ctx) {
-                    Ok(slice) => slice.to_vec(),
-                    Err(_) => return Ok(JSValue::boolean(&ctx, false)),
-                }
+            let Ok(key_data) = js_value_to_bytes(&ctx, &args[1]) else {
+                return Ok(JSValue::boolean(&ctx, false));
             };
 
             // Get signature
-            let sig_obj = match args[2].to_object(&ctx) {
-                Ok(obj) => obj,
-                Err(_) => return Ok(JSValue::boolean(&ctx, false)),
-            };
-
-            let signature = unsafe {
-                match sig_obj.get_typed_array_buffer(&ctx) {
-                    Ok(slice) => slice.to_vec(),
-                    Err(_) => return Ok(JSValue::boolean(&ctx, false)),
-                }
+            let Ok(signature) = js_value_to_bytes(&ctx, &args[2]) else {
+                return Ok(JSValue::boolean(&ctx, false));
             };
 
             // Get data
-            let data_obj = match args[3].to_object(&ctx) {
-                Ok(obj) => obj,
-                Err(_) => return Ok(JSValue::boolean(&ctx, false)),
-            };
-
-            let data = unsafe {
-                match data_obj.get_typed_array_buffer(&ctx) {
-                    Ok(slice) => slice.to_vec(),
-                    Err(_) => return Ok(JSValue::boolean(&ctx, false)),
-                }
+            let Ok(data) = js_value_to_bytes(&ctx, &args[3]) else {
+                return Ok(JSValue::boolean(&ctx, false));
             };
 
             // Select algorithm
@@ -296,31 +282,9 @@ pub fn setup_crypto(context: &mut JSContext) {
                 ));
             }
 
-            let key_obj = match args[0].to_object(&ctx) {
-                Ok(obj) => obj,
-                Err(_) => return Err(JSValue::string(&ctx, "Private key must be a Uint8Array")),
-            };
-
-            let private_key_data = unsafe {
-                match key_obj.get_typed_array_buffer(&ctx) {
-                    Ok(slice) => slice.to_vec(),
-                    Err(_) => {
-                        return Err(JSValue::string(&ctx, "Private key must be a Uint8Array"));
-                    }
-                }
-            };
-
-            let data_obj = match args[1].to_object(&ctx) {
-                Ok(obj) => obj,
-                Err(_) => return Err(JSValue::string(&ctx, "Data must be a Uint8Array")),
-            };
+            let private_key_data = js_value_to_bytes(&ctx, &args[0])?;
 
-            let data = unsafe {
-                match data_obj.get_typed_array_buffer(&ctx) {
-                    Ok(slice) => slice.to_vec(),
-                    Err(_) => return Err(JSValue::string(&ctx, "Data must be a Uint8Array")),
-                }
-            };
+            let data = js_value_to_bytes(&ctx, &args[1])?;
 
             let rng = rand::SystemRandom::new();
 
@@ -358,40 +322,16 @@ pub fn setup_crypto(context: &mut JSContext) {
                 return Ok(JSValue::boolean(&ctx, false));
             }
 
-            let public_key_obj = match args[0].to_object(&ctx) {
-                Ok(obj) => obj,
-                Err(_) => return Ok(JSValue::boolean(&ctx, false)),
-            };
-
-            let public_key_data = unsafe {
-                match public_key_obj.get_typed_array_buffer(&ctx) {
-                    Ok(slice) => slice.to_vec(),
-                    Err(_) => return Ok(JSValue::boolean(&ctx, false)),
-                }
-            };
-
-            let sig_obj = match args[1].to_object(&ctx) {
-                Ok(obj) => obj,
-                Err(_) => return Ok(JSValue::boolean(&ctx, false)),
-            };
-
-            let sig_data = unsafe {
-                match sig_obj.get_typed_array_buffer(&ctx) {
-                    Ok(slice) => slice.to_vec(),
-                    Err(_) => return Ok(JSValue::boolean(&ctx, false)),
-                }
+            let Ok(public_key_data) = js_value_to_bytes(&ctx, &args[0]) else {
+                return Ok(JSValue::boolean(&ctx, false));
             };
 
-            let data_obj = match args[2].to_object(&ctx) {
-                Ok(obj) => obj,
-                Err(_) => return Ok(JSValue::boolean(&ctx, false)),
+            let Ok(sig_data) = js_value_to_bytes(&ctx, &args[1]) else {
+                return Ok(JSValue::boolean(&ctx, false));
             };
 
-            let data = unsafe {
-                match data_obj.get_typed_array_buffer(&ctx) {
-                    Ok(slice) => slice.to_vec(),
-                    Err(_) => return Ok(JSValue::boolean(&ctx, false)),
-                }
+            let Ok(data) = js_value_to_bytes(&ctx, &args[2]) else {
+                return Ok(JSValue::boolean(&ctx, false));
             };
 
             // Verify using UnparsedPublicKey
@@ -421,31 +361,9 @@ pub fn setup_crypto(context: &mut JSContext) {
                 Err(_) => return Err(JSValue::string(&ctx, "Hash algorithm must be a string")),
             };
 
-            let key_obj = match args[1].to_object(&ctx) {
-                Ok(obj) => obj,
-                Err(_) => return Err(JSValue::string(&ctx, "Private key must be a Uint8Array")),
-            };
-
-            let private_key_data = unsafe {
-                match key_obj.get_typed_array_buffer(&ctx) {
-                    Ok(slice) => slice.to_vec(),
-                    Err(_) => {
-                        return Err(JSValue::string(&ctx, "Private key must be a Uint8Array"));
-                    }
-                }
-            };
-
-            let data_obj = match args[2].to_object(&ctx) {
-                Ok(obj) => obj,
-                Err(_) => return Err(JSValue::string(&ctx, "Data must be a Uint8Array")),
-            };
+            let private_key_data = js_value_to_bytes(&ctx, &args[1])?;
 
-            let data = unsafe {
-                match data_obj.get_typed_array_buffer(&ctx) {
-                    Ok(slice) => slice.to_vec(),
-                    Err(_) => return Err(JSValue::string(&ctx, "Data must be a Uint8Array")),
-                }
-            };
+            let data = js_value_to_bytes(&ctx, &args[2])?;
 
             // Select padding/encoding based on hash algorithm
             let padding = match hash_algo.as_str() {
@@ -492,40 +410,16 @@ pub fn setup_crypto(context: &mut JSContext) {
                 Err(_) => return Ok(JSValue::boolean(&ctx, false)),
             };
 
-            let public_key_obj = match args[1].to_object(&ctx) {
-                Ok(obj) => obj,
-                Err(_) => return Ok(JSValue::boolean(&ctx, false)),
-            };
-
-            let public_key_data = unsafe {
-                match public_key_obj.get_typed_array_buffer(&ctx) {
-                    Ok(slice) => slice.to_vec(),
-                    Err(_) => return Ok(JSValue::boolean(&ctx, false)),
-                }
-            };
-
-            let sig_obj = match args[2].to_object(&ctx) {
-                Ok(obj) => obj,
-                Err(_) => return Ok(JSValue::boolean(&ctx, false)),
-            };
-
-            let sig_data = unsafe {
-                match sig_obj.get_typed_array_buffer(&ctx) {
-                    Ok(slice) => slice.to_vec(),
-                    Err(_) => return Ok(JSValue::boolean(&ctx, false)),
-                }
+            let Ok(public_key_data) = js_value_to_bytes(&ctx, &args[1]) else {
+                return Ok(JSValue::boolean(&ctx, false));
             };
 
-            let data_obj = match args[3].to_object(&ctx) {
-                Ok(obj) => obj,
-                Err(_) => return Ok(JSValue::boolean(&ctx, false)),
+            let Ok(sig_data) = js_value_to_bytes(&ctx, &args[2]) else {
+                return Ok(JSValue::boolean(&ctx, false));
             };
 
-            let data = unsafe {
-                match data_obj.get_typed_array_buffer(&ctx) {
-                    Ok(slice) => slice.to_vec(),
-                    Err(_) => return Ok(JSValue::boolean(&ctx, false)),
-                }
+            let Ok(data) = js_value_to_bytes(&ctx, &args[3]) else {
+                return Ok(JSValue::boolean(&ctx, false));
             };
 
             // Select verification algorithm based on hash
@@ -543,158 +437,1180 @@ pub fn setup_crypto(context: &mut JSContext) {
         }
     );
 
-    // Add native functions to global
-    let mut global = context.get_global_object();
-    global
-        .set_property(
-            context,
-            "__nativeGetRandomValues",
-            get_random_values_fn.into(),
-        )
-        .unwrap();
-    global
-        .set_property(context, "__nativeRandomUUID", random_uuid_fn.into())
-        .unwrap();
-    global
-        .set_property(context, "__nativeDigest", digest_fn.into())
-        .unwrap();
-    global
-        .set_property(context, "__nativeHmacSign", hmac_sign_fn.into())
-        .unwrap();
-    global
-        .set_property(context, "__nativeHmacVerify", hmac_verify_fn.into())
-        .unwrap();
-    global
-        .set_property(
-            context,
-            "__nativeEcdsaGenerateKey",
-            ecdsa_generate_fn.into(),
-        )
-        .unwrap();
-    global
-        .set_property(context, "__nativeEcdsaSign", ecdsa_sign_fn.into())
-        .unwrap();
-    global
-        .set_property(context, "__nativeEcdsaVerify", ecdsa_verify_fn.into())
-        .unwrap();
-    global
-        .set_property(context, "__nativeRsaSign", rsa_sign_fn.into())
-        .unwrap();
-    global
-        .set_property(context, "__nativeRsaVerify", rsa_verify_fn.into())
-        .unwrap();
+    // Create __nativeRsaPssSign(hashAlgo, privateKeyDer, data) -> ArrayBuffer
+    // ring uses a salt as long as the digest (the WebCrypto saltLength for PSS)
+    let rsa_pss_sign_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            let hash_algo = match args.first().map(|arg| arg.to_js_string(&ctx)) {
+                Some(Ok(s)) => s.to_string().to_uppercase(),
+                _ => return Err(JSValue::string(&ctx, "Hash algorithm must be a string")),
+            };
 
-    // Create crypto object and subtle with JS wrappers
-    let crypto_script = r#"
-        // Create crypto object
-        globalThis.crypto = {
-            getRandomValues: function(typedArray) {
-                return __nativeGetRandomValues(typedArray);
-            },
-            randomUUID: function() {
-                return __nativeRandomUUID();
-            },
-            subtle: {}
-        };
+            let [private_key_data, data] = match read_byte_args::<2>(&ctx, &args[1..]) {
+                Some(bytes) => bytes,
+                None => {
+                    return Err(JSValue::string(
+                        &ctx,
+                        "rsaPssSign requires hashAlgo, privateKey, and data",
+                    ));
+                }
+            };
 
-        // Simple key storage
-        const __cryptoKeys = new Map();
-        let __nextKeyId = 1;
+            let padding = match hash_algo.as_str() {
+                "SHA-256" => &signature::RSA_PSS_SHA256,
+                "SHA-384" => &signature::RSA_PSS_SHA384,
+                "SHA-512" => &signature::RSA_PSS_SHA512,
+                _ => return Err(JSValue::string(&ctx, "Unsupported hash algorithm")),
+            };
 
-        // crypto.subtle.digest(algorithm, data) -> Promise<ArrayBuffer>
-        crypto.subtle.digest = function(algorithm, data) {
-            return new Promise((resolve, reject) => {
-                try {
-                    let bytes;
-                    if (data instanceof ArrayBuffer) {
-                        bytes = new Uint8Array(data);
-                    } else if (data instanceof Uint8Array) {
-                        bytes = data;
-                    } else {
-                        reject(new Error('Data must be ArrayBuffer or Uint8Array'));
-                        return;
-                    }
+            let key_pair = match rsa::KeyPair::from_der(&private_key_data) {
+                Ok(kp) => kp,
+                Err(_) => return Err(JSValue::string(&ctx, "Invalid RSA private key")),
+            };
 
-                    const algoName = typeof algorithm === 'string' ? algorithm : algorithm.name;
-                    const result = __nativeDigest(algoName, bytes);
+            let rng = rand::SystemRandom::new();
+            let mut sig = vec![0u8; key_pair.public().modulus_len()];
 
-                    if (result) {
-                        resolve(result);
-                    } else {
-                        reject(new Error('Unsupported algorithm: ' + algoName));
+            match key_pair.sign(padding, &rng, &data, &mut sig) {
+                Ok(_) => {
+                    let json_array = serde_json::to_string(&sig).unwrap();
+                    let script = format!("new Uint8Array({}).buffer", json_array);
+
+                    match ctx.evaluate_script(&script, 1) {
+                        Ok(buffer) => Ok(buffer),
+                        Err(_) => Err(JSValue::string(&ctx, "Failed to create signature buffer")),
                     }
-                } catch (e) {
-                    reject(e);
                 }
-            });
-        };
+                Err(_) => Err(JSValue::string(&ctx, "RSA-PSS signing failed")),
+            }
+        }
+    );
 
-        // crypto.subtle.generateKey - ECDSA only
-        crypto.subtle.generateKey = function(algorithm, extractable, keyUsages) {
-            return new Promise((resolve, reject) => {
-                try {
-                    const algoName = typeof algorithm === 'string' ? algorithm : algorithm.name;
+    // Create __nativeRsaPssVerify(hashAlgo, publicKeyDer, signature, data) -> boolean
+    let rsa_pss_verify_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            let hash_algo = match args.first().map(|arg| arg.to_js_string(&ctx)) {
+                Some(Ok(s)) => s.to_string().to_uppercase(),
+                _ => return Ok(JSValue::boolean(&ctx, false)),
+            };
 
-                    if (algoName === 'ECDSA') {
-                        const namedCurve = algorithm.namedCurve || 'P-256';
-                        if (namedCurve !== 'P-256') {
-                            reject(new Error('Only P-256 curve is supported'));
-                            return;
-                        }
+            let Some([public_key_data, sig_data, data]) = read_byte_args::<3>(&ctx, &args[1..])
+            else {
+                return Ok(JSValue::boolean(&ctx, false));
+            };
 
-                        const result = __nativeEcdsaGenerateKey();
-                        if (!result) {
-                            reject(new Error('Key generation failed'));
-                            return;
-                        }
+            let algorithm: &dyn signature::VerificationAlgorithm = match hash_algo.as_str() {
+                "SHA-256" => &signature::RSA_PSS_2048_8192_SHA256,
+                "SHA-384" => &signature::RSA_PSS_2048_8192_SHA384,
+                "SHA-512" => &signature::RSA_PSS_2048_8192_SHA512,
+                _ => return Ok(JSValue::boolean(&ctx, false)),
+            };
 
-                        const keyPair = {
-                            privateKey: {
-                                type: 'private',
-                                extractable: extractable,
-                                algorithm: { name: 'ECDSA', namedCurve: 'P-256' },
-                                usages: keyUsages.filter(u => u === 'sign'),
-                                __keyData: new Uint8Array(result.privateKey),
-                                __publicKeyData: new Uint8Array(result.publicKey)
-                            },
-                            publicKey: {
-                                type: 'public',
-                                extractable: true,
-                                algorithm: { name: 'ECDSA', namedCurve: 'P-256' },
-                                usages: keyUsages.filter(u => u === 'verify'),
-                                __keyData: new Uint8Array(result.publicKey)
-                            }
-                        };
+            let public_key = signature::UnparsedPublicKey::new(algorithm, &public_key_data);
+            let is_valid = public_key.verify(&data, &sig_data).is_ok();
 
-                        resolve(keyPair);
-                    } else {
-                        reject(new Error('Only ECDSA algorithm is supported for generateKey'));
-                    }
-                } catch (e) {
-                    reject(e);
+            Ok(JSValue::boolean(&ctx, is_valid))
+        }
+    );
+
+    // Create __nativeRsaOaepEncrypt(hashAlgo, publicKeyDer, data) -> ArrayBuffer
+    let rsa_oaep_encrypt_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            let hash_algo = match args.first().map(|arg| arg.to_js_string(&ctx)) {
+                Some(Ok(s)) => s.to_string().to_uppercase(),
+                _ => return Err(JSValue::string(&ctx, "Hash algorithm must be a string")),
+            };
+
+            let [public_key_data, data] = match read_byte_args::<2>(&ctx, &args[1..]) {
+                Some(bytes) => bytes,
+                None => {
+                    return Err(JSValue::string(
+                        &ctx,
+                        "rsaOaepEncrypt requires hashAlgo, publicKey, and data",
+                    ));
                 }
-            });
-        };
+            };
 
-        // crypto.subtle.importKey - HMAC, ECDSA, RSA
-        crypto.subtle.importKey = function(format, keyData, algorithm, extractable, keyUsages) {
-            return new Promise((resolve, reject) => {
-                try {
-                    const algoName = typeof algorithm === 'string' ? algorithm : algorithm.name;
+            let public_key = match RsaPublicKey::from_public_key_der(&public_key_data)
+                .or_else(|_| RsaPublicKey::from_pkcs1_der(&public_key_data))
+            {
+                Ok(key) => key,
+                Err(_) => return Err(JSValue::string(&ctx, "Invalid RSA public key")),
+            };
 
-                    let keyBytes;
-                    if (keyData instanceof ArrayBuffer) {
-                        keyBytes = new Uint8Array(keyData);
-                    } else if (keyData instanceof Uint8Array) {
-                        keyBytes = keyData;
-                    } else {
-                        reject(new Error('Key data must be ArrayBuffer or Uint8Array'));
-                        return;
-                    }
+            let (padding, hash_len) = match oaep_padding(&hash_algo) {
+                Some(padding) => padding,
+                None => return Err(JSValue::string(&ctx, "Unsupported hash algorithm")),
+            };
 
-                    if (algoName === 'HMAC') {
-                        if (format !== 'raw') {
-                            reject(new Error('Only "raw" format is supported for HMAC'));
+            // OAEP overhead: two hashes plus two bytes
+            let max_len = public_key.size().saturating_sub(2 * hash_len + 2);
+            if data.len() > max_len {
+                let message = format!(
+                    "Plaintext too long for RSA-OAEP: {} bytes (max {})",
+                    data.len(),
+                    max_len
+                );
+                return Err(JSValue::string(&ctx, message.as_str()));
+            }
+
+            let ciphertext = match public_key.encrypt(&mut ::rsa::rand_core::OsRng, padding, &data)
+            {
+                Ok(ciphertext) => ciphertext,
+                Err(_) => return Err(JSValue::string(&ctx, "RSA-OAEP encryption failed")),
+            };
+
+            let json_array = serde_json::to_string(&ciphertext).unwrap();
+            let script = format!("new Uint8Array({}).buffer", json_array);
+
+            match ctx.evaluate_script(&script, 1) {
+                Ok(buffer) => Ok(buffer),
+                Err(_) => Err(JSValue::string(&ctx, "Failed to create ArrayBuffer")),
+            }
+        }
+    );
+
+    // Create __nativeRsaOaepDecrypt(hashAlgo, privateKeyDer, data) -> ArrayBuffer
+    // Known exposure: the rsa crate is affected by RUSTSEC-2023-0071 (Marvin
+    // attack), a timing side channel in private key operations that can leak
+    // enough to recover plaintexts from many observed decryptions. No fixed
+    // release exists yet; don't decrypt attacker-supplied ciphertexts where
+    // timing can be observed, and move to a constant-time implementation once
+    // one ships.
+    let rsa_oaep_decrypt_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            let hash_algo = match args.first().map(|arg| arg.to_js_string(&ctx)) {
+                Some(Ok(s)) => s.to_string().to_uppercase(),
+                _ => return Err(JSValue::string(&ctx, "Hash algorithm must be a string")),
+            };
+
+            let [private_key_data, data] = match read_byte_args::<2>(&ctx, &args[1..]) {
+                Some(bytes) => bytes,
+                None => {
+                    return Err(JSValue::string(
+                        &ctx,
+                        "rsaOaepDecrypt requires hashAlgo, privateKey, and data",
+                    ));
+                }
+            };
+
+            let private_key = match RsaPrivateKey::from_pkcs8_der(&private_key_data)
+                .or_else(|_| RsaPrivateKey::from_pkcs1_der(&private_key_data))
+            {
+                Ok(key) => key,
+                Err(_) => return Err(JSValue::string(&ctx, "Invalid RSA private key")),
+            };
+
+            let (padding, _) = match oaep_padding(&hash_algo) {
+                Some(padding) => padding,
+                None => return Err(JSValue::string(&ctx, "Unsupported hash algorithm")),
+            };
+
+            let plaintext = match private_key.decrypt(padding, &data) {
+                Ok(plaintext) => plaintext,
+                Err(_) => return Err(JSValue::string(&ctx, "RSA-OAEP decryption failed")),
+            };
+
+            let json_array = serde_json::to_string(&plaintext).unwrap();
+            let script = format!("new Uint8Array({}).buffer", json_array);
+
+            match ctx.evaluate_script(&script, 1) {
+                Ok(buffer) => Ok(buffer),
+                Err(_) => Err(JSValue::string(&ctx, "Failed to create ArrayBuffer")),
+            }
+        }
+    );
+
+    // Create __nativeAesGcmEncrypt(keyData, iv, data, additionalData) -> ArrayBuffer
+    // The result is the ciphertext followed by the 128-bit tag
+    let aes_gcm_encrypt_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            let [key_data, iv, mut data, aad] = match read_byte_args::<4>(&ctx, args) {
+                Some(bytes) => bytes,
+                None => {
+                    return Err(JSValue::string(
+                        &ctx,
+                        "aesGcmEncrypt requires keyData, iv, data and additionalData",
+                    ));
+                }
+            };
+
+            let (key, nonce) = match aes_gcm_key(&key_data, &iv) {
+                Ok(key) => key,
+                Err(e) => return Err(JSValue::string(&ctx, e)),
+            };
+
+            if key
+                .seal_in_place_append_tag(nonce, aead::Aad::from(aad), &mut data)
+                .is_err()
+            {
+                return Err(JSValue::string(&ctx, "AES-GCM encryption failed"));
+            }
+
+            let json_str = serde_json::to_string(&data).unwrap();
+            let script = format!("new Uint8Array({}).buffer", json_str);
+
+            match ctx.evaluate_script(&script, 1) {
+                Ok(buffer) => Ok(buffer),
+                Err(_) => Err(JSValue::string(&ctx, "Failed to create ArrayBuffer")),
+            }
+        }
+    );
+
+    // Create __nativeAesGcmDecrypt(keyData, iv, data, additionalData) -> ArrayBuffer
+    let aes_gcm_decrypt_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            let [key_data, iv, mut data, aad] = match read_byte_args::<4>(&ctx, args) {
+                Some(bytes) => bytes,
+                None => {
+                    return Err(JSValue::string(
+                        &ctx,
+                        "aesGcmDecrypt requires keyData, iv, data and additionalData",
+                    ));
+                }
+            };
+
+            let (key, nonce) = match aes_gcm_key(&key_data, &iv) {
+                Ok(key) => key,
+                Err(e) => return Err(JSValue::string(&ctx, e)),
+            };
+
+            // Fails on a wrong key, IV, additional data or a tampered ciphertext
+            let plaintext = match key.open_in_place(nonce, aead::Aad::from(aad), &mut data) {
+                Ok(plaintext) => plaintext.to_vec(),
+                Err(_) => return Err(JSValue::string(&ctx, "AES-GCM decryption failed")),
+            };
+
+            let json_str = serde_json::to_string(&plaintext).unwrap();
+            let script = format!("new Uint8Array({}).buffer", json_str);
+
+            match ctx.evaluate_script(&script, 1) {
+                Ok(buffer) => Ok(buffer),
+                Err(_) => Err(JSValue::string(&ctx, "Failed to create ArrayBuffer")),
+            }
+        }
+    );
+
+    // Create __nativeAesKwWrap(keyData, data) -> ArrayBuffer
+    let aes_kw_wrap_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            let [key_data, data] = match read_byte_args::<2>(&ctx, args) {
+                Some(bytes) => bytes,
+                None => return Err(JSValue::string(&ctx, "aesKwWrap requires keyData and data")),
+            };
+
+            let wrapped = match aes_kw(&key_data, &data, true) {
+                Ok(wrapped) => wrapped,
+                Err(e) => return Err(JSValue::string(&ctx, e)),
+            };
+
+            let json_str = serde_json::to_string(&wrapped).unwrap();
+            let script = format!("new Uint8Array({}).buffer", json_str);
+
+            match ctx.evaluate_script(&script, 1) {
+                Ok(buffer) => Ok(buffer),
+                Err(_) => Err(JSValue::string(&ctx, "Failed to create ArrayBuffer")),
+            }
+        }
+    );
+
+    // Create __nativeAesKwUnwrap(keyData, data) -> ArrayBuffer
+    let aes_kw_unwrap_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            let [key_data, data] = match read_byte_args::<2>(&ctx, args) {
+                Some(bytes) => bytes,
+                None => {
+                    return Err(JSValue::string(
+                        &ctx,
+                        "aesKwUnwrap requires keyData and data",
+                    ));
+                }
+            };
+
+            // Fails on a wrong key or a tampered wrapped key (integrity check)
+            let unwrapped = match aes_kw(&key_data, &data, false) {
+                Ok(unwrapped) => unwrapped,
+                Err(e) => return Err(JSValue::string(&ctx, e)),
+            };
+
+            let json_str = serde_json::to_string(&unwrapped).unwrap();
+            let script = format!("new Uint8Array({}).buffer", json_str);
+
+            match ctx.evaluate_script(&script, 1) {
+                Ok(buffer) => Ok(buffer),
+                Err(_) => Err(JSValue::string(&ctx, "Failed to create ArrayBuffer")),
+            }
+        }
+    );
+
+    // Incremental digests, referenced from JS by handle
+    let digests = Arc::new(Mutex::new(DigestContexts::default()));
+
+    // Create __nativeDigestInit(algorithm) -> handle
+    let digests_init = digests.clone();
+    let digest_init_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            let algo = match args.first().map(|arg| arg.to_js_string(&ctx)) {
+                Some(Ok(s)) => s.to_string().to_uppercase(),
+                _ => return Err(JSValue::string(&ctx, "Algorithm must be a string")),
+            };
+
+            let algorithm = match digest_algorithm(&algo) {
+                Some(algorithm) => algorithm,
+                None => return Err(JSValue::string(&ctx, "Unsupported algorithm")),
+            };
+
+            let handle = digests_init
+                .lock()
+                .unwrap()
+                .insert(HashContext::Digest(digest::Context::new(algorithm)));
+            Ok(JSValue::number(&ctx, handle as f64))
+        }
+    );
+
+    // Create __nativeHmacInit(hash, keyData) -> handle (fed through
+    // __nativeDigestUpdate, finished by __nativeDigestFinal)
+    let digests_hmac = digests.clone();
+    let hmac_init_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            if args.len() < 2 {
+                return Err(JSValue::string(&ctx, "hmacInit requires hash and keyData"));
+            }
+
+            let algo = match args[0].to_js_string(&ctx) {
+                Ok(s) => s.to_string().to_uppercase(),
+                Err(_) => return Err(JSValue::string(&ctx, "Algorithm must be a string")),
+            };
+
+            let key_data = js_value_to_bytes(&ctx, &args[1])?;
+
+            let algorithm = match algo.as_str() {
+                "SHA-1" => hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY,
+                "SHA-256" => hmac::HMAC_SHA256,
+                "SHA-384" => hmac::HMAC_SHA384,
+                "SHA-512" => hmac::HMAC_SHA512,
+                _ => return Err(JSValue::string(&ctx, "Unsupported HMAC algorithm")),
+            };
+
+            let key = hmac::Key::new(algorithm, &key_data);
+            let handle = digests_hmac
+                .lock()
+                .unwrap()
+                .insert(HashContext::Hmac(hmac::Context::with_key(&key)));
+            Ok(JSValue::number(&ctx, handle as f64))
+        }
+    );
+
+    // Create __nativeDigestUpdate(handle, chunk)
+    let digests_update = digests.clone();
+    let digest_update_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            if args.len() < 2 {
+                return Err(JSValue::string(
+                    &ctx,
+                    "digestUpdate requires handle and data",
+                ));
+            }
+
+            let handle = match args[0].to_number(&ctx) {
+                Ok(n) => n as u64,
+                Err(_) => return Err(JSValue::string(&ctx, "Invalid digest handle")),
+            };
+
+            // Hash straight from the typed array, no copy
+            let data = unsafe { js_value_bytes_mut(&ctx, &args[1])? };
+
+            if !digests_update.lock().unwrap().update(handle, data) {
+                return Err(JSValue::string(&ctx, "Unknown digest handle"));
+            }
+
+            Ok(JSValue::undefined(&ctx))
+        }
+    );
+
+    // Create __nativeDigestFinal(handle) -> ArrayBuffer
+    let digests_final = digests;
+    let digest_final_fn = rusty_jsc::callback_closure!(
+        context,
+        move |mut ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            let handle = match args.first().map(|arg| arg.to_number(&ctx)) {
+                Some(Ok(n)) => n as u64,
+                _ => return Err(JSValue::string(&ctx, "Invalid digest handle")),
+            };
+
+            let result = match digests_final.lock().unwrap().finish(handle) {
+                Some(result) => result,
+                None => return Err(JSValue::string(&ctx, "Unknown digest handle")),
+            };
+
+            let json_str = serde_json::to_string(&result).unwrap();
+            let script = format!("new Uint8Array({}).buffer", json_str);
+
+            match ctx.evaluate_script(&script, 1) {
+                Ok(buffer) => Ok(buffer),
+                Err(_) => Err(JSValue::string(&ctx, "Failed to create ArrayBuffer")),
+            }
+        }
+    );
+
+    // Create __nativeEcdhGenerateKey(namedCurve) -> { privateKey, publicKey, d }
+    let ecdh_generate_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            let curve = match args.first().and_then(|v| v.to_js_string(&ctx).ok()) {
+                Some(s) => s.to_string(),
+                None => return Err(JSValue::string(&ctx, "ecdhGenerateKey requires a curve")),
+            };
+
+            match ecdh_key_pair(&curve, EcdhSecret::Generate) {
+                Ok(pair) => ecdh_key_pair_object(&ctx, &pair),
+                Err(e) => Err(JSValue::string(&ctx, e)),
+            }
+        }
+    );
+
+    // Create __nativeEcdhPrivateKey(namedCurve, format, bytes) -> { privateKey, publicKey, d }
+    // format is "pkcs8" (DER) or "d" (the raw private scalar, as in a JWK)
+    let ecdh_private_key_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            if args.len() < 3 {
+                return Err(JSValue::string(
+                    &ctx,
+                    "ecdhPrivateKey requires namedCurve, format and keyData",
+                ));
+            }
+
+            let (curve, format) = match (args[0].to_js_string(&ctx), args[1].to_js_string(&ctx)) {
+                (Ok(curve), Ok(format)) => (curve.to_string(), format.to_string()),
+                _ => return Err(JSValue::string(&ctx, "Invalid curve or format")),
+            };
+
+            let key_data = match read_byte_args::<1>(&ctx, &args[2..]) {
+                Some([bytes]) => bytes,
+                None => return Err(JSValue::string(&ctx, "Invalid key data")),
+            };
+
+            let secret = match format.as_str() {
+                "pkcs8" => EcdhSecret::Pkcs8(&key_data),
+                "d" => EcdhSecret::Scalar(&key_data),
+                _ => return Err(JSValue::string(&ctx, "Unsupported ECDH key format")),
+            };
+
+            match ecdh_key_pair(&curve, secret) {
+                Ok(pair) => ecdh_key_pair_object(&ctx, &pair),
+                Err(e) => Err(JSValue::string(&ctx, e)),
+            }
+        }
+    );
+
+    // Create __nativeEcdhDeriveBits(namedCurve, privateKeyPkcs8, peerPublicKey) -> ArrayBuffer
+    let ecdh_derive_bits_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            if args.len() < 3 {
+                return Err(JSValue::string(
+                    &ctx,
+                    "ecdhDeriveBits requires namedCurve, privateKey and publicKey",
+                ));
+            }
+
+            let curve = match args[0].to_js_string(&ctx) {
+                Ok(s) => s.to_string(),
+                Err(_) => return Err(JSValue::string(&ctx, "Invalid curve")),
+            };
+
+            let [private_key, peer_public_key] = match read_byte_args::<2>(&ctx, &args[1..]) {
+                Some(bytes) => bytes,
+                None => return Err(JSValue::string(&ctx, "Invalid private or public key")),
+            };
+
+            let secret = match ecdh_derive(&curve, &private_key, &peer_public_key) {
+                Ok(secret) => secret,
+                Err(e) => return Err(JSValue::string(&ctx, e)),
+            };
+
+            let json_array = serde_json::to_string(&secret).unwrap();
+            let script = format!("new Uint8Array({}).buffer", json_array);
+
+            match ctx.evaluate_script(&script, 1) {
+                Ok(buffer) => Ok(buffer),
+                Err(_) => Err(JSValue::string(&ctx, "Failed to create ArrayBuffer")),
+            }
+        }
+    );
+
+    // Add native functions to global
+    let mut global = context.get_global_object();
+    global
+        .set_property(
+            context,
+            "__nativeGetRandomValues",
+            get_random_values_fn.into(),
+        )
+        .unwrap();
+    global
+        .set_property(context, "__nativeRandomUUID", random_uuid_fn.into())
+        .unwrap();
+    global
+        .set_property(context, "__nativeDigest", digest_fn.into())
+        .unwrap();
+    global
+        .set_property(context, "__nativeDigestInit", digest_init_fn.into())
+        .unwrap();
+    global
+        .set_property(context, "__nativeDigestUpdate", digest_update_fn.into())
+        .unwrap();
+    global
+        .set_property(context, "__nativeDigestFinal", digest_final_fn.into())
+        .unwrap();
+    global
+        .set_property(context, "__nativeHmacInit", hmac_init_fn.into())
+        .unwrap();
+    global
+        .set_property(context, "__nativeHmacSign", hmac_sign_fn.into())
+        .unwrap();
+    global
+        .set_property(context, "__nativeHmacVerify", hmac_verify_fn.into())
+        .unwrap();
+    global
+        .set_property(
+            context,
+            "__nativeEcdsaGenerateKey",
+            ecdsa_generate_fn.into(),
+        )
+        .unwrap();
+    global
+        .set_property(context, "__nativeEcdsaSign", ecdsa_sign_fn.into())
+        .unwrap();
+    global
+        .set_property(context, "__nativeEcdsaVerify", ecdsa_verify_fn.into())
+        .unwrap();
+    global
+        .set_property(context, "__nativeRsaSign", rsa_sign_fn.into())
+        .unwrap();
+    global
+        .set_property(context, "__nativeRsaVerify", rsa_verify_fn.into())
+        .unwrap();
+
+    global
+        .set_property(context, "__nativeRsaPssSign", rsa_pss_sign_fn.into())
+        .unwrap();
+    global
+        .set_property(context, "__nativeRsaPssVerify", rsa_pss_verify_fn.into())
+        .unwrap();
+    global
+        .set_property(
+            context,
+            "__nativeRsaOaepEncrypt",
+            rsa_oaep_encrypt_fn.into(),
+        )
+        .unwrap();
+    global
+        .set_property(
+            context,
+            "__nativeRsaOaepDecrypt",
+            rsa_oaep_decrypt_fn.into(),
+        )
+        .unwrap();
+    global
+        .set_property(context, "__nativeAesGcmEncrypt", aes_gcm_encrypt_fn.into())
+        .unwrap();
+    global
+        .set_property(context, "__nativeAesGcmDecrypt", aes_gcm_decrypt_fn.into())
+        .unwrap();
+    global
+        .set_property(context, "__nativeAesKwWrap", aes_kw_wrap_fn.into())
+        .unwrap();
+    global
+        .set_property(context, "__nativeAesKwUnwrap", aes_kw_unwrap_fn.into())
+        .unwrap();
+    global
+        .set_property(context, "__nativeEcdhGenerateKey", ecdh_generate_fn.into())
+        .unwrap();
+    global
+        .set_property(
+            context,
+            "__nativeEcdhPrivateKey",
+            ecdh_private_key_fn.into(),
+        )
+        .unwrap();
+    global
+        .set_property(
+            context,
+            "__nativeEcdhDeriveBits",
+            ecdh_derive_bits_fn.into(),
+        )
+        .unwrap();
+
+    // Create crypto object and subtle with JS wrappers
+    let crypto_script = r#"
+        // Create crypto object
+        globalThis.crypto = {
+            getRandomValues: function(typedArray) {
+                return __nativeGetRandomValues(typedArray);
+            },
+            randomUUID: function() {
+                return __nativeRandomUUID();
+            },
+            subtle: {}
+        };
+
+        // Simple key storage
+        const __cryptoKeys = new Map();
+        let __nextKeyId = 1;
+
+        // DOMException-like error
+        const __cryptoError = (name, message) => {
+            const error = new Error(message);
+            error.name = name;
+            return error;
+        };
+
+        const __createAesKey = (algoName, keyBytes, extractable, keyUsages) => {
+            const keyId = __nextKeyId++;
+            const key = {
+                type: 'secret',
+                extractable: extractable,
+                algorithm: { name: algoName, length: keyBytes.length * 8 },
+                usages: keyUsages,
+                __keyId: keyId,
+                __keyData: keyBytes
+            };
+
+            __cryptoKeys.set(keyId, key);
+            return key;
+        };
+
+        // Run AES-GCM with a key and algorithm params ({ name, iv, additionalData, tagLength })
+        const __aesGcm = (native, algorithm, key, bytes) => {
+            if (!key.__keyData || key.algorithm.name !== 'AES-GCM') {
+                throw __cryptoError('InvalidAccessError', 'Key is not an AES-GCM key');
+            }
+            if (algorithm.tagLength !== undefined && algorithm.tagLength !== 128) {
+                throw __cryptoError('NotSupportedError', 'Only 128-bit AES-GCM tags are supported');
+            }
+
+            const iv = __toBytes(algorithm.iv, 'iv');
+            const additionalData = algorithm.additionalData === undefined
+                ? new Uint8Array(0)
+                : __toBytes(algorithm.additionalData, 'additionalData');
+
+            try {
+                return native(key.__keyData, iv, bytes, additionalData);
+            } catch (e) {
+                throw __cryptoError('OperationError', String(e));
+            }
+        };
+
+        // Wrap or unwrap key bytes with an AES-KW key (RFC 3394)
+        const __aesKw = (native, key, bytes) => {
+            if (!key.__keyData || key.algorithm.name !== 'AES-KW') {
+                throw __cryptoError('InvalidAccessError', 'Key is not an AES-KW key');
+            }
+
+            try {
+                return native(key.__keyData, bytes);
+            } catch (e) {
+                throw __cryptoError('OperationError', String(e));
+            }
+        };
+
+        const __toBytes = (data, name) => {
+            if (data instanceof ArrayBuffer) {
+                return new Uint8Array(data);
+            }
+            if (ArrayBuffer.isView(data)) {
+                return new Uint8Array(data.buffer, data.byteOffset, data.byteLength);
+            }
+            throw new TypeError(name + ' must be ArrayBuffer or ArrayBufferView');
+        };
+
+        // Export key material (shared by exportKey and wrapKey)
+        const __exportKey = (format, key) => {
+            if (!key || !key.__keyData) {
+                throw new Error('Invalid key');
+            }
+            if (!key.extractable) {
+                throw __cryptoError('InvalidAccessError', 'Key is not extractable');
+            }
+
+            if (format === 'jwk') {
+                return __exportJwk(key);
+            }
+            if (format === 'raw' && (key.type === 'secret' || (key.algorithm.name === 'ECDSA' || key.algorithm.name === 'ECDH') && key.type === 'public')) {
+                return key.__keyData.slice().buffer;
+            }
+            if (format === 'pkcs8' && key.type === 'private') {
+                return key.__keyData.slice().buffer;
+            }
+
+            throw __cryptoError('NotSupportedError', 'Unsupported export format "' + format + '" for ' + key.algorithm.name + ' ' + key.type + ' key');
+        };
+
+        // JWK "alg" suffixes for HMAC (HS*) hashes
+        const __jwkHashes = { 'SHA-1': '1', 'SHA-256': '256', 'SHA-384': '384', 'SHA-512': '512' };
+
+        // PKCS#8 layout of a P-256 private key, as generated by ring:
+        // prefix || d (32 bytes) || middle || uncompressed public point (65 bytes)
+        const __p256Pkcs8Prefix = [
+            0x30, 0x81, 0x87, 0x02, 0x01, 0x00, 0x30, 0x13, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02,
+            0x01, 0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07, 0x04, 0x6d, 0x30, 0x6b, 0x02,
+            0x01, 0x01, 0x04, 0x20
+        ];
+        const __p256Pkcs8Middle = [0xa1, 0x44, 0x03, 0x42, 0x00];
+
+        const __fromBase64Url = (value, member) => {
+            if (typeof value !== 'string') {
+                throw __cryptoError('DataError', 'JWK member "' + member + '" is missing');
+            }
+            try {
+                return Uint8Array.fromBase64(value, { alphabet: 'base64url' });
+            } catch (e) {
+                throw __cryptoError('DataError', 'JWK member "' + member + '" is not base64url');
+            }
+        };
+
+        const __toBase64Url = (bytes) => bytes.toBase64({ alphabet: 'base64url', omitPadding: true });
+
+        // ECDH private key object from a native { privateKey, publicKey } pair
+        const __createEcdhPrivateKey = (namedCurve, pair, extractable, keyUsages) => ({
+            type: 'private',
+            extractable: extractable,
+            algorithm: { name: 'ECDH', namedCurve: namedCurve },
+            usages: keyUsages.filter(u => u === 'deriveBits' || u === 'deriveKey'),
+            __keyData: new Uint8Array(pair.privateKey),
+            __publicKeyData: new Uint8Array(pair.publicKey)
+        });
+
+        // Parse an ECDH private key natively (format "pkcs8" or "d")
+        const __ecdhPrivateKey = (namedCurve, format, bytes) => {
+            try {
+                return __nativeEcdhPrivateKey(namedCurve, format, bytes);
+            } catch (e) {
+                throw __cryptoError('DataError', String(e));
+            }
+        };
+
+        // Import a JWK (HMAC "oct", ECDSA/ECDH "EC") into a key object
+        const __importJwk = (jwk, algorithm, extractable, keyUsages) => {
+            if (!jwk || typeof jwk !== 'object' || ArrayBuffer.isView(jwk) || jwk instanceof ArrayBuffer) {
+                throw new TypeError('JWK key data must be an object');
+            }
+
+            const algoName = typeof algorithm === 'string' ? algorithm : algorithm.name;
+            if (jwk.ext === false && extractable) {
+                throw __cryptoError('DataError', 'JWK is not extractable');
+            }
+
+            // Usages default to the JWK key_ops, and must be allowed by them
+            const usages = keyUsages && keyUsages.length ? keyUsages : (jwk.key_ops || []);
+            if (Array.isArray(jwk.key_ops) && usages.some(u => !jwk.key_ops.includes(u))) {
+                throw __cryptoError('DataError', 'Key usages are not allowed by JWK key_ops');
+            }
+
+            if (algoName === 'HMAC') {
+                if (jwk.kty !== 'oct') {
+                    throw __cryptoError('DataError', 'HMAC JWK must have kty "oct"');
+                }
+
+                const hash = typeof algorithm === 'object' ? algorithm.hash : undefined;
+                const hashName = typeof hash === 'string' ? hash : hash && hash.name;
+                const jwkHash = Object.keys(__jwkHashes).find(h => 'HS' + __jwkHashes[h] === jwk.alg);
+                if (jwk.alg !== undefined && (!jwkHash || hashName && hashName !== jwkHash)) {
+                    throw __cryptoError('DataError', 'JWK alg "' + jwk.alg + '" does not match the HMAC hash');
+                }
+
+                const keyId = __nextKeyId++;
+                const key = {
+                    type: 'secret',
+                    extractable: extractable,
+                    algorithm: { name: 'HMAC', hash: { name: hashName || jwkHash || 'SHA-256' } },
+                    usages: usages,
+                    __keyId: keyId,
+                    __keyData: __fromBase64Url(jwk.k, 'k')
+                };
+
+                __cryptoKeys.set(keyId, key);
+                return key;
+            }
+
+            if (algoName === 'ECDSA' || algoName === 'ECDH') {
+                if (jwk.kty !== 'EC') {
+                    throw __cryptoError('DataError', algoName + ' JWK must have kty "EC"');
+                }
+
+                const namedCurve = (typeof algorithm === 'object' && algorithm.namedCurve) || jwk.crv;
+                if (jwk.crv !== namedCurve) {
+                    throw __cryptoError('DataError', 'JWK crv "' + jwk.crv + '" does not match ' + namedCurve);
+                }
+                const size = { 'P-256': 32, 'P-384': 48 }[namedCurve];
+                if (!size || algoName === 'ECDSA' && namedCurve !== 'P-256') {
+                    throw __cryptoError('NotSupportedError', 'Unsupported ' + algoName + ' curve: ' + namedCurve);
+                }
+                if (algoName === 'ECDSA' && jwk.alg !== undefined && jwk.alg !== 'ES256') {
+                    throw __cryptoError('DataError', 'JWK alg "' + jwk.alg + '" does not match ECDSA P-256');
+                }
+
+                const x = __fromBase64Url(jwk.x, 'x');
+                const y = __fromBase64Url(jwk.y, 'y');
+                if (x.length !== size || y.length !== size) {
+                    throw __cryptoError('DataError', 'Invalid ' + namedCurve + ' JWK coordinates');
+                }
+
+                const point = new Uint8Array(1 + 2 * size);
+                point[0] = 0x04;
+                point.set(x, 1);
+                point.set(y, 1 + size);
+
+                if (jwk.d === undefined) {
+                    return {
+                        type: 'public',
+                        extractable: extractable,
+                        algorithm: { name: algoName, namedCurve: namedCurve },
+                        usages: algoName === 'ECDH' ? [] : usages,
+                        __keyData: point
+                    };
+                }
+
+                const d = __fromBase64Url(jwk.d, 'd');
+                if (d.length !== size) {
+                    throw __cryptoError('DataError', 'Invalid ' + namedCurve + ' JWK private key');
+                }
+
+                if (algoName === 'ECDH') {
+                    const pair = __ecdhPrivateKey(namedCurve, 'd', d);
+                    const derived = new Uint8Array(pair.publicKey);
+                    if (derived.length !== point.length || derived.some((b, i) => b !== point[i])) {
+                        throw __cryptoError('DataError', 'JWK x and y do not match the private key');
+                    }
+                    return __createEcdhPrivateKey(namedCurve, pair, extractable, usages);
+                }
+
+                const pkcs8 = new Uint8Array(__p256Pkcs8Prefix.length + size + __p256Pkcs8Middle.length + point.length);
+                pkcs8.set(__p256Pkcs8Prefix, 0);
+                pkcs8.set(d, __p256Pkcs8Prefix.length);
+                pkcs8.set(__p256Pkcs8Middle, __p256Pkcs8Prefix.length + size);
+                pkcs8.set(point, __p256Pkcs8Prefix.length + size + __p256Pkcs8Middle.length);
+
+                return {
+                    type: 'private',
+                    extractable: extractable,
+                    algorithm: { name: 'ECDSA', namedCurve: namedCurve },
+                    usages: usages,
+                    __keyData: pkcs8,
+                    __publicKeyData: point
+                };
+            }
+
+            throw __cryptoError('NotSupportedError', 'JWK import is not supported for ' + algoName);
+        };
+
+        // Export a key object as a JWK (called once extractability is checked)
+        const __exportJwk = (key) => {
+            const common = { key_ops: key.usages.slice(), ext: key.extractable };
+            const algoName = key.algorithm.name;
+
+            if (algoName === 'HMAC') {
+                return Object.assign({
+                    kty: 'oct',
+                    k: __toBase64Url(key.__keyData),
+                    alg: 'HS' + __jwkHashes[key.algorithm.hash.name]
+                }, common);
+            }
+            if (algoName === 'AES-GCM' || algoName === 'AES-KW') {
+                return Object.assign({
+                    kty: 'oct',
+                    k: __toBase64Url(key.__keyData),
+                    alg: 'A' + key.algorithm.length + (algoName === 'AES-GCM' ? 'GCM' : 'KW')
+                }, common);
+            }
+
+            if (algoName === 'ECDSA' || algoName === 'ECDH') {
+                let point = key.__keyData;
+                let d;
+
+                if (key.type === 'private' && algoName === 'ECDH') {
+                    const pair = __ecdhPrivateKey(key.algorithm.namedCurve, 'pkcs8', key.__keyData);
+                    d = new Uint8Array(pair.d);
+                    point = new Uint8Array(pair.publicKey);
+                } else if (key.type === 'private') {
+                    // Only the fixed P-256 layout is understood, without a DER parser
+                    const bytes = key.__keyData;
+                    const middleAt = __p256Pkcs8Prefix.length + 32;
+                    const layoutMatches = bytes.length === middleAt + __p256Pkcs8Middle.length + 65
+                        && __p256Pkcs8Prefix.every((b, i) => bytes[i] === b)
+                        && __p256Pkcs8Middle.every((b, i) => bytes[middleAt + i] === b);
+                    if (!layoutMatches) {
+                        throw __cryptoError('NotSupportedError', 'This PKCS#8 key cannot be exported as a JWK');
+                    }
+
+                    d = bytes.slice(__p256Pkcs8Prefix.length, middleAt);
+                    point = bytes.slice(middleAt + __p256Pkcs8Middle.length);
+                }
+
+                const size = (point.length - 1) / 2;
+                const jwk = {
+                    kty: 'EC',
+                    crv: key.algorithm.namedCurve,
+                    x: __toBase64Url(point.slice(1, 1 + size)),
+                    y: __toBase64Url(point.slice(1 + size))
+                };
+                if (d) {
+                    jwk.d = __toBase64Url(d);
+                }
+                return Object.assign(jwk, common);
+            }
+
+            throw __cryptoError('NotSupportedError', 'JWK export is not supported for ' + algoName);
+        };
+
+        // Run RSA-OAEP with a key of the expected type ({ name, label })
+        const __rsaOaep = (native, keyType, algorithm, key, bytes) => {
+            if (!key.__keyData || key.algorithm.name !== 'RSA-OAEP' || key.type !== keyType) {
+                throw __cryptoError('InvalidAccessError', 'Key is not an RSA-OAEP ' + keyType + ' key');
+            }
+            if (typeof algorithm === 'object' && algorithm.label !== undefined
+                && __toBytes(algorithm.label, 'label').length > 0) {
+                throw __cryptoError('NotSupportedError', 'RSA-OAEP labels are not supported');
+            }
+
+            try {
+                return native(key.algorithm.hash.name, key.__keyData, bytes);
+            } catch (e) {
+                throw __cryptoError('OperationError', String(e));
+            }
+        };
+
+        // RSA-PSS salts are as long as the digest (the only length ring supports)
+        const __checkPssSaltLength = (algorithm, hashName) => {
+            const expected = { 'SHA-256': 32, 'SHA-384': 48, 'SHA-512': 64 }[hashName];
+            const saltLength = typeof algorithm === 'object' ? algorithm.saltLength : undefined;
+            if (saltLength !== undefined && saltLength !== expected) {
+                throw __cryptoError('NotSupportedError',
+                    'RSA-PSS with ' + hashName + ' only supports saltLength ' + expected);
+            }
+        };
+
+        // ECDH shared secret truncated to length bits (null for the full secret)
+        const __ecdhDeriveBits = (algorithm, baseKey, length) => {
+            const publicKey = algorithm.public;
+            if (!baseKey || baseKey.algorithm.name !== 'ECDH' || baseKey.type !== 'private') {
+                throw __cryptoError('InvalidAccessError', 'Base key is not an ECDH private key');
+            }
+            if (!publicKey || !publicKey.__keyData || publicKey.algorithm.name !== 'ECDH' || publicKey.type !== 'public') {
+                throw __cryptoError('InvalidAccessError', 'algorithm.public is not an ECDH public key');
+            }
+            if (publicKey.algorithm.namedCurve !== baseKey.algorithm.namedCurve) {
+                throw __cryptoError('InvalidAccessError', 'ECDH keys use different curves');
+            }
+
+            let secret;
+            try {
+                secret = new Uint8Array(__nativeEcdhDeriveBits(baseKey.algorithm.namedCurve,
+                    baseKey.__keyData, publicKey.__keyData));
+            } catch (e) {
+                throw __cryptoError('OperationError', String(e));
+            }
+
+            if (length === null || length === undefined) {
+                return secret.buffer;
+            }
+            if (length % 8 !== 0 || length > secret.length * 8) {
+                throw __cryptoError('OperationError', 'Invalid ECDH length: ' + length);
+            }
+            return secret.slice(0, length / 8).buffer;
+        };
+
+        // Wrapping algorithm check (AES-GCM or AES-KW); returns the algorithm name
+        const __checkWrappingKey = (algorithm, key, usage) => {
+            const algoName = typeof algorithm === 'string' ? algorithm : algorithm.name;
+            if (algoName !== 'AES-GCM' && algoName !== 'AES-KW') {
+                throw __cryptoError('NotSupportedError', 'Unsupported wrapping algorithm: ' + algoName);
+            }
+            if (!key.usages || !key.usages.includes(usage)) {
+                throw __cryptoError('InvalidAccessError', 'Key does not allow ' + usage);
+            }
+            return algoName;
+        };
+
+        // crypto.subtle.digest(algorithm, data) -> Promise<ArrayBuffer>
+        // Non-standard: a ReadableStream (e.g. a Request or Response body) is
+        // hashed chunk by chunk, as with digestStream
+        crypto.subtle.digest = function(algorithm, data) {
+            if (data instanceof ReadableStream) {
+                return crypto.subtle.digestStream(algorithm, data);
+            }
+
+            return new Promise((resolve, reject) => {
+                try {
+                    const bytes = __toBytes(data, 'Data');
+                    const algoName = typeof algorithm === 'string' ? algorithm : algorithm.name;
+                    const result = __nativeDigest(algoName, bytes);
+
+                    if (result) {
+                        resolve(result);
+                    } else {
+                        reject(new Error('Unsupported algorithm: ' + algoName));
+                    }
+                } catch (e) {
+                    reject(e);
+                }
+            });
+        };
+
+        // crypto.subtle.digestStream(algorithm, stream) -> Promise<ArrayBuffer>
+        // Non-standard: hashes a ReadableStream chunk by chunk without buffering it
+        crypto.subtle.digestStream = async function(algorithm, stream) {
+            const algoName = typeof algorithm === 'string' ? algorithm : algorithm.name;
+            if (!stream || typeof stream.getReader !== 'function') {
+                throw new TypeError('digestStream expects a ReadableStream');
+            }
+
+            return __hashStream(__nativeDigestInit(algoName), stream);
+        };
+
+        // Feed a stream into a native digest or HMAC context, one chunk at a
+        // time, and finish it
+        const __hashStream = async function(handle, stream) {
+            const reader = stream.getReader();
+
+            try {
+                while (true) {
+                    const { done, value } = await reader.read();
+                    if (done) {
+                        break;
+                    }
+
+                    const bytes = typeof value === 'string'
+                        ? new TextEncoder().encode(value)
+                        : __toBytes(value, 'Stream chunk');
+                    if (bytes.length > 0) {
+                        __nativeDigestUpdate(handle, bytes);
+                    }
+                }
+            } catch (e) {
+                // Release the native context
+                __nativeDigestFinal(handle);
+                reader.releaseLock();
+                throw e;
+            }
+
+            reader.releaseLock();
+            return __nativeDigestFinal(handle);
+        };
+
+        // crypto.subtle.generateKey - ECDSA, ECDH, AES-GCM, AES-KW
+        crypto.subtle.generateKey = function(algorithm, extractable, keyUsages) {
+            return new Promise((resolve, reject) => {
+                try {
+                    const algoName = typeof algorithm === 'string' ? algorithm : algorithm.name;
+
+                    if (algoName === 'ECDSA') {
+                        const namedCurve = algorithm.namedCurve || 'P-256';
+                        if (namedCurve !== 'P-256') {
+                            reject(new Error('Only P-256 curve is supported'));
+                            return;
+                        }
+
+                        const result = __nativeEcdsaGenerateKey();
+                        if (!result) {
+                            reject(new Error('Key generation failed'));
+                            return;
+                        }
+
+                        const keyPair = {
+                            privateKey: {
+                                type: 'private',
+                                extractable: extractable,
+                                algorithm: { name: 'ECDSA', namedCurve: 'P-256' },
+                                usages: keyUsages.filter(u => u === 'sign'),
+                                __keyData: new Uint8Array(result.privateKey),
+                                __publicKeyData: new Uint8Array(result.publicKey)
+                            },
+                            publicKey: {
+                                type: 'public',
+                                extractable: true,
+                                algorithm: { name: 'ECDSA', namedCurve: 'P-256' },
+                                usages: keyUsages.filter(u => u === 'verify'),
+                                __keyData: new Uint8Array(result.publicKey)
+                            }
+                        };
+
+                        resolve(keyPair);
+                    } else if (algoName === 'AES-GCM') {
+                        const length = algorithm.length;
+                        if (length !== 128 && length !== 256) {
+                            reject(new Error('AES-GCM key length must be 128 or 256'));
+                            return;
+                        }
+
+                        const keyBytes = crypto.getRandomValues(new Uint8Array(length / 8));
+                        resolve(__createAesKey('AES-GCM', keyBytes, extractable, keyUsages));
+                    } else if (algoName === 'AES-KW') {
+                        const length = algorithm.length;
+                        if (length !== 128 && length !== 192 && length !== 256) {
+                            reject(new Error('AES-KW key length must be 128, 192 or 256'));
+                            return;
+                        }
+
+                        const keyBytes = crypto.getRandomValues(new Uint8Array(length / 8));
+                        resolve(__createAesKey('AES-KW', keyBytes, extractable, keyUsages));
+                    } else if (algoName === 'ECDH') {
+                        const namedCurve = algorithm.namedCurve;
+                        if (namedCurve !== 'P-256' && namedCurve !== 'P-384') {
+                            reject(__cryptoError('NotSupportedError', 'Only P-256 and P-384 curves are supported for ECDH'));
+                            return;
+                        }
+
+                        const result = __nativeEcdhGenerateKey(namedCurve);
+
+                        resolve({
+                            privateKey: __createEcdhPrivateKey(namedCurve, result, extractable, keyUsages),
+                            publicKey: {
+                                type: 'public',
+                                extractable: true,
+                                algorithm: { name: 'ECDH', namedCurve: namedCurve },
+                                usages: [],
+                                __keyData: new Uint8Array(result.publicKey)
+                            }
+                        });
+                    } else {
+                        reject(new Error('Only ECDSA, ECDH, AES-GCM and AES-KW algorithms are supported for generateKey'));
+                    }
+                } catch (e) {
+                    reject(e);
+                }
+            });
+        };
+
+        // crypto.subtle.importKey - HMAC, ECDSA, ECDH, RSA (PKCS#1 v1.5, PSS, OAEP), AES-GCM; jwk for HMAC and EC
+        crypto.subtle.importKey = function(format, keyData, algorithm, extractable, keyUsages) {
+            return new Promise((resolve, reject) => {
+                try {
+                    const algoName = typeof algorithm === 'string' ? algorithm : algorithm.name;
+
+                    if (format === 'jwk') {
+                        resolve(__importJwk(keyData, algorithm, extractable, keyUsages));
+                        return;
+                    }
+
+                    const keyBytes = __toBytes(keyData, 'Key data');
+
+                    if (algoName === 'HMAC') {
+                        if (format !== 'raw') {
+                            reject(new Error('Only "raw" format is supported for HMAC'));
                             return;
                         }
 
@@ -744,7 +1660,39 @@ pub fn setup_crypto(context: &mut JSContext) {
                         } else {
                             reject(new Error('Only "raw" and "pkcs8" formats are supported for ECDSA'));
                         }
-                    } else if (algoName === 'RSASSA-PKCS1-v1_5') {
+                    } else if (algoName === 'ECDH') {
+                        const namedCurve = algorithm.namedCurve;
+                        const pointLength = { 'P-256': 65, 'P-384': 97 }[namedCurve];
+                        if (!pointLength) {
+                            reject(__cryptoError('NotSupportedError', 'Only P-256 and P-384 curves are supported for ECDH'));
+                            return;
+                        }
+
+                        if (format === 'pkcs8') {
+                            // Normalized to the DER the native side re-encodes
+                            const pair = __ecdhPrivateKey(namedCurve, 'pkcs8', keyBytes);
+                            resolve(__createEcdhPrivateKey(namedCurve, pair, extractable, keyUsages));
+                            return;
+                        }
+                        if (format !== 'raw') {
+                            reject(new Error('Only "raw" and "pkcs8" formats are supported for ECDH'));
+                            return;
+                        }
+
+                        // Raw format is the uncompressed point
+                        if (keyBytes.length !== pointLength || keyBytes[0] !== 4) {
+                            reject(__cryptoError('DataError', 'Invalid ' + namedCurve + ' public key'));
+                            return;
+                        }
+
+                        resolve({
+                            type: 'public',
+                            extractable: extractable,
+                            algorithm: { name: 'ECDH', namedCurve: namedCurve },
+                            usages: [],
+                            __keyData: keyBytes.slice()
+                        });
+                    } else if (algoName === 'RSASSA-PKCS1-v1_5' || algoName === 'RSA-PSS' || algoName === 'RSA-OAEP') {
                         const hashName = typeof algorithm === 'object' && algorithm.hash
                             ? (typeof algorithm.hash === 'string' ? algorithm.hash : algorithm.hash.name)
                             : 'SHA-256';
@@ -754,7 +1702,7 @@ pub fn setup_crypto(context: &mut JSContext) {
                             const key = {
                                 type: 'private',
                                 extractable: extractable,
-                                algorithm: { name: 'RSASSA-PKCS1-v1_5', hash: { name: hashName } },
+                                algorithm: { name: algoName, hash: { name: hashName } },
                                 usages: keyUsages,
                                 __keyData: keyBytes
                             };
@@ -764,7 +1712,7 @@ pub fn setup_crypto(context: &mut JSContext) {
                             const key = {
                                 type: 'public',
                                 extractable: extractable,
-                                algorithm: { name: 'RSASSA-PKCS1-v1_5', hash: { name: hashName } },
+                                algorithm: { name: algoName, hash: { name: hashName } },
                                 usages: keyUsages,
                                 __keyData: keyBytes
                             };
@@ -772,6 +1720,30 @@ pub fn setup_crypto(context: &mut JSContext) {
                         } else {
                             reject(new Error('Only "pkcs8" and "spki" formats are supported for RSA'));
                         }
+                    } else if (algoName === 'AES-GCM') {
+                        if (format !== 'raw') {
+                            reject(new Error('Only "raw" format is supported for AES-GCM'));
+                            return;
+                        }
+
+                        if (keyBytes.length !== 16 && keyBytes.length !== 32) {
+                            reject(new Error('AES-GCM key must be 128 or 256 bits'));
+                            return;
+                        }
+
+                        resolve(__createAesKey('AES-GCM', keyBytes.slice(), extractable, keyUsages));
+                    } else if (algoName === 'AES-KW') {
+                        if (format !== 'raw') {
+                            reject(new Error('Only "raw" format is supported for AES-KW'));
+                            return;
+                        }
+
+                        if (keyBytes.length !== 16 && keyBytes.length !== 24 && keyBytes.length !== 32) {
+                            reject(new Error('AES-KW key must be 128, 192 or 256 bits'));
+                            return;
+                        }
+
+                        resolve(__createAesKey('AES-KW', keyBytes.slice(), extractable, keyUsages));
                     } else {
                         reject(new Error('Unsupported algorithm: ' + algoName));
                     }
@@ -781,21 +1753,28 @@ pub fn setup_crypto(context: &mut JSContext) {
             });
         };
 
-        // crypto.subtle.sign - HMAC, ECDSA, RSA
+        // crypto.subtle.sign - HMAC, ECDSA, RSA (PKCS#1 v1.5, PSS)
+        // Non-standard: HMAC also signs a ReadableStream chunk by chunk
         crypto.subtle.sign = function(algorithm, key, data) {
+            if (data instanceof ReadableStream) {
+                const algoName = typeof algorithm === 'string' ? algorithm : algorithm.name;
+                if (algoName !== 'HMAC') {
+                    return Promise.reject(new TypeError('Only HMAC can sign a ReadableStream'));
+                }
+                if (!key || !key.__keyData) {
+                    return Promise.reject(new Error('Invalid key'));
+                }
+                return (async () => __hashStream(
+                    __nativeHmacInit(key.algorithm.hash.name, key.__keyData),
+                    data
+                ))();
+            }
+
             return new Promise((resolve, reject) => {
                 try {
                     const algoName = typeof algorithm === 'string' ? algorithm : algorithm.name;
 
-                    let dataBytes;
-                    if (data instanceof ArrayBuffer) {
-                        dataBytes = new Uint8Array(data);
-                    } else if (data instanceof Uint8Array) {
-                        dataBytes = data;
-                    } else {
-                        reject(new Error('Data must be ArrayBuffer or Uint8Array'));
-                        return;
-                    }
+                    const dataBytes = __toBytes(data, 'Data');
 
                     if (!key.__keyData) {
                         reject(new Error('Invalid key'));
@@ -837,6 +1816,15 @@ pub fn setup_crypto(context: &mut JSContext) {
                         } else {
                             reject(new Error('RSA sign failed'));
                         }
+                    } else if (algoName === 'RSA-PSS') {
+                        if (key.type !== 'private' || key.algorithm.name !== 'RSA-PSS') {
+                            reject(new Error('Invalid key for RSA-PSS signing'));
+                            return;
+                        }
+
+                        const hashName = key.algorithm.hash.name;
+                        __checkPssSaltLength(algorithm, hashName);
+                        resolve(__nativeRsaPssSign(hashName, key.__keyData, dataBytes));
                     } else {
                         reject(new Error('Unsupported algorithm: ' + algoName));
                     }
@@ -846,30 +1834,14 @@ pub fn setup_crypto(context: &mut JSContext) {
             });
         };
 
-        // crypto.subtle.verify - HMAC, ECDSA, RSA
+        // crypto.subtle.verify - HMAC, ECDSA, RSA (PKCS#1 v1.5, PSS)
         crypto.subtle.verify = function(algorithm, key, signature, data) {
             return new Promise((resolve, reject) => {
                 try {
                     const algoName = typeof algorithm === 'string' ? algorithm : algorithm.name;
 
-                    let dataBytes, sigBytes;
-                    if (data instanceof ArrayBuffer) {
-                        dataBytes = new Uint8Array(data);
-                    } else if (data instanceof Uint8Array) {
-                        dataBytes = data;
-                    } else {
-                        reject(new Error('Data must be ArrayBuffer or Uint8Array'));
-                        return;
-                    }
-
-                    if (signature instanceof ArrayBuffer) {
-                        sigBytes = new Uint8Array(signature);
-                    } else if (signature instanceof Uint8Array) {
-                        sigBytes = signature;
-                    } else {
-                        reject(new Error('Signature must be ArrayBuffer or Uint8Array'));
-                        return;
-                    }
+                    const dataBytes = __toBytes(data, 'Data');
+                    const sigBytes = __toBytes(signature, 'Signature');
 
                     if (!key.__keyData) {
                         reject(new Error('Invalid key'));
@@ -899,6 +1871,55 @@ pub fn setup_crypto(context: &mut JSContext) {
                         const hashName = key.algorithm.hash.name;
                         const isValid = __nativeRsaVerify(hashName, key.__keyData, sigBytes, dataBytes);
                         resolve(isValid);
+                    } else if (algoName === 'RSA-PSS') {
+                        if (key.algorithm.name !== 'RSA-PSS') {
+                            reject(new Error('Invalid key for RSA-PSS verification'));
+                            return;
+                        }
+
+                        const hashName = key.algorithm.hash.name;
+                        __checkPssSaltLength(algorithm, hashName);
+                        resolve(__nativeRsaPssVerify(hashName, key.__keyData, sigBytes, dataBytes));
+                    } else {
+                        reject(new Error('Unsupported algorithm: ' + algoName));
+                    }
+                } catch (e) {
+                    reject(e);
+                }
+            });
+        };
+
+        // crypto.subtle.encrypt - AES-GCM, RSA-OAEP
+        crypto.subtle.encrypt = function(algorithm, key, data) {
+            return new Promise((resolve, reject) => {
+                try {
+                    const algoName = typeof algorithm === 'string' ? algorithm : algorithm.name;
+                    const bytes = __toBytes(data, 'Data');
+
+                    if (algoName === 'AES-GCM') {
+                        resolve(__aesGcm(__nativeAesGcmEncrypt, algorithm, key, bytes));
+                    } else if (algoName === 'RSA-OAEP') {
+                        resolve(__rsaOaep(__nativeRsaOaepEncrypt, 'public', algorithm, key, bytes));
+                    } else {
+                        reject(new Error('Unsupported algorithm: ' + algoName));
+                    }
+                } catch (e) {
+                    reject(e);
+                }
+            });
+        };
+
+        // crypto.subtle.decrypt - AES-GCM, RSA-OAEP
+        crypto.subtle.decrypt = function(algorithm, key, data) {
+            return new Promise((resolve, reject) => {
+                try {
+                    const algoName = typeof algorithm === 'string' ? algorithm : algorithm.name;
+                    const bytes = __toBytes(data, 'Data');
+
+                    if (algoName === 'AES-GCM') {
+                        resolve(__aesGcm(__nativeAesGcmDecrypt, algorithm, key, bytes));
+                    } else if (algoName === 'RSA-OAEP') {
+                        resolve(__rsaOaep(__nativeRsaOaepDecrypt, 'private', algorithm, key, bytes));
                     } else {
                         reject(new Error('Unsupported algorithm: ' + algoName));
                     }
@@ -907,9 +1928,370 @@ pub fn setup_crypto(context: &mut JSContext) {
                 }
             });
         };
+
+        // crypto.subtle.exportKey - raw (secret and EC public keys), pkcs8 (private keys), jwk
+        crypto.subtle.exportKey = function(format, key) {
+            return new Promise((resolve, reject) => {
+                try {
+                    resolve(__exportKey(format, key));
+                } catch (e) {
+                    reject(e);
+                }
+            });
+        };
+
+        // crypto.subtle.deriveBits - ECDH
+        crypto.subtle.deriveBits = function(algorithm, baseKey, length) {
+            return new Promise((resolve, reject) => {
+                try {
+                    if (algorithm.name !== 'ECDH') {
+                        reject(__cryptoError('NotSupportedError', 'Only ECDH is supported for deriveBits'));
+                        return;
+                    }
+                    if (!baseKey.usages.includes('deriveBits')) {
+                        reject(__cryptoError('InvalidAccessError', 'Key does not allow deriveBits'));
+                        return;
+                    }
+
+                    resolve(__ecdhDeriveBits(algorithm, baseKey, length));
+                } catch (e) {
+                    reject(e);
+                }
+            });
+        };
+
+        // crypto.subtle.deriveKey - ECDH into an AES-GCM or HMAC key
+        crypto.subtle.deriveKey = function(algorithm, baseKey, derivedKeyAlgorithm, extractable, keyUsages) {
+            return new Promise((resolve, reject) => {
+                try {
+                    if (algorithm.name !== 'ECDH') {
+                        reject(__cryptoError('NotSupportedError', 'Only ECDH is supported for deriveKey'));
+                        return;
+                    }
+                    if (!baseKey.usages.includes('deriveKey')) {
+                        reject(__cryptoError('InvalidAccessError', 'Key does not allow deriveKey'));
+                        return;
+                    }
+
+                    let length;
+                    if (derivedKeyAlgorithm.name === 'AES-GCM') {
+                        length = derivedKeyAlgorithm.length;
+                    } else if (derivedKeyAlgorithm.name === 'HMAC') {
+                        // Defaults to the hash block size, like the spec
+                        const hash = derivedKeyAlgorithm.hash;
+                        const hashName = typeof hash === 'string' ? hash : hash && hash.name;
+                        length = derivedKeyAlgorithm.length
+                            || { 'SHA-1': 512, 'SHA-256': 512, 'SHA-384': 1024, 'SHA-512': 1024 }[hashName];
+                    } else {
+                        reject(__cryptoError('NotSupportedError', 'Unsupported derived key algorithm: ' + derivedKeyAlgorithm.name));
+                        return;
+                    }
+
+                    const bits = __ecdhDeriveBits(algorithm, baseKey, length);
+                    resolve(crypto.subtle.importKey('raw', bits, derivedKeyAlgorithm, extractable, keyUsages));
+                } catch (e) {
+                    reject(e);
+                }
+            });
+        };
+
+        // crypto.subtle.wrapKey - export then encrypt with an AES-GCM or AES-KW key
+        // (AES-KW needs key bytes in 64-bit blocks, at least two)
+        crypto.subtle.wrapKey = function(format, key, wrappingKey, wrapAlgorithm) {
+            return new Promise((resolve, reject) => {
+                try {
+                    const algoName = __checkWrappingKey(wrapAlgorithm, wrappingKey, 'wrapKey');
+                    const exported = __exportKey(format, key);
+                    const keyBytes = format === 'jwk'
+                        ? new TextEncoder().encode(JSON.stringify(exported))
+                        : new Uint8Array(exported);
+                    resolve(algoName === 'AES-KW'
+                        ? __aesKw(__nativeAesKwWrap, wrappingKey, keyBytes)
+                        : __aesGcm(__nativeAesGcmEncrypt, wrapAlgorithm, wrappingKey, keyBytes));
+                } catch (e) {
+                    reject(e);
+                }
+            });
+        };
+
+        // crypto.subtle.unwrapKey - decrypt with an AES-GCM or AES-KW key then import
+        crypto.subtle.unwrapKey = function(format, wrappedKey, unwrappingKey, unwrapAlgorithm,
+                                           unwrappedKeyAlgorithm, extractable, keyUsages) {
+            return new Promise((resolve, reject) => {
+                try {
+                    const algoName = __checkWrappingKey(unwrapAlgorithm, unwrappingKey, 'unwrapKey');
+                    const wrappedBytes = __toBytes(wrappedKey, 'Wrapped key');
+                    const keyBytes = algoName === 'AES-KW'
+                        ? new Uint8Array(__aesKw(__nativeAesKwUnwrap, unwrappingKey, wrappedBytes))
+                        : __aesGcm(__nativeAesGcmDecrypt, unwrapAlgorithm, unwrappingKey, wrappedBytes);
+                    const keyData = format === 'jwk'
+                        ? JSON.parse(new TextDecoder().decode(keyBytes))
+                        : keyBytes;
+                    resolve(crypto.subtle.importKey(format, keyData, unwrappedKeyAlgorithm, extractable, keyUsages));
+                } catch (e) {
+                    reject(e);
+                }
+            });
+        };
     "#;
 
     context
         .evaluate_script(crypto_script, 1)
         .expect("Failed to setup crypto");
 }
+
+/// Copy the first N arguments out of their typed arrays
+fn read_byte_args<const N: usize>(ctx: &JSContext, args: &[JSValue]) -> Option<[Vec<u8>; N]> {
+    if args.len() < N {
+        return None;
+    }
+
+    let mut out: [Vec<u8>; N] = std::array::from_fn(|_| Vec::new());
+    for (slot, arg) in out.iter_mut().zip(args) {
+        *slot = js_value_to_bytes(ctx, arg).ok()?;
+    }
+
+    Some(out)
+}
+
+/// AES key wrap or unwrap (RFC 3394) with a 128, 192 or 256-bit key
+fn aes_kw(key_data: &[u8], data: &[u8], wrap: bool) -> Result<Vec<u8>, &'static str> {
+    use aes_kw::{KekAes128, KekAes192, KekAes256};
+
+    let result = match key_data.len() {
+        16 => KekAes128::try_from(key_data).and_then(|kek| {
+            if wrap {
+                kek.wrap_vec(data)
+            } else {
+                kek.unwrap_vec(data)
+            }
+        }),
+        24 => KekAes192::try_from(key_data).and_then(|kek| {
+            if wrap {
+                kek.wrap_vec(data)
+            } else {
+                kek.unwrap_vec(data)
+            }
+        }),
+        32 => KekAes256::try_from(key_data).and_then(|kek| {
+            if wrap {
+                kek.wrap_vec(data)
+            } else {
+                kek.unwrap_vec(data)
+            }
+        }),
+        _ => return Err("AES-KW key must be 128, 192 or 256 bits"),
+    };
+
+    result.map_err(|_| {
+        if wrap {
+            "AES-KW wrapping failed (key data must be 16+ bytes in 8-byte blocks)"
+        } else {
+            "AES-KW unwrapping failed"
+        }
+    })
+}
+
+/// Build an AES-GCM key (128 or 256 bit) and its 96-bit nonce
+fn aes_gcm_key(
+    key_data: &[u8],
+    iv: &[u8],
+) -> Result<(aead::LessSafeKey, aead::Nonce), &'static str> {
+    let algorithm = match key_data.len() {
+        16 => &aead::AES_128_GCM,
+        32 => &aead::AES_256_GCM,
+        _ => return Err("AES-GCM key must be 128 or 256 bits"),
+    };
+
+    let key = aead::UnboundKey::new(algorithm, key_data).map_err(|_| "Invalid AES-GCM key")?;
+    let nonce =
+        aead::Nonce::try_assume_unique_for_key(iv).map_err(|_| "AES-GCM iv must be 96 bits")?;
+
+    Ok((aead::LessSafeKey::new(key), nonce))
+}
+
+/// OAEP padding (MGF1 with the same hash) and the digest length
+fn oaep_padding(hash_algo: &str) -> Option<(Oaep, usize)> {
+    match hash_algo {
+        "SHA-256" => Some((Oaep::new::<sha2::Sha256>(), 32)),
+        "SHA-384" => Some((Oaep::new::<sha2::Sha384>(), 48)),
+        "SHA-512" => Some((Oaep::new::<sha2::Sha512>(), 64)),
+        _ => None,
+    }
+}
+
+/// ring digest algorithm for a WebCrypto hash name (upper case)
+fn digest_algorithm(name: &str) -> Option<&'static digest::Algorithm> {
+    match name {
+        "SHA-1" => Some(&digest::SHA1_FOR_LEGACY_USE_ONLY),
+        "SHA-256" => Some(&digest::SHA256),
+        "SHA-384" => Some(&digest::SHA384),
+        "SHA-512" => Some(&digest::SHA512),
+        _ => None,
+    }
+}
+
+/// An incremental digest or HMAC
+enum HashContext {
+    Digest(digest::Context),
+    Hmac(hmac::Context),
+}
+
+impl HashContext {
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            HashContext::Digest(context) => context.update(data),
+            HashContext::Hmac(context) => context.update(data),
+        }
+    }
+
+    fn finish(self) -> Vec<u8> {
+        match self {
+            HashContext::Digest(context) => context.finish().as_ref().to_vec(),
+            HashContext::Hmac(context) => context.sign().as_ref().to_vec(),
+        }
+    }
+}
+
+/// In-progress incremental digests and HMACs, referenced from JS by handle
+#[derive(Default)]
+struct DigestContexts {
+    next_id: u64,
+    contexts: HashMap<u64, HashContext>,
+}
+
+impl DigestContexts {
+    fn insert(&mut self, context: HashContext) -> u64 {
+        self.next_id += 1;
+        self.contexts.insert(self.next_id, context);
+        self.next_id
+    }
+
+    fn update(&mut self, id: u64, data: &[u8]) -> bool {
+        match self.contexts.get_mut(&id) {
+            Some(context) => {
+                context.update(data);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn finish(&mut self, id: u64) -> Option<Vec<u8>> {
+        self.contexts.remove(&id).map(HashContext::finish)
+    }
+}
+
+/// Run `$body` with `$curve` naming the RustCrypto crate of a WebCrypto
+/// ECDH curve (P-256 or P-384)
+macro_rules! with_ecdh_curve {
+    ($named_curve:expr, $curve:ident => $body:expr) => {
+        match $named_curve {
+            "P-256" => {
+                use p256 as $curve;
+                $body
+            }
+            "P-384" => {
+                use p384 as $curve;
+                $body
+            }
+            _ => Err("Unsupported ECDH curve"),
+        }
+    };
+}
+
+/// Where an ECDH private key comes from
+enum EcdhSecret<'a> {
+    Generate,
+    /// PKCS#8 DER
+    Pkcs8(&'a [u8]),
+    /// Raw private scalar (JWK "d")
+    Scalar(&'a [u8]),
+}
+
+/// An ECDH private key (PKCS#8), its public point (uncompressed) and scalar
+struct EcdhKeyPair {
+    private_key: Vec<u8>,
+    public_key: Vec<u8>,
+    d: Vec<u8>,
+}
+
+fn ecdh_key_pair(named_curve: &str, secret: EcdhSecret<'_>) -> Result<EcdhKeyPair, &'static str> {
+    use rand::SecureRandom;
+
+    with_ecdh_curve!(named_curve, curve => {
+        use curve::elliptic_curve::sec1::ToEncodedPoint;
+        use curve::pkcs8::{DecodePrivateKey, EncodePrivateKey};
+
+        let secret_key = match secret {
+            EcdhSecret::Generate => {
+                // Retry the (negligible) out-of-range draws
+                let rng = rand::SystemRandom::new();
+                let mut bytes = curve::FieldBytes::default();
+                loop {
+                    rng.fill(&mut bytes).map_err(|_| "Key generation failed")?;
+                    if let Ok(key) = curve::SecretKey::from_bytes(&bytes) {
+                        break key;
+                    }
+                }
+            }
+            EcdhSecret::Pkcs8(der) => curve::SecretKey::from_pkcs8_der(der)
+                .map_err(|_| "Invalid ECDH private key")?,
+            EcdhSecret::Scalar(d) => {
+                curve::SecretKey::from_slice(d).map_err(|_| "Invalid ECDH private key")?
+            }
+        };
+
+        let private_key = secret_key
+            .to_pkcs8_der()
+            .map_err(|_| "Failed to encode ECDH private key")?
+            .as_bytes()
+            .to_vec();
+        let public_key = secret_key
+            .public_key()
+            .to_encoded_point(false)
+            .as_bytes()
+            .to_vec();
+
+        Ok(EcdhKeyPair {
+            private_key,
+            public_key,
+            d: secret_key.to_bytes().to_vec(),
+        })
+    })
+}
+
+/// ECDH shared secret (the x coordinate) of a PKCS#8 private key and a peer point
+fn ecdh_derive(
+    named_curve: &str,
+    private_key: &[u8],
+    peer_public_key: &[u8],
+) -> Result<Vec<u8>, &'static str> {
+    with_ecdh_curve!(named_curve, curve => {
+        use curve::pkcs8::DecodePrivateKey;
+
+        let secret_key = curve::SecretKey::from_pkcs8_der(private_key)
+            .map_err(|_| "Invalid ECDH private key")?;
+        let public_key = curve::PublicKey::from_sec1_bytes(peer_public_key)
+            .map_err(|_| "Invalid ECDH public key")?;
+
+        let shared = curve::ecdh::diffie_hellman(
+            secret_key.to_nonzero_scalar(),
+            public_key.as_affine(),
+        );
+        Ok(shared.raw_secret_bytes().to_vec())
+    })
+}
+
+/// `{ privateKey, publicKey, d }` ArrayBuffers for JS
+fn ecdh_key_pair_object(ctx: &JSContext, pair: &EcdhKeyPair) -> Result<JSValue, JSValue> {
+    let script = format!(
+        "({{ privateKey: new Uint8Array({}).buffer, publicKey: new Uint8Array({}).buffer, d: new Uint8Array({}).buffer }})",
+        serde_json::to_string(&pair.private_key).unwrap(),
+        serde_json::to_string(&pair.public_key).unwrap(),
+        serde_json::to_string(&pair.d).unwrap()
+    );
+
+    ctx.evaluate_script(&script, 1)
+        .map_err(|_| JSValue::string(ctx, "Failed to create key pair object"))
+}
@@ -0,0 +1,172 @@
+/// Event, CustomEvent and EventTarget implementation (WHATWG DOM spec)
+pub const EVENT_TARGET_JS: &str = r#"
+    globalThis.Event = class Event {
+        constructor(type, init) {
+            if (arguments.length === 0) {
+                throw new TypeError("Failed to construct 'Event': 1 argument required");
+            }
+            init = init || {};
+
+            this._type = String(type);
+            this._bubbles = !!init.bubbles;
+            this._cancelable = !!init.cancelable;
+            this._composed = !!init.composed;
+            this._defaultPrevented = false;
+            this._stopImmediate = false;
+            this._target = null;
+            this._currentTarget = null;
+            this._timeStamp = performance.now();
+        }
+
+        get type() { return this._type; }
+        get bubbles() { return this._bubbles; }
+        get cancelable() { return this._cancelable; }
+        get composed() { return this._composed; }
+        get defaultPrevented() { return this._defaultPrevented; }
+        get target() { return this._target; }
+        get currentTarget() { return this._currentTarget; }
+        get timeStamp() { return this._timeStamp; }
+        get eventPhase() { return this._currentTarget ? Event.AT_TARGET : Event.NONE; }
+
+        preventDefault() {
+            if (this._cancelable) {
+                this._defaultPrevented = true;
+            }
+        }
+
+        // No propagation path: every event is dispatched at its target only
+        stopPropagation() {}
+
+        stopImmediatePropagation() {
+            this._stopImmediate = true;
+        }
+    };
+
+    Event.NONE = 0;
+    Event.CAPTURING_PHASE = 1;
+    Event.AT_TARGET = 2;
+    Event.BUBBLING_PHASE = 3;
+
+    globalThis.CustomEvent = class CustomEvent extends Event {
+        constructor(type, init) {
+            if (arguments.length === 0) {
+                throw new TypeError("Failed to construct 'CustomEvent': 1 argument required");
+            }
+            super(type, init);
+            this._detail = init && init.detail !== undefined ? init.detail : null;
+        }
+
+        get detail() { return this._detail; }
+    };
+
+    globalThis.EventTarget = class EventTarget {
+        constructor() {
+            this._listeners = new Map();
+        }
+
+        addEventListener(type, listener, options) {
+            if (listener === null || listener === undefined) {
+                return;
+            }
+            if (typeof listener !== 'function' && typeof listener !== 'object') {
+                throw new TypeError('EventTarget listener must be a function or an object');
+            }
+
+            const capture = typeof options === 'boolean' ? options : !!(options && options.capture);
+            const once = typeof options === 'object' && options !== null && !!options.once;
+            const signal = typeof options === 'object' && options !== null ? options.signal : undefined;
+            if (signal && signal.aborted) {
+                return;
+            }
+
+            type = String(type);
+            let listeners = this._listeners.get(type);
+            if (!listeners) {
+                listeners = [];
+                this._listeners.set(type, listeners);
+            }
+
+            // Registering the same listener twice is a no-op
+            if (listeners.some(entry => entry.listener === listener && entry.capture === capture)) {
+                return;
+            }
+
+            const entry = { listener, capture, once, removed: false };
+            listeners.push(entry);
+
+            if (signal) {
+                signal.addEventListener('abort', () => this.removeEventListener(type, listener, { capture }), { once: true });
+            }
+        }
+
+        removeEventListener(type, listener, options) {
+            const capture = typeof options === 'boolean' ? options : !!(options && options.capture);
+            const listeners = this._listeners.get(String(type));
+            if (!listeners) {
+                return;
+            }
+
+            const index = listeners.findIndex(entry => entry.listener === listener && entry.capture === capture);
+            if (index !== -1) {
+                listeners[index].removed = true;
+                listeners.splice(index, 1);
+            }
+        }
+
+        // Returns false if a listener called preventDefault() on a cancelable event
+        dispatchEvent(event) {
+            if (!(event instanceof Event)) {
+                throw new TypeError("Failed to execute 'dispatchEvent': parameter 1 is not of type 'Event'");
+            }
+
+            event._target = this;
+            event._currentTarget = this;
+            event._stopImmediate = false;
+
+            // Listeners added during dispatch are not called for this event
+            const listeners = (this._listeners.get(event.type) || []).slice();
+            for (const entry of listeners) {
+                if (entry.removed) {
+                    continue;
+                }
+                if (entry.once) {
+                    this.removeEventListener(event.type, entry.listener, { capture: entry.capture });
+                }
+
+                try {
+                    if (typeof entry.listener === 'function') {
+                        entry.listener.call(this, event);
+                    } else if (typeof entry.listener.handleEvent === 'function') {
+                        entry.listener.handleEvent(event);
+                    }
+                } catch (error) {
+                    console.error('[EventTarget] Error in ' + event.type + ' listener:', error);
+                }
+
+                if (event._stopImmediate) {
+                    break;
+                }
+            }
+
+            event._currentTarget = null;
+            return !event.defaultPrevented;
+        }
+
+        // Internal: dispatch after calling the on<type> handler property
+        // (AbortSignal.onabort, WebSocket.onmessage, ...)
+        _dispatchWithHandler(event) {
+            const handler = this['on' + event.type];
+            if (typeof handler === 'function') {
+                event._target = this;
+                event._currentTarget = this;
+                try {
+                    handler.call(this, event);
+                } catch (error) {
+                    console.error('[EventTarget] Error in on' + event.type + ' handler:', error);
+                }
+            }
+
+            return this.dispatchEvent(event);
+        }
+    };
+"#;
@@ -1,8 +1,12 @@
+use crate::runtime::fetch_policy::PublicDnsResolver;
 use crate::runtime::stream_manager::{StreamChunk, StreamId, StreamManager};
+use crate::runtime::typed_array::js_value_to_bytes;
 use bytes::Bytes;
 use futures_util::StreamExt;
-use openworkers_core::{HttpMethod, HttpRequest, HttpResponseMeta, RequestBody};
-use rusty_jsc::{JSContext, JSValue};
+use openworkers_core::{
+    HttpMethod, HttpRequest, HttpResponse, OpFuture, OperationsHandler, RequestBody, ResponseBody,
+};
+use rusty_jsc::{JSContext, JSObject, JSValue};
 use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::Arc;
@@ -121,10 +125,130 @@ pub fn parse_headers_from_js(
     Ok(headers)
 }
 
+/// Accept header sent on fetches that don't set one, as browsers do
+pub const DEFAULT_ACCEPT: &str = "*/*";
+
+/// Accept-Encoding sent by fetch: the encodings it decodes
+pub const DEFAULT_ACCEPT_ENCODING: &str = "gzip, deflate, br";
+
+/// Add an Accept header unless the request already has one (any case)
+pub fn apply_default_accept(headers: &mut HashMap<String, String>, accept: &str) {
+    apply_default_header(headers, "accept", accept);
+}
+
+/// Add an Accept-Encoding header (`DEFAULT_ACCEPT_ENCODING`) unless the
+/// request already has one; responses in these encodings are decoded
+pub fn apply_default_accept_encoding(headers: &mut HashMap<String, String>) {
+    apply_default_header(headers, "accept-encoding", DEFAULT_ACCEPT_ENCODING);
+}
+
+/// Add a header unless the request already has it (any case)
+fn apply_default_header(headers: &mut HashMap<String, String>, name: &str, value: &str) {
+    if !headers.keys().any(|key| key.eq_ignore_ascii_case(name)) {
+        headers.insert(name.to_string(), value.to_string());
+    }
+}
+
 // ============================================================================
 // Request
 // ============================================================================
 
+/// How fetch handles 3xx responses (`options.redirect`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RedirectMode {
+    /// Follow redirects (default)
+    #[default]
+    Follow,
+    /// Return the 3xx response as-is
+    Manual,
+    /// Reject the fetch when a redirect is returned
+    Error,
+}
+
+impl FromStr for RedirectMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "follow" => Ok(RedirectMode::Follow),
+            "manual" => Ok(RedirectMode::Manual),
+            "error" => Ok(RedirectMode::Error),
+            _ => Err(format!("Invalid redirect mode: {}", s)),
+        }
+    }
+}
+
+/// Final location of a fetch after redirects
+#[derive(Debug, Clone)]
+pub struct FetchedUrl {
+    pub url: String,
+    pub redirected: bool,
+}
+
+/// Parse `options.redirect` from JavaScript (defaults to follow)
+pub fn parse_redirect_mode(
+    context: &JSContext,
+    options_val: Option<&JSValue>,
+) -> Result<RedirectMode, String> {
+    let Some(options) = options_val else {
+        return Ok(RedirectMode::Follow);
+    };
+
+    let Ok(options_obj) = options.to_object(context) else {
+        return Ok(RedirectMode::Follow);
+    };
+
+    match options_obj.get_property(context, "redirect") {
+        Some(val) if !val.is_undefined(context) && !val.is_null(context) => {
+            let mode = val
+                .to_js_string(context)
+                .map_err(|_| "redirect must be a string")?;
+            RedirectMode::from_str(&mode.to_string())
+        }
+        _ => Ok(RedirectMode::Follow),
+    }
+}
+
+/// Parse the non-standard `options.buffer` flag from JavaScript
+///
+/// Buffered fetches read the whole body before resolving, skipping the
+/// per-chunk stream round trips. Meant for small responses.
+pub fn parse_buffer_option(context: &JSContext, options_val: Option<&JSValue>) -> bool {
+    options_val
+        .and_then(|options| options.to_object(context).ok())
+        .and_then(|options_obj| options_obj.get_property(context, "buffer"))
+        .is_some_and(|val| val.to_bool(context))
+}
+
+/// Parse the non-standard `options.onprogress` callback from JavaScript
+///
+/// The callback is called with `{ type, loaded, total, lengthComputable }`
+/// as the request body is sent ("upload") and the response body arrives
+/// ("download").
+pub fn parse_progress_option(
+    context: &JSContext,
+    options_val: Option<&JSValue>,
+) -> Option<JSObject> {
+    options_val
+        .and_then(|options| options.to_object(context).ok())
+        .and_then(|options_obj| options_obj.get_property(context, "onprogress"))
+        .filter(|val| !val.is_undefined(context) && !val.is_null(context))
+        .and_then(|val| val.to_object(context).ok())
+}
+
+/// Cumulative byte count of a fetch in flight
+#[derive(Debug, Clone)]
+pub struct FetchProgress {
+    /// Request body bytes (true) or response body bytes (false)
+    pub upload: bool,
+    /// Bytes transferred so far
+    pub loaded: u64,
+    /// Expected total (request body size or Content-Length), if known
+    pub total: Option<u64>,
+    /// Last report for this fetch
+    pub done: bool,
+}
+
 /// Parse fetch options from JavaScript
 pub fn parse_fetch_options(
     context: &JSContext,
@@ -157,10 +281,12 @@ pub fn parse_fetch_options(
             }
         }
 
-        // Parse body
+        // Parse body: typed arrays are sent as raw bytes, anything else as a string
         if let Some(body_val) = options_obj.get_property(context, "body") {
             if !body_val.is_null(context) && !body_val.is_undefined(context) {
-                if let Ok(body_str) = body_val.to_js_string(context) {
+                if let Ok(bytes) = js_value_to_bytes(context, &body_val) {
+                    body = RequestBody::Bytes(Bytes::from(bytes));
+                } else if let Ok(body_str) = body_val.to_js_string(context) {
                     body = RequestBody::Bytes(Bytes::from(body_str.to_string()));
                 }
             }
@@ -175,15 +301,171 @@ pub fn parse_fetch_options(
     })
 }
 
-/// Execute HTTP request with streaming response
-/// Returns metadata and stream ID immediately, body is streamed through StreamManager
-pub async fn execute_fetch_streaming(
-    request: HttpRequest,
-    stream_manager: Arc<StreamManager>,
-) -> Result<(HttpResponseMeta, StreamId), String> {
-    let client = reqwest::Client::new();
+// ============================================================================
+// Client
+// ============================================================================
+
+/// reqwest client settings for the built-in HTTP client
+///
+/// Fetches made from JS go through the embedder's `OperationsHandler`,
+/// unless `WorkerOptions::fetch_client` sets a configuration: they are then
+/// sent by a `FetchClient` built from it. The `execute_fetch_streaming*`
+/// helpers also use it.
+#[derive(Debug, Clone, Default)]
+pub struct FetchClientConfig {
+    root_certificates: Vec<reqwest::Certificate>,
+    default_accept: Option<String>,
+    block_private_ips: bool,
+}
+
+impl FetchClientConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Trust an extra root CA (PEM), on top of the built-in roots
+    ///
+    /// Meant for internal services signed by a private CA.
+    pub fn add_root_certificate(mut self, pem: &[u8]) -> Result<Self, String> {
+        const PEM_HEADER: &[u8] = b"-----BEGIN CERTIFICATE-----";
+
+        if !pem.windows(PEM_HEADER.len()).any(|w| w == PEM_HEADER) {
+            return Err("Invalid root certificate: no PEM certificate found".to_string());
+        }
 
-    // Build the request
+        let certificate = reqwest::Certificate::from_pem(pem)
+            .map_err(|e| format!("Invalid root certificate: {}", e))?;
+
+        // rustls only parses the certificate when a client is built: fail
+        // here rather than on the first fetch
+        reqwest::Client::builder()
+            .add_root_certificate(certificate.clone())
+            .build()
+            .map_err(|e| format!("Invalid root certificate: {}", e))?;
+
+        self.root_certificates.push(certificate);
+        Ok(self)
+    }
+
+    /// Accept header for requests without one (default `DEFAULT_ACCEPT`)
+    pub fn default_accept(mut self, accept: impl Into<String>) -> Self {
+        self.default_accept = Some(accept.into());
+        self
+    }
+
+    /// Refuse to connect to hosts resolving to private addresses
+    ///
+    /// The check runs in the client's DNS resolver, on the addresses it then
+    /// connects to (see `FetchPolicy::block_private_ips`, which sets this for
+    /// the worker's client).
+    pub fn block_private_ips(mut self) -> Self {
+        self.block_private_ips = true;
+        self
+    }
+
+    /// Build a client with this configuration and the given redirect mode
+    pub fn build_client(&self, redirect: RedirectMode) -> Result<reqwest::Client, String> {
+        let redirect_policy = match redirect {
+            RedirectMode::Follow => reqwest::redirect::Policy::default(),
+            RedirectMode::Manual | RedirectMode::Error => reqwest::redirect::Policy::none(),
+        };
+
+        // Compressed bodies are decoded transparently; reqwest then drops the
+        // Content-Encoding and Content-Length headers, matching browsers
+        let mut builder = reqwest::Client::builder()
+            .redirect(redirect_policy)
+            .gzip(true)
+            .brotli(true)
+            .deflate(true);
+
+        for certificate in &self.root_certificates {
+            builder = builder.add_root_certificate(certificate.clone());
+        }
+
+        if self.block_private_ips {
+            builder = builder.dns_resolver(Arc::new(PublicDnsResolver));
+        }
+
+        builder
+            .build()
+            .map_err(|e| format!("Failed to build HTTP client: {}", e))
+    }
+}
+
+// ============================================================================
+// Content decoding
+// ============================================================================
+
+/// Decode a gzip, deflate or br encoded response body, as browsers do
+///
+/// Decoded responses lose their Content-Encoding and Content-Length headers.
+/// Other (or stacked) encodings and empty bodies are passed through as-is.
+pub fn decode_response_body(
+    headers: &mut Vec<(String, String)>,
+    body: ResponseBody,
+) -> ResponseBody {
+    use async_compression::tokio::bufread::{BrotliDecoder, GzipDecoder, ZlibDecoder};
+    use tokio::io::AsyncRead;
+    use tokio_util::io::{ReaderStream, StreamReader};
+
+    let Some(encoding) = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-encoding"))
+        .map(|(_, value)| value.trim().to_ascii_lowercase())
+    else {
+        return body;
+    };
+
+    if !matches!(encoding.as_str(), "gzip" | "x-gzip" | "deflate" | "br") {
+        return body;
+    }
+
+    let chunks = match body {
+        ResponseBody::None => return ResponseBody::None,
+        ResponseBody::Bytes(bytes) if bytes.is_empty() => return ResponseBody::Bytes(bytes),
+        ResponseBody::Bytes(bytes) => {
+            futures_util::stream::once(async move { Ok::<_, std::io::Error>(bytes) }).boxed()
+        }
+        ResponseBody::Stream(rx) => tokio_stream::wrappers::ReceiverStream::new(rx)
+            .map(|chunk| chunk.map_err(std::io::Error::other))
+            .boxed(),
+    };
+
+    headers.retain(|(name, _)| {
+        !name.eq_ignore_ascii_case("content-encoding")
+            && !name.eq_ignore_ascii_case("content-length")
+    });
+
+    let reader = StreamReader::new(chunks);
+    let decoder: std::pin::Pin<Box<dyn AsyncRead + Send>> = match encoding.as_str() {
+        "gzip" | "x-gzip" => Box::pin(GzipDecoder::new(reader)),
+        "deflate" => Box::pin(ZlibDecoder::new(reader)),
+        _ => Box::pin(BrotliDecoder::new(reader)),
+    };
+
+    let (tx, rx) = tokio::sync::mpsc::channel(16);
+    tokio::spawn(async move {
+        let mut decoded = ReaderStream::new(decoder);
+
+        while let Some(chunk) = decoded.next().await {
+            let chunk =
+                chunk.map_err(|e| format!("Failed to decode {} response body: {}", encoding, e));
+            let failed = chunk.is_err();
+
+            if tx.send(chunk).await.is_err() || failed {
+                break;
+            }
+        }
+    });
+
+    ResponseBody::Stream(rx)
+}
+
+/// reqwest request for an HttpRequest, headers and buffered body included
+fn build_request(
+    client: &reqwest::Client,
+    request: &HttpRequest,
+) -> Result<reqwest::RequestBuilder, String> {
     let mut req_builder = match request.method {
         HttpMethod::Get => client.get(&request.url),
         HttpMethod::Post => client.post(&request.url),
@@ -196,12 +478,10 @@ pub async fn execute_fetch_streaming(
         }
     };
 
-    // Add headers
     for (key, value) in &request.headers {
         req_builder = req_builder.header(key, value);
     }
 
-    // Add body if present
     match request.body {
         RequestBody::Bytes(ref bytes) => {
             req_builder = req_builder.body(bytes.clone());
@@ -213,12 +493,140 @@ pub async fn execute_fetch_streaming(
         RequestBody::None => {}
     }
 
+    Ok(req_builder)
+}
+
+/// Response headers, keeping repeated ones (e.g. Set-Cookie) as separate pairs
+fn response_headers(response: &reqwest::Response) -> Vec<(String, String)> {
+    response
+        .headers()
+        .iter()
+        .filter_map(|(key, value)| Some((key.to_string(), value.to_str().ok()?.to_string())))
+        .collect()
+}
+
+/// OperationsHandler sending fetches with the built-in reqwest client
+///
+/// Used for fetch() from JS in place of the embedder's transport when
+/// `WorkerOptions::fetch_client` is set. Redirects are not followed here:
+/// the runtime follows them itself, checking each hop against the policy.
+pub struct FetchClient {
+    client: reqwest::Client,
+}
+
+impl FetchClient {
+    pub fn new(config: &FetchClientConfig) -> Result<Self, String> {
+        Ok(Self {
+            client: config.build_client(RedirectMode::Manual)?,
+        })
+    }
+}
+
+impl OperationsHandler for FetchClient {
+    fn handle_fetch(&self, request: HttpRequest) -> OpFuture<'_, Result<HttpResponse, String>> {
+        Box::pin(async move {
+            let response = build_request(&self.client, &request)?
+                .send()
+                .await
+                .map_err(|e| format!("Request failed: {}", e))?;
+
+            let status = response.status().as_u16();
+            let headers = response_headers(&response);
+
+            let (tx, rx) = tokio::sync::mpsc::channel(16);
+            tokio::spawn(async move {
+                let mut body = response.bytes_stream();
+
+                while let Some(chunk) = body.next().await {
+                    let chunk = chunk.map_err(|e| format!("Stream read error: {}", e));
+                    let failed = chunk.is_err();
+
+                    if tx.send(chunk).await.is_err() || failed {
+                        break;
+                    }
+                }
+            });
+
+            Ok(HttpResponse {
+                status,
+                headers,
+                body: ResponseBody::Stream(rx),
+            })
+        })
+    }
+}
+
+/// Response status and headers of a fetch, before the body
+///
+/// Unlike `HttpResponseMeta`, headers are an ordered list of pairs so that
+/// repeated headers such as Set-Cookie are all kept.
+#[derive(Debug, Clone)]
+pub struct FetchResponseMeta {
+    pub status: u16,
+    pub status_text: String,
+    pub headers: Vec<(String, String)>,
+}
+
+/// Execute HTTP request with streaming response
+/// Returns metadata and stream ID immediately, body is streamed through StreamManager
+pub async fn execute_fetch_streaming(
+    request: HttpRequest,
+    stream_manager: Arc<StreamManager>,
+) -> Result<(FetchResponseMeta, StreamId), String> {
+    execute_fetch_streaming_with_redirect(request, stream_manager, RedirectMode::Follow)
+        .await
+        .map(|(meta, stream_id, _)| (meta, stream_id))
+}
+
+/// Execute HTTP request with streaming response and the given redirect mode
+pub async fn execute_fetch_streaming_with_redirect(
+    request: HttpRequest,
+    stream_manager: Arc<StreamManager>,
+    redirect: RedirectMode,
+) -> Result<(FetchResponseMeta, StreamId, FetchedUrl), String> {
+    execute_fetch_streaming_with_config(
+        request,
+        stream_manager,
+        redirect,
+        &FetchClientConfig::default(),
+    )
+    .await
+}
+
+/// Execute HTTP request with streaming response using a client configuration
+pub async fn execute_fetch_streaming_with_config(
+    mut request: HttpRequest,
+    stream_manager: Arc<StreamManager>,
+    redirect: RedirectMode,
+    config: &FetchClientConfig,
+) -> Result<(FetchResponseMeta, StreamId, FetchedUrl), String> {
+    let client = config.build_client(redirect)?;
+
+    apply_default_accept(
+        &mut request.headers,
+        config.default_accept.as_deref().unwrap_or(DEFAULT_ACCEPT),
+    );
+    apply_default_accept_encoding(&mut request.headers);
+    let req_builder = build_request(&client, &request)?;
+
     // Execute request
     let response = req_builder
         .send()
         .await
         .map_err(|e| format!("Request failed: {}", e))?;
 
+    if redirect == RedirectMode::Error && response.status().is_redirection() {
+        return Err(format!(
+            "Redirect from {} not allowed (redirect: 'error')",
+            request.url
+        ));
+    }
+
+    let fetched_url = FetchedUrl {
+        url: response.url().to_string(),
+        redirected: reqwest::Url::parse(&request.url).is_ok_and(|u| &u != response.url()),
+    };
+
     // Extract response metadata
     let status = response.status().as_u16();
     let status_text = response
@@ -227,13 +635,7 @@ pub async fn execute_fetch_streaming(
         .unwrap_or("")
         .to_string();
 
-    // Extract headers
-    let mut headers = std::collections::HashMap::new();
-    for (key, value) in response.headers() {
-        if let Ok(value_str) = value.to_str() {
-            headers.insert(key.to_string(), value_str.to_string());
-        }
-    }
+    let headers = response_headers(&response);
 
     // Create stream for body
     let stream_id = stream_manager.create_stream(request.url.clone());
@@ -245,6 +647,8 @@ pub async fn execute_fetch_streaming(
 
         while let Some(chunk_result) = byte_stream.next().await {
             match chunk_result {
+                // Never hand JS an empty chunk: an empty body reads as done at once
+                Ok(chunk) if chunk.is_empty() => {}
                 Ok(chunk) => {
                     if let Err(e) = manager
                         .write_chunk(stream_id, StreamChunk::Data(chunk))
@@ -272,11 +676,12 @@ pub async fn execute_fetch_streaming(
     });
 
     Ok((
-        HttpResponseMeta {
+        FetchResponseMeta {
             status,
             status_text,
             headers,
         },
         stream_id,
+        fetched_url,
     ))
 }
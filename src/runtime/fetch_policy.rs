@@ -0,0 +1,367 @@
+use super::fetch::{DEFAULT_ACCEPT, apply_default_accept};
+use reqwest::Url;
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+/// Policy applied to outbound fetch requests before they are handed to the
+/// OperationsHandler
+///
+/// Host entries match the exact hostname as well as any of its subdomains
+/// (`example.com` matches `api.example.com`). The denylist always wins; when
+/// an allowlist is configured, hosts not on it are rejected.
+#[derive(Debug, Clone, Default)]
+pub struct FetchPolicy {
+    /// Hosts that may be fetched (None = any host)
+    pub allowed_hosts: Option<Vec<String>>,
+    /// Hosts that may never be fetched
+    pub denied_hosts: Vec<String>,
+    /// Reject hosts resolving to private, loopback or link-local addresses
+    pub block_private_ips: bool,
+    /// Maximum number of request or response headers (None = unlimited)
+    pub max_header_count: Option<usize>,
+    /// Maximum total size of header names and values in bytes (None = unlimited)
+    pub max_header_bytes: Option<usize>,
+    /// Accept header added to requests without one (None = `DEFAULT_ACCEPT`)
+    pub default_accept: Option<String>,
+}
+
+impl FetchPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a host to the allowlist (enables allowlist mode)
+    pub fn allow_host(mut self, host: impl Into<String>) -> Self {
+        self.allowed_hosts
+            .get_or_insert_with(Vec::new)
+            .push(normalize_host(&host.into()));
+        self
+    }
+
+    /// Add a host to the denylist
+    pub fn deny_host(mut self, host: impl Into<String>) -> Self {
+        self.denied_hosts.push(normalize_host(&host.into()));
+        self
+    }
+
+    /// Reject requests to private, loopback and link-local addresses (SSRF protection)
+    pub fn block_private_ips(mut self) -> Self {
+        self.block_private_ips = true;
+        self
+    }
+
+    /// Limit the number of headers on outbound requests and upstream responses
+    pub fn max_header_count(mut self, count: usize) -> Self {
+        self.max_header_count = Some(count);
+        self
+    }
+
+    /// Limit the total header size on outbound requests and upstream responses
+    pub fn max_header_bytes(mut self, bytes: usize) -> Self {
+        self.max_header_bytes = Some(bytes);
+        self
+    }
+
+    /// Send `accept` as the Accept header of fetches that don't set one
+    pub fn default_accept(mut self, accept: impl Into<String>) -> Self {
+        self.default_accept = Some(accept.into());
+        self
+    }
+
+    /// Add the default Accept header to a request that has none
+    pub fn apply_default_accept(&self, headers: &mut HashMap<String, String>) {
+        apply_default_accept(
+            headers,
+            self.default_accept.as_deref().unwrap_or(DEFAULT_ACCEPT),
+        );
+    }
+
+    /// Check a header list against the configured count and size limits
+    pub fn check_headers<'a, I>(&self, headers: I) -> Result<(), String>
+    where
+        I: IntoIterator<Item = (&'a String, &'a String)>,
+    {
+        if self.max_header_count.is_none() && self.max_header_bytes.is_none() {
+            return Ok(());
+        }
+
+        let mut count = 0;
+        let mut bytes = 0;
+
+        for (name, value) in headers {
+            count += 1;
+            bytes += name.len() + value.len();
+        }
+
+        if let Some(max) = self.max_header_count {
+            if count > max {
+                return Err(format!("Too many headers: {} (limit {})", count, max));
+            }
+        }
+
+        if let Some(max) = self.max_header_bytes {
+            if bytes > max {
+                return Err(format!(
+                    "Headers too large: {} bytes (limit {})",
+                    bytes, max
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check whether a request to `url` is allowed
+    pub fn check_url(&self, url: &str) -> Result<(), String> {
+        if self.allowed_hosts.is_none() && self.denied_hosts.is_empty() {
+            return Ok(());
+        }
+
+        let parsed = Url::parse(url).map_err(|e| format!("Invalid URL '{}': {}", url, e))?;
+
+        let host = parsed
+            .host_str()
+            .map(normalize_host)
+            .ok_or_else(|| format!("URL '{}' has no host", url))?;
+
+        if self.denied_hosts.iter().any(|h| host_matches(&host, h)) {
+            return Err(format!("Fetch to host '{}' is denied by policy", host));
+        }
+
+        if let Some(allowed) = &self.allowed_hosts {
+            if !allowed.iter().any(|h| host_matches(&host, h)) {
+                return Err(format!("Fetch to host '{}' is not allowed by policy", host));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Resolve the target host and reject it if any address is private
+    ///
+    /// The actual connection is made by the OperationsHandler, so this does not
+    /// protect against DNS rebinding between the check and the connect. The
+    /// built-in `FetchClient` closes that gap by connecting only to addresses
+    /// checked by `PublicDnsResolver`.
+    pub async fn check_resolved(&self, url: &str) -> Result<(), String> {
+        if !self.block_private_ips {
+            return Ok(());
+        }
+
+        let parsed = Url::parse(url).map_err(|e| format!("Invalid URL '{}': {}", url, e))?;
+
+        let host = parsed
+            .host_str()
+            .map(normalize_host)
+            .ok_or_else(|| format!("URL '{}' has no host", url))?;
+
+        let addrs: Vec<IpAddr> = match host.parse::<IpAddr>() {
+            Ok(ip) => vec![ip],
+            Err(_) => {
+                let port = parsed.port_or_known_default().unwrap_or(80);
+                tokio::net::lookup_host((host.as_str(), port))
+                    .await
+                    .map_err(|e| format!("Failed to resolve '{}': {}", host, e))?
+                    .map(|addr| addr.ip())
+                    .collect()
+            }
+        };
+
+        if let Some(ip) = addrs.iter().find(|ip| is_private_ip(ip)) {
+            return Err(format!(
+                "Fetch to host '{}' is blocked: {} is a private address",
+                host, ip
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// reqwest DNS resolver refusing hosts that resolve to a private address
+///
+/// Installed by `FetchClientConfig::block_private_ips`: the addresses checked
+/// here are the ones the client connects to, so a host cannot pass
+/// `check_resolved` and then rebind to a private address. IP literals are not
+/// resolved and are left to `check_resolved`.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct PublicDnsResolver;
+
+impl reqwest::dns::Resolve for PublicDnsResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let host = name.as_str().to_string();
+
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> =
+                tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+
+            if let Some(addr) = addrs.iter().find(|addr| is_private_ip(&addr.ip())) {
+                return Err(format!(
+                    "Fetch to host '{}' is blocked: {} is a private address",
+                    host,
+                    addr.ip()
+                )
+                .into());
+            }
+
+            let addrs: reqwest::dns::Addrs = Box::new(addrs.into_iter());
+            Ok(addrs)
+        })
+    }
+}
+
+/// Private, loopback, link-local and unspecified addresses
+fn is_private_ip(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+
+            v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                // "This network" 0.0.0.0/8
+                || a == 0
+                // Carrier-grade NAT 100.64.0.0/10
+                || (a == 100 && (b & 0xc0) == 64)
+        }
+        IpAddr::V6(v6) => {
+            // IPv4-mapped (::ffff:a.b.c.d) and IPv4-compatible (::a.b.c.d)
+            if let Some(v4) = v6.to_ipv4() {
+                return is_private_ip(&IpAddr::V4(v4));
+            }
+
+            // NAT64 well-known prefix 64:ff9b::/96
+            let segments = v6.segments();
+            if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+                let v4 = Ipv4Addr::from((u32::from(segments[6]) << 16) | u32::from(segments[7]));
+                return is_private_ip(&IpAddr::V4(v4));
+            }
+
+            v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_unique_local()
+                || v6.is_unicast_link_local()
+        }
+    }
+}
+
+/// Lowercase a host and strip brackets / trailing dot
+fn normalize_host(host: &str) -> String {
+    host.trim_start_matches('[')
+        .trim_end_matches(']')
+        .trim_end_matches('.')
+        .to_lowercase()
+}
+
+/// Exact match or subdomain match
+fn host_matches(host: &str, entry: &str) -> bool {
+    host == entry || host.ends_with(&format!(".{}", entry))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_allows_everything() {
+        let policy = FetchPolicy::new();
+        assert!(policy.check_url("https://example.com/").is_ok());
+    }
+
+    #[test]
+    fn test_denylist() {
+        let policy = FetchPolicy::new().deny_host("evil.example");
+
+        assert!(policy.check_url("https://evil.example/steal").is_err());
+        assert!(policy.check_url("https://API.Evil.Example/").is_err());
+        assert!(policy.check_url("https://notevil.example/").is_ok());
+        assert!(policy.check_url("https://good.example/").is_ok());
+    }
+
+    #[test]
+    fn test_allowlist() {
+        let policy = FetchPolicy::new().allow_host("example.com");
+
+        assert!(policy.check_url("https://example.com/").is_ok());
+        assert!(policy.check_url("https://api.example.com/").is_ok());
+        assert!(policy.check_url("https://other.com/").is_err());
+    }
+
+    #[test]
+    fn test_private_ip_ranges() {
+        for ip in [
+            "10.0.0.1",
+            "172.16.5.4",
+            "192.168.1.1",
+            "127.0.0.1",
+            "169.254.169.254",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "0.1.2.3",
+            "100.64.0.1",
+            "100.127.255.254",
+            "::127.0.0.1",
+            "::10.0.0.1",
+            "64:ff9b::10.0.0.1",
+            "64:ff9b::7f00:1",
+        ] {
+            assert!(
+                is_private_ip(&ip.parse().unwrap()),
+                "{} should be private",
+                ip
+            );
+        }
+
+        for ip in [
+            "93.184.216.34",
+            "8.8.8.8",
+            "100.128.0.1",
+            "2606:4700::1111",
+            "::8.8.8.8",
+            "64:ff9b::8.8.8.8",
+        ] {
+            assert!(
+                !is_private_ip(&ip.parse().unwrap()),
+                "{} should be public",
+                ip
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_resolved_blocks_loopback() {
+        let policy = FetchPolicy::new().block_private_ips();
+
+        assert!(policy.check_resolved("http://127.0.0.1/").await.is_err());
+        assert!(policy.check_resolved("http://[::1]:8080/").await.is_err());
+        assert!(policy.check_resolved("http://8.8.8.8/").await.is_ok());
+    }
+
+    #[test]
+    fn test_header_limits() {
+        let policy = FetchPolicy::new().max_header_count(2).max_header_bytes(16);
+
+        let a = ("a".to_string(), "1".to_string());
+        let b = ("b".to_string(), "2".to_string());
+        let c = ("c".to_string(), "3".to_string());
+        let big = ("big".to_string(), "x".repeat(32));
+
+        assert!(policy.check_headers([(&a.0, &a.1), (&b.0, &b.1)]).is_ok());
+        assert!(
+            policy
+                .check_headers([(&a.0, &a.1), (&b.0, &b.1), (&c.0, &c.1)])
+                .is_err()
+        );
+        assert!(policy.check_headers([(&big.0, &big.1)]).is_err());
+    }
+
+    #[test]
+    fn test_invalid_url() {
+        let policy = FetchPolicy::new().deny_host("evil.example");
+        assert!(policy.check_url("not a url").is_err());
+    }
+}
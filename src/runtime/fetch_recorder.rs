@@ -0,0 +1,133 @@
+use openworkers_core::{
+    HttpRequest, HttpResponse, OpFuture, OperationsHandle, OperationsHandler, ResponseBody,
+};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// A single recorded fetch exchange
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedFetch {
+    pub method: String,
+    pub url: String,
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+/// OperationsHandler that forwards fetches to another handler and records
+/// every request/response pair
+///
+/// Response bodies are buffered in full so they can be written to the
+/// recording. Call `save` once the worker is done to persist the exchanges.
+pub struct FetchRecorder {
+    inner: OperationsHandle,
+    entries: Mutex<Vec<RecordedFetch>>,
+}
+
+impl FetchRecorder {
+    pub fn new(inner: OperationsHandle) -> Self {
+        Self {
+            inner,
+            entries: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Exchanges recorded so far, in request order
+    pub fn entries(&self) -> Vec<RecordedFetch> {
+        self.entries.lock().unwrap().clone()
+    }
+
+    /// Write the recording as JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let json = serde_json::to_vec_pretty(&*self.entries.lock().unwrap())?;
+        std::fs::write(path, json)
+    }
+}
+
+impl OperationsHandler for FetchRecorder {
+    fn handle_fetch(&self, request: HttpRequest) -> OpFuture<'_, Result<HttpResponse, String>> {
+        Box::pin(async move {
+            let method = request.method.as_str().to_string();
+            let url = request.url.clone();
+
+            let response = self.inner.handle_fetch(request).await?;
+
+            let body = match response.body {
+                ResponseBody::None => Vec::new(),
+                ResponseBody::Bytes(bytes) => bytes.to_vec(),
+                ResponseBody::Stream(mut rx) => {
+                    let mut body = Vec::new();
+                    while let Some(chunk) = rx.recv().await {
+                        body.extend_from_slice(&chunk?);
+                    }
+                    body
+                }
+            };
+
+            self.entries.lock().unwrap().push(RecordedFetch {
+                method,
+                url,
+                status: response.status,
+                headers: response.headers.clone(),
+                body: body.clone(),
+            });
+
+            Ok(HttpResponse {
+                status: response.status,
+                headers: response.headers,
+                body: ResponseBody::Bytes(body.into()),
+            })
+        })
+    }
+}
+
+/// OperationsHandler that answers fetches from a recording without touching
+/// the network
+///
+/// Each recorded exchange is served once, matched by method and URL in
+/// recording order. Requests with no remaining match fail.
+pub struct FetchReplayer {
+    entries: Mutex<Vec<Option<RecordedFetch>>>,
+}
+
+impl FetchReplayer {
+    pub fn new(entries: Vec<RecordedFetch>) -> Self {
+        Self {
+            entries: Mutex::new(entries.into_iter().map(Some).collect()),
+        }
+    }
+
+    /// Load a recording written by `FetchRecorder::save`
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let json = std::fs::read(path)?;
+        let entries: Vec<RecordedFetch> = serde_json::from_slice(&json)?;
+        Ok(Self::new(entries))
+    }
+}
+
+impl OperationsHandler for FetchReplayer {
+    fn handle_fetch(&self, request: HttpRequest) -> OpFuture<'_, Result<HttpResponse, String>> {
+        Box::pin(async move {
+            let method = request.method.as_str();
+
+            let entry = self
+                .entries
+                .lock()
+                .unwrap()
+                .iter_mut()
+                .find(|e| {
+                    e.as_ref()
+                        .is_some_and(|e| e.method == method && e.url == request.url)
+                })
+                .and_then(Option::take)
+                .ok_or_else(|| format!("No recorded response for {} {}", method, request.url))?;
+
+            Ok(HttpResponse {
+                status: entry.status,
+                headers: entry.headers,
+                body: ResponseBody::Bytes(entry.body.into()),
+            })
+        })
+    }
+}
@@ -0,0 +1,126 @@
+/// FormData class implementation (WHATWG XMLHttpRequest spec, string values only)
+pub const FORM_DATA_JS: &str = r#"
+    globalThis.FormData = class FormData {
+        constructor() {
+            this._entries = [];
+        }
+
+        append(name, value) {
+            this._entries.push([String(name), String(value)]);
+        }
+
+        set(name, value) {
+            name = String(name);
+            const index = this._entries.findIndex(([key]) => key === name);
+
+            if (index === -1) {
+                this._entries.push([name, String(value)]);
+                return;
+            }
+
+            // Replace the first entry and drop the others
+            this._entries[index] = [name, String(value)];
+            this._entries = this._entries.filter(([key], i) => key !== name || i === index);
+        }
+
+        get(name) {
+            name = String(name);
+            const entry = this._entries.find(([key]) => key === name);
+            return entry ? entry[1] : null;
+        }
+
+        getAll(name) {
+            name = String(name);
+            return this._entries.filter(([key]) => key === name).map(([, value]) => value);
+        }
+
+        has(name) {
+            name = String(name);
+            return this._entries.some(([key]) => key === name);
+        }
+
+        delete(name) {
+            name = String(name);
+            this._entries = this._entries.filter(([key]) => key !== name);
+        }
+
+        forEach(callback, thisArg) {
+            for (const [key, value] of this._entries) {
+                callback.call(thisArg, value, key, this);
+            }
+        }
+
+        *entries() {
+            for (const [key, value] of this._entries) {
+                yield [key, value];
+            }
+        }
+
+        *keys() {
+            for (const [key] of this._entries) {
+                yield key;
+            }
+        }
+
+        *values() {
+            for (const [, value] of this._entries) {
+                yield value;
+            }
+        }
+
+        [Symbol.iterator]() {
+            return this.entries();
+        }
+
+        // Internal: parse a body according to its Content-Type
+        static _parse(text, contentType) {
+            const type = (contentType || '').toLowerCase();
+            const formData = new FormData();
+
+            if (type.startsWith('application/x-www-form-urlencoded')) {
+                for (const [key, value] of new URLSearchParams(text)) {
+                    formData.append(key, value);
+                }
+                return formData;
+            }
+
+            if (type.startsWith('multipart/form-data')) {
+                const match = /boundary=(?:"([^"]+)"|([^;]+))/i.exec(contentType);
+                if (!match) {
+                    throw new TypeError('Missing multipart boundary');
+                }
+                const boundary = '--' + (match[1] || match[2]).trim();
+
+                // Everything between the first and the closing boundary
+                const parts = text.split(boundary).slice(1);
+                for (const part of parts) {
+                    if (part.startsWith('--')) {
+                        break;
+                    }
+
+                    const headerEnd = part.indexOf('\r\n\r\n');
+                    if (headerEnd === -1) {
+                        continue;
+                    }
+
+                    const rawHeaders = part.slice(0, headerEnd);
+                    let value = part.slice(headerEnd + 4);
+                    if (value.endsWith('\r\n')) {
+                        value = value.slice(0, -2);
+                    }
+
+                    const disposition = rawHeaders
+                        .split('\r\n')
+                        .find(line => line.toLowerCase().startsWith('content-disposition:'));
+                    const name = disposition && /\bname="([^"]*)"/i.exec(disposition);
+                    if (name) {
+                        formData.append(name[1], value);
+                    }
+                }
+                return formData;
+            }
+
+            throw new TypeError('Unsupported Content-Type for formData(): ' + contentType);
+        }
+    };
+"#;
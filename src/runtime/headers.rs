@@ -1,18 +1,23 @@
-use rusty_jsc::JSContext;
-
-/// Setup global Headers class
-pub fn setup_headers(context: &mut JSContext) {
-    let code = r#"
+/// Headers class implementation
+pub const HEADERS_JS: &str = r#"
         globalThis.Headers = class Headers {
             constructor(init) {
                 this._map = new Map();
+                // Original casing of each header name (non-standard, see raw())
+                this._names = new Map();
+                // Set-Cookie values kept apart: they cannot be comma-joined
+                this._cookies = [];
 
                 if (init) {
                     if (init instanceof Headers) {
                         // Copy from another Headers object
-                        for (const [key, value] of init) {
+                        for (const [key, value] of init._map) {
                             this._map.set(key, value);
                         }
+                        for (const [key, name] of init._names) {
+                            this._names.set(key, name);
+                        }
+                        this._cookies = init._cookies.slice();
                     } else if (Array.isArray(init)) {
                         // Array of [key, value] pairs
                         for (const [key, value] of init) {
@@ -32,18 +37,46 @@ pub fn setup_headers(context: &mut JSContext) {
                 return String(name).toLowerCase();
             }
 
+            // Header names must be RFC 7230 tokens
+            static _validateName(name) {
+                name = String(name);
+                if (!/^[!#$%&'*+\-.^_`|~0-9A-Za-z]+$/.test(name)) {
+                    throw new TypeError(`Invalid header name: '${name}'`);
+                }
+                return name;
+            }
+
+            // Strip leading/trailing HTTP whitespace; NUL, CR and LF are rejected
+            static _normalizeValue(name, value) {
+                const normalized = String(value).replace(/^[\t\n\r ]+|[\t\n\r ]+$/g, '');
+                if (/[\0\r\n]/.test(normalized)) {
+                    throw new TypeError(`Invalid header value for '${name}'`);
+                }
+                return normalized;
+            }
+
             append(name, value) {
+                name = Headers._validateName(name);
                 const key = this._normalizeKey(name);
-                const strValue = String(value);
+                const strValue = Headers._normalizeValue(name, value);
+                if (key === 'set-cookie') {
+                    this._cookies.push(strValue);
+                }
                 if (this._map.has(key)) {
                     this._map.set(key, this._map.get(key) + ', ' + strValue);
                 } else {
                     this._map.set(key, strValue);
+                    this._names.set(key, String(name));
                 }
             }
 
             delete(name) {
-                this._map.delete(this._normalizeKey(name));
+                const key = this._normalizeKey(name);
+                this._map.delete(key);
+                this._names.delete(key);
+                if (key === 'set-cookie') {
+                    this._cookies = [];
+                }
             }
 
             get(name) {
@@ -56,24 +89,48 @@ pub fn setup_headers(context: &mut JSContext) {
             }
 
             set(name, value) {
-                this._map.set(this._normalizeKey(name), String(value));
+                name = Headers._validateName(name);
+                const key = this._normalizeKey(name);
+                const strValue = Headers._normalizeValue(name, value);
+                this._map.set(key, strValue);
+                this._names.set(key, name);
+                if (key === 'set-cookie') {
+                    this._cookies = [strValue];
+                }
+            }
+
+            // Non-standard: [name, value] pairs with the original name casing
+            raw() {
+                return Array.from(this.entries(), ([key, value]) => [this._names.get(key) || key, value]);
             }
 
-            // Iteration methods
+            // Iteration methods (each Set-Cookie value is its own entry)
             *entries() {
-                yield* this._map.entries();
+                for (const [key, value] of this._map) {
+                    if (key === 'set-cookie') {
+                        for (const cookie of this._cookies) {
+                            yield [key, cookie];
+                        }
+                    } else {
+                        yield [key, value];
+                    }
+                }
             }
 
             *keys() {
-                yield* this._map.keys();
+                for (const [key] of this.entries()) {
+                    yield key;
+                }
             }
 
             *values() {
-                yield* this._map.values();
+                for (const [, value] of this.entries()) {
+                    yield value;
+                }
             }
 
             forEach(callback, thisArg) {
-                for (const [key, value] of this._map) {
+                for (const [key, value] of this.entries()) {
                     callback.call(thisArg, value, key, this);
                 }
             }
@@ -85,17 +142,7 @@ pub fn setup_headers(context: &mut JSContext) {
 
             // getSetCookie returns all Set-Cookie headers as array
             getSetCookie() {
-                const cookies = [];
-                const value = this._map.get('set-cookie');
-                if (value) {
-                    cookies.push(value);
-                }
-                return cookies;
+                return this._cookies.slice();
             }
         };
     "#;
-
-    context
-        .evaluate_script(code, 1)
-        .expect("Failed to setup Headers");
-}
@@ -0,0 +1,88 @@
+use rusty_jsc::{JSContext, JSObject, JSValue};
+use std::sync::{Arc, Mutex};
+
+/// Heap size limit in bytes reported as `performance.memory.jsHeapSizeLimit`
+/// (None = no limit) - set by the worker from its RuntimeLimits
+pub type HeapLimit = Arc<Mutex<Option<u64>>>;
+
+// JavaScriptCore heap statistics (JSBasePrivate.h). Returns an object with
+// heapSize, heapCapacity, extraMemorySize, objectCount, ... in bytes/counts.
+unsafe extern "C" {
+    fn JSGetMemoryUsageStatistics(ctx: rusty_jsc_sys::JSContextRef) -> rusty_jsc_sys::JSObjectRef;
+}
+
+/// Setup `__heapUsed()` and `performance.memory` backed by the JSC heap statistics
+pub fn setup_heap_stats(context: &mut JSContext, heap_limit: HeapLimit) {
+    // Create __nativeHeapStatistics() -> { heapSize, heapCapacity, extraMemorySize, ... }
+    let heap_statistics_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, _args: &[JSValue]| {
+            let stats = unsafe { JSGetMemoryUsageStatistics(ctx.get_ref()) };
+            if stats.is_null() {
+                return Err(JSValue::string(&ctx, "Heap statistics are unavailable"));
+            }
+            Ok(JSObject::from(stats).into())
+        }
+    );
+
+    // Create __nativeHeapLimit() -> bytes (Infinity without a limit)
+    let heap_limit_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, _args: &[JSValue]| {
+            let limit = heap_limit
+                .lock()
+                .unwrap()
+                .map_or(f64::INFINITY, |bytes| bytes as f64);
+            Ok(JSValue::number(&ctx, limit))
+        }
+    );
+
+    let mut global = context.get_global_object();
+    global
+        .set_property(context, "__nativeHeapStatistics", heap_statistics_fn.into())
+        .unwrap();
+    global
+        .set_property(context, "__nativeHeapLimit", heap_limit_fn.into())
+        .unwrap();
+
+    let code = r#"
+        (function() {
+            const stats = __nativeHeapStatistics;
+            const limit = __nativeHeapLimit;
+            delete globalThis.__nativeHeapStatistics;
+            delete globalThis.__nativeHeapLimit;
+
+            // Bytes currently used by the JS heap (objects plus their external memory)
+            const heapUsed = () => {
+                const s = stats();
+                return s.heapSize + (s.extraMemorySize || 0);
+            };
+
+            Object.defineProperty(globalThis, '__heapUsed', {
+                value: heapUsed,
+                writable: false,
+                enumerable: false,
+                configurable: false
+            });
+
+            // Non-standard, shaped like Chrome's performance.memory
+            Object.defineProperty(performance, 'memory', {
+                get() {
+                    const s = stats();
+                    return {
+                        usedJSHeapSize: s.heapSize + (s.extraMemorySize || 0),
+                        totalJSHeapSize: s.heapCapacity + (s.extraMemorySize || 0),
+                        jsHeapSizeLimit: limit(),
+                        objectCount: s.objectCount
+                    };
+                },
+                enumerable: true,
+                configurable: true
+            });
+        })();
+    "#;
+
+    context
+        .evaluate_script(code, 1)
+        .expect("Failed to setup heap statistics");
+}
@@ -1,19 +1,62 @@
+mod abort;
 mod base64;
 pub mod bindings;
+mod blob;
+pub mod clock;
+mod compression;
+mod cookie;
 mod crypto;
+mod event_target;
 pub mod fetch;
+pub mod fetch_policy;
+pub mod fetch_recorder;
+mod form_data;
 mod headers;
+mod heap;
 mod request;
 mod response;
+mod runtime_info;
 pub mod stream_manager;
 mod streams;
 mod text_encoding;
+mod typed_array;
 mod url;
+pub mod websocket;
 
 // Re-export fetch functions for internal use
-pub use fetch::{execute_fetch_streaming, parse_fetch_options};
+pub use fetch::{
+    DEFAULT_ACCEPT, DEFAULT_ACCEPT_ENCODING, FetchClient, FetchClientConfig, FetchProgress,
+    FetchResponseMeta, FetchedUrl, RedirectMode, execute_fetch_streaming,
+    execute_fetch_streaming_with_config, execute_fetch_streaming_with_redirect,
+    parse_buffer_option, parse_fetch_options, parse_progress_option, parse_redirect_mode,
+};
+pub use fetch_policy::FetchPolicy;
+pub use fetch_recorder::{FetchRecorder, FetchReplayer, RecordedFetch};
+
+/// Pure-JS prelude scripts, in evaluation order (captured by snapshots)
+pub(crate) const PRELUDE: &[(&str, &str)] = &[
+    ("base64", base64::BASE64_JS),
+    ("streams", streams::READABLE_STREAM_JS),
+    ("blob", blob::BLOB_JS),
+    ("headers", headers::HEADERS_JS),
+    ("response", response::RESPONSE_JS),
+    ("form-data", form_data::FORM_DATA_JS),
+    ("request", request::REQUEST_JS),
+    ("event-target", event_target::EVENT_TARGET_JS),
+    ("abort", abort::ABORT_JS),
+    ("cookie", cookie::COOKIE_JS),
+];
+
+/// Join named prelude scripts into the single script the runtime evaluates
+pub(crate) fn prelude_source<'a>(scripts: impl IntoIterator<Item = (&'a str, &'a str)>) -> String {
+    scripts
+        .into_iter()
+        .map(|(name, source)| format!("// {}\n{}\n;", name, source))
+        .collect()
+}
 
-use openworkers_core::{HttpRequest, HttpResponseMeta};
+use crate::snapshot::Snapshot;
+use openworkers_core::{HttpRequest, HttpResponse};
 use rusty_jsc::{JSContext, JSObject, JSValue};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
@@ -23,20 +66,47 @@ use tokio::sync::mpsc;
 /// Unique ID for callbacks
 pub type CallbackId = u64;
 
+/// Bytes read at a time from a host reader (see `Runtime::create_reader_stream`)
+const READER_STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+/// How often a draining event loop checks whether in-flight work is done
+const DRAIN_CHECK_INTERVAL: Duration = Duration::from_millis(5);
+
 /// Message sent from JS to schedule async operations
 pub enum SchedulerMessage {
     /// Schedule a timeout: (callback_id, delay_ms)
     ScheduleTimeout(CallbackId, u64),
     /// Schedule an interval: (callback_id, interval_ms)
     ScheduleInterval(CallbackId, u64),
+    /// Schedule an immediate (zero-delay macrotask): (callback_id)
+    ScheduleImmediate(CallbackId),
     /// Clear a timer (timeout or interval): (callback_id)
     ClearTimer(CallbackId),
-    /// Fetch with streaming response: (promise_id, request)
-    FetchStreaming(CallbackId, HttpRequest),
+    /// Fetch with streaming response: (promise_id, request, redirect mode, report progress)
+    FetchStreaming(CallbackId, HttpRequest, RedirectMode, bool),
+    /// Fetch reading the whole body before resolving: (promise_id, request, redirect mode, report progress)
+    FetchBuffered(CallbackId, HttpRequest, RedirectMode, bool),
+    /// Abort an in-flight fetch: (promise_id)
+    FetchAbort(CallbackId),
     /// Read next chunk from stream: (callback_id, stream_id)
     StreamRead(CallbackId, stream_manager::StreamId),
+    /// Wait for room in a stream buffer: (callback_id, stream_id)
+    StreamWaitReady(CallbackId, stream_manager::StreamId),
     /// Cancel/close a stream
     StreamCancel(stream_manager::StreamId),
+    /// Open a WebSocket: (socket_id, url, subprotocols)
+    WebSocketConnect(CallbackId, String, Vec<String>),
+    /// Send a frame on an open WebSocket: (socket_id, frame)
+    WebSocketSend(CallbackId, websocket::WebSocketFrame),
+    /// Start the closing handshake: (socket_id, code, reason)
+    WebSocketClose(CallbackId, Option<u16>, String),
+    /// Set (or disable) the idle timeout of the event loop
+    SetIdleTimeout(Option<Duration>),
+    /// A task started: restart the idle timeout
+    Activity,
+    /// Stop accepting new work and shut down once in-flight fetches and
+    /// streams finish, aborting whatever is left after the timeout
+    Drain(Duration),
     /// Shutdown the event loop
     Shutdown,
 }
@@ -53,10 +123,23 @@ pub enum CallbackMessage {
     ExecutePromiseReject(CallbackId, String),
     /// Reject a fetch Promise with error
     FetchError(CallbackId, String),
-    /// Fetch streaming success: metadata + stream ID
-    FetchStreamingSuccess(CallbackId, HttpResponseMeta, stream_manager::StreamId),
+    /// Fetch streaming success: metadata + stream ID + final URL
+    FetchStreamingSuccess(
+        CallbackId,
+        FetchResponseMeta,
+        stream_manager::StreamId,
+        FetchedUrl,
+    ),
+    /// Buffered fetch success: metadata + whole body + final URL
+    FetchBufferedSuccess(CallbackId, FetchResponseMeta, bytes::Bytes, FetchedUrl),
+    /// Fetch progress for the promise's onprogress callback
+    FetchProgress(CallbackId, FetchProgress),
     /// Stream chunk ready
     StreamChunk(CallbackId, stream_manager::StreamChunk),
+    /// Stream buffer has room for another chunk
+    StreamReady(CallbackId),
+    /// WebSocket event for the socket's dispatch callback
+    WebSocketEvent(CallbackId, websocket::WebSocketEvent),
 }
 
 /// Runtime that manages JSContext and tokio event loop
@@ -74,11 +157,25 @@ pub struct Runtime {
     pub(crate) next_callback_id: Arc<Mutex<CallbackId>>,
     /// Track which callbacks are intervals (vs timeouts) - shared with bindings
     pub(crate) intervals: Arc<Mutex<std::collections::HashSet<CallbackId>>>,
-    /// Sender for fetch response (set during fetch execution)
-    pub(crate) fetch_response_tx: Arc<Mutex<Option<tokio::sync::oneshot::Sender<String>>>>,
+    /// IDs of pending timers (timeouts, intervals, immediates) - shared with bindings
+    pub(crate) timers: Arc<Mutex<std::collections::HashSet<CallbackId>>>,
+    /// Fetch onprogress callbacks (promise_id -> JSObject function) - shared with bindings
+    pub(crate) fetch_progress: Arc<Mutex<HashMap<CallbackId, JSObject>>>,
+    /// Signals that the current fetch event has a response (set during fetch execution)
+    pub(crate) fetch_response_tx: Arc<Mutex<Option<tokio::sync::oneshot::Sender<()>>>>,
+    /// Response set from Rust for the current or next fetch event (see `respond_with`)
+    pub(crate) host_response: Arc<Mutex<Option<HttpResponse>>>,
     /// Stream manager for handling streaming responses
-    #[allow(dead_code)]
     pub(crate) stream_manager: Arc<stream_manager::StreamManager>,
+    /// Clock shared by timers, Date.now() and performance.now()
+    pub clock: clock::Clock,
+    /// Seeded RNG for getRandomValues/randomUUID (see `with_rng`) - shared with crypto
+    pub(crate) seeded_rng: crypto::SharedRng,
+    /// Headers added to every fetch of the current event (see
+    /// WorkerOptions::propagate_header) - shared with bindings
+    pub(crate) propagated_headers: bindings::PropagatedHeaders,
+    /// Reported heap size limit (see `set_heap_limit`) - shared with heap
+    pub(crate) heap_limit: heap::HeapLimit,
 }
 
 impl Runtime {
@@ -88,6 +185,41 @@ impl Runtime {
         mpsc::UnboundedSender<CallbackMessage>,
         Arc<stream_manager::StreamManager>,
     ) {
+        Self::with_prelude(&prelude_source(PRELUDE.iter().copied()))
+            .expect("Built-in prelude should evaluate")
+    }
+
+    /// Create a runtime from a snapshot
+    ///
+    /// Native bindings are always set up in code; the snapshot provides the
+    /// pure-JS prelude source, which is evaluated in place of the built-in one
+    /// (see `crate::snapshot`). Fails if that prelude throws, e.g. for a
+    /// corrupt snapshot.
+    pub fn from_snapshot(
+        snapshot: &Snapshot,
+    ) -> Result<
+        (
+            Self,
+            mpsc::UnboundedReceiver<SchedulerMessage>,
+            mpsc::UnboundedSender<CallbackMessage>,
+            Arc<stream_manager::StreamManager>,
+        ),
+        String,
+    > {
+        Self::with_prelude(&snapshot.source())
+    }
+
+    fn with_prelude(
+        prelude: &str,
+    ) -> Result<
+        (
+            Self,
+            mpsc::UnboundedReceiver<SchedulerMessage>,
+            mpsc::UnboundedSender<CallbackMessage>,
+            Arc<stream_manager::StreamManager>,
+        ),
+        String,
+    > {
         let (scheduler_tx, scheduler_rx) = mpsc::unbounded_channel();
         let (callback_tx, callback_rx) = mpsc::unbounded_channel();
 
@@ -96,38 +228,62 @@ impl Runtime {
         let next_callback_id: Arc<Mutex<CallbackId>> = Arc::new(Mutex::new(1));
         let intervals: Arc<Mutex<std::collections::HashSet<CallbackId>>> =
             Arc::new(Mutex::new(std::collections::HashSet::new()));
-        let fetch_response_tx: Arc<Mutex<Option<tokio::sync::oneshot::Sender<String>>>> =
+        let timers: Arc<Mutex<std::collections::HashSet<CallbackId>>> =
+            Arc::new(Mutex::new(std::collections::HashSet::new()));
+        let fetch_progress: Arc<Mutex<HashMap<CallbackId, JSObject>>> =
+            Arc::new(Mutex::new(HashMap::new()));
+        let fetch_response_tx: Arc<Mutex<Option<tokio::sync::oneshot::Sender<()>>>> =
             Arc::new(Mutex::new(None));
         let stream_manager = Arc::new(stream_manager::StreamManager::new());
+        let clock = clock::Clock::new();
+        let seeded_rng: crypto::SharedRng = Arc::new(Mutex::new(None));
+        let propagated_headers: bindings::PropagatedHeaders = Arc::new(Mutex::new(Vec::new()));
+        let heap_limit: heap::HeapLimit = Arc::new(Mutex::new(None));
 
         let mut context = JSContext::default();
 
+        // WorkerGlobalScope-style `self`: a getter, so it is always globalThis
+        context
+            .evaluate_script(
+                "Object.defineProperty(globalThis, 'self', { get() { return globalThis; }, configurable: true, enumerable: true });",
+                1,
+            )
+            .expect("Failed to setup self");
+
         // Setup queueMicrotask
         bindings::setup_microtask(&mut context);
 
-        // Setup TextEncoder/TextDecoder
-        text_encoding::setup_text_encoding(&mut context);
+        // Setup Date.now() and performance.now() on the shared runtime clock
+        clock::setup_clock(&mut context, clock);
 
-        // Setup atob/btoa (depends on TextEncoder/TextDecoder)
-        base64::setup_base64(&mut context);
+        // Setup __heapUsed() and performance.memory (needs performance)
+        heap::setup_heap_stats(&mut context, heap_limit.clone());
 
-        // Setup ReadableStream
-        streams::setup_readable_stream(&mut context);
-
-        // Setup Headers (before Response)
-        headers::setup_headers(&mut context);
+        // Setup TextEncoder/TextDecoder
+        text_encoding::setup_text_encoding(&mut context);
 
-        // Setup Response (uses ReadableStream and Headers)
-        response::setup_response(&mut context);
+        // Setup the pure-JS prelude in one script: atob/btoa, ReadableStream,
+        // Blob, Headers, Response, FormData, Request, EventTarget, AbortController
+        // and the cookie helpers (depends on TextEncoder/TextDecoder)
+        if let Err(e) = context.evaluate_script(prelude, 1) {
+            let message = e
+                .to_js_string(&context)
+                .map(|s| s.to_string())
+                .unwrap_or_else(|_| "unknown error".to_string());
+            return Err(format!("Failed to evaluate prelude: {}", message));
+        }
 
-        // Setup Request (uses ReadableStream, Headers, TextEncoder)
-        request::setup_request(&mut context);
+        // Setup Uint8Array base64/hex methods
+        base64::setup_uint8array_encoding(&mut context);
 
         // Setup URL API
         url::setup_url_api(&mut context);
 
+        // Setup DecompressionStream (needs TransformStream)
+        compression::setup_compression(&mut context);
+
         // Setup crypto API
-        crypto::setup_crypto(&mut context);
+        crypto::setup_crypto(&mut context, seeded_rng.clone());
 
         // Setup fetch API
         bindings::setup_fetch(
@@ -135,6 +291,8 @@ impl Runtime {
             scheduler_tx.clone(),
             callbacks.clone(),
             next_callback_id.clone(),
+            fetch_progress.clone(),
+            propagated_headers.clone(),
         );
 
         // Setup timer bindings (pass shared state)
@@ -144,6 +302,7 @@ impl Runtime {
             callbacks.clone(),
             next_callback_id.clone(),
             intervals.clone(),
+            timers.clone(),
         );
 
         // Setup stream operations for native streaming
@@ -154,8 +313,25 @@ impl Runtime {
             next_callback_id.clone(),
         );
 
+        // Setup WebSocket client
+        websocket::setup_websocket(
+            &mut context,
+            scheduler_tx.clone(),
+            callbacks.clone(),
+            next_callback_id.clone(),
+        );
+
         // Setup response stream operations for streaming all responses
-        bindings::setup_response_stream_ops(&mut context, stream_manager.clone());
+        bindings::setup_response_stream_ops(
+            &mut context,
+            stream_manager.clone(),
+            scheduler_tx.clone(),
+            callbacks.clone(),
+            next_callback_id.clone(),
+        );
+
+        // Setup __runtime diagnostics (version and installed subsystems)
+        runtime_info::setup_runtime_info(&mut context);
 
         let runtime = Self {
             context,
@@ -164,11 +340,36 @@ impl Runtime {
             callbacks,
             next_callback_id,
             intervals,
+            timers,
+            fetch_progress,
             fetch_response_tx,
+            host_response: Arc::new(Mutex::new(None)),
             stream_manager: stream_manager.clone(),
+            clock,
+            seeded_rng,
+            propagated_headers,
+            heap_limit,
         };
 
-        (runtime, scheduler_rx, callback_tx, stream_manager)
+        Ok((runtime, scheduler_rx, callback_tx, stream_manager))
+    }
+
+    /// Make getRandomValues and randomUUID reproducible from `seed`
+    ///
+    /// For tests only: the sequence is not cryptographically secure.
+    /// crypto.subtle operations that draw randomness natively (EC and RSA
+    /// keys, signatures) keep using the system RNG.
+    pub fn with_rng(self, seed: u64) -> Self {
+        *self.seeded_rng.lock().unwrap() = Some(crypto::SeededRng::new(seed));
+        self
+    }
+
+    /// Report `bytes` as `performance.memory.jsHeapSizeLimit` (None = Infinity)
+    ///
+    /// Informational, so workers can throttle themselves: JSC does not
+    /// enforce it.
+    pub fn set_heap_limit(&self, bytes: Option<u64>) {
+        *self.heap_limit.lock().unwrap() = bytes;
     }
 
     /// Clear a timer (remove from callbacks and intervals)
@@ -179,271 +380,596 @@ impl Runtime {
         let mut intervals = self.intervals.lock().unwrap();
         intervals.remove(&callback_id);
 
+        let mut timers = self.timers.lock().unwrap();
+        timers.remove(&callback_id);
+
         // Send clear message to event loop
         let _ = self
             .scheduler_tx
             .send(SchedulerMessage::ClearTimer(callback_id));
     }
 
+    /// Cancel every active interval
+    ///
+    /// Intervals otherwise outlive the code that started them and keep sending
+    /// `ExecuteInterval` messages. Ticks already queued are skipped.
+    pub fn reset(&mut self) {
+        let ids: Vec<CallbackId> = self.intervals.lock().unwrap().drain().collect();
+
+        for callback_id in ids {
+            log::debug!("Reset: clearing interval {}", callback_id);
+            self.clear_timer(callback_id);
+            bindings::drop_timer_args(&self.context, callback_id);
+        }
+    }
+
+    /// Shut the event loop down after `timeout` without tasks (None disables)
+    ///
+    /// The timeout restarts on every `record_activity` call and scheduler
+    /// message, and never fires while timers, fetches or stream reads are in
+    /// flight. Once the loop has exited, `is_shut_down` returns true and the
+    /// runtime should be dropped to free its JSC heap.
+    pub fn set_idle_timeout(&self, timeout: Option<Duration>) {
+        let _ = self
+            .scheduler_tx
+            .send(SchedulerMessage::SetIdleTimeout(timeout));
+    }
+
+    /// Shut the event loop down gracefully
+    ///
+    /// New timers, fetches and WebSocket connections are refused (fetches
+    /// reject), intervals are cancelled, and the loop keeps serving stream
+    /// reads until in-flight fetches and unread streams are done, so bodies
+    /// already being streamed are not truncated. Work still pending after
+    /// `timeout` is aborted. Callbacks must keep being processed meanwhile.
+    pub fn drain(&self, timeout: Duration) {
+        let _ = self.scheduler_tx.send(SchedulerMessage::Drain(timeout));
+    }
+
+    /// Answer the running fetch event from Rust, bypassing the JS response
+    ///
+    /// For host-side middleware. While a handler runs, `response` wins over
+    /// anything it later passes to `respondWith`; with no fetch event in
+    /// flight, the next one is answered without running the JS handler.
+    pub fn respond_with(&self, response: HttpResponse) {
+        *self.host_response.lock().unwrap() = Some(response);
+
+        if let Some(tx) = self.fetch_response_tx.lock().unwrap().take() {
+            let _ = tx.send(());
+        }
+    }
+
+    /// Restart the idle timeout (called for each task)
+    pub fn record_activity(&self) {
+        let _ = self.scheduler_tx.send(SchedulerMessage::Activity);
+    }
+
+    /// Whether the event loop has exited (shutdown, drain or idle timeout)
+    pub fn is_shut_down(&self) -> bool {
+        self.scheduler_tx.is_closed()
+    }
+
     /// Process pending callbacks (non-blocking)
     pub fn process_callbacks(&mut self) {
         while let Ok(msg) = self.callback_rx.try_recv() {
-            match msg {
-                CallbackMessage::ExecuteTimeout(callback_id) => {
-                    // Timeouts are one-shot: remove the callback after execution
-                    let callback_opt = {
-                        let mut cbs = self.callbacks.lock().unwrap();
-                        cbs.remove(&callback_id)
-                    };
+            self.dispatch_callback(msg);
+        }
+    }
 
-                    if let Some(callback) = callback_opt {
-                        log::debug!("Executing timeout callback {}", callback_id);
-
-                        // Call the callback
-                        match callback.call_as_function(&self.context, None, &[]) {
-                            Ok(_) => log::debug!("Callback {} executed successfully", callback_id),
-                            Err(e) => {
-                                if let Ok(err_str) = e.to_js_string(&self.context) {
-                                    log::error!("Callback {} failed: {}", callback_id, err_str);
-                                } else {
-                                    log::error!(
-                                        "Callback {} failed with unknown error",
-                                        callback_id
-                                    );
-                                }
+    /// Wait for the next callback message, then process every ready callback
+    ///
+    /// Returns false once the event loop is gone and no callback can arrive.
+    pub async fn process_next_callbacks(&mut self) -> bool {
+        match self.callback_rx.recv().await {
+            Some(msg) => {
+                self.dispatch_callback(msg);
+                self.process_callbacks();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn dispatch_callback(&mut self, msg: CallbackMessage) {
+        match msg {
+            CallbackMessage::ExecuteTimeout(callback_id) => {
+                // Timeouts are one-shot: remove the callback after execution
+                self.timers.lock().unwrap().remove(&callback_id);
+                let callback_opt = {
+                    let mut cbs = self.callbacks.lock().unwrap();
+                    cbs.remove(&callback_id)
+                };
+
+                if let Some(callback) = callback_opt {
+                    log::debug!("Executing timeout callback {}", callback_id);
+
+                    // Call the callback with the arguments passed to setTimeout
+                    match bindings::call_timer(&self.context, callback_id, &callback, true) {
+                        Ok(_) => log::debug!("Callback {} executed successfully", callback_id),
+                        Err(e) => {
+                            if let Ok(err_str) = e.to_js_string(&self.context) {
+                                log::error!("Callback {} failed: {}", callback_id, err_str);
+                            } else {
+                                log::error!("Callback {} failed with unknown error", callback_id);
                             }
                         }
                     }
                 }
-                CallbackMessage::ExecutePromiseResolve(callback_id, result_str) => {
-                    // Execute resolve callback with result
-                    let callback_opt = {
-                        let mut cbs = self.callbacks.lock().unwrap();
-                        cbs.remove(&callback_id)
-                    };
-
-                    if let Some(callback) = callback_opt {
-                        log::debug!("Executing promise resolve callback {}", callback_id);
-
-                        let result_val = JSValue::string(&self.context, result_str.as_str());
-                        match callback.call_as_function(&self.context, None, &[result_val]) {
-                            Ok(_) => log::debug!("Promise resolved successfully"),
-                            Err(e) => {
-                                if let Ok(err_str) = e.to_js_string(&self.context) {
-                                    log::error!("Promise resolve failed: {}", err_str);
-                                }
+            }
+            CallbackMessage::ExecutePromiseResolve(callback_id, result_str) => {
+                // Execute resolve callback with result
+                let callback_opt = {
+                    let mut cbs = self.callbacks.lock().unwrap();
+                    cbs.remove(&callback_id)
+                };
+
+                if let Some(callback) = callback_opt {
+                    log::debug!("Executing promise resolve callback {}", callback_id);
+
+                    let result_val = JSValue::string(&self.context, result_str.as_str());
+                    match callback.call_as_function(&self.context, None, &[result_val]) {
+                        Ok(_) => log::debug!("Promise resolved successfully"),
+                        Err(e) => {
+                            if let Ok(err_str) = e.to_js_string(&self.context) {
+                                log::error!("Promise resolve failed: {}", err_str);
                             }
                         }
                     }
                 }
-                CallbackMessage::ExecutePromiseReject(callback_id, error_msg) => {
-                    // Execute reject callback with error
-                    let callback_opt = {
-                        let mut cbs = self.callbacks.lock().unwrap();
-                        cbs.remove(&callback_id)
-                    };
-
-                    if let Some(callback) = callback_opt {
-                        log::debug!("Executing promise reject callback {}", callback_id);
-
-                        let error_val = JSValue::string(&self.context, error_msg.as_str());
-                        match callback.call_as_function(&self.context, None, &[error_val]) {
-                            Ok(_) => log::debug!("Promise rejected successfully"),
-                            Err(e) => {
-                                if let Ok(err_str) = e.to_js_string(&self.context) {
-                                    log::error!("Promise reject failed: {}", err_str);
-                                }
+            }
+            CallbackMessage::ExecutePromiseReject(callback_id, error_msg) => {
+                // Execute reject callback with error
+                let callback_opt = {
+                    let mut cbs = self.callbacks.lock().unwrap();
+                    cbs.remove(&callback_id)
+                };
+
+                if let Some(callback) = callback_opt {
+                    log::debug!("Executing promise reject callback {}", callback_id);
+
+                    let error_val = JSValue::string(&self.context, error_msg.as_str());
+                    match callback.call_as_function(&self.context, None, &[error_val]) {
+                        Ok(_) => log::debug!("Promise rejected successfully"),
+                        Err(e) => {
+                            if let Ok(err_str) = e.to_js_string(&self.context) {
+                                log::error!("Promise reject failed: {}", err_str);
                             }
                         }
                     }
                 }
-                CallbackMessage::FetchError(callback_id, error_msg) => {
-                    // Execute fetch reject callback
-                    let callback_opt = {
-                        let mut cbs = self.callbacks.lock().unwrap();
-                        cbs.remove(&callback_id)
-                    };
-
-                    if let Some(callback) = callback_opt {
-                        log::debug!("Rejecting fetch promise {}: {}", callback_id, error_msg);
-
-                        let error_val = JSValue::string(&self.context, error_msg.as_str());
-                        match callback.call_as_function(&self.context, None, &[error_val]) {
-                            Ok(_) => log::debug!("Fetch promise rejected successfully"),
-                            Err(e) => {
-                                if let Ok(err_str) = e.to_js_string(&self.context) {
-                                    log::error!("Fetch reject callback failed: {}", err_str);
-                                }
+            }
+            CallbackMessage::FetchError(callback_id, error_msg) => {
+                // Execute fetch reject callback (no progress follows an error)
+                self.fetch_progress.lock().unwrap().remove(&callback_id);
+                let callback_opt = {
+                    let mut cbs = self.callbacks.lock().unwrap();
+                    cbs.remove(&callback_id)
+                };
+
+                if let Some(callback) = callback_opt {
+                    log::debug!("Rejecting fetch promise {}: {}", callback_id, error_msg);
+
+                    let error_val = JSValue::string(&self.context, error_msg.as_str());
+                    match callback.call_as_function(&self.context, None, &[error_val]) {
+                        Ok(_) => log::debug!("Fetch promise rejected successfully"),
+                        Err(e) => {
+                            if let Ok(err_str) = e.to_js_string(&self.context) {
+                                log::error!("Fetch reject callback failed: {}", err_str);
                             }
                         }
                     }
                 }
-                CallbackMessage::ExecuteInterval(callback_id) => {
-                    // Intervals keep the callback for repeated execution
-                    let callback_opt = {
-                        let cbs = self.callbacks.lock().unwrap();
-                        cbs.get(&callback_id).cloned()
+            }
+            CallbackMessage::ExecuteInterval(callback_id) => {
+                // Intervals keep the callback for repeated execution
+                let callback_opt = {
+                    let cbs = self.callbacks.lock().unwrap();
+                    cbs.get(&callback_id).cloned()
+                };
+
+                if let Some(callback) = callback_opt {
+                    // Check if interval is still active
+                    let is_active = {
+                        let intervals = self.intervals.lock().unwrap();
+                        intervals.contains(&callback_id)
                     };
 
-                    if let Some(callback) = callback_opt {
-                        // Check if interval is still active
-                        let is_active = {
-                            let intervals = self.intervals.lock().unwrap();
-                            intervals.contains(&callback_id)
-                        };
+                    if !is_active {
+                        log::debug!("Interval {} was cleared, skipping execution", callback_id);
+                        continue;
+                    }
 
-                        if !is_active {
-                            log::debug!("Interval {} was cleared, skipping execution", callback_id);
-                            continue;
-                        }
+                    log::debug!("Executing interval callback {}", callback_id);
 
-                        log::debug!("Executing interval callback {}", callback_id);
-
-                        // Call the callback
-                        match callback.call_as_function(&self.context, None, &[]) {
-                            Ok(_) => log::debug!("Interval {} executed successfully", callback_id),
-                            Err(e) => {
-                                if let Ok(err_str) = e.to_js_string(&self.context) {
-                                    log::error!("Interval {} failed: {}", callback_id, err_str);
-                                } else {
-                                    log::error!(
-                                        "Interval {} failed with unknown error",
-                                        callback_id
-                                    );
-                                }
+                    // Call the callback (arguments are kept for the next tick)
+                    match bindings::call_timer(&self.context, callback_id, &callback, false) {
+                        Ok(_) => log::debug!("Interval {} executed successfully", callback_id),
+                        Err(e) => {
+                            if let Ok(err_str) = e.to_js_string(&self.context) {
+                                log::error!("Interval {} failed: {}", callback_id, err_str);
+                            } else {
+                                log::error!("Interval {} failed with unknown error", callback_id);
                             }
                         }
                     }
                 }
-                CallbackMessage::FetchStreamingSuccess(callback_id, meta, stream_id) => {
-                    // Execute fetch resolve callback with a full Response object
-                    let callback_opt = {
-                        let mut cbs = self.callbacks.lock().unwrap();
-                        cbs.remove(&callback_id)
-                    };
-
-                    if let Some(callback) = callback_opt {
-                        log::debug!(
-                            "Resolving fetch streaming promise {} with stream {}",
-                            callback_id,
-                            stream_id
-                        );
-
-                        // Create a Response with streaming body using __createNativeStream
-                        let headers_json =
-                            serde_json::to_string(&meta.headers).unwrap_or("{}".to_string());
-                        let response_script = format!(
-                            r#"(function() {{
-                                const stream = __createNativeStream({});
-                                const response = new Response(stream, {{
-                                    status: {},
-                                    statusText: "{}",
-                                    headers: {}
-                                }});
-                                // Mark as streaming response
-                                response._isStreaming = true;
-                                return response;
-                            }})()"#,
-                            stream_id, meta.status, meta.status_text, headers_json
-                        );
-
-                        match self.context.evaluate_script(&response_script, 1) {
-                            Ok(response_obj) => {
-                                match callback.call_as_function(
-                                    &self.context,
-                                    None,
-                                    &[response_obj],
-                                ) {
-                                    Ok(_) => log::debug!("Fetch streaming resolved successfully"),
-                                    Err(e) => {
-                                        if let Ok(err_str) = e.to_js_string(&self.context) {
-                                            log::error!(
-                                                "Fetch streaming callback failed: {}",
-                                                err_str
-                                            );
-                                        }
+            }
+            CallbackMessage::FetchStreamingSuccess(callback_id, meta, stream_id, fetched) => {
+                // Execute fetch resolve callback with a full Response object
+                let callback_opt = {
+                    let mut cbs = self.callbacks.lock().unwrap();
+                    cbs.remove(&callback_id)
+                };
+
+                if let Some(callback) = callback_opt {
+                    log::debug!(
+                        "Resolving fetch streaming promise {} with stream {}",
+                        callback_id,
+                        stream_id
+                    );
+
+                    // Create a Response with streaming body using __createNativeStream
+                    let headers_json =
+                        serde_json::to_string(&meta.headers).unwrap_or("[]".to_string());
+                    let response_script = format!(
+                        r#"(function() {{
+                            const stream = __createNativeStream({});
+                            const status = {};
+                            // Null body statuses: drop whatever the upstream sent
+                            const nullBody = Response._isNullBodyStatus(status);
+                            if (nullBody) {{
+                                stream.cancel();
+                            }}
+                            const response = new Response(nullBody ? null : stream, {{
+                                status,
+                                statusText: "{}",
+                                headers: {}
+                            }});
+                            // Mark as streaming response
+                            response._isStreaming = true;
+                            response.url = {};
+                            response.redirected = {};
+                            response.type = 'cors';
+                            return response;
+                        }})()"#,
+                        stream_id,
+                        meta.status,
+                        meta.status_text,
+                        headers_json,
+                        serde_json::to_string(&fetched.url).unwrap_or("\"\"".to_string()),
+                        fetched.redirected
+                    );
+
+                    match self.context.evaluate_script(&response_script, 1) {
+                        Ok(response_obj) => {
+                            match callback.call_as_function(&self.context, None, &[response_obj]) {
+                                Ok(_) => log::debug!("Fetch streaming resolved successfully"),
+                                Err(e) => {
+                                    if let Ok(err_str) = e.to_js_string(&self.context) {
+                                        log::error!("Fetch streaming callback failed: {}", err_str);
                                     }
                                 }
                             }
-                            Err(e) => {
-                                if let Ok(err_str) = e.to_js_string(&self.context) {
-                                    log::error!("Failed to create streaming Response: {}", err_str);
-                                }
+                        }
+                        Err(e) => {
+                            if let Ok(err_str) = e.to_js_string(&self.context) {
+                                log::error!("Failed to create streaming Response: {}", err_str);
                             }
                         }
                     }
                 }
-                CallbackMessage::StreamChunk(callback_id, chunk) => {
-                    // Execute stream read callback with chunk result
-                    let callback_opt = {
-                        let mut cbs = self.callbacks.lock().unwrap();
-                        cbs.remove(&callback_id)
-                    };
-
-                    if let Some(callback) = callback_opt {
-                        log::debug!("Executing stream chunk callback {}", callback_id);
-
-                        // Create result object based on chunk type
-                        let result_script = match chunk {
-                            stream_manager::StreamChunk::Data(bytes) => {
-                                // Convert bytes to Uint8Array
-                                let bytes_array: Vec<u8> = bytes.to_vec();
-                                let bytes_str = format!("{:?}", bytes_array);
-                                format!(
-                                    r#"({{
-                                        done: false,
-                                        value: new Uint8Array({})
-                                    }})"#,
-                                    bytes_str
-                                )
-                            }
-                            stream_manager::StreamChunk::Done => {
-                                r#"({ done: true, value: undefined })"#.to_string()
+            }
+            CallbackMessage::FetchBufferedSuccess(callback_id, meta, body, fetched) => {
+                // Execute fetch resolve callback with a Response over the whole body
+                let callback_opt = {
+                    let mut cbs = self.callbacks.lock().unwrap();
+                    cbs.remove(&callback_id)
+                };
+
+                if let Some(callback) = callback_opt {
+                    log::debug!(
+                        "Resolving buffered fetch promise {} with {} bytes",
+                        callback_id,
+                        body.len()
+                    );
+
+                    let headers_json =
+                        serde_json::to_string(&meta.headers).unwrap_or("[]".to_string());
+                    let factory_script = format!(
+                        r#"(function(body) {{
+                            const status = {};
+                            const hasBody = body.length > 0 && !Response._isNullBodyStatus(status);
+                            const response = new Response(hasBody ? body : null, {{
+                                status,
+                                statusText: "{}",
+                                headers: {}
+                            }});
+                            response.url = {};
+                            response.redirected = {};
+                            response.type = 'cors';
+                            return response;
+                        }})"#,
+                        meta.status,
+                        meta.status_text,
+                        headers_json,
+                        serde_json::to_string(&fetched.url).unwrap_or("\"\"".to_string()),
+                        fetched.redirected
+                    );
+
+                    let response = self.new_uint8_array(&body).and_then(|body_array| {
+                        self.context
+                            .evaluate_script(&factory_script, 1)?
+                            .to_object(&self.context)?
+                            .call_as_function(&self.context, None, &[body_array])
+                    });
+
+                    match response {
+                        Ok(response_obj) => {
+                            if let Err(e) =
+                                callback.call_as_function(&self.context, None, &[response_obj])
+                                && let Ok(err_str) = e.to_js_string(&self.context)
+                            {
+                                log::error!("Buffered fetch callback failed: {}", err_str);
                             }
-                            stream_manager::StreamChunk::Error(err) => {
-                                format!(r#"({{ error: "{}" }})"#, err.replace('"', "\\\""))
+                        }
+                        Err(e) => {
+                            if let Ok(err_str) = e.to_js_string(&self.context) {
+                                log::error!("Failed to create buffered Response: {}", err_str);
                             }
-                        };
-
-                        match self.context.evaluate_script(&result_script, 1) {
-                            Ok(result_obj) => {
-                                match callback.call_as_function(&self.context, None, &[result_obj])
-                                {
-                                    Ok(_) => log::debug!("Stream chunk callback executed"),
-                                    Err(e) => {
-                                        if let Ok(err_str) = e.to_js_string(&self.context) {
-                                            log::error!(
-                                                "Stream chunk callback failed: {}",
-                                                err_str
-                                            );
-                                        }
+                        }
+                    }
+                }
+            }
+            CallbackMessage::FetchProgress(promise_id, progress) => {
+                // The last report releases the callback
+                let callback_opt = {
+                    let mut callbacks = self.fetch_progress.lock().unwrap();
+                    if progress.done {
+                        callbacks.remove(&promise_id)
+                    } else {
+                        callbacks.get(&promise_id).cloned()
+                    }
+                };
+
+                if let Some(callback) = callback_opt {
+                    let args = [
+                        JSValue::string(
+                            &self.context,
+                            if progress.upload {
+                                "upload"
+                            } else {
+                                "download"
+                            },
+                        ),
+                        JSValue::number(&self.context, progress.loaded as f64),
+                        match progress.total {
+                            Some(total) => JSValue::number(&self.context, total as f64),
+                            None => JSValue::null(&self.context),
+                        },
+                    ];
+
+                    if let Err(e) = callback.call_as_function(&self.context, None, &args)
+                        && let Ok(err_str) = e.to_js_string(&self.context)
+                    {
+                        log::error!("Fetch progress callback failed: {}", err_str);
+                    }
+                }
+            }
+            CallbackMessage::StreamChunk(callback_id, chunk) => {
+                // Execute stream read callback with chunk result
+                let callback_opt = {
+                    let mut cbs = self.callbacks.lock().unwrap();
+                    cbs.remove(&callback_id)
+                };
+
+                if let Some(callback) = callback_opt {
+                    log::debug!("Executing stream chunk callback {}", callback_id);
+
+                    // Create result object based on chunk type
+                    let result_script = match chunk {
+                        stream_manager::StreamChunk::Data(bytes) => {
+                            // Convert bytes to Uint8Array
+                            let bytes_array: Vec<u8> = bytes.to_vec();
+                            let bytes_str = format!("{:?}", bytes_array);
+                            format!(
+                                r#"({{
+                                    done: false,
+                                    value: new Uint8Array({})
+                                }})"#,
+                                bytes_str
+                            )
+                        }
+                        stream_manager::StreamChunk::Done => {
+                            r#"({ done: true, value: undefined })"#.to_string()
+                        }
+                        stream_manager::StreamChunk::Error(err) => {
+                            format!(r#"({{ error: "{}" }})"#, err.replace('"', "\\\""))
+                        }
+                    };
+
+                    match self.context.evaluate_script(&result_script, 1) {
+                        Ok(result_obj) => {
+                            match callback.call_as_function(&self.context, None, &[result_obj]) {
+                                Ok(_) => log::debug!("Stream chunk callback executed"),
+                                Err(e) => {
+                                    if let Ok(err_str) = e.to_js_string(&self.context) {
+                                        log::error!("Stream chunk callback failed: {}", err_str);
                                     }
                                 }
                             }
-                            Err(e) => {
-                                if let Ok(err_str) = e.to_js_string(&self.context) {
-                                    log::error!("Failed to create stream result: {}", err_str);
-                                }
+                        }
+                        Err(e) => {
+                            if let Ok(err_str) = e.to_js_string(&self.context) {
+                                log::error!("Failed to create stream result: {}", err_str);
                             }
                         }
                     }
                 }
             }
+            CallbackMessage::StreamReady(callback_id) => {
+                let callback_opt = {
+                    let mut cbs = self.callbacks.lock().unwrap();
+                    cbs.remove(&callback_id)
+                };
+
+                if let Some(callback) = callback_opt {
+                    log::debug!("Executing stream ready callback {}", callback_id);
+
+                    if let Err(e) = callback.call_as_function(&self.context, None, &[]) {
+                        if let Ok(err_str) = e.to_js_string(&self.context) {
+                            log::error!("Stream ready callback failed: {}", err_str);
+                        }
+                    }
+                }
+            }
+            CallbackMessage::WebSocketEvent(socket_id, event) => {
+                // Sockets keep their dispatch callback until the close event
+                let callback_opt = {
+                    let mut cbs = self.callbacks.lock().unwrap();
+                    match event {
+                        websocket::WebSocketEvent::Close { .. } => cbs.remove(&socket_id),
+                        _ => cbs.get(&socket_id).cloned(),
+                    }
+                };
+
+                let Some(callback) = callback_opt else {
+                    continue;
+                };
+
+                let args = match event {
+                    websocket::WebSocketEvent::Open(protocol) => Ok(vec![
+                        JSValue::string(&self.context, "open"),
+                        JSValue::string(&self.context, protocol.as_str()),
+                    ]),
+                    websocket::WebSocketEvent::Message(websocket::WebSocketFrame::Text(text)) => {
+                        Ok(vec![
+                            JSValue::string(&self.context, "message"),
+                            JSValue::string(&self.context, text.as_str()),
+                        ])
+                    }
+                    websocket::WebSocketEvent::Message(websocket::WebSocketFrame::Binary(data)) => {
+                        self.new_uint8_array(&data)
+                            .map(|data| vec![JSValue::string(&self.context, "message"), data])
+                    }
+                    websocket::WebSocketEvent::Error(error) => Ok(vec![
+                        JSValue::string(&self.context, "error"),
+                        JSValue::string(&self.context, error.as_str()),
+                    ]),
+                    websocket::WebSocketEvent::Close {
+                        code,
+                        reason,
+                        was_clean,
+                    } => Ok(vec![
+                        JSValue::string(&self.context, "close"),
+                        JSValue::number(&self.context, code as f64),
+                        JSValue::string(&self.context, reason.as_str()),
+                        JSValue::boolean(&self.context, was_clean),
+                    ]),
+                };
+
+                if let Err(e) =
+                    args.and_then(|args| callback.call_as_function(&self.context, None, &args))
+                    && let Ok(err_str) = e.to_js_string(&self.context)
+                {
+                    log::error!("WebSocket {} dispatch failed: {}", socket_id, err_str);
+                }
+            }
         }
     }
 
+    /// Run a single event-loop iteration without blocking
+    ///
+    /// Processes every callback that is ready, then runs a microtask
+    /// checkpoint. Returns true while timers, fetches or stream reads are still
+    /// pending, so embedders can drive the runtime from their own loop.
+    pub fn poll_once(&mut self) -> bool {
+        self.process_callbacks();
+
+        // JSC drains the microtask queue when script evaluation returns
+        let _ = self.context.evaluate_script("void 0", 1);
+
+        !self.callback_rx.is_empty() || !self.callbacks.lock().unwrap().is_empty()
+    }
+
+    /// Create a Uint8Array holding a copy of `bytes`
+    fn new_uint8_array(&self, bytes: &[u8]) -> Result<JSValue, JSValue> {
+        let array = self
+            .context
+            .evaluate_script(&format!("new Uint8Array({})", bytes.len()), 1)?;
+
+        let buffer = unsafe { typed_array::js_value_bytes_mut(&self.context, &array)? };
+        buffer.copy_from_slice(bytes);
+
+        Ok(array)
+    }
+
     /// Evaluate a JavaScript script
     pub fn evaluate(&mut self, script: &str) -> Result<JSValue, JSValue> {
         self.context.evaluate_script(script, 1)
     }
+
+    /// Expose a host reader (e.g. a static asset file) as a native stream
+    ///
+    /// Returns a stream id for `__createNativeStream(id)`, which can be used
+    /// as a Response body. The reader is drained on a tokio task (so this must
+    /// be called within a tokio runtime), waiting whenever the stream buffer
+    /// is full; a read error errors the stream, and cancelling it from JS stops
+    /// reading.
+    pub fn create_reader_stream<R>(&self, reader: R) -> stream_manager::StreamId
+    where
+        R: tokio::io::AsyncRead + Send + Unpin + 'static,
+    {
+        use tokio::io::AsyncReadExt;
+
+        let manager = self.stream_manager.clone();
+        let stream_id = manager.create_stream("reader".to_string());
+
+        tokio::spawn(async move {
+            let mut reader = reader;
+
+            loop {
+                let mut buf = bytes::BytesMut::with_capacity(READER_STREAM_CHUNK_SIZE);
+                let chunk = match reader.read_buf(&mut buf).await {
+                    Ok(0) => stream_manager::StreamChunk::Done,
+                    Ok(_) => stream_manager::StreamChunk::Data(buf.freeze()),
+                    Err(e) => stream_manager::StreamChunk::Error(e.to_string()),
+                };
+
+                let last = !matches!(chunk, stream_manager::StreamChunk::Data(_));
+                if manager.write_chunk(stream_id, chunk).await.is_err() || last {
+                    break;
+                }
+            }
+        });
+
+        stream_id
+    }
 }
 
 /// Background event loop that handles scheduled tasks
 pub async fn run_event_loop(
+    scheduler_rx: mpsc::UnboundedReceiver<SchedulerMessage>,
+    callback_tx: mpsc::UnboundedSender<CallbackMessage>,
+    stream_manager: Arc<stream_manager::StreamManager>,
+    ops: openworkers_core::OperationsHandle,
+) {
+    run_event_loop_with_policy(
+        scheduler_rx,
+        callback_tx,
+        stream_manager,
+        ops,
+        FetchPolicy::default(),
+    )
+    .await;
+}
+
+/// Background event loop with a FetchPolicy applied to outbound fetches
+pub async fn run_event_loop_with_policy(
     mut scheduler_rx: mpsc::UnboundedReceiver<SchedulerMessage>,
     callback_tx: mpsc::UnboundedSender<CallbackMessage>,
     stream_manager: Arc<stream_manager::StreamManager>,
     ops: openworkers_core::OperationsHandle,
+    policy: FetchPolicy,
 ) {
     use std::collections::HashMap;
     use tokio::task::JoinHandle;
@@ -453,7 +979,104 @@ pub async fn run_event_loop(
     // Track running tasks so we can cancel them
     let mut running_tasks: HashMap<CallbackId, JoinHandle<()>> = HashMap::new();
 
-    while let Some(msg) = scheduler_rx.recv().await {
+    // Command channels of open WebSockets
+    let mut websockets: HashMap<CallbackId, mpsc::UnboundedSender<websocket::WebSocketCommand>> =
+        HashMap::new();
+
+    // Interval tasks never finish on their own, so a drain cancels them
+    let mut interval_ids: std::collections::HashSet<CallbackId> = std::collections::HashSet::new();
+
+    // Shut down after this long without messages (see Runtime::set_idle_timeout)
+    let mut idle_timeout: Option<Duration> = None;
+
+    // Set once draining (see Runtime::drain)
+    let mut drain_deadline: Option<tokio::time::Instant> = None;
+
+    loop {
+        if let Some(deadline) = drain_deadline {
+            running_tasks.retain(|_, handle| !handle.is_finished());
+            if running_tasks.is_empty() && stream_manager.unread_count() == 0 {
+                log::info!("Event loop drained, shutting down");
+                break;
+            }
+
+            if tokio::time::Instant::now() >= deadline {
+                log::warn!(
+                    "Drain timed out with {} tasks in flight, aborting",
+                    running_tasks.len()
+                );
+                for (_, handle) in running_tasks.drain() {
+                    handle.abort();
+                }
+                break;
+            }
+        }
+
+        let msg = match (drain_deadline, idle_timeout) {
+            (Some(deadline), _) => {
+                let check_at = deadline.min(tokio::time::Instant::now() + DRAIN_CHECK_INTERVAL);
+                match tokio::time::timeout_at(check_at, scheduler_rx.recv()).await {
+                    Ok(msg) => msg,
+                    Err(_) => continue,
+                }
+            }
+            (None, Some(timeout)) => match tokio::time::timeout(timeout, scheduler_rx.recv()).await
+            {
+                Ok(msg) => msg,
+                Err(_) => {
+                    // Background work still in flight: not idle yet
+                    running_tasks.retain(|_, handle| !handle.is_finished());
+                    if !running_tasks.is_empty() {
+                        continue;
+                    }
+
+                    log::info!("Event loop idle for {:?}, shutting down", timeout);
+                    break;
+                }
+            },
+            (None, None) => scheduler_rx.recv().await,
+        };
+
+        let Some(msg) = msg else {
+            break;
+        };
+
+        // While draining, new work is refused
+        let msg = if drain_deadline.is_some() {
+            match msg {
+                SchedulerMessage::ScheduleTimeout(callback_id, _)
+                | SchedulerMessage::ScheduleInterval(callback_id, _)
+                | SchedulerMessage::ScheduleImmediate(callback_id) => {
+                    log::debug!("Draining: not scheduling timer {}", callback_id);
+                    continue;
+                }
+                SchedulerMessage::FetchStreaming(promise_id, ..)
+                | SchedulerMessage::FetchBuffered(promise_id, ..) => {
+                    let _ = callback_tx.send(CallbackMessage::FetchError(
+                        promise_id,
+                        "Runtime is shutting down".to_string(),
+                    ));
+                    continue;
+                }
+                SchedulerMessage::WebSocketConnect(socket_id, ..) => {
+                    for event in [
+                        websocket::WebSocketEvent::Error("Runtime is shutting down".to_string()),
+                        websocket::WebSocketEvent::Close {
+                            code: 1006,
+                            reason: String::new(),
+                            was_clean: false,
+                        },
+                    ] {
+                        let _ = callback_tx.send(CallbackMessage::WebSocketEvent(socket_id, event));
+                    }
+                    continue;
+                }
+                msg => msg,
+            }
+        } else {
+            msg
+        };
+
         match msg {
             SchedulerMessage::ScheduleTimeout(callback_id, delay_ms) => {
                 log::debug!(
@@ -470,16 +1093,25 @@ pub async fn run_event_loop(
 
                 running_tasks.insert(callback_id, handle);
             }
+            SchedulerMessage::ScheduleImmediate(callback_id) => {
+                log::debug!("Scheduling immediate {}", callback_id);
+
+                // No timer: the callback runs on the next process_callbacks pass
+                let _ = callback_tx.send(CallbackMessage::ExecuteTimeout(callback_id));
+            }
             SchedulerMessage::ScheduleInterval(callback_id, interval_ms) => {
                 log::debug!(
                     "Scheduling interval {} with period {}ms",
                     callback_id,
                     interval_ms
                 );
+                interval_ids.insert(callback_id);
 
                 let callback_tx = callback_tx.clone();
                 let handle = tokio::spawn(async move {
-                    let mut interval = tokio::time::interval(Duration::from_millis(interval_ms));
+                    // tokio rejects a zero period: zero-delay intervals tick every millisecond
+                    let period = Duration::from_millis(interval_ms.max(1));
+                    let mut interval = tokio::time::interval(period);
                     // Skip the first tick (it fires immediately)
                     interval.tick().await;
 
@@ -497,42 +1129,73 @@ pub async fn run_event_loop(
 
                 running_tasks.insert(callback_id, handle);
             }
-            SchedulerMessage::FetchStreaming(promise_id, request) => {
-                log::debug!(
-                    "Fetching streaming {} {}",
-                    request.method.as_str(),
-                    request.url
-                );
+            SchedulerMessage::FetchStreaming(promise_id, request, redirect, progress) => {
+                if let Some(handle) = spawn_fetch(
+                    promise_id,
+                    request,
+                    redirect,
+                    false,
+                    progress,
+                    &callback_tx,
+                    &stream_manager,
+                    &ops,
+                    &policy,
+                ) {
+                    running_tasks.insert(promise_id, handle);
+                }
+            }
+            SchedulerMessage::FetchBuffered(promise_id, request, redirect, progress) => {
+                if let Some(handle) = spawn_fetch(
+                    promise_id,
+                    request,
+                    redirect,
+                    true,
+                    progress,
+                    &callback_tx,
+                    &stream_manager,
+                    &ops,
+                    &policy,
+                ) {
+                    running_tasks.insert(promise_id, handle);
+                }
+            }
+            SchedulerMessage::FetchAbort(promise_id) => {
+                log::debug!("Aborting fetch {}", promise_id);
 
-                let callback_tx = callback_tx.clone();
-                let manager = stream_manager.clone();
-                let ops = ops.clone();
-
-                tokio::spawn(async move {
-                    match execute_fetch_via_ops(request, manager, ops).await {
-                        Ok((meta, stream_id)) => {
-                            let _ = callback_tx.send(CallbackMessage::FetchStreamingSuccess(
-                                promise_id, meta, stream_id,
-                            ));
-                        }
-                        Err(e) => {
-                            let _ = callback_tx.send(CallbackMessage::FetchError(promise_id, e));
-                        }
-                    }
-                });
+                if let Some(handle) = running_tasks.remove(&promise_id) {
+                    handle.abort();
+                }
             }
             SchedulerMessage::StreamRead(callback_id, stream_id) => {
                 log::debug!("Reading stream {} for callback {}", stream_id, callback_id);
 
                 let callback_tx = callback_tx.clone();
                 let manager = stream_manager.clone();
-                tokio::spawn(async move {
+                let handle = tokio::spawn(async move {
                     let chunk = match manager.read_chunk(stream_id).await {
                         Ok(chunk) => chunk,
                         Err(e) => stream_manager::StreamChunk::Error(e),
                     };
                     let _ = callback_tx.send(CallbackMessage::StreamChunk(callback_id, chunk));
                 });
+
+                running_tasks.insert(callback_id, handle);
+            }
+            SchedulerMessage::StreamWaitReady(callback_id, stream_id) => {
+                log::debug!(
+                    "Waiting for room in stream {} (callback {})",
+                    stream_id,
+                    callback_id
+                );
+
+                let callback_tx = callback_tx.clone();
+                let manager = stream_manager.clone();
+                let handle = tokio::spawn(async move {
+                    manager.wait_for_capacity(stream_id).await;
+                    let _ = callback_tx.send(CallbackMessage::StreamReady(callback_id));
+                });
+
+                running_tasks.insert(callback_id, handle);
             }
             SchedulerMessage::StreamCancel(stream_id) => {
                 log::debug!("Cancelling stream {}", stream_id);
@@ -540,11 +1203,75 @@ pub async fn run_event_loop(
             }
             SchedulerMessage::ClearTimer(callback_id) => {
                 log::debug!("Clearing timer {}", callback_id);
+                interval_ids.remove(&callback_id);
 
                 if let Some(handle) = running_tasks.remove(&callback_id) {
                     handle.abort();
                 }
             }
+            SchedulerMessage::WebSocketConnect(socket_id, url, protocols) => {
+                log::debug!("Opening WebSocket {} to {}", socket_id, url);
+
+                if let Err(e) = policy.check_url(&url) {
+                    log::warn!("WebSocket blocked: {}", e);
+                    for event in [
+                        websocket::WebSocketEvent::Error(e),
+                        websocket::WebSocketEvent::Close {
+                            code: 1006,
+                            reason: String::new(),
+                            was_clean: false,
+                        },
+                    ] {
+                        let _ = callback_tx.send(CallbackMessage::WebSocketEvent(socket_id, event));
+                    }
+                    continue;
+                }
+
+                let (command_tx, command_rx) = mpsc::unbounded_channel();
+                websockets.retain(|_, tx| !tx.is_closed());
+                websockets.insert(socket_id, command_tx);
+
+                let handle = tokio::spawn(websocket::run_websocket(
+                    socket_id,
+                    url,
+                    protocols,
+                    command_rx,
+                    callback_tx.clone(),
+                    policy.clone(),
+                ));
+
+                running_tasks.insert(socket_id, handle);
+            }
+            SchedulerMessage::WebSocketSend(socket_id, frame) => {
+                if let Some(tx) = websockets.get(&socket_id) {
+                    let _ = tx.send(websocket::WebSocketCommand::Send(frame));
+                }
+            }
+            SchedulerMessage::WebSocketClose(socket_id, code, reason) => {
+                log::debug!("Closing WebSocket {}", socket_id);
+
+                if let Some(tx) = websockets.remove(&socket_id) {
+                    let _ = tx.send(websocket::WebSocketCommand::Close(code, reason));
+                }
+            }
+            SchedulerMessage::SetIdleTimeout(timeout) => {
+                log::debug!("Setting idle timeout to {:?}", timeout);
+                idle_timeout = timeout;
+            }
+            SchedulerMessage::Activity => {
+                // Receiving the message restarts the idle timeout
+            }
+            SchedulerMessage::Drain(timeout) => {
+                log::info!("Draining event loop (timeout {:?})", timeout);
+
+                for callback_id in interval_ids.drain() {
+                    if let Some(handle) = running_tasks.remove(&callback_id) {
+                        handle.abort();
+                    }
+                }
+
+                drain_deadline = Some(tokio::time::Instant::now() + timeout);
+            }
             SchedulerMessage::Shutdown => {
                 log::info!("Shutting down event loop");
 
@@ -559,50 +1286,337 @@ pub async fn run_event_loop(
     }
 }
 
-/// Execute fetch via OperationsHandler
-async fn execute_fetch_via_ops(
-    request: openworkers_core::HttpRequest,
-    stream_manager: Arc<stream_manager::StreamManager>,
+/// Check a fetch against the policy and spawn it
+///
+/// Streaming fetches resolve with a native stream; buffered ones read the
+/// whole body first and resolve it in a single callback. Returns None when
+/// the request was rejected up front. With `progress`, cumulative byte
+/// counts are reported as `FetchProgress` messages.
+#[allow(clippy::too_many_arguments)]
+fn spawn_fetch(
+    promise_id: CallbackId,
+    mut request: HttpRequest,
+    redirect: RedirectMode,
+    buffered: bool,
+    progress: bool,
+    callback_tx: &mpsc::UnboundedSender<CallbackMessage>,
+    stream_manager: &Arc<stream_manager::StreamManager>,
+    ops: &openworkers_core::OperationsHandle,
+    policy: &FetchPolicy,
+) -> Option<tokio::task::JoinHandle<()>> {
+    log::debug!(
+        "Fetching {} {} {}",
+        if buffered { "buffered" } else { "streaming" },
+        request.method.as_str(),
+        request.url
+    );
+
+    // Reject disallowed hosts and oversized headers before the request
+    // leaves the runtime
+    if let Err(e) = policy
+        .check_url(&request.url)
+        .and_then(|_| policy.check_headers(&request.headers))
+    {
+        log::warn!("fetch blocked: {}", e);
+        let _ = callback_tx.send(CallbackMessage::FetchError(promise_id, e));
+        return None;
+    }
+
+    policy.apply_default_accept(&mut request.headers);
+    fetch::apply_default_accept_encoding(&mut request.headers);
+
+    let progress = progress.then(|| ProgressReporter {
+        promise_id,
+        callback_tx: callback_tx.clone(),
+    });
+    let callback_tx = callback_tx.clone();
+    let manager = stream_manager.clone();
+    let ops = ops.clone();
+    let policy = policy.clone();
+
+    Some(tokio::spawn(async move {
+        // SSRF protection: resolve the host and reject private addresses
+        if let Err(e) = policy.check_resolved(&request.url).await {
+            log::warn!("fetch blocked: {}", e);
+            let _ = callback_tx.send(CallbackMessage::FetchError(promise_id, e));
+            return;
+        }
+
+        let result = if buffered {
+            execute_fetch_buffered_via_ops(request, redirect, ops, &policy, progress)
+                .await
+                .map(|(meta, body, fetched)| {
+                    CallbackMessage::FetchBufferedSuccess(promise_id, meta, body, fetched)
+                })
+        } else {
+            execute_fetch_via_ops(request, redirect, manager, ops, &policy, progress)
+                .await
+                .map(|(meta, stream_id, fetched)| {
+                    CallbackMessage::FetchStreamingSuccess(promise_id, meta, stream_id, fetched)
+                })
+        };
+
+        let msg = result.unwrap_or_else(|e| CallbackMessage::FetchError(promise_id, e));
+        let _ = callback_tx.send(msg);
+    }))
+}
+
+/// Maximum number of redirects followed by a single fetch
+const MAX_REDIRECTS: usize = 20;
+
+/// Request headers not forwarded when a redirect leaves the origin
+const CREDENTIAL_HEADERS: [&str; 3] = ["authorization", "cookie", "proxy-authorization"];
+
+/// Request headers describing the body, dropped when a redirect switches to GET
+const BODY_HEADERS: [&str; 5] = [
+    "content-encoding",
+    "content-language",
+    "content-location",
+    "content-length",
+    "content-type",
+];
+
+/// Send a fetch through the OperationsHandler
+///
+/// Redirects are handled here so the redirect mode applies regardless of the
+/// handler; handlers that follow redirects themselves only ever return the
+/// final response.
+async fn fetch_via_ops(
+    mut request: openworkers_core::HttpRequest,
+    redirect: RedirectMode,
     ops: openworkers_core::OperationsHandle,
-) -> Result<(openworkers_core::HttpResponseMeta, stream_manager::StreamId), String> {
-    use openworkers_core::{Operation, OperationResult, ResponseBody};
+    policy: &FetchPolicy,
+) -> Result<
+    (
+        FetchResponseMeta,
+        openworkers_core::ResponseBody,
+        FetchedUrl,
+    ),
+    String,
+> {
+    use openworkers_core::{HttpMethod, Operation, OperationResult, RequestBody};
+    use std::str::FromStr;
+
+    let mut redirects = 0;
+
+    let (response, url) = loop {
+        // Keep what is needed to replay the request on a redirect
+        let url = request.url.clone();
+        let method = request.method.as_str().to_string();
+        let headers = request.headers.clone();
+        let body = match &request.body {
+            RequestBody::Bytes(bytes) => Some(RequestBody::Bytes(bytes.clone())),
+            RequestBody::None => Some(RequestBody::None),
+            RequestBody::Stream(_) => None,
+        };
+
+        let result = ops.handle(Operation::Fetch(request)).await;
+
+        let response = match result {
+            OperationResult::Http(r) => r?,
+            _ => return Err("Unexpected result type for fetch".into()),
+        };
+
+        let location = response
+            .headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case("location"))
+            .map(|(_, v)| v.clone());
+
+        let location = match location {
+            Some(location) if matches!(response.status, 301 | 302 | 303 | 307 | 308) => location,
+            _ => break (response, url),
+        };
+
+        match redirect {
+            RedirectMode::Manual => break (response, url),
+            RedirectMode::Error => {
+                return Err(format!(
+                    "Redirect from {} not allowed (redirect: 'error')",
+                    url
+                ));
+            }
+            RedirectMode::Follow => {}
+        }
 
-    let result = ops.handle(Operation::Fetch(request)).await;
+        redirects += 1;
+        if redirects > MAX_REDIRECTS {
+            return Err(format!("Too many redirects fetching {}", url));
+        }
+
+        let base_url = reqwest::Url::parse(&url)
+            .map_err(|e| format!("Invalid redirect location '{}': {}", location, e))?;
+        let target_url = base_url
+            .join(&location)
+            .map_err(|e| format!("Invalid redirect location '{}': {}", location, e))?;
+        let next_url = target_url.to_string();
+
+        // Redirect targets are subject to the same policy as the original request
+        policy.check_url(&next_url)?;
+        policy.check_resolved(&next_url).await?;
+
+        // 303 (and 301/302 after POST) switch to a bodyless GET
+        let to_get = response.status == 303
+            || (matches!(response.status, 301 | 302) && method.eq_ignore_ascii_case("POST"));
+
+        let mut headers = headers;
+
+        // Credentials stay with the origin they were meant for
+        if target_url.origin() != base_url.origin() {
+            headers.retain(|name, _| {
+                !CREDENTIAL_HEADERS
+                    .iter()
+                    .any(|header| name.eq_ignore_ascii_case(header))
+            });
+        }
+
+        let (method, body) = if to_get {
+            headers.retain(|name, _| {
+                !BODY_HEADERS
+                    .iter()
+                    .any(|header| name.eq_ignore_ascii_case(header))
+            });
+            (HttpMethod::Get, RequestBody::None)
+        } else {
+            let method = HttpMethod::from_str(&method)
+                .map_err(|_| format!("Invalid HTTP method: {}", method))?;
+            let body = body.ok_or("Cannot follow redirect with a streaming request body")?;
+            (method, body)
+        };
+
+        request = openworkers_core::HttpRequest {
+            method,
+            url: next_url,
+            headers,
+            body,
+        };
+    };
 
-    let response = match result {
-        OperationResult::Http(r) => r?,
-        _ => return Err("Unexpected result type for fetch".into()),
+    let fetched = FetchedUrl {
+        url,
+        redirected: redirects > 0,
     };
 
-    let meta = openworkers_core::HttpResponseMeta {
+    // Reject upstream responses exceeding the header limits
+    policy
+        .check_headers(response.headers.iter().map(|(k, v)| (k, v)))
+        .map_err(|e| format!("Upstream response rejected: {}", e))?;
+
+    // Compressed bodies reach JS decoded, whatever the transport did
+    let mut headers = response.headers;
+    let body = fetch::decode_response_body(&mut headers, response.body);
+
+    let meta = FetchResponseMeta {
         status: response.status,
         status_text: status_text(response.status),
-        headers: response.headers.into_iter().collect(),
+        headers,
     };
 
+    Ok((meta, body, fetched))
+}
+
+/// Sends `FetchProgress` messages for one fetch
+struct ProgressReporter {
+    promise_id: CallbackId,
+    callback_tx: mpsc::UnboundedSender<CallbackMessage>,
+}
+
+impl ProgressReporter {
+    fn report(&self, upload: bool, loaded: u64, total: Option<u64>, done: bool) {
+        let _ = self.callback_tx.send(CallbackMessage::FetchProgress(
+            self.promise_id,
+            FetchProgress {
+                upload,
+                loaded,
+                total,
+                done,
+            },
+        ));
+    }
+
+    /// Report the request body as sent (it is handed over in one piece)
+    fn report_upload(&self, size: Option<u64>) {
+        if let Some(size) = size.filter(|&size| size > 0) {
+            self.report(true, size, Some(size), false);
+        }
+    }
+}
+
+/// Size of a request body, if known up front
+fn request_body_size(request: &openworkers_core::HttpRequest) -> Option<u64> {
+    match &request.body {
+        openworkers_core::RequestBody::Bytes(bytes) => Some(bytes.len() as u64),
+        _ => None,
+    }
+}
+
+/// Content-Length of a response, if valid
+fn content_length(meta: &FetchResponseMeta) -> Option<u64> {
+    meta.headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse().ok())
+}
+
+/// Execute fetch via OperationsHandler, exposing the body as a native stream
+async fn execute_fetch_via_ops(
+    request: openworkers_core::HttpRequest,
+    redirect: RedirectMode,
+    stream_manager: Arc<stream_manager::StreamManager>,
+    ops: openworkers_core::OperationsHandle,
+    policy: &FetchPolicy,
+    progress: Option<ProgressReporter>,
+) -> Result<(FetchResponseMeta, stream_manager::StreamId, FetchedUrl), String> {
+    use openworkers_core::ResponseBody;
+
+    let upload_size = request_body_size(&request);
+    let (meta, body, fetched) = fetch_via_ops(request, redirect, ops, policy).await?;
+
+    let total = content_length(&meta);
+    if let Some(progress) = &progress {
+        progress.report_upload(upload_size);
+    }
+
     let stream_id = stream_manager.create_stream("ops_fetch".to_string());
 
-    match response.body {
+    match body {
         ResponseBody::None => {
             let _ = stream_manager
                 .write_chunk(stream_id, stream_manager::StreamChunk::Done)
                 .await;
+
+            if let Some(progress) = &progress {
+                progress.report(false, 0, total, true);
+            }
         }
         ResponseBody::Bytes(bytes) => {
-            let _ = stream_manager
-                .write_chunk(stream_id, stream_manager::StreamChunk::Data(bytes))
-                .await;
+            let loaded = bytes.len() as u64;
+
+            // A zero-length body closes the stream right away
+            if !bytes.is_empty() {
+                let _ = stream_manager
+                    .write_chunk(stream_id, stream_manager::StreamChunk::Data(bytes))
+                    .await;
+            }
             let _ = stream_manager
                 .write_chunk(stream_id, stream_manager::StreamChunk::Done)
                 .await;
+
+            if let Some(progress) = &progress {
+                progress.report(false, loaded, total, true);
+            }
         }
         ResponseBody::Stream(mut rx) => {
             let manager = stream_manager.clone();
 
             tokio::spawn(async move {
+                let mut loaded = 0u64;
+
                 while let Some(result) = rx.recv().await {
                     match result {
+                        Ok(bytes) if bytes.is_empty() => {}
                         Ok(bytes) => {
+                            let len = bytes.len() as u64;
                             if manager
                                 .write_chunk(stream_id, stream_manager::StreamChunk::Data(bytes))
                                 .await
@@ -610,6 +1624,11 @@ async fn execute_fetch_via_ops(
                             {
                                 break;
                             }
+
+                            loaded += len;
+                            if let Some(progress) = &progress {
+                                progress.report(false, loaded, total, false);
+                            }
                         }
                         Err(e) => {
                             let _ = manager
@@ -623,11 +1642,57 @@ async fn execute_fetch_via_ops(
                 let _ = manager
                     .write_chunk(stream_id, stream_manager::StreamChunk::Done)
                     .await;
+
+                if let Some(progress) = &progress {
+                    progress.report(false, loaded, total, true);
+                }
             });
         }
     }
 
-    Ok((meta, stream_id))
+    Ok((meta, stream_id, fetched))
+}
+
+/// Execute fetch via OperationsHandler, reading the whole body up front
+async fn execute_fetch_buffered_via_ops(
+    request: openworkers_core::HttpRequest,
+    redirect: RedirectMode,
+    ops: openworkers_core::OperationsHandle,
+    policy: &FetchPolicy,
+    progress: Option<ProgressReporter>,
+) -> Result<(FetchResponseMeta, bytes::Bytes, FetchedUrl), String> {
+    use openworkers_core::ResponseBody;
+
+    let upload_size = request_body_size(&request);
+    let (meta, body, fetched) = fetch_via_ops(request, redirect, ops, policy).await?;
+
+    let total = content_length(&meta);
+    if let Some(progress) = &progress {
+        progress.report_upload(upload_size);
+    }
+
+    let body = match body {
+        ResponseBody::None => bytes::Bytes::new(),
+        ResponseBody::Bytes(bytes) => bytes,
+        ResponseBody::Stream(mut rx) => {
+            let mut body = Vec::new();
+            while let Some(chunk) = rx.recv().await {
+                body.extend_from_slice(&chunk?);
+
+                if let Some(progress) = &progress {
+                    progress.report(false, body.len() as u64, total, false);
+                }
+            }
+            body.into()
+        }
+    };
+
+    // Sent before the success message, so the last report precedes the Response
+    if let Some(progress) = &progress {
+        progress.report(false, body.len() as u64, total, true);
+    }
+
+    Ok((meta, body, fetched))
 }
 
 /// Get HTTP status text
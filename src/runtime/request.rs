@@ -38,8 +38,16 @@ pub const REQUEST_JS: &str = r#"
         }
 
         _initBody(body) {
-            if (body instanceof ReadableStream) {
+            if (Response._promiseBodies && body && typeof body.then === 'function') {
+                // Non-standard: stream whatever the promise resolves to
+                this.body = Response._promisedBody(body);
+            } else if (body instanceof ReadableStream) {
                 this.body = body;
+            } else if (body instanceof Blob) {
+                this.body = body.stream();
+                if (body.type && !this.headers.has('content-type')) {
+                    this.headers.set('content-type', body.type);
+                }
             } else if (body instanceof Uint8Array || body instanceof ArrayBuffer) {
                 const bytes = body instanceof Uint8Array ? body : new Uint8Array(body);
                 this.body = new ReadableStream({
@@ -63,6 +71,11 @@ pub const REQUEST_JS: &str = r#"
             }
         }
 
+        // Non-standard: pathname with `.`/`..` resolved and `//` collapsed, for routing
+        get normalizedPathname() {
+            return __normalizePathname(this.url);
+        }
+
         async text() {
             if (this.bodyUsed) {
                 throw new TypeError('Body has already been consumed');
@@ -103,6 +116,12 @@ pub const REQUEST_JS: &str = r#"
             return JSON.parse(text);
         }
 
+        async formData() {
+            const contentType = this.headers.get('content-type');
+            const text = await this.text();
+            return FormData._parse(text, contentType);
+        }
+
         async arrayBuffer() {
             if (this.bodyUsed) {
                 throw new TypeError('Body has already been consumed');
@@ -142,12 +161,18 @@ pub const REQUEST_JS: &str = r#"
                 throw new TypeError('Cannot clone a Request whose body has been consumed');
             }
 
-            // For simplicity, create a new Request with same properties
-            // Note: proper implementation would tee() the body stream
+            // Tee the body so each request reads (and uses up) its own branch
+            let body = null;
+            if (this.body) {
+                const [first, second] = this.body.tee();
+                this.body = first;
+                body = second;
+            }
+
             return new Request(this.url, {
                 method: this.method,
-                headers: this.headers,
-                body: this.body,
+                headers: new Headers(this.headers),
+                body,
                 mode: this.mode,
                 credentials: this.credentials,
                 cache: this.cache,
@@ -158,12 +183,3 @@ pub const REQUEST_JS: &str = r#"
         }
     };
 "#;
-
-use rusty_jsc::JSContext;
-
-/// Setup Request class
-pub fn setup_request(context: &mut JSContext) {
-    context
-        .evaluate_script(REQUEST_JS, 1)
-        .expect("Failed to setup Request class");
-}
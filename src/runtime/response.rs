@@ -1,17 +1,34 @@
-use rusty_jsc::JSContext;
-
-/// Setup global Response class with streaming body support
-pub fn setup_response(context: &mut JSContext) {
-    let code = r#"
+/// Response class implementation with streaming body support
+pub const RESPONSE_JS: &str = r#"
         globalThis.Response = class Response {
             constructor(body, init) {
                 init = init || {};
-                this.status = init.status || 200;
-                this.statusText = init.statusText || '';
+
+                const status = init.status === undefined ? 200 : Math.trunc(Number(init.status));
+                if (!(status >= 200 && status <= 599)) {
+                    throw new RangeError(`Invalid response status: ${init.status}`);
+                }
+
+                // No default reason phrase: statusText stays empty unless given
+                const statusText = init.statusText === undefined ? '' : String(init.statusText);
+                if (!/^[\t\x20-\x7E\x80-\xFF]*$/.test(statusText)) {
+                    throw new TypeError('Invalid response statusText');
+                }
+
+                this.status = status;
+                this.statusText = statusText;
                 this.ok = this.status >= 200 && this.status < 300;
                 this.bodyUsed = false;
+                this.url = '';
+                this.redirected = false;
+                this.type = 'default';
                 this._nativeStreamId = null;  // Will be set if body is a native stream
 
+                // Null body statuses (204, 205, 304) never carry a body
+                if (Response._isNullBodyStatus(this.status)) {
+                    body = null;
+                }
+
                 // Convert headers to Headers instance if available
                 if (typeof Headers !== 'undefined') {
                     if (init.headers instanceof Headers) {
@@ -25,13 +42,22 @@ pub fn setup_response(context: &mut JSContext) {
                 }
 
                 // Support different body types
-                if (body instanceof ReadableStream) {
+                if (Response._promiseBodies && body && typeof body.then === 'function') {
+                    // Non-standard: stream whatever the promise resolves to
+                    this.body = Response._promisedBody(body);
+                } else if (body instanceof ReadableStream) {
                     // Already a stream - use it directly
                     this.body = body;
                     // Check if this is a native stream (from fetch)
                     if (body._nativeStreamId !== undefined) {
                         this._nativeStreamId = body._nativeStreamId;
                     }
+                } else if (body instanceof Blob) {
+                    // Blob - use its bytes and default Content-Type to blob.type
+                    this.body = body.stream();
+                    if (body.type && !this.headers.has('content-type')) {
+                        this.headers.set('content-type', body.type);
+                    }
                 } else if (body instanceof Uint8Array || body instanceof ArrayBuffer) {
                     // Binary data - wrap in a stream
                     const bytes = body instanceof Uint8Array ? body : new Uint8Array(body);
@@ -45,18 +71,44 @@ pub fn setup_response(context: &mut JSContext) {
                     // Empty body
                     this.body = null;
                 } else {
-                    // String or other - convert to bytes and wrap in stream
-                    const encoder = new TextEncoder();
-                    const bytes = encoder.encode(String(body));
+                    // String or other - UTF-8 encode natively and wrap in stream,
+                    // keeping the text and bytes for reads that skip the stream
+                    const text = String(body);
+                    const bytes = __nativeStringToResponseBytes(text);
                     this.body = new ReadableStream({
                         start(controller) {
                             controller.enqueue(bytes);
                             controller.close();
                         }
                     });
+                    this._stringBody = { text, bytes, stream: this.body };
+                }
+
+                // Non-standard: decode the body per Content-Encoding when read
+                if (init.decompress && this.body) {
+                    const encoding = (this.headers.get('content-encoding') || '').trim().toLowerCase();
+                    const format = encoding === 'gzip' || encoding === 'x-gzip' ? 'gzip'
+                        : encoding === 'deflate' ? 'deflate' : null;
+                    if (format) {
+                        this.body = Response._decoded(this.body, format);
+                        this._nativeStreamId = null;
+                        this._stringBody = undefined;
+                        // The headers now describe the decoded body
+                        this.headers.delete('content-encoding');
+                        this.headers.delete('content-length');
+                    }
                 }
             }
 
+            // Internal: the string body ({ text, bytes }) while its stream is untouched
+            _untouchedStringBody() {
+                const stringBody = this._stringBody;
+                if (!stringBody || this.body !== stringBody.stream || this.body._disturbed) {
+                    return null;
+                }
+                return stringBody;
+            }
+
             // text() method - read stream and decode to string
             async text() {
                 if (this.bodyUsed) {
@@ -64,6 +116,12 @@ pub fn setup_response(context: &mut JSContext) {
                 }
                 this.bodyUsed = true;
 
+                const stringBody = this._untouchedStringBody();
+                if (stringBody) {
+                    this.body._disturbed = true;
+                    return stringBody.text;
+                }
+
                 if (!this.body) {
                     return '';
                 }
@@ -101,6 +159,12 @@ pub fn setup_response(context: &mut JSContext) {
                 }
                 this.bodyUsed = true;
 
+                const stringBody = this._untouchedStringBody();
+                if (stringBody) {
+                    this.body._disturbed = true;
+                    return stringBody.bytes.buffer;
+                }
+
                 if (!this.body) {
                     return new ArrayBuffer(0);
                 }
@@ -137,6 +201,12 @@ pub fn setup_response(context: &mut JSContext) {
                 }
                 this.bodyUsed = true;
 
+                const stringBody = this._untouchedStringBody();
+                if (stringBody) {
+                    this.body._disturbed = true;
+                    return stringBody.bytes;
+                }
+
                 if (!this.body) {
                     return new Uint8Array(0);
                 }
@@ -166,72 +236,116 @@ pub fn setup_response(context: &mut JSContext) {
                 return result;
             }
 
+            // blob() method - body bytes as a Blob typed by Content-Type
+            async blob() {
+                const bytes = await this.bytes();
+                return new Blob([bytes], { type: this.headers.get('content-type') || '' });
+            }
+
             // json() method - decode and parse
             async json() {
                 const text = await this.text();
                 return JSON.parse(text);
             }
 
-            // Internal method to synchronously get raw body bytes
-            // Used by the Rust runtime to extract response body
-            _getRawBody() {
-                if (!this.body || !this.body._controller) {
-                    return new Uint8Array(0);
-                }
+            // formData() method - parse urlencoded or multipart bodies
+            async formData() {
+                const contentType = this.headers.get('content-type');
+                const text = await this.text();
+                return FormData._parse(text, contentType);
+            }
 
-                const queue = this.body._controller._queue;
-                if (!queue || queue.length === 0) {
-                    return new Uint8Array(0);
+            // Clone the response
+            clone() {
+                if (this.bodyUsed) {
+                    throw new TypeError('Cannot clone a Response whose body has been consumed');
                 }
 
-                // Concatenate all chunks in the queue
-                const chunks = [];
-                for (const item of queue) {
-                    if (item.type === 'chunk' && item.value) {
-                        chunks.push(item.value);
-                    }
+                // Tee the body so chunks enqueued later reach both responses
+                let body = null;
+                if (this.body) {
+                    const [first, second] = this.body.tee();
+                    this.body = first;
+                    this._nativeStreamId = null;
+                    body = second;
                 }
 
-                if (chunks.length === 0) {
-                    return new Uint8Array(0);
-                }
+                const clone = new Response(body, { headers: new Headers(this.headers) });
 
-                // Single chunk - return directly
-                if (chunks.length === 1) {
-                    return chunks[0];
-                }
+                // Copied after construction: opaque and error responses have status 0
+                clone.type = this.type;
+                clone.status = this.status;
+                clone.statusText = this.statusText;
+                clone.ok = this.ok;
+                return clone;
+            }
 
-                // Multiple chunks - concatenate
-                const totalLength = chunks.reduce((sum, chunk) => sum + chunk.length, 0);
-                const result = new Uint8Array(totalLength);
-                let offset = 0;
-                for (const chunk of chunks) {
-                    result.set(chunk, offset);
-                    offset += chunk.length;
-                }
+            // Static methods
+            // Internal: statuses whose responses never carry a body
+            static _isNullBodyStatus(status) {
+                return status === 101 || status === 103 || status === 204
+                    || status === 205 || status === 304;
+            }
 
-                return result;
+            // Internal: a stream over the body a promise resolves to (opt-in via
+            // WorkerOptions::promise_bodies)
+            static _promisedBody(promise) {
+                return new ReadableStream({
+                    async start(controller) {
+                        const body = new Response(await promise).body;
+                        if (body) {
+                            const reader = body.getReader();
+                            while (true) {
+                                const { done, value } = await reader.read();
+                                if (done) break;
+                                controller.enqueue(value);
+                            }
+                        }
+                        controller.close();
+                    }
+                });
             }
 
-            // Clone the response
-            clone() {
-                if (this.bodyUsed) {
-                    throw new TypeError('Cannot clone a Response whose body has been consumed');
+            // Internal: a stream decompressing `stream` (opt-in via the
+            // `decompress` init flag); nothing is decoded until the first read
+            static _decoded(stream, format) {
+                let reader = null;
+                return new ReadableStream({
+                    async pull(controller) {
+                        if (!reader) {
+                            reader = stream.pipeThrough(new DecompressionStream(format)).getReader();
+                        }
+                        const { done, value } = await reader.read();
+                        if (done) {
+                            controller.close();
+                        } else {
+                            controller.enqueue(value);
+                        }
+                    },
+                    cancel(reason) {
+                        return reader ? reader.cancel(reason) : stream.cancel(reason);
+                    }
+                });
+            }
+
+            // Internal: opaque filtered response for no-cors fetches (status 0,
+            // no headers, no body); the network body is cancelled unread
+            static _opaque(response) {
+                if (response.body) {
+                    response.body.cancel().catch(() => {});
                 }
 
-                // For simplicity, get raw body and create new response
-                // Note: proper implementation would tee() the body stream
-                const bodyBytes = this._getRawBody();
-                return new Response(bodyBytes, {
-                    status: this.status,
-                    statusText: this.statusText,
-                    headers: this.headers
-                });
+                const opaque = new Response(null);
+                opaque.status = 0;
+                opaque.ok = false;
+                opaque.type = 'opaque';
+                return opaque;
             }
 
-            // Static methods
             static error() {
-                const response = new Response(null, { status: 0, statusText: '' });
+                const response = new Response(null);
+                response.status = 0;
+                response.ok = false;
                 response.type = 'error';
                 return response;
             }
@@ -240,9 +354,16 @@ pub fn setup_response(context: &mut JSContext) {
                 if (![301, 302, 303, 307, 308].includes(status)) {
                     throw new RangeError('Invalid status code for redirect');
                 }
+
+                // Workers have no base URL, so path-absolute locations are kept as-is
+                url = String(url);
+                const location = url.startsWith('/') && !url.startsWith('//')
+                    ? url
+                    : new URL(url).href;
+
                 return new Response(null, {
                     status: status,
-                    headers: { 'Location': url }
+                    headers: { 'Location': location }
                 });
             }
 
@@ -258,9 +379,6 @@ pub fn setup_response(context: &mut JSContext) {
                 });
             }
         };
-    "#;
 
-    context
-        .evaluate_script(code, 1)
-        .expect("Failed to setup Response");
-}
+        Response._promiseBodies = false;
+    "#;
@@ -0,0 +1,57 @@
+use rusty_jsc::JSContext;
+
+/// Subsystems installed by Runtime::new, in setup order
+const SUBSYSTEMS: &[&str] = &[
+    "microtask",
+    "performance",
+    "heap",
+    "text-encoding",
+    "base64",
+    "streams",
+    "blob",
+    "headers",
+    "response",
+    "form-data",
+    "request",
+    "event-target",
+    "abort",
+    "cookie",
+    "url",
+    "compression",
+    "crypto",
+    "fetch",
+    "timers",
+    "websocket",
+];
+
+/// Setup the frozen `globalThis.__runtime` diagnostics object
+pub fn setup_runtime_info(context: &mut JSContext) {
+    let mut features: Vec<&str> = SUBSYSTEMS.to_vec();
+    if cfg!(feature = "actix") {
+        features.push("actix");
+    }
+
+    let info = serde_json::json!({
+        "name": env!("CARGO_PKG_NAME"),
+        "version": env!("CARGO_PKG_VERSION"),
+        "engine": "JavaScriptCore",
+        "features": features,
+    });
+
+    let script = format!(
+        r#"Object.defineProperty(globalThis, '__runtime', {{
+            value: (function deepFreeze(obj) {{
+                Object.values(obj).forEach(v => typeof v === 'object' && deepFreeze(v));
+                return Object.freeze(obj);
+            }})({}),
+            writable: false,
+            enumerable: false,
+            configurable: false
+        }});"#,
+        info
+    );
+
+    context
+        .evaluate_script(&script, 1)
+        .expect("Failed to setup runtime info");
+}
@@ -1,5 +1,6 @@
 use bytes::Bytes;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc;
 
@@ -9,6 +10,9 @@ pub type StreamId = u64;
 /// This provides backpressure when the consumer is slow
 pub const DEFAULT_HIGH_WATER_MARK: usize = 16;
 
+/// Default maximum size of a single chunk written from JS (16 MiB)
+pub const DEFAULT_MAX_CHUNK_SIZE: usize = 16 * 1024 * 1024;
+
 /// A chunk of data from a stream
 #[derive(Debug, Clone)]
 pub enum StreamChunk {
@@ -35,6 +39,8 @@ pub struct StreamManager {
     next_id: Arc<Mutex<StreamId>>,
     /// High water mark for new streams
     high_water_mark: usize,
+    /// Maximum size of a chunk accepted by `try_write_chunk` (0 = unlimited)
+    max_chunk_size: Arc<AtomicUsize>,
 }
 
 impl StreamManager {
@@ -50,6 +56,24 @@ impl StreamManager {
             metadata: Arc::new(Mutex::new(HashMap::new())),
             next_id: Arc::new(Mutex::new(1)),
             high_water_mark,
+            max_chunk_size: Arc::new(AtomicUsize::new(DEFAULT_MAX_CHUNK_SIZE)),
+        }
+    }
+
+    /// Limit the size of a single chunk written from JS (None = unlimited)
+    pub fn set_max_chunk_size(&self, max: Option<usize>) {
+        self.max_chunk_size
+            .store(max.unwrap_or(0), Ordering::SeqCst);
+    }
+
+    /// Reject chunks larger than the configured maximum
+    pub fn check_chunk_size(&self, len: usize) -> Result<(), String> {
+        match self.max_chunk_size.load(Ordering::SeqCst) {
+            max if max > 0 && len > max => Err(format!(
+                "Stream chunk of {} bytes exceeds the {} byte limit",
+                len, max
+            )),
+            _ => Ok(()),
         }
     }
 
@@ -88,9 +112,14 @@ impl StreamManager {
         }
     }
 
-    /// Try to write a chunk without waiting (returns error if buffer is full)
+    /// Try to write a chunk without waiting (returns error if buffer is full
+    /// or the chunk is over the size limit)
     /// Useful for non-async contexts
     pub fn try_write_chunk(&self, stream_id: StreamId, chunk: StreamChunk) -> Result<(), String> {
+        if let StreamChunk::Data(ref bytes) = chunk {
+            self.check_chunk_size(bytes.len())?;
+        }
+
         let senders = self.senders.lock().unwrap();
 
         if let Some(tx) = senders.get(&stream_id) {
@@ -105,6 +134,31 @@ impl StreamManager {
         }
     }
 
+    /// Whether `try_write_chunk` has room in the stream buffer
+    ///
+    /// Unknown streams report room, so the following write surfaces the error.
+    pub fn has_capacity(&self, stream_id: StreamId) -> bool {
+        self.senders
+            .lock()
+            .unwrap()
+            .get(&stream_id)
+            .is_none_or(|tx| tx.capacity() > 0)
+    }
+
+    /// Wait until the stream buffer has room for a chunk
+    ///
+    /// Returns immediately for unknown or closed streams, so the following
+    /// write surfaces the error.
+    pub async fn wait_for_capacity(&self, stream_id: StreamId) {
+        // Clone the sender so the lock isn't held during the await
+        let tx = self.senders.lock().unwrap().get(&stream_id).cloned();
+
+        if let Some(tx) = tx {
+            // The permit is released on drop, leaving the slot free
+            let _ = tx.reserve().await;
+        }
+    }
+
     /// Read the next chunk from a stream (async, called from scheduler)
     /// This temporarily takes ownership of the receiver to await on it
     pub async fn read_chunk(&self, stream_id: StreamId) -> Result<StreamChunk, String> {
@@ -160,6 +214,12 @@ impl StreamManager {
     pub fn active_count(&self) -> usize {
         self.senders.lock().unwrap().len()
     }
+
+    /// Count streams JS may still read from (not finished, not handed off
+    /// with `take_receiver`, and not currently being read)
+    pub fn unread_count(&self) -> usize {
+        self.receivers.lock().unwrap().len()
+    }
 }
 
 impl Default for StreamManager {
@@ -236,6 +296,25 @@ mod tests {
         assert_eq!(manager.active_count(), 0);
     }
 
+    #[tokio::test]
+    async fn test_try_write_rejects_oversized_chunk() {
+        let manager = StreamManager::new();
+        manager.set_max_chunk_size(Some(4));
+        let id = manager.create_stream("https://example.com".to_string());
+
+        let result = manager.try_write_chunk(id, StreamChunk::Data(Bytes::from("12345")));
+        assert!(result.unwrap_err().contains("exceeds"));
+
+        manager
+            .try_write_chunk(id, StreamChunk::Data(Bytes::from("1234")))
+            .unwrap();
+
+        manager.set_max_chunk_size(None);
+        manager
+            .try_write_chunk(id, StreamChunk::Data(Bytes::from("12345")))
+            .unwrap();
+    }
+
     #[tokio::test]
     async fn test_backpressure() {
         // Create manager with small buffer to test backpressure
@@ -253,6 +332,7 @@ mod tests {
             .unwrap();
 
         // try_write should fail when buffer is full
+        assert!(!manager.has_capacity(id));
         let result = manager.try_write_chunk(id, StreamChunk::Data(Bytes::from("3")));
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("backpressure"));
@@ -261,7 +341,35 @@ mod tests {
         let _ = manager.read_chunk(id).await.unwrap();
 
         // Now try_write should succeed
+        assert!(manager.has_capacity(id));
         let result = manager.try_write_chunk(id, StreamChunk::Data(Bytes::from("3")));
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_wait_for_capacity() {
+        let manager = StreamManager::with_high_water_mark(1);
+        let id = manager.create_stream("https://example.com".to_string());
+
+        manager
+            .write_chunk(id, StreamChunk::Data(Bytes::from("1")))
+            .await
+            .unwrap();
+
+        // Pending while the buffer is full
+        let waiter = {
+            let manager = manager.clone();
+            tokio::spawn(async move { manager.wait_for_capacity(id).await })
+        };
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        assert!(!waiter.is_finished());
+
+        // Reading wakes the waiter, leaving the slot free
+        let _ = manager.read_chunk(id).await.unwrap();
+        waiter.await.unwrap();
+        assert!(manager.has_capacity(id));
+
+        // Unknown streams don't wait
+        manager.wait_for_capacity(id + 1).await;
+    }
 }
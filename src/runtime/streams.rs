@@ -1,8 +1,5 @@
-use rusty_jsc::JSContext;
-
-/// Setup ReadableStream API (WHATWG Streams spec)
-pub fn setup_readable_stream(context: &mut JSContext) {
-    let code = r#"
+/// ReadableStream, WritableStream and TransformStream implementation (WHATWG Streams spec)
+pub const READABLE_STREAM_JS: &str = r#"
         // ReadableStream implementation (simplified WHATWG spec)
         globalThis.ReadableStream = class ReadableStream {
             constructor(underlyingSource = {}) {
@@ -11,9 +8,13 @@ pub fn setup_readable_stream(context: &mut JSContext) {
                 this._reader = null;
                 this._state = 'readable'; // 'readable', 'closed', 'errored'
                 this._storedError = null;
+                // Set once a reader was acquired or the stream was cancelled
+                this._disturbed = false;
 
-                // Create controller
-                const controller = new ReadableStreamDefaultController(this);
+                // Create controller (byte streams get a ReadableByteStreamController)
+                const controller = underlyingSource.type === 'bytes'
+                    ? new ReadableByteStreamController(this)
+                    : new ReadableStreamDefaultController(this);
                 this._controller = controller;
 
                 // Start the stream
@@ -25,16 +26,26 @@ pub fn setup_readable_stream(context: &mut JSContext) {
                 }
             }
 
-            getReader() {
+            getReader(options = {}) {
                 if (this._reader) {
                     throw new TypeError('ReadableStream is locked to a reader');
                 }
+                this._disturbed = true;
+                if (options.mode === 'byob') {
+                    if (!(this._controller instanceof ReadableByteStreamController)) {
+                        throw new TypeError('BYOB readers require a byte stream');
+                    }
+                    const reader = new ReadableStreamBYOBReader(this);
+                    this._reader = reader;
+                    return reader;
+                }
                 const reader = new ReadableStreamDefaultReader(this);
                 this._reader = reader;
                 return reader;
             }
 
             cancel(reason) {
+                this._disturbed = true;
                 if (this._state === 'closed') {
                     return Promise.resolve();
                 }
@@ -44,8 +55,11 @@ pub fn setup_readable_stream(context: &mut JSContext) {
 
                 this._state = 'closed';
 
-                // Clear reader
+                // Clear reader, pending reads complete as done
                 if (this._reader) {
+                    while (this._reader._readRequests.length > 0) {
+                        this._reader._readRequests.shift().resolve({ done: true, value: undefined });
+                    }
                     this._reader._closePending();
                     this._reader = null;
                 }
@@ -58,6 +72,209 @@ pub fn setup_readable_stream(context: &mut JSContext) {
                 return Promise.resolve();
             }
 
+            // Split into two branches that each receive every chunk
+            tee() {
+                const reader = this.getReader();
+                const controllers = [];
+                const branch = () => new ReadableStream({
+                    start(controller) {
+                        controllers.push(controller);
+                    }
+                });
+                const branches = [branch(), branch()];
+
+                // Skip branches that were cancelled
+                const forEachOpen = (fn) => {
+                    for (const controller of controllers) {
+                        if (controller._stream._state === 'readable' && !controller._closeRequested) {
+                            fn(controller);
+                        }
+                    }
+                };
+
+                (async () => {
+                    try {
+                        while (true) {
+                            const { done, value } = await reader.read();
+                            if (done) {
+                                forEachOpen(controller => controller.close());
+                                break;
+                            }
+                            forEachOpen(controller => controller.enqueue(value));
+                        }
+                    } catch (e) {
+                        forEachOpen(controller => controller.error(e));
+                    }
+                })();
+
+                return branches;
+            }
+
+            // Pipe every chunk into a WritableStream, resolves once the destination is closed
+            pipeTo(destination, options = {}) {
+                if (!(destination instanceof WritableStream)) {
+                    return Promise.reject(new TypeError('pipeTo destination must be a WritableStream'));
+                }
+                if (this.locked) {
+                    return Promise.reject(new TypeError('ReadableStream is locked to a reader'));
+                }
+                if (destination.locked) {
+                    return Promise.reject(new TypeError('WritableStream is locked to a writer'));
+                }
+
+                options = options || {};
+                const preventClose = !!options.preventClose;
+                const preventAbort = !!options.preventAbort;
+                const preventCancel = !!options.preventCancel;
+                const signal = options.signal;
+
+                const reader = this.getReader();
+                const writer = destination.getWriter();
+
+                return new Promise((resolve, reject) => {
+                    let settled = false;
+                    const finish = (error, failed) => {
+                        if (settled) {
+                            return;
+                        }
+                        settled = true;
+                        if (signal) {
+                            signal.removeEventListener('abort', onAbort);
+                        }
+                        if (reader._stream && reader._readRequests.length === 0) {
+                            reader.releaseLock();
+                        }
+                        writer.releaseLock();
+                        failed ? reject(error) : resolve();
+                    };
+
+                    const onAbort = () => {
+                        const reason = signal.reason;
+                        const actions = [];
+                        if (!preventAbort && destination._state === 'writable') {
+                            actions.push(destination._abort(reason));
+                        }
+                        if (!preventCancel && this._state === 'readable') {
+                            actions.push(this.cancel(reason));
+                        }
+                        Promise.all(actions).then(() => finish(reason, true), e => finish(e, true));
+                    };
+
+                    if (signal) {
+                        if (signal.aborted) {
+                            onAbort();
+                            return;
+                        }
+                        signal.addEventListener('abort', onAbort, { once: true });
+                    }
+
+                    (async () => {
+                        while (!settled) {
+                            let result;
+                            try {
+                                result = await reader.read();
+                            } catch (error) {
+                                // Source errored
+                                if (!preventAbort) {
+                                    await destination._abort(error).catch(() => {});
+                                }
+                                return finish(error, true);
+                            }
+
+                            if (settled) {
+                                return;
+                            }
+                            if (result.done) {
+                                break;
+                            }
+
+                            try {
+                                await writer.write(result.value);
+                            } catch (error) {
+                                // Destination errored
+                                if (!preventCancel) {
+                                    await this.cancel(error).catch(() => {});
+                                }
+                                return finish(error, true);
+                            }
+                        }
+
+                        if (settled) {
+                            return;
+                        }
+                        try {
+                            if (!preventClose) {
+                                await writer.close();
+                            }
+                            finish();
+                        } catch (error) {
+                            finish(error, true);
+                        }
+                    })();
+                });
+            }
+
+            // Pipe into a { writable, readable } pair and return its readable side
+            pipeThrough(transform, options = {}) {
+                if (!transform || !(transform.writable instanceof WritableStream) || !(transform.readable instanceof ReadableStream)) {
+                    throw new TypeError('pipeThrough requires a { writable, readable } pair');
+                }
+                if (this.locked) {
+                    throw new TypeError('ReadableStream is locked to a reader');
+                }
+                if (transform.writable.locked) {
+                    throw new TypeError('WritableStream is locked to a writer');
+                }
+
+                this.pipeTo(transform.writable, options).catch(() => {});
+                return transform.readable;
+            }
+
+            // Async iterator over the chunks, cancels the stream on early exit
+            values(options = {}) {
+                const reader = this.getReader();
+                const preventCancel = !!(options && options.preventCancel);
+                const release = () => {
+                    if (reader._stream && reader._readRequests.length === 0) {
+                        reader.releaseLock();
+                    }
+                };
+
+                return {
+                    next() {
+                        if (!reader._stream) {
+                            return Promise.resolve({ done: true, value: undefined });
+                        }
+                        return reader.read().then(result => {
+                            if (result.done) {
+                                release();
+                            }
+                            return result;
+                        }, error => {
+                            release();
+                            throw error;
+                        });
+                    },
+                    return(value) {
+                        if (!reader._stream) {
+                            return Promise.resolve({ done: true, value });
+                        }
+                        if (preventCancel) {
+                            release();
+                            return Promise.resolve({ done: true, value });
+                        }
+                        return reader.cancel(value).then(() => ({ done: true, value }));
+                    },
+                    [Symbol.asyncIterator]() {
+                        return this;
+                    }
+                };
+            }
+
+            [Symbol.asyncIterator](options) {
+                return this.values(options);
+            }
+
             get locked() {
                 return this._reader !== null;
             }
@@ -130,6 +347,52 @@ pub fn setup_readable_stream(context: &mut JSContext) {
             }
         };
 
+        // ReadableByteStreamController (chunks are always Uint8Array copies)
+        globalThis.ReadableByteStreamController = class ReadableByteStreamController extends ReadableStreamDefaultController {
+            enqueue(chunk) {
+                if (!ArrayBuffer.isView(chunk)) {
+                    throw new TypeError('Chunk must be an ArrayBuffer view');
+                }
+                if (chunk.byteLength === 0) {
+                    throw new TypeError('Chunk must not be empty');
+                }
+
+                // Copy so later writes to the source buffer don't leak into the queue
+                const bytes = new Uint8Array(chunk.byteLength);
+                bytes.set(new Uint8Array(chunk.buffer, chunk.byteOffset, chunk.byteLength));
+                super.enqueue(bytes);
+            }
+
+            // Pending BYOB read the underlying source may fill directly
+            get byobRequest() {
+                const reader = this._stream._reader;
+                if (!(reader instanceof ReadableStreamBYOBReader) || reader._readRequests.length === 0) {
+                    return null;
+                }
+
+                const request = reader._readRequests[0];
+                return {
+                    view: new Uint8Array(request.view.buffer, request.view.byteOffset, request.view.byteLength),
+                    respond: (bytesWritten) => {
+                        if (reader._readRequests[0] !== request) {
+                            throw new TypeError('BYOB request is no longer valid');
+                        }
+                        if (bytesWritten > request.view.byteLength) {
+                            throw new RangeError('bytesWritten out of range');
+                        }
+                        reader._readRequests.shift();
+                        request.resolve({ done: false, value: reader._filledView(request.view, bytesWritten) });
+                    },
+                    respondWithNewView: (view) => {
+                        if (view.buffer !== request.view.buffer || view.byteOffset !== request.view.byteOffset) {
+                            throw new RangeError('View must use the same buffer region');
+                        }
+                        this.byobRequest.respond(view.byteLength);
+                    }
+                };
+            }
+        };
+
         // ReadableStreamDefaultReader
         globalThis.ReadableStreamDefaultReader = class ReadableStreamDefaultReader {
             constructor(stream) {
@@ -276,9 +539,374 @@ pub fn setup_readable_stream(context: &mut JSContext) {
                 return this._closedPromise;
             }
         };
-    "#;
 
-    context
-        .evaluate_script(code, 1)
-        .expect("Failed to setup ReadableStream");
-}
+        // ReadableStreamBYOBReader (reads into caller-provided views)
+        globalThis.ReadableStreamBYOBReader = class ReadableStreamBYOBReader extends ReadableStreamDefaultReader {
+            read(view) {
+                if (!ArrayBuffer.isView(view) || view.byteLength === 0) {
+                    return Promise.reject(new TypeError('read() requires a non-empty ArrayBuffer view'));
+                }
+                if (!this._stream) {
+                    return Promise.reject(new TypeError('Reader is released'));
+                }
+                if (this._stream._state === 'errored') {
+                    return Promise.reject(this._stream._storedError);
+                }
+
+                return new Promise((resolve, reject) => {
+                    this._readRequests.push({ view, resolve, reject });
+                    this._processQueue();
+
+                    // Still waiting: ask the source for more data
+                    const controller = this._stream && this._stream._controller;
+                    const underlyingSource = this._stream && this._stream._underlyingSource;
+                    if (this._readRequests.length > 0 && underlyingSource && underlyingSource.pull) {
+                        const pullPromise = underlyingSource.pull(controller);
+                        if (pullPromise && typeof pullPromise.then === 'function') {
+                            pullPromise.catch(e => {
+                                controller.error(e);
+                            });
+                        }
+                    }
+                });
+            }
+
+            _processQueue() {
+                const controller = this._stream._controller;
+
+                while (this._readRequests.length > 0 && controller._queue.length > 0) {
+                    const request = this._readRequests[0];
+                    const item = controller._queue[0];
+
+                    if (item.type === 'close') {
+                        break;
+                    }
+
+                    // Copy as many whole elements as fit into the caller's view
+                    const elementSize = request.view.BYTES_PER_ELEMENT || 1;
+                    const room = request.view.byteLength;
+                    let count = Math.min(room, item.value.byteLength);
+                    count -= count % elementSize;
+                    if (count === 0) {
+                        break;
+                    }
+
+                    const target = new Uint8Array(request.view.buffer, request.view.byteOffset, count);
+                    target.set(item.value.subarray(0, count));
+
+                    if (count < item.value.byteLength) {
+                        item.value = item.value.subarray(count);
+                    } else {
+                        controller._queue.shift();
+                    }
+
+                    this._readRequests.shift();
+                    request.resolve({ done: false, value: this._filledView(request.view, count) });
+                }
+
+                // Close once all queued bytes have been consumed
+                if (controller._queue.length > 0 && controller._queue[0].type === 'close') {
+                    controller._queue.shift();
+                    this._stream._state = 'closed';
+                }
+
+                if (this._stream._state === 'closed') {
+                    while (this._readRequests.length > 0) {
+                        const request = this._readRequests.shift();
+                        request.resolve({ done: true, value: this._filledView(request.view, 0) });
+                    }
+                    this._closePending();
+                }
+            }
+
+            // View of the same type as `view` covering the first `bytes` bytes
+            _filledView(view, bytes) {
+                const elementSize = view.BYTES_PER_ELEMENT || 1;
+                if (view instanceof DataView) {
+                    return new DataView(view.buffer, view.byteOffset, bytes);
+                }
+                return new view.constructor(view.buffer, view.byteOffset, Math.floor(bytes / elementSize));
+            }
+        };
+
+        // WritableStream implementation (simplified WHATWG spec, sink calls run one at a time)
+        globalThis.WritableStream = class WritableStream {
+            constructor(underlyingSink = {}) {
+                this._underlyingSink = underlyingSink || {};
+                this._state = 'writable'; // 'writable', 'closing', 'closed', 'errored'
+                this._storedError = null;
+                this._writer = null;
+                this._controller = new WritableStreamDefaultController(this);
+
+                // Every sink operation waits for the previous one
+                this._pending = Promise.resolve();
+
+                if (this._underlyingSink.start) {
+                    this._enqueue(() => this._underlyingSink.start(this._controller));
+                }
+            }
+
+            getWriter() {
+                if (this._writer) {
+                    throw new TypeError('WritableStream is locked to a writer');
+                }
+                const writer = new WritableStreamDefaultWriter(this);
+                this._writer = writer;
+                return writer;
+            }
+
+            close() {
+                if (this.locked) {
+                    return Promise.reject(new TypeError('WritableStream is locked to a writer'));
+                }
+                return this._close();
+            }
+
+            abort(reason) {
+                if (this.locked) {
+                    return Promise.reject(new TypeError('WritableStream is locked to a writer'));
+                }
+                return this._abort(reason);
+            }
+
+            get locked() {
+                return this._writer !== null;
+            }
+
+            _enqueue(operation) {
+                const result = this._pending.then(() => {
+                    if (this._state === 'errored') {
+                        throw this._storedError;
+                    }
+                    return operation();
+                });
+                result.catch(e => this._error(e));
+                this._pending = result.catch(() => {});
+                return result;
+            }
+
+            _write(chunk) {
+                if (this._state === 'errored') {
+                    return Promise.reject(this._storedError);
+                }
+                if (this._state !== 'writable') {
+                    return Promise.reject(new TypeError('Cannot write to a closing or closed stream'));
+                }
+                const sink = this._underlyingSink;
+                return this._enqueue(() => sink.write ? sink.write(chunk, this._controller) : undefined);
+            }
+
+            _close() {
+                if (this._state === 'errored') {
+                    return Promise.reject(this._storedError);
+                }
+                if (this._state !== 'writable') {
+                    return Promise.reject(new TypeError('Stream is already closing or closed'));
+                }
+
+                this._state = 'closing';
+                const sink = this._underlyingSink;
+                return this._enqueue(() => sink.close ? sink.close() : undefined).then(() => {
+                    this._state = 'closed';
+                    if (this._writer) {
+                        this._writer._closePending();
+                    }
+                });
+            }
+
+            _abort(reason) {
+                if (this._state === 'closed' || this._state === 'errored') {
+                    return Promise.resolve();
+                }
+
+                this._error(reason);
+                const sink = this._underlyingSink;
+                return Promise.resolve(sink.abort ? sink.abort(reason) : undefined);
+            }
+
+            _error(error) {
+                if (this._state === 'closed' || this._state === 'errored') {
+                    return;
+                }
+                this._state = 'errored';
+                this._storedError = error;
+                if (this._writer) {
+                    this._writer._errorPending(error);
+                }
+            }
+        };
+
+        // WritableStreamDefaultController
+        globalThis.WritableStreamDefaultController = class WritableStreamDefaultController {
+            constructor(stream) {
+                this._stream = stream;
+            }
+
+            error(error) {
+                this._stream._error(error);
+            }
+        };
+
+        // WritableStreamDefaultWriter
+        globalThis.WritableStreamDefaultWriter = class WritableStreamDefaultWriter {
+            constructor(stream) {
+                if (stream._writer) {
+                    throw new TypeError('Stream is already locked');
+                }
+
+                this._stream = stream;
+                this._closedPromise = new Promise((resolve, reject) => {
+                    this._closedPromiseResolve = resolve;
+                    this._closedPromiseReject = reject;
+                });
+                // Avoid unhandled rejections when nobody awaits `closed`
+                this._closedPromise.catch(() => {});
+
+                if (stream._state === 'closed') {
+                    this._closePending();
+                } else if (stream._state === 'errored') {
+                    this._errorPending(stream._storedError);
+                }
+            }
+
+            write(chunk) {
+                if (!this._stream) {
+                    return Promise.reject(new TypeError('Writer is released'));
+                }
+                return this._stream._write(chunk);
+            }
+
+            close() {
+                if (!this._stream) {
+                    return Promise.reject(new TypeError('Writer is released'));
+                }
+                return this._stream._close();
+            }
+
+            abort(reason) {
+                if (!this._stream) {
+                    return Promise.reject(new TypeError('Writer is released'));
+                }
+                return this._stream._abort(reason);
+            }
+
+            releaseLock() {
+                if (!this._stream) {
+                    return;
+                }
+                this._stream._writer = null;
+                this._stream = null;
+            }
+
+            // No backpressure: the sink queue is unbounded
+            get ready() {
+                return Promise.resolve();
+            }
+
+            get desiredSize() {
+                if (!this._stream) {
+                    throw new TypeError('Writer is released');
+                }
+                if (this._stream._state === 'errored') {
+                    return null;
+                }
+                return this._stream._state === 'writable' ? 1 : 0;
+            }
+
+            get closed() {
+                return this._closedPromise;
+            }
+
+            _closePending() {
+                if (this._closedPromiseResolve) {
+                    this._closedPromiseResolve();
+                    this._closedPromiseResolve = null;
+                    this._closedPromiseReject = null;
+                }
+            }
+
+            _errorPending(error) {
+                if (this._closedPromiseReject) {
+                    this._closedPromiseReject(error);
+                    this._closedPromiseResolve = null;
+                    this._closedPromiseReject = null;
+                }
+            }
+        };
+
+        // TransformStream: chunks written to `writable` come out of `readable` after transform()
+        globalThis.TransformStream = class TransformStream {
+            constructor(transformer = {}) {
+                transformer = transformer || {};
+
+                let readableController;
+                this._readable = new ReadableStream({
+                    start(controller) {
+                        readableController = controller;
+                    },
+                    cancel: (reason) => {
+                        this._writable._error(reason);
+                    }
+                });
+
+                const controller = new TransformStreamDefaultController(this, readableController);
+                this._controller = controller;
+
+                this._writable = new WritableStream({
+                    start: () => transformer.start ? transformer.start(controller) : undefined,
+                    write: (chunk) => transformer.transform
+                        ? transformer.transform(chunk, controller)
+                        : controller.enqueue(chunk),
+                    close: async () => {
+                        if (transformer.flush) {
+                            await transformer.flush(controller);
+                        }
+                        if (this._readable._state === 'readable' && !readableController._closeRequested) {
+                            readableController.close();
+                        }
+                    },
+                    abort: (reason) => {
+                        readableController.error(reason);
+                    }
+                });
+            }
+
+            get readable() {
+                return this._readable;
+            }
+
+            get writable() {
+                return this._writable;
+            }
+        };
+
+        // TransformStreamDefaultController
+        globalThis.TransformStreamDefaultController = class TransformStreamDefaultController {
+            constructor(stream, readableController) {
+                this._stream = stream;
+                this._readableController = readableController;
+            }
+
+            enqueue(chunk) {
+                this._readableController.enqueue(chunk);
+            }
+
+            error(error) {
+                this._readableController.error(error);
+                this._stream._writable._error(error);
+            }
+
+            // Close the readable side and error the writable side
+            terminate() {
+                const controller = this._readableController;
+                if (this._stream._readable._state === 'readable' && !controller._closeRequested) {
+                    controller.close();
+                }
+                this._stream._writable._error(new TypeError('TransformStream terminated'));
+            }
+
+            get desiredSize() {
+                return this._readableController.desiredSize;
+            }
+        };
+    "#;
@@ -1,17 +1,92 @@
+use super::typed_array::js_value_bytes_mut;
 use rusty_jsc::JSContext;
 
-/// Setup TextEncoder and TextDecoder APIs
+/// Setup TextEncoder, TextDecoder and TextDecoderStream APIs
 /// These are essential for converting between strings and bytes
 pub fn setup_text_encoding(context: &mut JSContext) {
+    // Create __nativeEncodeInto(source, Uint8Array) -> { read, written }
+    // Writes whole UTF-8 sequences only, never splitting a multibyte character
+    let encode_into_fn = rusty_jsc::callback_closure!(
+        context,
+        move |mut ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            if args.len() < 2 {
+                return Err(JSValue::string(
+                    &ctx,
+                    "encodeInto requires a source string and a Uint8Array",
+                ));
+            }
+
+            let source = match args[0].to_js_string(&ctx) {
+                Ok(s) => s.to_string(),
+                Err(_) => return Err(JSValue::string(&ctx, "Source must be a string")),
+            };
+
+            let dest = unsafe { js_value_bytes_mut(&ctx, &args[1])? };
+
+            // read counts UTF-16 code units, written counts bytes
+            let mut read = 0;
+            let mut written = 0;
+            let mut buf = [0u8; 4];
+
+            for c in source.chars() {
+                let encoded = c.encode_utf8(&mut buf).as_bytes();
+                if written + encoded.len() > dest.len() {
+                    break;
+                }
+                dest[written..written + encoded.len()].copy_from_slice(encoded);
+                written += encoded.len();
+                read += c.len_utf16();
+            }
+
+            let script = format!("({{ read: {}, written: {} }})", read, written);
+            match ctx.evaluate_script(&script, 1) {
+                Ok(result) => Ok(result),
+                Err(_) => Err(JSValue::string(&ctx, "Failed to create encodeInto result")),
+            }
+        }
+    );
+
+    // Create __nativeStringToResponseBytes(string) -> Uint8Array
+    // UTF-8 encodes string bodies in one pass (lone surrogates become U+FFFD)
+    let string_to_bytes_fn = rusty_jsc::callback_closure!(
+        context,
+        move |mut ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            let source = match args.first().map(|arg| arg.to_js_string(&ctx)) {
+                Some(Ok(s)) => s.to_string(),
+                _ => return Err(JSValue::string(&ctx, "Body must be a string")),
+            };
+
+            let array = ctx.evaluate_script(&format!("new Uint8Array({})", source.len()), 1)?;
+            let bytes = unsafe { js_value_bytes_mut(&ctx, &array)? };
+            bytes.copy_from_slice(source.as_bytes());
+
+            Ok(array)
+        }
+    );
+
+    let mut global = context.get_global_object();
+    global
+        .set_property(context, "__nativeEncodeInto", encode_into_fn.into())
+        .unwrap();
+    global
+        .set_property(
+            context,
+            "__nativeStringToResponseBytes",
+            string_to_bytes_fn.into(),
+        )
+        .unwrap();
+
     let code = r#"
         // TextEncoder - encode strings to UTF-8 bytes
         globalThis.TextEncoder = class TextEncoder {
-            constructor() {
-                this.encoding = 'utf-8';
+            get encoding() {
+                return 'utf-8';
             }
 
-            encode(input) {
-                const str = String(input || '');
+            // input is a USVString defaulting to '' (so undefined encodes as empty);
+            // anything else is stringified, Symbols throw
+            encode(input = '') {
+                const str = `${input}`;
                 const bytes = [];
 
                 // UTF-8 encoding with proper surrogate pair handling
@@ -21,6 +96,9 @@ pub fn setup_text_encoding(context: &mut JSContext) {
                     // Skip low surrogate (already processed with high surrogate)
                     if (code > 0xFFFF) i++;
 
+                    // Lone surrogates are replaced with U+FFFD
+                    if (code >= 0xD800 && code <= 0xDFFF) code = 0xFFFD;
+
                     if (code < 0x80) {
                         bytes.push(code);
                     } else if (code < 0x800) {
@@ -40,60 +118,360 @@ pub fn setup_text_encoding(context: &mut JSContext) {
 
                 return new Uint8Array(bytes);
             }
+
+            encodeInto(source, destination) {
+                if (!(destination instanceof Uint8Array)) {
+                    throw new TypeError('Destination must be a Uint8Array');
+                }
+                return __nativeEncodeInto(`${source}`, destination);
+            }
+
+            // Non-standard: encode() prefixed with the UTF-8 BOM (EF BB BF)
+            encodeWithBom(input) {
+                const body = this.encode(input);
+                const bytes = new Uint8Array(body.length + 3);
+                bytes.set([0xEF, 0xBB, 0xBF]);
+                bytes.set(body, 3);
+                return bytes;
+            }
         };
 
-        // TextDecoder - decode UTF-8 bytes to strings
+        // TextDecoder - decode bytes to strings (utf-8, utf-16le, utf-16be, windows-1252)
         globalThis.TextDecoder = class TextDecoder {
-            constructor(encoding = 'utf-8') {
-                this.encoding = encoding.toLowerCase();
-                if (this.encoding !== 'utf-8' && this.encoding !== 'utf8') {
-                    throw new RangeError('Only UTF-8 encoding is supported');
+            constructor(label = 'utf-8', options = {}) {
+                const encoding = TextDecoder._resolveLabel(label);
+                if (!encoding) {
+                    throw new RangeError(`The encoding label provided ('${label}') is invalid`);
+                }
+
+                options = options || {};
+                this._encoding = encoding;
+                this._fatal = Boolean(options.fatal);
+                this._ignoreBOM = Boolean(options.ignoreBOM);
+
+                // Streaming state: bytes of an incomplete sequence, and whether the BOM was handled
+                this._pending = new Uint8Array(0);
+                this._bomSeen = false;
+            }
+
+            // Read-only attributes
+            get encoding() {
+                return this._encoding;
+            }
+
+            get fatal() {
+                return this._fatal;
+            }
+
+            get ignoreBOM() {
+                return this._ignoreBOM;
+            }
+
+            // Map an encoding label to its canonical name (WHATWG Encoding spec)
+            static _resolveLabel(label) {
+                // Labels are matched without surrounding ASCII whitespace, case-insensitively
+                switch (String(label).replace(/^[\t\n\f\r ]+|[\t\n\f\r ]+$/g, '').toLowerCase()) {
+                    case 'utf-8':
+                    case 'utf8':
+                    case 'unicode-1-1-utf-8':
+                        return 'utf-8';
+                    case 'utf-16le':
+                    case 'utf-16':
+                    case 'ucs-2':
+                    case 'unicode':
+                        return 'utf-16le';
+                    case 'utf-16be':
+                    case 'unicodefffe':
+                        return 'utf-16be';
+                    case 'latin1':
+                    case 'iso-8859-1':
+                    case 'iso8859-1':
+                    case 'l1':
+                    case 'ascii':
+                    case 'us-ascii':
+                    case 'cp1252':
+                    case 'windows-1252':
+                        return 'windows-1252';
+                    default:
+                        return null;
                 }
             }
 
-            decode(input) {
-                if (!input) return '';
+            // Get a Uint8Array view over any BufferSource
+            static _toBytes(input) {
+                if (input instanceof Uint8Array) {
+                    return input;
+                }
+                if (input instanceof ArrayBuffer) {
+                    return new Uint8Array(input);
+                }
+                if (ArrayBuffer.isView(input)) {
+                    return new Uint8Array(input.buffer, input.byteOffset, input.byteLength);
+                }
+                return new Uint8Array(input);
+            }
+
+            // With { stream: true }, a trailing incomplete sequence is kept for the next call
+            decode(input, options = {}) {
+                const stream = Boolean(options && options.stream);
+                let bytes = input ? TextDecoder._toBytes(input) : new Uint8Array(0);
+
+                if (this._pending.length > 0) {
+                    const joined = new Uint8Array(this._pending.length + bytes.length);
+                    joined.set(this._pending);
+                    joined.set(bytes, this._pending.length);
+                    bytes = joined;
+                }
+
+                const end = stream ? bytes.length - this._incompleteTail(bytes) : bytes.length;
+                this._pending = bytes.slice(end);
+                bytes = bytes.subarray(0, end);
+
+                // The BOM is only skipped at the start of a stream
+                const skipBOM = !this.ignoreBOM && !this._bomSeen;
+                this._bomSeen = stream && (this._bomSeen || bytes.length > 0);
+
+                switch (this.encoding) {
+                    case 'utf-16le':
+                        return this._decodeUtf16(bytes, true, skipBOM);
+                    case 'utf-16be':
+                        return this._decodeUtf16(bytes, false, skipBOM);
+                    case 'windows-1252':
+                        return this._decodeWindows1252(bytes);
+                    default:
+                        return this._decodeUtf8(bytes, skipBOM);
+                }
+            }
+
+            // Number of trailing bytes that may be completed by the next chunk
+            _incompleteTail(bytes) {
+                if (this.encoding === 'windows-1252') {
+                    return 0;
+                }
+
+                if (this.encoding !== 'utf-8') {
+                    // Odd byte, plus a trailing high surrogate waiting for its pair
+                    let tail = bytes.length % 2;
+                    const last = bytes.length - tail - 2;
+                    if (last >= 0) {
+                        const unit = this.encoding === 'utf-16le'
+                            ? bytes[last] | (bytes[last + 1] << 8)
+                            : (bytes[last] << 8) | bytes[last + 1];
+                        if (unit >= 0xD800 && unit <= 0xDBFF) {
+                            tail += 2;
+                        }
+                    }
+                    return tail;
+                }
+
+                // Walk back over continuation bytes to the lead byte
+                for (let k = 1; k <= Math.min(3, bytes.length); k++) {
+                    const byte = bytes[bytes.length - k];
+                    if ((byte & 0xC0) === 0x80) {
+                        continue;
+                    }
+                    const length = byte >= 0xF0 && byte <= 0xF4 ? 4
+                        : byte >= 0xE0 && byte <= 0xEF ? 3
+                        : byte >= 0xC2 && byte <= 0xDF ? 2
+                        : 1;
+                    return length > k ? k : 0;
+                }
+                return 0;
+            }
 
-                // Convert to Uint8Array if needed
-                const bytes = input instanceof Uint8Array ? input : new Uint8Array(input);
-                const chars = [];
+            // Replacement character, or TypeError in fatal mode
+            _replacement() {
+                if (this.fatal) {
+                    throw new TypeError(`The encoded data was not valid for encoding ${this.encoding}`);
+                }
+                return 0xFFFD;
+            }
 
-                // Simple UTF-8 decoding
+            _decodeUtf8(bytes, skipBOM) {
+                const codePoints = [];
                 let i = 0;
+
+                // Skip UTF-8 BOM
+                if (skipBOM && bytes.length >= 3 &&
+                    bytes[0] === 0xEF && bytes[1] === 0xBB && bytes[2] === 0xBF) {
+                    i = 3;
+                }
+
                 while (i < bytes.length) {
-                    const byte1 = bytes[i++];
+                    const byte1 = bytes[i];
 
                     if (byte1 < 0x80) {
                         // 1-byte character (ASCII)
-                        chars.push(String.fromCharCode(byte1));
-                    } else if ((byte1 & 0xE0) === 0xC0) {
-                        // 2-byte character
-                        const byte2 = bytes[i++];
-                        const code = ((byte1 & 0x1F) << 6) | (byte2 & 0x3F);
-                        chars.push(String.fromCharCode(code));
-                    } else if ((byte1 & 0xF0) === 0xE0) {
-                        // 3-byte character
-                        const byte2 = bytes[i++];
-                        const byte3 = bytes[i++];
-                        const code = ((byte1 & 0x0F) << 12) | ((byte2 & 0x3F) << 6) | (byte3 & 0x3F);
-                        chars.push(String.fromCharCode(code));
-                    } else if ((byte1 & 0xF8) === 0xF0) {
-                        // 4-byte character (emojis, etc.)
-                        const byte2 = bytes[i++];
-                        const byte3 = bytes[i++];
-                        const byte4 = bytes[i++];
-                        const code = ((byte1 & 0x07) << 18) | ((byte2 & 0x3F) << 12) |
-                                    ((byte3 & 0x3F) << 6) | (byte4 & 0x3F);
-                        chars.push(String.fromCodePoint(code));
+                        codePoints.push(byte1);
+                        i++;
+                        continue;
+                    }
+
+                    let needed;
+                    let codePoint;
+                    let lower = 0x80;
+                    let upper = 0xBF;
+
+                    if (byte1 >= 0xC2 && byte1 <= 0xDF) {
+                        needed = 1;
+                        codePoint = byte1 & 0x1F;
+                    } else if (byte1 >= 0xE0 && byte1 <= 0xEF) {
+                        needed = 2;
+                        codePoint = byte1 & 0x0F;
+                        if (byte1 === 0xE0) lower = 0xA0; // Overlong
+                        if (byte1 === 0xED) upper = 0x9F; // Surrogates
+                    } else if (byte1 >= 0xF0 && byte1 <= 0xF4) {
+                        needed = 3;
+                        codePoint = byte1 & 0x07;
+                        if (byte1 === 0xF0) lower = 0x90; // Overlong
+                        if (byte1 === 0xF4) upper = 0x8F; // Above U+10FFFF
+                    } else {
+                        // Invalid lead byte
+                        codePoints.push(this._replacement());
+                        i++;
+                        continue;
+                    }
+
+                    // Consume continuation bytes; an invalid one is not consumed
+                    let consumed = 1;
+                    while (consumed <= needed) {
+                        const byte = bytes[i + consumed];
+                        if (byte === undefined || byte < lower || byte > upper) {
+                            break;
+                        }
+                        codePoint = (codePoint << 6) | (byte & 0x3F);
+                        lower = 0x80;
+                        upper = 0xBF;
+                        consumed++;
+                    }
+
+                    if (consumed <= needed) {
+                        codePoints.push(this._replacement());
+                    } else {
+                        codePoints.push(codePoint);
+                    }
+                    i += consumed;
+                }
+
+                return TextDecoder._fromCodePoints(codePoints);
+            }
+
+            _decodeUtf16(bytes, littleEndian, skipBOM) {
+                const codePoints = [];
+                let i = 0;
+
+                // Skip BOM matching the endianness
+                if (skipBOM && bytes.length >= 2) {
+                    if (littleEndian && bytes[0] === 0xFF && bytes[1] === 0xFE) i = 2;
+                    if (!littleEndian && bytes[0] === 0xFE && bytes[1] === 0xFF) i = 2;
+                }
+
+                const unitAt = (offset) => littleEndian
+                    ? bytes[offset] | (bytes[offset + 1] << 8)
+                    : (bytes[offset] << 8) | bytes[offset + 1];
+
+                while (i + 1 < bytes.length) {
+                    const unit = unitAt(i);
+                    i += 2;
+
+                    if (unit >= 0xD800 && unit <= 0xDBFF) {
+                        // High surrogate: must be followed by a low surrogate
+                        if (i + 1 < bytes.length) {
+                            const next = unitAt(i);
+                            if (next >= 0xDC00 && next <= 0xDFFF) {
+                                codePoints.push(0x10000 + ((unit - 0xD800) << 10) + (next - 0xDC00));
+                                i += 2;
+                                continue;
+                            }
+                        }
+                        codePoints.push(this._replacement());
+                    } else if (unit >= 0xDC00 && unit <= 0xDFFF) {
+                        // Lone low surrogate
+                        codePoints.push(this._replacement());
                     } else {
-                        // Invalid UTF-8, skip
-                        chars.push('\uFFFD'); // Replacement character
+                        codePoints.push(unit);
                     }
                 }
 
-                return chars.join('');
+                // Trailing odd byte
+                if (i < bytes.length) {
+                    codePoints.push(this._replacement());
+                }
+
+                return TextDecoder._fromCodePoints(codePoints);
+            }
+
+            _decodeWindows1252(bytes) {
+                const codePoints = new Array(bytes.length);
+                for (let i = 0; i < bytes.length; i++) {
+                    const byte = bytes[i];
+                    codePoints[i] = byte >= 0x80 && byte <= 0x9F
+                        ? TextDecoder._windows1252High[byte - 0x80]
+                        : byte;
+                }
+                return TextDecoder._fromCodePoints(codePoints);
+            }
+
+            // Build a string in slices to stay below argument count limits
+            static _fromCodePoints(codePoints) {
+                const CHUNK = 8192;
+                let result = '';
+                for (let i = 0; i < codePoints.length; i += CHUNK) {
+                    result += String.fromCodePoint.apply(null, codePoints.slice(i, i + CHUNK));
+                }
+                return result;
             }
         };
+
+        // TextDecoderStream - decode a stream of byte chunks into strings
+        globalThis.TextDecoderStream = class TextDecoderStream {
+            constructor(label = 'utf-8', options = {}) {
+                const decoder = new TextDecoder(label, options);
+                this._decoder = decoder;
+                this._transform = new TransformStream({
+                    transform(chunk, controller) {
+                        const text = decoder.decode(chunk, { stream: true });
+                        if (text) {
+                            controller.enqueue(text);
+                        }
+                    },
+                    flush(controller) {
+                        const text = decoder.decode();
+                        if (text) {
+                            controller.enqueue(text);
+                        }
+                    }
+                });
+            }
+
+            get encoding() {
+                return this._decoder.encoding;
+            }
+
+            get fatal() {
+                return this._decoder.fatal;
+            }
+
+            get ignoreBOM() {
+                return this._decoder.ignoreBOM;
+            }
+
+            get readable() {
+                return this._transform.readable;
+            }
+
+            get writable() {
+                return this._transform.writable;
+            }
+        };
+
+        // windows-1252 mapping for bytes 0x80-0x9F (the rest matches latin1)
+        TextDecoder._windows1252High = [
+            0x20AC, 0x0081, 0x201A, 0x0192, 0x201E, 0x2026, 0x2020, 0x2021,
+            0x02C6, 0x2030, 0x0160, 0x2039, 0x0152, 0x008D, 0x017D, 0x008F,
+            0x0090, 0x2018, 0x2019, 0x201C, 0x201D, 0x2022, 0x2013, 0x2014,
+            0x02DC, 0x2122, 0x0161, 0x203A, 0x0153, 0x009D, 0x017E, 0x0178
+        ];
     "#;
 
     context
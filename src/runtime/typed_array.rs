@@ -0,0 +1,82 @@
+use rusty_jsc::{JSContext, JSValue};
+use rusty_jsc_sys::{
+    JSObjectGetArrayBufferByteLength, JSObjectGetArrayBufferBytesPtr, JSObjectGetTypedArrayBuffer,
+    JSObjectGetTypedArrayByteLength, JSObjectGetTypedArrayByteOffset,
+    JSObjectGetTypedArrayBytesPtr, JSObjectRef, JSValueGetTypedArrayType, JSValueIsObject,
+    JSValueRef,
+};
+
+// kJSTypedArrayTypeArrayBuffer (JSValueRef.h)
+const TYPED_ARRAY_TYPE_ARRAY_BUFFER: u32 = 9;
+
+/// Copy the bytes of an ArrayBuffer, DataView or any TypedArray
+///
+/// Views contribute only the `byteLength` bytes starting at their
+/// `byteOffset`, so a `subarray()` yields just its own bytes.
+pub(crate) fn js_value_to_bytes(ctx: &JSContext, value: &JSValue) -> Result<Vec<u8>, JSValue> {
+    unsafe { js_value_bytes_mut(ctx, value) }.map(|bytes| bytes.to_vec())
+}
+
+/// Borrow the bytes of an ArrayBuffer, DataView or any TypedArray in place
+/// (see `js_value_to_bytes`)
+///
+/// # Safety
+///
+/// The slice points into the JS heap: it must not be used after JS runs
+/// again, since the buffer may be detached, resized or collected.
+pub(crate) unsafe fn js_value_bytes_mut<'a>(
+    ctx: &JSContext,
+    value: &JSValue,
+) -> Result<&'a mut [u8], JSValue> {
+    let invalid = || JSValue::string(ctx, "Expected an ArrayBuffer, TypedArray or DataView");
+
+    let ctx_ref = ctx.get_ref();
+    let value_ref = value.get_ref();
+    if !unsafe { JSValueIsObject(ctx_ref, value_ref) } {
+        return Err(invalid());
+    }
+    let object = value_ref as JSObjectRef;
+
+    let mut exception: JSValueRef = std::ptr::null();
+    let (ptr, len) = unsafe {
+        if JSValueGetTypedArrayType(ctx_ref, value_ref, &mut exception) as u32
+            == TYPED_ARRAY_TYPE_ARRAY_BUFFER
+        {
+            (
+                JSObjectGetArrayBufferBytesPtr(ctx_ref, object, &mut exception) as *mut u8,
+                JSObjectGetArrayBufferByteLength(ctx_ref, object, &mut exception),
+            )
+        } else {
+            // Any ArrayBufferView (TypedArrays and DataView) has a backing buffer;
+            // its bytes pointer is the start of that buffer, not of the view
+            if JSObjectGetTypedArrayBuffer(ctx_ref, object, &mut exception).is_null() {
+                return Err(invalid());
+            }
+
+            let base = JSObjectGetTypedArrayBytesPtr(ctx_ref, object, &mut exception) as *mut u8;
+            let offset = JSObjectGetTypedArrayByteOffset(ctx_ref, object, &mut exception);
+            let len = JSObjectGetTypedArrayByteLength(ctx_ref, object, &mut exception);
+            (
+                if base.is_null() {
+                    base
+                } else {
+                    base.add(offset)
+                },
+                len,
+            )
+        }
+    };
+
+    if !exception.is_null() {
+        return Err(invalid());
+    }
+
+    if len == 0 {
+        return Ok(&mut []);
+    }
+    if ptr.is_null() {
+        return Err(invalid());
+    }
+
+    Ok(unsafe { std::slice::from_raw_parts_mut(ptr, len) })
+}
@@ -1,39 +1,140 @@
 use rusty_jsc::JSContext;
+use url::{Url, quirks};
 
 /// Setup URL and URLSearchParams APIs
 pub fn setup_url_api(context: &mut JSContext) {
-    // Minimal URL implementation for parsing
-    let url_impl = r#"
-        globalThis.URL = class URL {
-            constructor(url, base) {
-                // Simple URL parsing
-                this.href = base ? new URL(base).origin + url : url;
-
-                // Parse the URL
-                const match = this.href.match(/^(([^:/?#]+):)?(\/\/([^/?#]*))?([^?#]*)(\?([^#]*))?(#(.*))?/);
-
-                this.protocol = match[2] ? match[2] + ':' : '';
-                this.host = match[4] || '';
-                this.pathname = match[5] || '/';
-                this.search = match[6] || '';
-                this.hash = match[8] || '';
-
-                // Parse hostname and port
-                const hostMatch = this.host.match(/^([^:]+)(:(.+))?$/);
-                this.hostname = hostMatch ? hostMatch[1] : '';
-                this.port = hostMatch && hostMatch[3] ? hostMatch[3] : '';
-
-                // Origin
-                this.origin = this.protocol + '//' + this.host;
-
-                // SearchParams
-                this.searchParams = new URLSearchParams(this.search.substring(1));
+    // __normalizePathname(url) -> string
+    // Resolves `.`/`..` segments with the native URL parser and collapses `//`
+    let normalize_pathname_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            let url = match args.first().map(|arg| arg.to_js_string(&ctx)) {
+                Some(Ok(url)) => url.to_string(),
+                _ => return Err(JSValue::string(&ctx, "URL must be a string")),
+            };
+
+            match normalize_pathname(&url) {
+                Ok(pathname) => Ok(JSValue::string(&ctx, pathname.as_str())),
+                Err(e) => Err(JSValue::string(&ctx, e.as_str())),
             }
+        }
+    );
 
-            toString() {
-                return this.href;
+    // __nativeUrlParse(url, base) -> JSON components
+    // WHATWG URL parsing, with relative references resolved against the base
+    let parse_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            let input = match args.first().map(|arg| arg.to_js_string(&ctx)) {
+                Some(Ok(input)) => input.to_string(),
+                _ => return Err(JSValue::string(&ctx, "URL must be a string")),
+            };
+
+            let base = match args.get(1) {
+                Some(base) if !base.is_undefined(&ctx) => match base.to_js_string(&ctx) {
+                    Ok(base) => Some(base.to_string()),
+                    Err(_) => return Err(JSValue::string(&ctx, "Base URL must be a string")),
+                },
+                _ => None,
+            };
+
+            match parse_url(&input, base.as_deref()) {
+                Ok(url) => Ok(JSValue::string(&ctx, url_components(&url).as_str())),
+                Err(e) => Err(JSValue::string(&ctx, e.as_str())),
             }
-        };
+        }
+    );
+
+    // __nativeUrlSet(href, component, value) -> JSON components
+    // Component setters follow the WHATWG rules: invalid values are ignored
+    let set_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            let mut strings = Vec::with_capacity(3);
+            for i in 0..3 {
+                match args.get(i).map(|arg| arg.to_js_string(&ctx)) {
+                    Some(Ok(s)) => strings.push(s.to_string()),
+                    _ => return Err(JSValue::string(&ctx, "URL setter requires 3 strings")),
+                }
+            }
+
+            match set_url_component(&strings[0], &strings[1], &strings[2]) {
+                Ok(url) => Ok(JSValue::string(&ctx, url_components(&url).as_str())),
+                Err(e) => Err(JSValue::string(&ctx, e.as_str())),
+            }
+        }
+    );
+
+    let mut global = context.get_global_object();
+    global
+        .set_property(context, "__normalizePathname", normalize_pathname_fn.into())
+        .unwrap();
+    global
+        .set_property(context, "__nativeUrlParse", parse_fn.into())
+        .unwrap();
+    global
+        .set_property(context, "__nativeUrlSet", set_fn.into())
+        .unwrap();
+
+    // URL backed by the native WHATWG parser
+    let url_impl = r#"
+        (function() {
+            const parse = (fn) => {
+                try {
+                    return JSON.parse(fn());
+                } catch (e) {
+                    throw new TypeError(String(e));
+                }
+            };
+
+            globalThis.URL = class URL {
+                constructor(url, base) {
+                    this._parts = parse(() => base === undefined
+                        ? __nativeUrlParse(String(url))
+                        : __nativeUrlParse(String(url), String(base)));
+                    this.searchParams = new URLSearchParams(this._parts.search.substring(1));
+                }
+
+                static canParse(url, base) {
+                    try {
+                        new URL(url, base);
+                        return true;
+                    } catch (e) {
+                        return false;
+                    }
+                }
+
+                get href() { return this._parts.href; }
+                set href(value) {
+                    this._parts = parse(() => __nativeUrlParse(String(value)));
+                    this.searchParams = new URLSearchParams(this._parts.search.substring(1));
+                }
+
+                get origin() { return this._parts.origin; }
+
+                toString() {
+                    return this.href;
+                }
+
+                toJSON() {
+                    return this.href;
+                }
+            };
+
+            for (const name of ['protocol', 'username', 'password', 'host', 'hostname',
+                                'port', 'pathname', 'search', 'hash']) {
+                Object.defineProperty(URL.prototype, name, {
+                    get() { return this._parts[name]; },
+                    set(value) {
+                        this._parts = parse(() => __nativeUrlSet(this._parts.href, name, String(value)));
+                        if (name === 'search') {
+                            this.searchParams = new URLSearchParams(this._parts.search.substring(1));
+                        }
+                    },
+                    configurable: true
+                });
+            }
+        })();
 
         globalThis.URLSearchParams = class URLSearchParams {
             constructor(init) {
@@ -83,3 +184,100 @@ pub fn setup_url_api(context: &mut JSContext) {
 
     context.evaluate_script(url_impl, 1).unwrap();
 }
+
+/// Parse `input`, resolved against `base` when given
+fn parse_url(input: &str, base: Option<&str>) -> Result<Url, String> {
+    let base = base
+        .map(|base| Url::parse(base).map_err(|e| format!("Invalid base URL '{}': {}", base, e)))
+        .transpose()?;
+
+    Url::options()
+        .base_url(base.as_ref())
+        .parse(input)
+        .map_err(|e| format!("Invalid URL '{}': {}", input, e))
+}
+
+/// Apply a URL component setter (`protocol`, `host`, `pathname`, ...)
+fn set_url_component(href: &str, component: &str, value: &str) -> Result<Url, String> {
+    let mut url = Url::parse(href).map_err(|e| format!("Invalid URL '{}': {}", href, e))?;
+
+    // Setters report invalid values, which the URL standard ignores
+    let _ = match component {
+        "protocol" => quirks::set_protocol(&mut url, value),
+        "username" => quirks::set_username(&mut url, value),
+        "password" => quirks::set_password(&mut url, value),
+        "host" => quirks::set_host(&mut url, value),
+        "hostname" => quirks::set_hostname(&mut url, value),
+        "port" => quirks::set_port(&mut url, value),
+        "pathname" => {
+            quirks::set_pathname(&mut url, value);
+            Ok(())
+        }
+        "search" => {
+            quirks::set_search(&mut url, value);
+            Ok(())
+        }
+        "hash" => {
+            quirks::set_hash(&mut url, value);
+            Ok(())
+        }
+        _ => return Err(format!("Unknown URL component '{}'", component)),
+    };
+
+    Ok(url)
+}
+
+/// JSON object of the URL's components, as exposed on URL instances
+///
+/// Default ports are dropped by the parser, so `port` is empty for
+/// `https://host:443/`.
+fn url_components(url: &Url) -> String {
+    serde_json::json!({
+        "href": quirks::href(url),
+        "origin": quirks::origin(url),
+        "protocol": quirks::protocol(url),
+        "username": quirks::username(url),
+        "password": quirks::password(url),
+        "host": quirks::host(url),
+        "hostname": quirks::hostname(url),
+        "port": quirks::port(url),
+        "pathname": quirks::pathname(url),
+        "search": quirks::search(url),
+        "hash": quirks::hash(url),
+    })
+    .to_string()
+}
+
+/// Normalized pathname of an absolute URL, for routing
+fn normalize_pathname(url: &str) -> Result<String, String> {
+    let parsed = Url::parse(url).map_err(|e| format!("Invalid URL '{}': {}", url, e))?;
+
+    let mut pathname = String::with_capacity(parsed.path().len());
+    for c in parsed.path().chars() {
+        if c == '/' && pathname.ends_with('/') {
+            continue;
+        }
+        pathname.push(c);
+    }
+
+    Ok(pathname)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_pathname() {
+        assert_eq!(
+            normalize_pathname("https://example.com/a/./b/../c").unwrap(),
+            "/a/c"
+        );
+        assert_eq!(
+            normalize_pathname("https://example.com//a///b/").unwrap(),
+            "/a/b/"
+        );
+        assert_eq!(normalize_pathname("https://example.com").unwrap(), "/");
+        assert!(normalize_pathname("not a url").is_err());
+    }
+}
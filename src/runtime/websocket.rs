@@ -0,0 +1,476 @@
+use super::{
+    CallbackId, CallbackMessage, FetchPolicy, SchedulerMessage, typed_array::js_value_to_bytes,
+};
+use bytes::Bytes;
+use futures::{SinkExt, StreamExt};
+use rusty_jsc::{JSContext, JSObject, JSValue};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::{
+    Message,
+    client::IntoClientRequest,
+    http::HeaderValue,
+    protocol::{CloseFrame, frame::coding::CloseCode},
+};
+
+/// A data frame sent or received over a WebSocket
+#[derive(Debug, Clone)]
+pub enum WebSocketFrame {
+    Text(String),
+    Binary(Bytes),
+}
+
+/// Connection event delivered to the JS WebSocket object
+#[derive(Debug, Clone)]
+pub enum WebSocketEvent {
+    /// Handshake completed with the negotiated subprotocol ("" if none)
+    Open(String),
+    /// A frame arrived from the server
+    Message(WebSocketFrame),
+    /// The connection failed (always followed by Close)
+    Error(String),
+    /// The connection is closed; no event follows
+    Close {
+        code: u16,
+        reason: String,
+        was_clean: bool,
+    },
+}
+
+/// Command forwarded from the event loop to a connection task
+pub(crate) enum WebSocketCommand {
+    Send(WebSocketFrame),
+    Close(Option<u16>, String),
+}
+
+/// Setup the WebSocket client
+///
+/// Each socket stores a dispatch function under its callback ID; the event
+/// loop calls it with every `WebSocketEvent` until the socket is closed.
+pub fn setup_websocket(
+    context: &mut JSContext,
+    scheduler_tx: mpsc::UnboundedSender<SchedulerMessage>,
+    callbacks: Arc<Mutex<HashMap<CallbackId, JSObject>>>,
+    next_id: Arc<Mutex<CallbackId>>,
+) {
+    let scheduler_tx_send = scheduler_tx.clone();
+    let scheduler_tx_close = scheduler_tx.clone();
+
+    // __nativeWebSocketConnect(url, protocols, dispatch) -> socket id
+    let connect_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            if args.len() < 3 {
+                return Err(JSValue::string(
+                    &ctx,
+                    "__nativeWebSocketConnect requires url, protocols and dispatch",
+                ));
+            }
+
+            let url = match args[0].to_js_string(&ctx) {
+                Ok(url) => url.to_string(),
+                Err(_) => return Err(JSValue::string(&ctx, "url must be a string")),
+            };
+
+            // Protocols arrive comma-separated (already validated as tokens)
+            let protocols: Vec<String> = match args[1].to_js_string(&ctx) {
+                Ok(protocols) => protocols
+                    .to_string()
+                    .split(',')
+                    .filter(|p| !p.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+                Err(_) => return Err(JSValue::string(&ctx, "protocols must be a string")),
+            };
+
+            let dispatch = match args[2].to_object(&ctx) {
+                Ok(obj) => obj,
+                Err(_) => return Err(JSValue::string(&ctx, "dispatch must be a function")),
+            };
+
+            let socket_id = {
+                let mut next = next_id.lock().unwrap();
+                let id = *next;
+                *next += 1;
+                id
+            };
+
+            callbacks.lock().unwrap().insert(socket_id, dispatch);
+
+            log::debug!("WebSocket {}: connecting to {}", socket_id, url);
+
+            let _ = scheduler_tx.send(SchedulerMessage::WebSocketConnect(
+                socket_id, url, protocols,
+            ));
+
+            Ok(JSValue::number(&ctx, socket_id as f64))
+        }
+    );
+
+    // __nativeWebSocketSend(id, data): data is a string or a Uint8Array
+    let send_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            if args.len() < 2 {
+                return Err(JSValue::string(
+                    &ctx,
+                    "__nativeWebSocketSend requires id and data",
+                ));
+            }
+
+            let socket_id = match args[0].to_number(&ctx) {
+                Ok(id) => id as CallbackId,
+                Err(_) => return Err(JSValue::string(&ctx, "id must be a number")),
+            };
+
+            let frame = if args[1].is_string(&ctx) {
+                match args[1].to_js_string(&ctx) {
+                    Ok(text) => WebSocketFrame::Text(text.to_string()),
+                    Err(_) => return Err(JSValue::string(&ctx, "Invalid text data")),
+                }
+            } else {
+                WebSocketFrame::Binary(Bytes::from(js_value_to_bytes(&ctx, &args[1])?))
+            };
+
+            let _ = scheduler_tx_send.send(SchedulerMessage::WebSocketSend(socket_id, frame));
+
+            Ok(JSValue::undefined(&ctx))
+        }
+    );
+
+    // __nativeWebSocketClose(id, code, reason): code is undefined for no status
+    let close_fn = rusty_jsc::callback_closure!(
+        context,
+        move |ctx: JSContext, _func: JSObject, _this: JSObject, args: &[JSValue]| {
+            let socket_id = match args.first().map(|arg| arg.to_number(&ctx)) {
+                Some(Ok(id)) => id as CallbackId,
+                _ => return Err(JSValue::string(&ctx, "id must be a number")),
+            };
+
+            let code = match args.get(1) {
+                Some(code) if !code.is_undefined(&ctx) => {
+                    code.to_number(&ctx).ok().map(|c| c as u16)
+                }
+                _ => None,
+            };
+
+            let reason = match args.get(2) {
+                Some(reason) if !reason.is_undefined(&ctx) => reason
+                    .to_js_string(&ctx)
+                    .map(|r| r.to_string())
+                    .unwrap_or_default(),
+                _ => String::new(),
+            };
+
+            let _ =
+                scheduler_tx_close.send(SchedulerMessage::WebSocketClose(socket_id, code, reason));
+
+            Ok(JSValue::undefined(&ctx))
+        }
+    );
+
+    let mut global = context.get_global_object();
+    global
+        .set_property(context, "__nativeWebSocketConnect", connect_fn.into())
+        .unwrap();
+    global
+        .set_property(context, "__nativeWebSocketSend", send_fn.into())
+        .unwrap();
+    global
+        .set_property(context, "__nativeWebSocketClose", close_fn.into())
+        .unwrap();
+
+    context
+        .evaluate_script(WEBSOCKET_JS, 1)
+        .expect("Failed to setup WebSocket");
+}
+
+/// WebSocket class (WHATWG WebSockets spec) over the native bindings
+const WEBSOCKET_JS: &str = r#"
+    (function() {
+        const tokenRe = /^[!#$%&'*+\-.^_`|~0-9A-Za-z]+$/;
+
+        // DOMException-like error
+        const domError = (name, message) => {
+            const error = new Error(message);
+            error.name = name;
+            return error;
+        };
+
+        class WebSocket extends EventTarget {
+            constructor(url, protocols) {
+                super();
+
+                let parsed;
+                try {
+                    parsed = new URL(String(url));
+                } catch (e) {
+                    throw domError('SyntaxError', "Invalid WebSocket URL '" + url + "'");
+                }
+
+                if (parsed.protocol === 'http:') {
+                    parsed.protocol = 'ws:';
+                } else if (parsed.protocol === 'https:') {
+                    parsed.protocol = 'wss:';
+                }
+                if (parsed.protocol !== 'ws:' && parsed.protocol !== 'wss:') {
+                    throw domError('SyntaxError', "WebSocket URL scheme must be 'ws' or 'wss', got '" + parsed.protocol + "'");
+                }
+                if (parsed.hash) {
+                    throw domError('SyntaxError', 'WebSocket URL must not contain a fragment');
+                }
+
+                if (protocols === undefined) {
+                    protocols = [];
+                } else if (typeof protocols === 'string') {
+                    protocols = [protocols];
+                } else {
+                    protocols = Array.from(protocols, String);
+                }
+                for (const protocol of protocols) {
+                    if (!tokenRe.test(protocol)) {
+                        throw domError('SyntaxError', "Invalid WebSocket subprotocol '" + protocol + "'");
+                    }
+                }
+                if (new Set(protocols.map(p => p.toLowerCase())).size !== protocols.length) {
+                    throw domError('SyntaxError', 'Duplicate WebSocket subprotocol');
+                }
+
+                this._url = parsed.href;
+                this._readyState = WebSocket.CONNECTING;
+                this._protocol = '';
+                this._binaryType = 'blob';
+                this.onopen = null;
+                this.onmessage = null;
+                this.onerror = null;
+                this.onclose = null;
+
+                this._id = __nativeWebSocketConnect(this._url, protocols.join(','), (type, a, b, c) => this._dispatch(type, a, b, c));
+            }
+
+            get url() { return this._url; }
+            get readyState() { return this._readyState; }
+            get protocol() { return this._protocol; }
+            get extensions() { return ''; }
+            get bufferedAmount() { return 0; }
+
+            get binaryType() { return this._binaryType; }
+            set binaryType(value) {
+                if (value === 'blob' || value === 'arraybuffer') {
+                    this._binaryType = value;
+                }
+            }
+
+            send(data) {
+                if (this._readyState === WebSocket.CONNECTING) {
+                    throw domError('InvalidStateError', 'WebSocket is still connecting');
+                }
+                if (this._readyState !== WebSocket.OPEN) {
+                    return;
+                }
+
+                if (data instanceof ArrayBuffer) {
+                    data = new Uint8Array(data.slice(0));
+                } else if (ArrayBuffer.isView(data)) {
+                    data = new Uint8Array(data.buffer.slice(data.byteOffset, data.byteOffset + data.byteLength));
+                } else if (data instanceof Blob) {
+                    data = data._bytes.slice();
+                } else {
+                    data = String(data);
+                }
+
+                __nativeWebSocketSend(this._id, data);
+            }
+
+            close(code, reason) {
+                if (code !== undefined) {
+                    code = Number(code);
+                    if (code !== 1000 && !(code >= 3000 && code <= 4999)) {
+                        throw domError('InvalidAccessError', 'Invalid WebSocket close code: ' + code);
+                    }
+                }
+                if (reason !== undefined) {
+                    reason = String(reason);
+                    if (new TextEncoder().encode(reason).length > 123) {
+                        throw domError('SyntaxError', 'WebSocket close reason must be at most 123 bytes');
+                    }
+                }
+
+                if (this._readyState === WebSocket.CLOSING || this._readyState === WebSocket.CLOSED) {
+                    return;
+                }
+
+                this._readyState = WebSocket.CLOSING;
+                __nativeWebSocketClose(this._id, code, reason);
+            }
+
+            // Internal: called by the event loop with (type, ...event data)
+            _dispatch(type, a, b, c) {
+                let event;
+
+                switch (type) {
+                    case 'open':
+                        this._readyState = WebSocket.OPEN;
+                        this._protocol = a;
+                        event = new Event(type);
+                        break;
+                    case 'message': {
+                        let data = a;
+                        if (typeof data !== 'string') {
+                            data = this._binaryType === 'arraybuffer' ? data.buffer : new Blob([data]);
+                        }
+                        event = Object.assign(new Event(type), { data, origin: new URL(this._url).origin });
+                        break;
+                    }
+                    case 'error':
+                        event = Object.assign(new Event(type), { message: a });
+                        break;
+                    case 'close':
+                        this._readyState = WebSocket.CLOSED;
+                        event = Object.assign(new Event(type), { code: a, reason: b, wasClean: c });
+                        break;
+                    default:
+                        return;
+                }
+
+                this._dispatchWithHandler(event);
+            }
+        }
+
+        ['CONNECTING', 'OPEN', 'CLOSING', 'CLOSED'].forEach((name, value) => {
+            Object.defineProperty(WebSocket, name, { value, enumerable: true });
+            Object.defineProperty(WebSocket.prototype, name, { value, enumerable: true });
+        });
+
+        globalThis.WebSocket = WebSocket;
+    })();
+"#;
+
+/// Connect to `url` and pump frames until the socket is closed
+///
+/// Every outcome ends with exactly one `WebSocketEvent::Close`, after which
+/// the runtime drops the socket's dispatch callback.
+pub(crate) async fn run_websocket(
+    socket_id: CallbackId,
+    url: String,
+    protocols: Vec<String>,
+    mut commands: mpsc::UnboundedReceiver<WebSocketCommand>,
+    callback_tx: mpsc::UnboundedSender<CallbackMessage>,
+    policy: FetchPolicy,
+) {
+    let emit = |event: WebSocketEvent| {
+        let _ = callback_tx.send(CallbackMessage::WebSocketEvent(socket_id, event));
+    };
+    let fail = |error: String| {
+        log::warn!("WebSocket {}: {}", socket_id, error);
+        emit(WebSocketEvent::Error(error));
+        emit(WebSocketEvent::Close {
+            code: 1006,
+            reason: String::new(),
+            was_clean: false,
+        });
+    };
+
+    // SSRF protection: resolve the host and reject private addresses
+    if let Err(e) = policy.check_resolved(&url).await {
+        fail(e);
+        return;
+    }
+
+    let mut request = match url.as_str().into_client_request() {
+        Ok(request) => request,
+        Err(e) => return fail(format!("Invalid WebSocket request: {}", e)),
+    };
+
+    if !protocols.is_empty() {
+        match HeaderValue::from_str(&protocols.join(", ")) {
+            Ok(value) => {
+                request
+                    .headers_mut()
+                    .insert("Sec-WebSocket-Protocol", value);
+            }
+            Err(e) => return fail(format!("Invalid WebSocket subprotocol: {}", e)),
+        }
+    }
+
+    let (stream, response) = match tokio_tungstenite::connect_async(request).await {
+        Ok(connected) => connected,
+        Err(e) => return fail(format!("WebSocket connection to '{}' failed: {}", url, e)),
+    };
+
+    let protocol = response
+        .headers()
+        .get("sec-websocket-protocol")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("")
+        .to_string();
+
+    log::debug!("WebSocket {}: connected to {}", socket_id, url);
+    emit(WebSocketEvent::Open(protocol));
+
+    let (mut sink, mut source) = stream.split();
+    let mut commands_open = true;
+    // Close frame received from the server: (code, reason)
+    let mut closing: Option<(u16, String)> = None;
+
+    loop {
+        tokio::select! {
+            command = commands.recv(), if commands_open => match command {
+                Some(WebSocketCommand::Send(frame)) => {
+                    let message = match frame {
+                        WebSocketFrame::Text(text) => Message::Text(text.into()),
+                        WebSocketFrame::Binary(data) => Message::Binary(data),
+                    };
+                    if let Err(e) = sink.send(message).await {
+                        return fail(format!("WebSocket send failed: {}", e));
+                    }
+                }
+                Some(WebSocketCommand::Close(code, reason)) => {
+                    let frame = code.map(|code| CloseFrame {
+                        code: CloseCode::from(code),
+                        reason: reason.into(),
+                    });
+                    // The server answers with its own close frame, read below
+                    let _ = sink.send(Message::Close(frame)).await;
+                }
+                None => commands_open = false,
+            },
+            message = source.next() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    emit(WebSocketEvent::Message(WebSocketFrame::Text(text.to_string())));
+                }
+                Some(Ok(Message::Binary(data))) => {
+                    emit(WebSocketEvent::Message(WebSocketFrame::Binary(data)));
+                }
+                Some(Ok(Message::Close(frame))) => {
+                    // Keep polling so the close reply is flushed before the stream ends
+                    closing = Some(
+                        frame
+                            .map(|frame| (u16::from(frame.code), frame.reason.to_string()))
+                            .unwrap_or((1005, String::new())),
+                    );
+                }
+                Some(Ok(_)) => {
+                    // Ping/pong frames are answered by tungstenite
+                }
+                Some(Err(e)) if closing.is_none() => {
+                    return fail(format!("WebSocket error: {}", e));
+                }
+                Some(Err(_)) | None => break,
+            },
+        }
+    }
+
+    let (code, reason, was_clean) = match closing {
+        Some((code, reason)) => (code, reason, true),
+        None => (1006, String::new(), false),
+    };
+
+    log::debug!("WebSocket {}: closed with code {}", socket_id, code);
+    emit(WebSocketEvent::Close {
+        code,
+        reason,
+        was_clean,
+    });
+}
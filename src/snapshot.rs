@@ -1,18 +1,91 @@
-/// Snapshot support (not implemented for JSCore)
+/// Snapshot support
 ///
-/// JSCore doesn't have the same snapshot capabilities as V8/Deno.
-/// This module provides stub implementations for compatibility.
+/// JSCore can't serialize a heap the way V8/Deno snapshots do, so a
+/// `Snapshot` is a source bundle: the pure-JS prelude (atob/btoa, streams,
+/// Blob, Headers, Response, FormData, Request, AbortController) as ordered
+/// scripts. `Runtime::from_snapshot` binds the native functions (console,
+/// timers, fetch, crypto, TextEncoder, URL, ...) in code, then parses and
+/// evaluates the bundle again. Every runtime pays the full prelude cost, so
+/// this is not a startup optimization; it pins the prelude an embedder ships
+/// alongside a given crate version.
+///
+/// Not captured: native bindings (always re-bound), heap state, globals
+/// created by worker scripts, and pending timers, fetches or streams.
+use crate::runtime::{PRELUDE, prelude_source};
+use serde::{Deserialize, Serialize};
+
+/// One prelude script, in evaluation order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotScript {
+    pub name: String,
+    pub source: String,
+}
+
+/// Prelude captured for runtime creation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Crate version that produced the snapshot (must match to restore)
+    pub version: String,
+    pub scripts: Vec<SnapshotScript>,
+}
+
+impl Snapshot {
+    /// Capture the built-in prelude of this crate version
+    pub fn capture() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            scripts: PRELUDE
+                .iter()
+                .map(|(name, source)| SnapshotScript {
+                    name: name.to_string(),
+                    source: source.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Serialize the snapshot
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("Snapshot is always serializable")
+    }
+
+    /// Deserialize a snapshot written by `to_bytes`
+    ///
+    /// Snapshots from another crate version are rejected: the prelude must
+    /// match the native bindings it relies on.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let snapshot: Self =
+            serde_json::from_slice(bytes).map_err(|e| format!("Invalid snapshot: {}", e))?;
+
+        if snapshot.version != env!("CARGO_PKG_VERSION") {
+            return Err(format!(
+                "Snapshot version {} does not match runtime version {}",
+                snapshot.version,
+                env!("CARGO_PKG_VERSION")
+            ));
+        }
+
+        Ok(snapshot)
+    }
+
+    /// The whole prelude as a single script
+    pub(crate) fn source(&self) -> String {
+        prelude_source(
+            self.scripts
+                .iter()
+                .map(|script| (script.name.as_str(), script.source.as_str())),
+        )
+    }
+}
 
 /// Snapshot output structure
 pub struct SnapshotOutput {
     pub output: Vec<u8>,
 }
 
-/// Create a runtime snapshot (not supported in JSCore)
+/// Create a runtime snapshot (serialized `Snapshot`)
 pub fn create_runtime_snapshot() -> Result<SnapshotOutput, String> {
-    // Return empty snapshot - JSCore doesn't support snapshots like V8
-    eprintln!("Warning: Snapshots are not supported in JSCore runtime");
-    eprintln!("Returning empty snapshot for compatibility");
-
-    Ok(SnapshotOutput { output: Vec::new() })
+    Ok(SnapshotOutput {
+        output: Snapshot::capture().to_bytes(),
+    })
 }
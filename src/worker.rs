@@ -1,16 +1,171 @@
-use crate::runtime::{Runtime, run_event_loop, stream_manager::StreamChunk};
+use crate::module::module_to_script;
+use crate::runtime::bindings::{ConsoleMessage, ConsoleState, SubrequestState};
+use crate::runtime::{
+    FetchClient, FetchClientConfig, FetchPolicy, Runtime, run_event_loop_with_policy,
+    stream_manager::StreamChunk,
+};
 use openworkers_core::{
     Event, HttpResponse, OperationsHandle, RequestBody, ResponseBody, RuntimeLimits, Script,
     TaskInit, TaskResult, TaskSource, TerminationReason,
 };
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Status sent to the embedder when the handler responds with Response.error()
+const NETWORK_ERROR_STATUS: u16 = 502;
+
+/// How long a fetch handler may take to provide a response without a wall time
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Default number of chunks buffered between the JS response stream and the embedder
+const DEFAULT_RESPONSE_STREAM_BUFFER_SIZE: usize = 16;
+
+/// Embedder options not covered by RuntimeLimits
+#[derive(Clone, Default)]
+pub struct WorkerOptions {
+    /// Policy applied to outbound fetches
+    pub fetch_policy: FetchPolicy,
+    /// Maximum console messages per request (None = unlimited)
+    pub max_log_events: Option<usize>,
+    /// Object nesting shown by console (None = `DEFAULT_CONSOLE_MAX_DEPTH`)
+    pub console_max_depth: Option<usize>,
+    /// Maximum console message length (None = `DEFAULT_CONSOLE_MAX_LENGTH`)
+    pub console_max_length: Option<usize>,
+    /// Channel receiving console output
+    pub log_tx: Option<mpsc::UnboundedSender<ConsoleMessage>>,
+    /// Maximum fetches per request (None = unlimited)
+    pub max_subrequests: Option<usize>,
+    /// Maximum depth of timers scheduled from timer callbacks (None = unlimited)
+    pub max_timer_nesting: Option<usize>,
+    /// Maximum size of a single response stream chunk written from JS
+    /// (None = the StreamManager default)
+    pub max_stream_chunk_size: Option<usize>,
+    /// Accept Promise bodies in the Request/Response constructors (non-standard)
+    pub promise_bodies: bool,
+    /// Deadline for a whole event, response stream forwarding and
+    /// waitUntil included (None = the default ~5s response budget)
+    pub wall_time: Option<Duration>,
+    /// Response body chunks buffered before JS writes wait for the embedder
+    /// (None = `DEFAULT_RESPONSE_STREAM_BUFFER_SIZE`, must be at least 1)
+    pub response_stream_buffer_size: Option<usize>,
+    /// Incoming request headers copied onto the event's outbound fetches
+    pub propagate_headers: Vec<String>,
+    /// Send fetches with the built-in client instead of the OperationsHandler
+    pub fetch_client: Option<FetchClientConfig>,
+}
+
+impl WorkerOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check outbound fetches against a FetchPolicy
+    pub fn fetch_policy(mut self, policy: FetchPolicy) -> Self {
+        self.fetch_policy = policy;
+        self
+    }
+
+    /// Cap console messages per request; further logs are dropped after a
+    /// single "log rate exceeded" warning
+    pub fn max_log_events(mut self, max: usize) -> Self {
+        self.max_log_events = Some(max);
+        self
+    }
+
+    /// Collapse objects nested deeper than `depth` in console output
+    pub fn console_max_depth(mut self, depth: usize) -> Self {
+        self.console_max_depth = Some(depth);
+        self
+    }
+
+    /// Truncate console messages longer than `length` with `…`
+    pub fn console_max_length(mut self, length: usize) -> Self {
+        self.console_max_length = Some(length);
+        self
+    }
+
+    /// Cap fetches per request; further fetches reject with a TypeError
+    pub fn max_subrequests(mut self, max: usize) -> Self {
+        self.max_subrequests = Some(max);
+        self
+    }
+
+    /// Cap how deeply timer callbacks may schedule further timers; past
+    /// `depth`, setTimeout/setInterval/setImmediate throw a RangeError
+    pub fn max_timer_nesting(mut self, depth: usize) -> Self {
+        self.max_timer_nesting = Some(depth);
+        self
+    }
+
+    /// Reject response body chunks larger than `bytes`
+    pub fn max_stream_chunk_size(mut self, bytes: usize) -> Self {
+        self.max_stream_chunk_size = Some(bytes);
+        self
+    }
+
+    /// Let Request/Response bodies be a Promise of a body, resolved before
+    /// streaming (non-standard, for framework compatibility)
+    pub fn promise_bodies(mut self, enabled: bool) -> Self {
+        self.promise_bodies = enabled;
+        self
+    }
+
+    /// Bound each event by `wall_time`, from dispatch until the response
+    /// body has been forwarded; a stream still running at the deadline is
+    /// terminated with an error
+    pub fn wall_time(mut self, wall_time: Duration) -> Self {
+        self.wall_time = Some(wall_time);
+        self
+    }
+
+    /// Buffer `chunks` response body chunks towards the embedder: lower
+    /// values bound memory per response, higher ones smooth throughput
+    pub fn response_stream_buffer_size(mut self, chunks: usize) -> Self {
+        self.response_stream_buffer_size = Some(chunks);
+        self
+    }
+
+    /// Copy the `name` header of each incoming request (e.g. `traceparent`,
+    /// `x-request-id`) onto the fetches its handler makes, unless a fetch
+    /// sets it explicitly
+    pub fn propagate_header(mut self, name: impl Into<String>) -> Self {
+        self.propagate_headers.push(name.into());
+        self
+    }
+
+    /// Send fetches made from JS with the built-in HTTP client configured
+    /// by `config` (e.g. with private root certificates) rather than through
+    /// the OperationsHandler passed to the worker
+    pub fn fetch_client(mut self, config: FetchClientConfig) -> Self {
+        self.fetch_client = Some(config);
+        self
+    }
+
+    /// Forward console output to a channel
+    pub fn log_tx(mut self, tx: mpsc::UnboundedSender<ConsoleMessage>) -> Self {
+        self.log_tx = Some(tx);
+        self
+    }
+}
 
 /// Worker that executes JavaScript with event handlers
 pub struct Worker {
     pub(crate) runtime: Runtime,
     event_loop_handle: tokio::task::JoinHandle<()>,
     aborted: Arc<AtomicBool>,
+    /// Console messages emitted by the current request
+    log_count: Arc<AtomicUsize>,
+    /// Fetches issued by the current request
+    subrequest_count: Arc<AtomicUsize>,
+    /// Deadline for each event (see WorkerOptions::wall_time)
+    wall_time: Option<Duration>,
+    /// Capacity of the channel forwarding response bodies to the embedder
+    response_stream_buffer_size: usize,
+    /// Incoming headers copied onto outbound fetches (see
+    /// WorkerOptions::propagate_header)
+    propagate_headers: Vec<String>,
 }
 
 impl Worker {
@@ -29,6 +184,33 @@ impl Worker {
     pub fn context(&self) -> &rusty_jsc::JSContext {
         &self.runtime.context
     }
+
+    /// Expose a host reader as a native stream (see `Runtime::create_reader_stream`)
+    pub fn create_reader_stream<R>(&self, reader: R) -> crate::runtime::stream_manager::StreamId
+    where
+        R: tokio::io::AsyncRead + Send + Unpin + 'static,
+    {
+        self.runtime.create_reader_stream(reader)
+    }
+
+    /// Answer a fetch event from Rust (see `Runtime::respond_with`)
+    pub fn respond_with(&self, response: HttpResponse) {
+        self.runtime.respond_with(response);
+    }
+
+    /// Send a response set with `respond_with` in place of the handler's
+    fn send_host_response(
+        res_tx: tokio::sync::oneshot::Sender<HttpResponse>,
+        response: HttpResponse,
+    ) -> HttpResponse {
+        let result = HttpResponse {
+            status: response.status,
+            headers: response.headers.clone(),
+            body: ResponseBody::None,
+        };
+        let _ = res_tx.send(response);
+        result
+    }
 }
 
 impl Worker {
@@ -37,11 +219,46 @@ impl Worker {
     /// All operations (fetch, log, etc.) go through the runner's OperationsHandler.
     pub async fn new_with_ops(
         script: Script,
-        _limits: Option<RuntimeLimits>,
+        limits: Option<RuntimeLimits>,
+        ops: OperationsHandle,
+    ) -> Result<Self, TerminationReason> {
+        Self::new_with_policy(script, limits, ops, FetchPolicy::default()).await
+    }
+
+    /// Create a new worker whose outbound fetches are checked against a FetchPolicy
+    pub async fn new_with_policy(
+        script: Script,
+        limits: Option<RuntimeLimits>,
         ops: OperationsHandle,
+        policy: FetchPolicy,
     ) -> Result<Self, TerminationReason> {
+        let options = WorkerOptions::new().fetch_policy(policy);
+        Self::new_with_options(script, limits, ops, options).await
+    }
+
+    /// Create a new worker with embedder options
+    pub async fn new_with_options(
+        script: Script,
+        limits: Option<RuntimeLimits>,
+        ops: OperationsHandle,
+        options: WorkerOptions,
+    ) -> Result<Self, TerminationReason> {
+        let response_stream_buffer_size = options
+            .response_stream_buffer_size
+            .unwrap_or(DEFAULT_RESPONSE_STREAM_BUFFER_SIZE);
+        if response_stream_buffer_size == 0 {
+            return Err(TerminationReason::Other(
+                "response_stream_buffer_size must be at least 1".to_string(),
+            ));
+        }
+
         let (mut runtime, scheduler_rx, callback_tx, stream_manager) = Runtime::new();
 
+        // Expose the heap limit through performance.memory
+        if let Some(limits) = &limits {
+            runtime.set_heap_limit(Some(limits.heap_max_mb as u64 * 1024 * 1024));
+        }
+
         // Setup addEventListener binding
         setup_event_listener(&mut runtime.context, runtime.fetch_response_tx.clone());
 
@@ -49,7 +266,46 @@ impl Worker {
         setup_env(&mut runtime.context, &script.env);
 
         // Setup console
-        crate::runtime::bindings::setup_console(&mut runtime.context);
+        let log_count = Arc::new(AtomicUsize::new(0));
+        crate::runtime::bindings::setup_console_with_state(
+            &mut runtime.context,
+            ConsoleState {
+                max_log_events: options.max_log_events,
+                max_depth: options.console_max_depth,
+                max_length: options.console_max_length,
+                log_count: log_count.clone(),
+                log_tx: options.log_tx,
+            },
+        );
+
+        // Setup the per-request subrequest cap (wraps fetch)
+        let subrequest_count = Arc::new(AtomicUsize::new(0));
+        crate::runtime::bindings::setup_subrequest_limit(
+            &mut runtime.context,
+            SubrequestState {
+                max_subrequests: options.max_subrequests,
+                count: subrequest_count.clone(),
+            },
+        );
+
+        // Setup the timer nesting cap (wraps the timer functions)
+        crate::runtime::bindings::setup_timer_nesting_limit(
+            &mut runtime.context,
+            options.max_timer_nesting,
+        );
+
+        if let Some(max) = options.max_stream_chunk_size {
+            runtime.stream_manager.set_max_chunk_size(Some(max));
+        }
+
+        if options.promise_bodies {
+            runtime
+                .context
+                .evaluate_script("Response._promiseBodies = true;", 1)
+                .map_err(|_| {
+                    TerminationReason::Exception("Failed to enable promise bodies".to_string())
+                })?;
+        }
 
         // TODO: Apply runtime limits
 
@@ -58,8 +314,12 @@ impl Worker {
             TerminationReason::Exception("Only JavaScript code is supported".to_string())
         })?;
 
+        // Module workers (`export default { fetch }`) are evaluated as classic
+        // scripts with the default export captured on globalThis
+        let js_code = module_to_script(js_code);
+
         // Load and evaluate the worker script
-        runtime.evaluate(js_code).map_err(|e| {
+        runtime.evaluate(&js_code).map_err(|e| {
             if let Ok(err_str) = e.to_js_string(&runtime.context) {
                 TerminationReason::Exception(format!("Script evaluation failed: {}", err_str))
             } else {
@@ -67,15 +327,53 @@ impl Worker {
             }
         })?;
 
+        // Prefer the module's fetch handler over addEventListener when present
+        runtime
+            .context
+            .evaluate_script("globalThis.__installDefaultExport();", 1)
+            .map_err(|_| {
+                TerminationReason::Exception("Failed to install module handlers".to_string())
+            })?;
+
+        // Fetches go through the built-in client when one is configured; it
+        // checks private addresses at resolution time, against DNS rebinding
+        let ops: OperationsHandle = match &options.fetch_client {
+            Some(config) => {
+                let mut config = config.clone();
+                if options.fetch_policy.block_private_ips {
+                    config = config.block_private_ips();
+                }
+
+                Arc::new(FetchClient::new(&config).map_err(|e| {
+                    TerminationReason::Other(format!("Invalid fetch client: {}", e))
+                })?)
+            }
+            None => ops,
+        };
+
         // Start event loop in background
+        let wall_time = options.wall_time;
+        let propagate_headers = options.propagate_headers;
+        let policy = options.fetch_policy;
         let event_loop_handle = tokio::spawn(async move {
-            run_event_loop(scheduler_rx, callback_tx, stream_manager, ops).await;
+            run_event_loop_with_policy(scheduler_rx, callback_tx, stream_manager, ops, policy)
+                .await;
         });
 
+        // Logs and fetches issued while loading the script don't count against
+        // the first request
+        log_count.store(0, Ordering::SeqCst);
+        subrequest_count.store(0, Ordering::SeqCst);
+
         Ok(Self {
             runtime,
             event_loop_handle,
             aborted: Arc::new(AtomicBool::new(false)),
+            log_count,
+            subrequest_count,
+            wall_time,
+            response_stream_buffer_size,
+            propagate_headers,
         })
     }
 
@@ -104,6 +402,8 @@ impl Worker {
             return Err(TerminationReason::Aborted);
         }
 
+        self.runtime.record_activity();
+
         match event {
             Event::Fetch(ref mut init) => {
                 let fetch_init = init.take().ok_or(TerminationReason::Other(
@@ -124,6 +424,8 @@ impl Worker {
 
     /// Execute an event and return the HTTP response directly
     pub async fn exec_http(&mut self, mut event: Event) -> Result<HttpResponse, TerminationReason> {
+        self.runtime.record_activity();
+
         match event {
             Event::Fetch(ref mut init) => {
                 let fetch_init = init.take().ok_or(TerminationReason::Other(
@@ -151,8 +453,31 @@ impl Worker {
         &mut self,
         fetch_init: openworkers_core::FetchInit,
     ) -> Result<HttpResponse, TerminationReason> {
+        let deadline = self.deadline();
+
+        // Reset the per-request console and subrequest caps
+        self.log_count.store(0, Ordering::SeqCst);
+        self.subrequest_count.store(0, Ordering::SeqCst);
+
+        // Short-circuited from Rust before the event: the handler never runs
+        if let Some(response) = self.runtime.host_response.lock().unwrap().take() {
+            return Ok(Self::send_host_response(fetch_init.res_tx, response));
+        }
+
         let req = &fetch_init.req;
 
+        // Trace headers this request passes on to its subrequests
+        *self.runtime.propagated_headers.lock().unwrap() = req
+            .headers
+            .iter()
+            .filter(|(name, _)| {
+                self.propagate_headers
+                    .iter()
+                    .any(|propagated| propagated.eq_ignore_ascii_case(name))
+            })
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+
         // Build headers object for JS
         let headers_json = serde_json::to_string(&req.headers).unwrap_or("{}".to_string());
 
@@ -170,6 +495,7 @@ impl Worker {
                 headers: new Headers({}),
                 text: () => Promise.resolve("{}"),
                 json: () => Promise.resolve(JSON.parse("{}")),
+                get normalizedPathname() {{ return __normalizePathname(this.url); }},
             }})"#,
             req.method,
             req.url,
@@ -187,7 +513,7 @@ impl Worker {
             })?;
 
         // Call the fetch event trigger (set by addEventListener)
-        // The Response is stored in __lastResponse by the event handler
+        // The Response is stored in __fetchState.response by the event handler
         let trigger_script = r#"
             (function(request) {
                 if (typeof globalThis.__triggerFetch === 'function') {
@@ -208,6 +534,10 @@ impl Worker {
             .to_object(&self.runtime.context)
             .map_err(|_| TerminationReason::Exception("Trigger is not a function".to_string()))?;
 
+        // __resolveResponse fires this once __fetchState.response is set
+        let (response_tx, mut response_rx) = tokio::sync::oneshot::channel();
+        *self.runtime.fetch_response_tx.lock().unwrap() = Some(response_tx);
+
         let trigger_result =
             trigger_fn.call_as_function(&self.runtime.context, None, &[request_obj]);
 
@@ -232,49 +562,47 @@ impl Worker {
             return Err(TerminationReason::Exception(error_msg));
         }
 
-        // Wait for __lastResponse to be set with adaptive polling
-        // Fast polling for sync responses, timeout after ~5s for async handlers
-        for iteration in 0..500 {
-            self.runtime.process_callbacks();
-
-            // Check if __lastResponse is set
-            let check_script = r#"
-                (function() {
-                    const resp = globalThis.__lastResponse;
-                    if (resp && typeof resp === 'object' && resp.status !== undefined) {
-                        return true;
-                    }
-                    return false;
-                })()
-            "#;
-
-            if let Ok(result) = self.runtime.context.evaluate_script(check_script, 1) {
-                if result.to_bool(&self.runtime.context) {
+        // Wait for the response, running timer and fetch callbacks as they arrive
+        let response_deadline =
+            deadline.unwrap_or_else(|| tokio::time::Instant::now() + RESPONSE_TIMEOUT);
+
+        self.runtime.process_callbacks();
+        loop {
+            tokio::select! {
+                biased;
+                result = &mut response_rx => {
+                    result.map_err(|_| {
+                        TerminationReason::Exception("Fetch response channel closed".to_string())
+                    })?;
                     break;
                 }
+                alive = self.runtime.process_next_callbacks() => {
+                    if !alive {
+                        return Err(TerminationReason::Exception(
+                            "Event loop stopped before the response".to_string(),
+                        ));
+                    }
+                }
+                _ = tokio::time::sleep_until(response_deadline) => {
+                    self.runtime.fetch_response_tx.lock().unwrap().take();
+                    return Err(TerminationReason::WallClockTimeout);
+                }
             }
+        }
 
-            // Adaptive sleep: fast for first checks, slower later
-            let sleep_duration = if iteration < 10 {
-                tokio::time::Duration::from_micros(1)
-            } else if iteration < 110 {
-                tokio::time::Duration::from_millis(1)
-            } else {
-                tokio::time::Duration::from_millis(10)
-            };
-
-            tokio::time::sleep(sleep_duration).await;
-
-            if iteration == 499 {
-                return Err(TerminationReason::WallClockTimeout);
-            }
+        // Answered from Rust while the handler ran
+        let host_response = self.runtime.host_response.lock().unwrap().take();
+        if let Some(response) = host_response {
+            let result = Self::send_host_response(fetch_init.res_tx, response);
+            self.wait_for_wait_until(response_deadline).await;
+            return Ok(result);
         }
 
-        // Extract response metadata from __lastResponse
+        // Extract response metadata from __fetchState.response
         // All responses with body are now streamed via _responseStreamId
         let extract_script = r#"
             (function() {
-                const resp = globalThis.__lastResponse;
+                const resp = globalThis.__fetchState && globalThis.__fetchState.response;
                 if (!resp) {
                     return JSON.stringify({ error: 'No response' });
                 }
@@ -300,7 +628,8 @@ impl Worker {
                     status: resp.status || 200,
                     headers: headers,
                     responseStreamId: responseStreamId !== undefined ? responseStreamId : null,
-                    hasBody: resp.body !== null
+                    hasBody: resp.body !== null,
+                    isError: resp.type === 'error'
                 });
             })()
         "#;
@@ -327,38 +656,74 @@ impl Worker {
             response_stream_id: Option<u64>,
             #[serde(rename = "hasBody")]
             has_body: bool,
+            #[serde(rename = "isError")]
+            is_error: bool,
         }
 
-        let extracted: ExtractedResponse = serde_json::from_str(&json_str).map_err(|e| {
+        let mut extracted: ExtractedResponse = serde_json::from_str(&json_str).map_err(|e| {
             TerminationReason::Exception(format!("Failed to parse extracted response: {}", e))
         })?;
 
+        // Response.error() has status 0, which is not a valid HTTP status:
+        // surface it to the embedder as a 502 Bad Gateway with no body
+        if extracted.is_error {
+            log::warn!("Fetch handler responded with a network error, returning 502");
+            extracted.status = NETWORK_ERROR_STATUS;
+            extracted.headers.clear();
+        }
+
+        // Terminates the forwarded body if JS is still writing it when exec()
+        // gives up at the deadline: nothing would drive the JS pump afterwards
+        let (abort_tx, mut abort_rx) = tokio::sync::oneshot::channel::<String>();
+
         // All responses with body are now streamed
         let body = if let Some(stream_id) = extracted.response_stream_id {
             // Take the receiver from stream manager
             if let Some(rx) = self.runtime.stream_manager.take_receiver(stream_id) {
                 // Create bounded channel for HttpBody
-                const RESPONSE_STREAM_BUFFER_SIZE: usize = 16;
-                let (tx, response_rx) = tokio::sync::mpsc::channel(RESPONSE_STREAM_BUFFER_SIZE);
+                let (tx, response_rx) =
+                    tokio::sync::mpsc::channel(self.response_stream_buffer_size);
 
                 // Spawn task to forward from StreamChunk to Result<Bytes, String>
                 tokio::spawn(async move {
-                    let mut rx = rx;
-                    while let Some(chunk) = rx.recv().await {
-                        match chunk {
-                            StreamChunk::Data(bytes) => {
-                                if tx.send(Ok(bytes)).await.is_err() {
+                    let forward = async {
+                        let mut rx = rx;
+                        loop {
+                            let chunk = tokio::select! {
+                                biased;
+                                Ok(reason) = &mut abort_rx => Some(StreamChunk::Error(reason)),
+                                chunk = rx.recv() => chunk,
+                            };
+                            let Some(chunk) = chunk else {
+                                break;
+                            };
+
+                            match chunk {
+                                StreamChunk::Data(bytes) => {
+                                    if tx.send(Ok(bytes)).await.is_err() {
+                                        break;
+                                    }
+                                }
+                                StreamChunk::Done => {
+                                    break;
+                                }
+                                StreamChunk::Error(e) => {
+                                    let _ = tx.send(Err(e)).await;
                                     break;
                                 }
-                            }
-                            StreamChunk::Done => {
-                                break;
-                            }
-                            StreamChunk::Error(e) => {
-                                let _ = tx.send(Err(e)).await;
-                                break;
                             }
                         }
+                    };
+
+                    let Some(deadline) = deadline else {
+                        return forward.await;
+                    };
+
+                    // Dropping the JS-side receiver makes further writes fail,
+                    // which cancels the body stream
+                    if tokio::time::timeout_at(deadline, forward).await.is_err() {
+                        log::warn!("Response stream exceeded the worker wall time, terminating");
+                        let _ = tx.send(Err("Worker wall time exceeded".to_string())).await;
                     }
                 });
 
@@ -382,6 +747,28 @@ impl Worker {
             body,
         });
 
+        // Keep processing callbacks until waitUntil promises settle
+        self.wait_for_wait_until(response_deadline).await;
+
+        // Dropping the JS-side receiver also makes the pump's next write fail
+        if self.response_streams_pending() {
+            log::warn!("Response stream still running at the deadline, terminating");
+            let reason = if self.wall_time.is_some() {
+                "Worker wall time exceeded"
+            } else {
+                "Response stream did not end before the deadline"
+            };
+            let _ = abort_tx.send(reason.to_string());
+        }
+
+        // The handler threw: the 500 response is already sent, report why
+        if let Some(error) = self.take_fetch_error() {
+            return Err(TerminationReason::Exception(format!(
+                "Fetch handler exception: {}",
+                error
+            )));
+        }
+
         // Return response for exec_http (body already sent via channel)
         Ok(HttpResponse {
             status: extracted.status,
@@ -390,7 +777,105 @@ impl Worker {
         })
     }
 
+    /// Process callbacks until all event.waitUntil() promises have settled
+    /// and the event's streamed response body has been written
+    ///
+    /// Bounded by the response deadline (the wall time, or the default
+    /// response timeout); pending work past that is abandoned with a warning
+    /// (the response has already been sent) and a response body still being
+    /// written is terminated with an error.
+    async fn wait_for_wait_until(&mut self, deadline: tokio::time::Instant) {
+        let check_script = r#"
+            (function() {
+                const state = globalThis.__fetchState;
+                return !(state && (state.waitUntilPending || state.responseStreamsPending));
+            })()
+        "#;
+
+        loop {
+            self.runtime.process_callbacks();
+
+            if let Ok(result) = self.runtime.context.evaluate_script(check_script, 1) {
+                if result.to_bool(&self.runtime.context) {
+                    return;
+                }
+            }
+
+            tokio::select! {
+                alive = self.runtime.process_next_callbacks() => {
+                    if !alive {
+                        break;
+                    }
+                }
+                _ = tokio::time::sleep_until(deadline) => break,
+            }
+        }
+
+        log::warn!("waitUntil promises did not settle in time, abandoning");
+    }
+
+    /// Whether JS is still writing the current event's response body
+    fn response_streams_pending(&mut self) -> bool {
+        let script =
+            "!!(globalThis.__fetchState && globalThis.__fetchState.responseStreamsPending)";
+
+        self.runtime
+            .context
+            .evaluate_script(script, 1)
+            .is_ok_and(|result| result.to_bool(&self.runtime.context))
+    }
+
+    /// Error (message and stack) recorded when the fetch handler threw or its
+    /// response promise rejected
+    fn take_fetch_error(&mut self) -> Option<String> {
+        let script = r#"
+            (function() {
+                const state = globalThis.__fetchState;
+                const error = state && state.error;
+                if (!error) {
+                    return null;
+                }
+                state.error = null;
+                return JSON.stringify(error);
+            })()
+        "#;
+
+        let result = self.runtime.context.evaluate_script(script, 1).ok()?;
+        if !result.is_string(&self.runtime.context) {
+            return None;
+        }
+        let json = result.to_js_string(&self.runtime.context).ok()?.to_string();
+
+        #[derive(serde::Deserialize)]
+        struct FetchError {
+            message: String,
+            stack: String,
+        }
+
+        let error: FetchError = serde_json::from_str(&json).ok()?;
+        if error.stack.is_empty() {
+            Some(error.message)
+        } else {
+            Some(format!("{}\n{}", error.message, error.stack))
+        }
+    }
+
+    /// Deadline of an event dispatched now (see WorkerOptions::wall_time)
+    fn deadline(&self) -> Option<tokio::time::Instant> {
+        self.wall_time
+            .map(|wall_time| tokio::time::Instant::now() + wall_time)
+    }
+
     async fn trigger_task_event(&mut self, task_init: TaskInit) -> Result<(), TerminationReason> {
+        let deadline = self.deadline();
+
+        // Reset the per-request console and subrequest caps
+        self.log_count.store(0, Ordering::SeqCst);
+        self.subrequest_count.store(0, Ordering::SeqCst);
+
+        // Tasks have no incoming request to propagate headers from
+        self.runtime.propagated_headers.lock().unwrap().clear();
+
         // Extract scheduled time if this is a schedule-triggered task
         let scheduled_time = match &task_init.source {
             Some(TaskSource::Schedule { time }) => Some(*time),
@@ -408,17 +893,25 @@ impl Worker {
             .map(|t| t.to_string())
             .unwrap_or_else(|| "undefined".to_string());
 
+        // TaskSource::Schedule carries no cron expression, so schedulers pass
+        // it as a `cron` string in the payload
         let event_script = format!(
-            r#"({{
-                taskId: "{}",
-                attempt: {},
-                payload: {},
-                scheduledTime: {}
-            }})"#,
+            r#"(function(payload) {{
+                return {{
+                    taskId: "{}",
+                    attempt: {},
+                    payload: payload,
+                    scheduledTime: {},
+                    cron: {} && payload && typeof payload.cron === 'string'
+                        ? payload.cron
+                        : undefined
+                }};
+            }})({})"#,
             task_init.task_id.replace('"', "\\\""),
             task_init.attempt,
-            payload_json,
-            scheduled_time_js
+            scheduled_time_js,
+            scheduled_time.is_some(),
+            payload_json
         );
 
         let event_obj = self
@@ -496,7 +989,7 @@ impl Worker {
 
             tokio::time::sleep(sleep_duration).await;
 
-            if iteration == 499 {
+            if iteration == 499 || deadline.is_some_and(|d| tokio::time::Instant::now() >= d) {
                 return Err(TerminationReason::WallClockTimeout);
             }
         }
@@ -561,29 +1054,19 @@ impl Drop for Worker {
 /// Setup addEventListener binding
 fn setup_event_listener(
     context: &mut rusty_jsc::JSContext,
-    fetch_response_tx: std::sync::Arc<
-        std::sync::Mutex<Option<tokio::sync::oneshot::Sender<String>>>,
-    >,
+    fetch_response_tx: std::sync::Arc<std::sync::Mutex<Option<tokio::sync::oneshot::Sender<()>>>>,
 ) {
-    // Setup native __sendFetchResponse function
+    // Setup native __resolveResponse function: wakes trigger_fetch_event once
+    // __fetchState.response is set
     let fetch_tx_clone = fetch_response_tx.clone();
-    let send_response_callback = rusty_jsc::callback_closure!(
+    let resolve_response_callback = rusty_jsc::callback_closure!(
         context,
         move |ctx: rusty_jsc::JSContext,
               _function: rusty_jsc::JSObject,
               _this: rusty_jsc::JSObject,
-              args: &[rusty_jsc::JSValue]| {
-            if args.is_empty() {
-                return Ok(rusty_jsc::JSValue::undefined(&ctx));
-            }
-
-            if let Ok(response_json) = args[0].to_js_string(&ctx) {
-                let response_str = response_json.to_string();
-
-                // Send the response through the channel
-                if let Some(tx) = fetch_tx_clone.lock().unwrap().take() {
-                    let _ = tx.send(response_str);
-                }
+              _args: &[rusty_jsc::JSValue]| {
+            if let Some(tx) = fetch_tx_clone.lock().unwrap().take() {
+                let _ = tx.send(());
             }
 
             Ok(rusty_jsc::JSValue::undefined(&ctx))
@@ -594,14 +1077,14 @@ fn setup_event_listener(
         .get_global_object()
         .set_property(
             context,
-            "__sendFetchResponse",
-            send_response_callback.into(),
+            "__resolveResponse",
+            resolve_response_callback.into(),
         )
         .unwrap();
 
     let add_event_listener_script = r#"
-        // Stream all response bodies to Rust
-        globalThis.__streamResponseBody = async function(response) {
+        // Stream all response bodies to Rust; `state` is the fetch event's
+        globalThis.__streamResponseBody = async function(response, state) {
             if (!response || !response.body) {
                 // No body to stream
                 return response;
@@ -617,145 +1100,393 @@ fn setup_event_listener(
             const streamId = __responseStreamCreate();
             response._responseStreamId = streamId;
 
-            // Start streaming asynchronously
+            // String bodies are already UTF-8 encoded: write them in one go
+            const stringBody = response._untouchedStringBody ? response._untouchedStringBody() : null;
+            if (stringBody) {
+                response.body._disturbed = true;
+                if (!__responseStreamWrite(streamId, stringBody.bytes)) {
+                    console.error('[__streamResponseBody] Failed to write chunk');
+                }
+                __responseStreamEnd(streamId);
+                return response;
+            }
+
+            // Resolves once the response buffer has room (backpressure): the
+            // event loop wakes the writer when the embedder reads
+            const whenReady = () => {
+                if (__responseStreamReady(streamId)) {
+                    return Promise.resolve();
+                }
+                return new Promise(resolve => __responseStreamWhenReady(streamId, resolve));
+            };
+
+            // Start streaming asynchronously (the worker keeps processing
+            // callbacks until the event's response stream has ended)
+            state.responseStreamsPending++;
             (async () => {
                 try {
                     const reader = response.body.getReader();
                     while (true) {
                         const { done, value } = await reader.read();
                         if (done) {
-                            __responseStreamEnd(streamId);
                             break;
                         }
-                        if (value) {
-                            __responseStreamWrite(streamId, value);
+                        if (!value) {
+                            continue;
+                        }
+
+                        // Strings and Uint8Arrays are written as-is; other views and
+                        // ArrayBuffers are wrapped without copying
+                        let chunk = value;
+                        if (value instanceof ArrayBuffer) {
+                            chunk = new Uint8Array(value);
+                        } else if (ArrayBuffer.isView(value) && !(value instanceof Uint8Array)) {
+                            chunk = new Uint8Array(value.buffer, value.byteOffset, value.byteLength);
+                        }
+
+                        await whenReady();
+
+                        if (!__responseStreamWrite(streamId, chunk)) {
+                            console.error('[__streamResponseBody] Failed to write chunk');
+                            reader.cancel();
+                            break;
                         }
                     }
                 } catch (e) {
                     console.error('[__streamResponseBody] Error:', e);
+                } finally {
+                    // The end marker also needs a free slot
+                    await whenReady();
                     __responseStreamEnd(streamId);
+                    state.responseStreamsPending--;
                 }
             })();
 
             return response;
         };
 
-        globalThis.addEventListener = function(type, handler) {
-            if (type === 'fetch') {
-                globalThis.__fetchHandler = handler;
-                globalThis.__triggerFetch = function(request) {
-                    // Reset last response
-                    globalThis.__lastResponse = null;
-
-                    const event = {
-                        request: request,
-                        respondWith: function(responseOrPromise) {
-                            // Handle both direct Response and Promise<Response>
-                            if (responseOrPromise && typeof responseOrPromise.then === 'function') {
-                                // It's a Promise, wait for it to resolve then stream
-                                responseOrPromise
-                                    .then(response => __streamResponseBody(response))
-                                    .then(response => {
-                                        globalThis.__lastResponse = response;
-                                    })
-                                    .catch(error => {
-                                        console.error('[respondWith] Promise rejected:', error);
-                                        globalThis.__lastResponse = new Response(null, { status: 500 });
-                                    });
-                            } else {
-                                // Direct Response object - stream it
-                                __streamResponseBody(responseOrPromise)
-                                    .then(response => {
-                                        globalThis.__lastResponse = response;
-                                    });
-                            }
-                        }
-                    };
+        // Registered listeners per event type, invoked in registration order
+        globalThis.__eventListeners = { fetch: [], scheduled: [], task: [] };
+
+        // Fresh state for each fetch event: promises from an earlier request
+        // settle into that request's state, never into the current one
+        const __beginFetch = function() {
+            const state = { response: null, waitUntilPending: 0, responseStreamsPending: 0 };
+            globalThis.__fetchState = state;
+            return state;
+        };
+
+        // Set the response of a fetch event; only the current event wakes the host
+        const __settleFetch = function(state, response) {
+            state.response = response;
+            if (globalThis.__fetchState === state) {
+                __resolveResponse(response);
+            }
+        };
+
+        // Remember why the runtime answered with a 500, so exec() can report it
+        const __recordFetchError = function(state, error) {
+            if (state.error) {
+                return;
+            }
+            const message = error instanceof Error ? `${error.name}: ${error.message}` : String(error);
+            const stack = error && error.stack ? String(error.stack) : '';
+            state.error = { message, stack };
+        };
+
+        // Keep the worker alive until the promise settles
+        const __trackWaitUntil = function(state, promise) {
+            state.waitUntilPending++;
+            Promise.resolve(promise)
+                .catch(error => {
+                    console.error('[waitUntil] Promise rejected:', error);
+                })
+                .finally(() => {
+                    state.waitUntilPending--;
+                });
+        };
 
-                    // Call handler synchronously
-                    try {
-                        handler(event);
-                    } catch (error) {
-                        console.error('[addEventListener] Error in fetch handler:', error);
-                        globalThis.__lastResponse = new Response(null, { status: 500 });
+        const __dispatchFetch = function(request) {
+            const handlers = globalThis.__eventListeners.fetch.slice();
+            if (handlers.length === 0) {
+                throw new Error("No fetch handler registered");
+            }
+
+            const state = __beginFetch();
+
+            // Only the first respondWith call is honored
+            let responded = false;
+
+            const event = {
+                request: request,
+                waitUntil: function(promise) {
+                    __trackWaitUntil(state, promise);
+                },
+                respondWith: function(responseOrPromise) {
+                    if (responded) {
+                        console.warn('[respondWith] Response already provided, ignoring');
+                        return;
                     }
-                };
-            } else if (type === 'scheduled') {
-                globalThis.__triggerScheduled = async function(event) {
-                    globalThis.__requestComplete = false;
-                    const promises = [];
+                    responded = true;
+
+                    // Handle both direct Response and Promise<Response>
+                    if (responseOrPromise && typeof responseOrPromise.then === 'function') {
+                        // It's a Promise, wait for it to resolve then stream
+                        responseOrPromise
+                            .then(response => __streamResponseBody(response, state))
+                            .then(response => {
+                                __settleFetch(state, response);
+                            })
+                            .catch(error => {
+                                console.error('[respondWith] Promise rejected:', error);
+                                __recordFetchError(state, error);
+                                __settleFetch(state, new Response(null, { status: 500 }));
+                            });
+                    } else {
+                        // Direct Response object - stream it
+                        __streamResponseBody(responseOrPromise, state)
+                            .then(response => {
+                                __settleFetch(state, response);
+                            });
+                    }
+                }
+            };
 
-                    event.waitUntil = function(promise) {
-                        promises.push(Promise.resolve(promise));
+            // Call every handler synchronously; later ones still run for side effects
+            let handlerFailed = false;
+            let handlerError;
+            for (const handler of handlers) {
+                try {
+                    handler(event);
+                } catch (error) {
+                    console.error('[addEventListener] Error in fetch handler:', error);
+                    if (!handlerFailed) {
+                        handlerError = error;
+                    }
+                    handlerFailed = true;
+                }
+            }
+
+            if (handlerFailed && !responded) {
+                responded = true;
+                __recordFetchError(state, handlerError);
+                __settleFetch(state, new Response(null, { status: 500 }));
+            }
+        };
+
+        const __dispatchScheduled = async function(event) {
+            globalThis.__requestComplete = false;
+            const promises = [];
+
+            event.waitUntil = function(promise) {
+                promises.push(Promise.resolve(promise));
+            };
+
+            try {
+                // Call every handler, then wait for them and their waitUntil promises
+                const handlers = globalThis.__eventListeners.scheduled.slice();
+                await Promise.all(handlers.map(handler => handler(event)));
+
+                if (promises.length > 0) {
+                    await Promise.all(promises);
+                }
+            } finally {
+                globalThis.__requestComplete = true;
+            }
+        };
+
+        const __dispatchTask = async function(event) {
+            globalThis.__requestComplete = false;
+            const waitUntilPromises = [];
+
+            // Default result (success with no data); the first result provided wins
+            globalThis.__taskResult = { success: true };
+            let responded = false;
+
+            const toTaskResult = function(result) {
+                if (result && typeof result === 'object' && 'success' in result) {
+                    return {
+                        success: result.success !== false,
+                        data: result.data,
+                        error: result.error
                     };
+                }
+                return { success: true, data: result };
+            };
 
-                    try {
-                        // Call handler
-                        await handler(event);
+            event.waitUntil = function(promise) {
+                waitUntilPromises.push(Promise.resolve(promise));
+            };
 
-                        // Wait for all promises
-                        if (promises.length > 0) {
-                            await Promise.all(promises);
-                        }
-                    } finally {
-                        globalThis.__requestComplete = true;
-                    }
+            event.respondWith = function(result) {
+                if (responded) {
+                    return;
+                }
+                responded = true;
+
+                if (result && typeof result === 'object') {
+                    globalThis.__taskResult = {
+                        success: result.success !== false,
+                        data: result.data,
+                        error: result.error
+                    };
+                } else {
+                    globalThis.__taskResult = { success: true, data: result };
+                }
+            };
+
+            try {
+                const handlers = globalThis.__eventListeners.task.slice();
+                const results = await Promise.all(handlers.map(handler => handler(event)));
+
+                // If a handler returns a value and respondWith wasn't called, use it
+                const returned = results.find(result => result !== undefined);
+                if (!responded && returned !== undefined) {
+                    responded = true;
+                    globalThis.__taskResult = toTaskResult(returned);
+                }
+
+                // Wait for all waitUntil promises to complete
+                if (waitUntilPromises.length > 0) {
+                    await Promise.all(waitUntilPromises);
+                }
+            } catch (error) {
+                globalThis.__taskResult = {
+                    success: false,
+                    error: error.message || String(error)
                 };
+            } finally {
+                globalThis.__requestComplete = true;
+            }
+        };
+
+        // Other event types go through a plain EventTarget, so workers can
+        // use addEventListener/dispatchEvent for their own events
+        const __globalEventTarget = new EventTarget();
+
+        globalThis.addEventListener = function(type, handler, options) {
+            const listeners = globalThis.__eventListeners[type];
+            if (!listeners) {
+                __globalEventTarget.addEventListener(type, handler, options);
+                return;
+            }
+            if (typeof handler !== 'function') {
+                return;
+            }
+
+            // Registering the same handler twice is a no-op
+            if (listeners.includes(handler)) {
+                return;
+            }
+            listeners.push(handler);
+
+            // Install the dispatcher the runtime looks for
+            if (type === 'fetch') {
+                globalThis.__triggerFetch = __dispatchFetch;
+            } else if (type === 'scheduled') {
+                globalThis.__triggerScheduled = __dispatchScheduled;
             } else if (type === 'task') {
-                globalThis.__taskHandler = async function(event) {
-                    globalThis.__requestComplete = false;
-                    const waitUntilPromises = [];
+                globalThis.__taskHandler = __dispatchTask;
+            }
+        };
 
-                    // Default result (success with no data)
-                    globalThis.__taskResult = { success: true };
+        // Module worker entrypoint: `export default { fetch(request, env, ctx) }`
+        const __dispatchModuleFetch = function(request) {
+            const exported = globalThis.__defaultExport;
 
-                    event.waitUntil = function(promise) {
-                        waitUntilPromises.push(Promise.resolve(promise));
-                    };
+            const state = __beginFetch();
 
-                    event.respondWith = function(result) {
-                        if (result && typeof result === 'object') {
-                            globalThis.__taskResult = {
-                                success: result.success !== false,
-                                data: result.data,
-                                error: result.error
-                            };
-                        } else {
-                            globalThis.__taskResult = { success: true, data: result };
-                        }
-                    };
+            const ctx = {
+                waitUntil: function(promise) {
+                    __trackWaitUntil(state, promise);
+                },
+                passThroughOnException: function() {}
+            };
 
-                    try {
-                        const result = await handler(event);
-
-                        // If handler returns a value and respondWith wasn't called, use it
-                        if (result !== undefined && globalThis.__taskResult.data === undefined) {
-                            if (result && typeof result === 'object' && 'success' in result) {
-                                globalThis.__taskResult = {
-                                    success: result.success !== false,
-                                    data: result.data,
-                                    error: result.error
-                                };
-                            } else {
-                                globalThis.__taskResult = { success: true, data: result };
-                            }
-                        }
+            let result;
+            try {
+                result = exported.fetch(request, globalThis.env, ctx);
+            } catch (error) {
+                console.error('[default.fetch] Error in fetch handler:', error);
+                __recordFetchError(state, error);
+                __settleFetch(state, new Response(null, { status: 500 }));
+                return;
+            }
 
-                        // Wait for all waitUntil promises to complete
-                        if (waitUntilPromises.length > 0) {
-                            await Promise.all(waitUntilPromises);
-                        }
-                    } catch (error) {
-                        globalThis.__taskResult = {
-                            success: false,
-                            error: error.message || String(error)
-                        };
-                    } finally {
-                        globalThis.__requestComplete = true;
-                    }
+            Promise.resolve(result)
+                .then(response => __streamResponseBody(response, state))
+                .then(response => {
+                    __settleFetch(state, response);
+                })
+                .catch(error => {
+                    console.error('[default.fetch] Promise rejected:', error);
+                    __recordFetchError(state, error);
+                    __settleFetch(state, new Response(null, { status: 500 }));
+                });
+        };
+
+        // Module worker entrypoint: `export default { scheduled(event, env, ctx) }`
+        const __dispatchModuleScheduled = async function(event) {
+            const exported = globalThis.__defaultExport;
+            globalThis.__requestComplete = false;
+            globalThis.__taskResult = { success: true };
+            const promises = [];
+
+            const ctx = {
+                waitUntil: function(promise) {
+                    promises.push(Promise.resolve(promise));
+                }
+            };
+            event.waitUntil = ctx.waitUntil;
+
+            try {
+                await exported.scheduled(event, globalThis.env, ctx);
+
+                if (promises.length > 0) {
+                    await Promise.all(promises);
+                }
+            } catch (error) {
+                console.error('[default.scheduled] Error in scheduled handler:', error);
+                globalThis.__taskResult = {
+                    success: false,
+                    error: error.message || String(error)
                 };
+            } finally {
+                globalThis.__requestComplete = true;
             }
         };
+
+        // Called once after the worker script is evaluated
+        globalThis.__installDefaultExport = function() {
+            const exported = globalThis.__defaultExport;
+            if (!exported) {
+                return;
+            }
+
+            if (typeof exported.fetch === 'function') {
+                globalThis.__triggerFetch = __dispatchModuleFetch;
+            }
+            if (typeof exported.scheduled === 'function') {
+                globalThis.__triggerScheduled = __dispatchModuleScheduled;
+            }
+        };
+
+        globalThis.removeEventListener = function(type, handler, options) {
+            const listeners = globalThis.__eventListeners[type];
+            if (!listeners) {
+                __globalEventTarget.removeEventListener(type, handler, options);
+                return;
+            }
+
+            const index = listeners.indexOf(handler);
+            if (index !== -1) {
+                listeners.splice(index, 1);
+            }
+        };
+
+        globalThis.dispatchEvent = function(event) {
+            return __globalEventTarget.dispatchEvent(event);
+        };
     "#;
 
     context
@@ -0,0 +1,131 @@
+mod common;
+
+use common::TestRunner;
+use std::time::Duration;
+
+/// Evaluate an expression and return it as a string
+fn eval_string(runner: &mut TestRunner, script: &str) -> String {
+    runner
+        .runtime
+        .evaluate(script)
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string()
+}
+
+#[tokio::test]
+async fn test_abort_signal_timeout() {
+    let mut runner = TestRunner::new();
+
+    let script = r#"
+        globalThis.result = null;
+        const start = performance.now();
+        const signal = AbortSignal.timeout(20);
+        globalThis.initiallyAborted = signal.aborted;
+
+        signal.addEventListener('abort', () => {
+            globalThis.result = {
+                elapsed: performance.now() - start,
+                name: signal.reason.name,
+                aborted: signal.aborted
+            };
+        });
+
+        try {
+            AbortSignal.timeout(-1);
+            globalThis.negativeThrows = false;
+        } catch (e) {
+            globalThis.negativeThrows = e instanceof TypeError;
+        }
+    "#;
+
+    runner.execute(script).expect("Script should execute");
+    assert_eq!(
+        eval_string(&mut runner, "String(initiallyAborted)"),
+        "false"
+    );
+
+    runner.process_for(Duration::from_millis(100)).await;
+
+    assert_eq!(
+        eval_string(
+            &mut runner,
+            "`${result.name} ${result.aborted} ${negativeThrows}`"
+        ),
+        "TimeoutError true true"
+    );
+    let elapsed: f64 = eval_string(&mut runner, "String(result.elapsed)")
+        .parse()
+        .unwrap();
+    assert!(elapsed >= 19.0, "abort fired after {}ms", elapsed);
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_abort_signal_any() {
+    let mut runner = TestRunner::new();
+
+    let script = r#"
+        const a = new AbortController();
+        const b = new AbortController();
+        const combined = AbortSignal.any([a.signal, b.signal]);
+
+        const events = [];
+        combined.addEventListener('abort', () => events.push(combined.reason));
+
+        const before = combined.aborted;
+        b.abort('b reason');
+        a.abort('a reason');
+
+        const preAborted = AbortSignal.any([new AbortController().signal, AbortSignal.abort('early')]);
+
+        globalThis.result = [
+            before,
+            combined.aborted,
+            combined.reason,
+            events.join(','),
+            preAborted.aborted,
+            preAborted.reason
+        ].join('|');
+    "#;
+
+    runner.execute(script).expect("Script should execute");
+
+    assert_eq!(
+        eval_string(&mut runner, "globalThis.result"),
+        "false|true|b reason|b reason|true|early"
+    );
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_abort_signal_is_event_target() {
+    let mut runner = TestRunner::new();
+
+    let script = r#"
+        const controller = new AbortController();
+        const signal = controller.signal;
+        const calls = [];
+
+        signal.onabort = (event) => calls.push('onabort:' + (event.target === signal));
+        signal.addEventListener('abort', { handleEvent: (event) => calls.push('object:' + (event instanceof Event)) });
+        signal.addEventListener('abort', () => calls.push('once'), { once: true });
+
+        controller.abort();
+        controller.abort();
+
+        globalThis.result = [signal instanceof EventTarget, calls.join(',')].join('|');
+    "#;
+
+    runner.execute(script).expect("Script should execute");
+
+    assert_eq!(
+        eval_string(&mut runner, "globalThis.result"),
+        "true|onabort:true,object:true,once"
+    );
+
+    runner.shutdown().await;
+}
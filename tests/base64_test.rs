@@ -114,3 +114,297 @@ async fn test_base64_roundtrip() {
     let body = response.body.collect().await.expect("Should have body");
     assert_eq!(String::from_utf8_lossy(&body), "OK");
 }
+
+#[tokio::test]
+async fn test_btoa_rejects_non_latin1() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            let result;
+            try {
+                btoa('\u0100');
+                result = 'FAIL: no error thrown';
+            } catch (e) {
+                result = e.name === 'InvalidCharacterError' ? 'OK' : `FAIL: ${e.name}`;
+            }
+            if (result === 'OK' && btoa('\u00ff') !== '/w==') {
+                result = `FAIL: ${btoa('\u00ff')}`;
+            }
+            event.respondWith(new Response(result));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+#[tokio::test]
+async fn test_atob_rejects_invalid_base64() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const inputs = ['not base64!!', 'abcde', 'ab=c', 'abc==='];
+            let result = 'OK';
+            for (const input of inputs) {
+                try {
+                    atob(input);
+                    result = `FAIL: no error for "${input}"`;
+                    break;
+                } catch (e) {
+                    if (e.name !== 'InvalidCharacterError') {
+                        result = `FAIL: ${e.name} for "${input}"`;
+                        break;
+                    }
+                }
+            }
+            event.respondWith(new Response(result));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+#[tokio::test]
+async fn test_atob_binary_string() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const checks = [
+                atob(btoa('hello')) === 'hello',
+                atob('/w==') === '\u00ff',
+                atob('aGVsbG8') === 'hello',
+                atob(' aGVs\nbG8= ') === 'hello',
+                atob(btoa('\u0000\u0080\u00ff')) === '\u0000\u0080\u00ff'
+            ];
+            const failed = checks.indexOf(false);
+            event.respondWith(new Response(failed === -1 ? 'OK' : `FAIL: check ${failed}`));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+#[tokio::test]
+async fn test_uint8array_base64_roundtrip() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const bytes = new Uint8Array(256);
+            for (let i = 0; i < bytes.length; i++) bytes[i] = i;
+
+            const same = (a, b) => a.length === b.length && a.every((v, i) => v === b[i]);
+            const sample = new Uint8Array([0xfb, 0xff, 0x00, 0x10, 0x7f]);
+
+            const checks = [
+                same(Uint8Array.fromBase64(bytes.toBase64()), bytes),
+                sample.toBase64() === '+/8AEH8=',
+                sample.toBase64({ alphabet: 'base64url' }) === '-_8AEH8=',
+                sample.toBase64({ alphabet: 'base64url', omitPadding: true }) === '-_8AEH8',
+                same(Uint8Array.fromBase64('-_8AEH8', { alphabet: 'base64url' }), sample),
+                same(Uint8Array.fromBase64(bytes.toBase64({ alphabet: 'base64url' }), { alphabet: 'base64url' }), bytes),
+                same(Uint8Array.fromBase64(' +/8A\nEH8= '), sample),
+                same(bytes.subarray(10, 13), Uint8Array.fromBase64(bytes.subarray(10, 13).toBase64()))
+            ];
+            const failed = checks.indexOf(false);
+            event.respondWith(new Response(failed === -1 ? 'OK' : `FAIL: check ${failed}`));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+#[tokio::test]
+async fn test_uint8array_from_base64_errors() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const throws = (fn, name) => {
+                try { fn(); return false; } catch (e) { return e.name === name; }
+            };
+
+            const checks = [
+                throws(() => Uint8Array.fromBase64('not base64!!'), 'SyntaxError'),
+                throws(() => Uint8Array.fromBase64('-_8A', { alphabet: 'base64' }), 'SyntaxError'),
+                throws(() => Uint8Array.fromBase64('aGk', { lastChunkHandling: 'strict' }), 'SyntaxError'),
+                throws(() => Uint8Array.fromBase64('aGk', { alphabet: 'hex' }), 'TypeError'),
+                throws(() => Uint8Array.fromBase64(42), 'TypeError'),
+                Uint8Array.fromBase64('aGVsbG8', { lastChunkHandling: 'stop-before-partial' }).length === 3
+            ];
+            const failed = checks.indexOf(false);
+            event.respondWith(new Response(failed === -1 ? 'OK' : `FAIL: check ${failed}`));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+#[tokio::test]
+async fn test_uint8array_hex_roundtrip() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const bytes = new Uint8Array([0x00, 0x01, 0x7f, 0x80, 0xab, 0xff]);
+            const same = (a, b) => a.length === b.length && a.every((v, i) => v === b[i]);
+            const throws = (fn) => {
+                try { fn(); return false; } catch (e) { return e.name === 'SyntaxError'; }
+            };
+
+            const checks = [
+                bytes.toHex() === '00017f80abff',
+                same(Uint8Array.fromHex('00017F80ABff'), bytes),
+                same(Uint8Array.fromHex(bytes.toHex()), bytes),
+                new Uint8Array(0).toHex() === '',
+                throws(() => Uint8Array.fromHex('abc')),
+                throws(() => Uint8Array.fromHex('zz'))
+            ];
+            const failed = checks.indexOf(false);
+            event.respondWith(new Response(failed === -1 ? 'OK' : `FAIL: check ${failed}`));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+#[tokio::test]
+async fn test_bytes_base64_helpers_roundtrip() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const bytes = new Uint8Array(512);
+            for (let i = 0; i < bytes.length; i++) bytes[i] = (i * 7) & 0xff;
+            bytes[0] = 0x00;
+            bytes[1] = 0xff;
+            bytes[511] = 0x00;
+
+            const same = (a, b) => a.length === b.length && a.every((v, i) => v === b[i]);
+
+            const standard = bytesToBase64(bytes);
+            const url = bytesToBase64(bytes, { url: true });
+
+            const checks = [
+                same(base64ToBytes(standard), bytes),
+                same(base64ToBytes(url, { url: true }), bytes),
+                !/[+/=]/.test(url),
+                bytesToBase64(new Uint8Array([0xfb, 0xff]).buffer) === '+/8=',
+                bytesToBase64(new Uint8Array([0xfb, 0xff]), { url: true }) === '-_8',
+                same(base64ToBytes('-_8', { url: true }), [0xfb, 0xff]),
+                bytesToBase64(new Uint8Array(0)) === '' && base64ToBytes('').length === 0
+            ];
+            const failed = checks.indexOf(false);
+            event.respondWith(new Response(failed === -1 ? 'OK' : `FAIL: check ${failed}`));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
@@ -0,0 +1,152 @@
+use openworkers_core::{Event, HttpMethod, HttpRequest, RequestBody, Script};
+use openworkers_runtime_jsc::Worker;
+use std::collections::HashMap;
+
+#[tokio::test]
+async fn test_blob_basic() {
+    let script = r#"
+        addEventListener('fetch', async (event) => {
+            const blob = new Blob(['Hello, ', new Uint8Array([87, 111]), new Blob(['rld'])], { type: 'Text/Plain' });
+
+            const text = await blob.text();
+            const buffer = await blob.arrayBuffer();
+
+            const result = blob.size === 12
+                && blob.type === 'text/plain'
+                && text === 'Hello, World'
+                && buffer instanceof ArrayBuffer && buffer.byteLength === 12
+                ? 'OK' : `FAIL: size=${blob.size} type=${blob.type} text=${text}`;
+
+            event.respondWith(new Response(result));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+#[tokio::test]
+async fn test_blob_slice() {
+    let script = r#"
+        addEventListener('fetch', async (event) => {
+            const blob = new Blob(['Hello, World']);
+
+            const middle = blob.slice(7, 12, 'text/plain');
+            const tail = blob.slice(-5);
+            const empty = blob.slice(8, 2);
+
+            const result = await middle.text() === 'World'
+                && middle.type === 'text/plain'
+                && await tail.text() === 'World'
+                && empty.size === 0
+                && await blob.slice().text() === 'Hello, World'
+                ? 'OK' : 'FAIL';
+
+            event.respondWith(new Response(result));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+#[tokio::test]
+async fn test_blob_stream() {
+    let script = r#"
+        addEventListener('fetch', async (event) => {
+            const reader = new Blob(['OK']).stream().getReader();
+            const { value } = await reader.read();
+            const { done } = await reader.read();
+
+            const result = new TextDecoder().decode(value) === 'OK' && done ? 'OK' : 'FAIL';
+            event.respondWith(new Response(result));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+#[tokio::test]
+async fn test_response_from_blob() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const blob = new Blob(['{"ok":true}'], { type: 'application/json' });
+            event.respondWith(new Response(blob));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let content_type = response
+        .headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("content-type"))
+        .map(|(_, v)| v.clone());
+    assert_eq!(content_type.as_deref(), Some("application/json"));
+
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), r#"{"ok":true}"#);
+}
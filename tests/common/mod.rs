@@ -1,4 +1,8 @@
-use openworkers_runtime_jsc::{DefaultOps, OperationsHandle, Runtime, run_event_loop};
+use openworkers_runtime_jsc::{
+    DefaultOps, FetchPolicy, HttpRequest, HttpResponse, OpFuture, OperationsHandle,
+    OperationsHandler, RequestBody, ResponseBody, Runtime, run_event_loop_with_policy,
+};
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
@@ -17,11 +21,16 @@ impl TestRunner {
     }
 
     pub fn new_with_ops(ops: OperationsHandle) -> Self {
+        Self::new_with_policy(ops, FetchPolicy::default())
+    }
+
+    pub fn new_with_policy(ops: OperationsHandle, policy: FetchPolicy) -> Self {
         let (runtime, scheduler_rx, callback_tx, stream_manager) = Runtime::new();
 
         // Spawn event loop
         let event_loop_handle = tokio::spawn(async move {
-            run_event_loop(scheduler_rx, callback_tx, stream_manager, ops).await;
+            run_event_loop_with_policy(scheduler_rx, callback_tx, stream_manager, ops, policy)
+                .await;
         });
 
         Self {
@@ -70,3 +79,102 @@ impl TestRunner {
         }
     }
 }
+
+/// Loopback transport that echoes the request body back as a streamed
+/// response body, split into `chunk_size` pieces
+#[allow(dead_code)]
+pub struct EchoOps {
+    pub chunk_size: usize,
+}
+
+impl OperationsHandler for EchoOps {
+    fn handle_fetch(&self, request: HttpRequest) -> OpFuture<'_, Result<HttpResponse, String>> {
+        Box::pin(async move {
+            let body = match request.body {
+                RequestBody::Bytes(bytes) => bytes,
+                RequestBody::None => bytes::Bytes::new(),
+                RequestBody::Stream(_) => return Err("EchoOps expects a buffered body".into()),
+            };
+
+            let (tx, rx) = tokio::sync::mpsc::channel(4);
+            let chunk_size = self.chunk_size.max(1);
+
+            // Stream the echo back chunk by chunk, as a real upstream would
+            tokio::spawn(async move {
+                for chunk in body.chunks(chunk_size) {
+                    if tx
+                        .send(Ok(bytes::Bytes::copy_from_slice(chunk)))
+                        .await
+                        .is_err()
+                    {
+                        break;
+                    }
+                    tokio::task::yield_now().await;
+                }
+            });
+
+            Ok(HttpResponse {
+                status: 200,
+                headers: vec![(
+                    "content-type".to_string(),
+                    "application/octet-stream".to_string(),
+                )],
+                body: ResponseBody::Stream(rx),
+            })
+        })
+    }
+}
+
+/// Outbound fetch request captured by `RecordingOps`
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: HashMap<String, String>,
+    /// Buffered request body (None when the request had no body or a stream)
+    pub body: Option<bytes::Bytes>,
+}
+
+/// Transport wrapper that records every outbound fetch before passing it on
+/// to `inner`, so tests can assert which calls a worker made
+#[allow(dead_code)]
+pub struct RecordingOps {
+    inner: OperationsHandle,
+    requests: Mutex<Vec<RecordedRequest>>,
+}
+
+#[allow(dead_code)]
+impl RecordingOps {
+    pub fn new(inner: OperationsHandle) -> Arc<Self> {
+        Arc::new(Self {
+            inner,
+            requests: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Requests made so far, in the order they reached the transport
+    pub fn recorded_requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl OperationsHandler for RecordingOps {
+    fn handle_fetch(&self, request: HttpRequest) -> OpFuture<'_, Result<HttpResponse, String>> {
+        Box::pin(async move {
+            let body = match &request.body {
+                RequestBody::Bytes(bytes) => Some(bytes.clone()),
+                RequestBody::None | RequestBody::Stream(_) => None,
+            };
+
+            self.requests.lock().unwrap().push(RecordedRequest {
+                method: request.method.as_str().to_string(),
+                url: request.url.clone(),
+                headers: request.headers.clone(),
+                body,
+            });
+
+            self.inner.handle_fetch(request).await
+        })
+    }
+}
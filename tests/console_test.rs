@@ -1,6 +1,8 @@
 mod common;
 
 use common::TestRunner;
+use openworkers_runtime_jsc::runtime::bindings;
+use std::time::Duration;
 
 #[tokio::test]
 async fn test_console_log_basic() {
@@ -52,3 +54,151 @@ async fn test_console_log_special_values() {
     runner.execute(script).expect("Script should execute");
     runner.shutdown().await;
 }
+
+/// Install the runtime console and capture its output into globalThis.__logs
+fn runner_with_captured_console() -> TestRunner {
+    runner_with_console_state(bindings::ConsoleState::default())
+}
+
+/// Same as runner_with_captured_console, with custom console limits
+fn runner_with_console_state(state: bindings::ConsoleState) -> TestRunner {
+    let mut runner = TestRunner::new();
+    bindings::setup_console_with_state(&mut runner.runtime.context, state);
+
+    runner
+        .execute(
+            r#"
+            globalThis.__logs = [];
+            globalThis.__console_log = (level, msg) => globalThis.__logs.push([level, msg]);
+            "#,
+        )
+        .expect("Console capture should install");
+
+    runner
+}
+
+#[tokio::test]
+async fn test_console_count() {
+    let mut runner = runner_with_captured_console();
+
+    let script = r#"
+        console.count();
+        console.count('hits');
+        console.count('hits');
+        console.count();
+        console.countReset('hits');
+        console.count('hits');
+        globalThis.result = __logs.map(([_, msg]) => msg).join('|');
+    "#;
+
+    runner.execute(script).expect("Script should execute");
+
+    let result = runner.runtime.evaluate("globalThis.result").unwrap();
+    let result = result
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    assert_eq!(result, "default: 1|hits: 1|hits: 2|default: 2|hits: 1");
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_console_time_end() {
+    let mut runner = runner_with_captured_console();
+
+    let script = r#"
+        console.time('wait');
+        setTimeout(() => {
+            console.timeEnd('wait');
+        }, 30);
+    "#;
+
+    runner.execute(script).expect("Script should execute");
+    runner.process_for(Duration::from_millis(100)).await;
+
+    let result = runner
+        .runtime
+        .evaluate("__logs.length === 1 ? __logs[0][1] : ''")
+        .unwrap();
+    let result = result
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+
+    let elapsed: f64 = result
+        .strip_prefix("wait: ")
+        .and_then(|s| s.strip_suffix("ms"))
+        .and_then(|s| s.parse().ok())
+        .unwrap_or_else(|| panic!("Unexpected timeEnd output: {}", result));
+    assert!(
+        elapsed >= 29.0,
+        "Elapsed should be >= 30ms, got {}",
+        elapsed
+    );
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_console_time_end_unknown_label_warns() {
+    let mut runner = runner_with_captured_console();
+
+    runner
+        .execute("console.timeEnd('missing');")
+        .expect("Script should execute");
+
+    let result = runner
+        .runtime
+        .evaluate("__logs[0][0] === 1 && __logs[0][1].includes('missing')")
+        .unwrap();
+    assert!(result.to_bool(&runner.runtime.context));
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_console_truncates_deep_and_long_objects() {
+    let mut runner = runner_with_console_state(bindings::ConsoleState {
+        max_depth: Some(2),
+        max_length: Some(60),
+        ..Default::default()
+    });
+
+    let script = r#"
+        const deep = { a: { b: { c: { d: 1 } } }, list: [[[1]]] };
+        console.log(deep);
+
+        const cyclic = { name: 'loop' };
+        cyclic.self = cyclic;
+        console.log(cyclic);
+
+        console.log('x'.repeat(100));
+        console.log({ big: 1n });
+        globalThis.result = JSON.stringify(__logs.map(([_, msg]) => msg));
+    "#;
+
+    runner.execute(script).expect("Script should execute");
+
+    let result = runner.runtime.evaluate("globalThis.result").unwrap();
+    let result = result
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    let logs: Vec<String> = serde_json::from_str(&result).unwrap();
+
+    assert_eq!(
+        logs[0],
+        r#"{"a":{"b":{"c":"[Object]"}},"list":[["[Array]"]]}"#
+    );
+    assert_eq!(
+        logs[1],
+        r#"{"name":"loop","self":{"name":"loop","self":{"name":"loop","self":"[Object]"}}}"#[..60]
+            .to_string()
+            + "\u{2026}"
+    );
+    assert_eq!(logs[2], "x".repeat(60) + "\u{2026}");
+    assert_eq!(logs[3], r#"{"big":"1n"}"#);
+
+    runner.shutdown().await;
+}
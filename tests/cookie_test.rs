@@ -0,0 +1,93 @@
+use openworkers_core::{Event, HttpMethod, HttpRequest, RequestBody, Script};
+use openworkers_runtime_jsc::Worker;
+use std::collections::HashMap;
+
+/// Test parsing the incoming Cookie header into a Map
+#[tokio::test]
+async fn test_parse_cookies() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const cookies = parseCookies(event.request.headers.get('cookie'));
+            const quoted = parseCookies('token="abc"; a=first; a=second; broken; =empty');
+            const missing = parseCookies(null);
+
+            const ok = cookies.size === 2 && cookies.get('a') === '1' && cookies.get('b') === '2'
+                && quoted.get('token') === 'abc' && quoted.get('a') === 'first' && quoted.size === 2
+                && missing.size === 0;
+            event.respondWith(new Response(ok ? 'OK' : `FAIL: ${[...cookies]} ${[...quoted]}`));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::from([("cookie".to_string(), "a=1; b=2".to_string())]),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+/// Test building a Set-Cookie value with attributes
+#[tokio::test]
+async fn test_serialize_cookie() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const cookie = serializeCookie('session', 'abc123', {
+                path: '/',
+                maxAge: 3600,
+                httpOnly: true,
+                secure: true,
+                sameSite: 'lax'
+            });
+            const expected = 'session=abc123; Max-Age=3600; Path=/; HttpOnly; Secure; SameSite=Lax';
+
+            const expires = serializeCookie('a', '1', { expires: new Date(0) });
+
+            const errors = [
+                () => serializeCookie('bad name', 'x'),
+                () => serializeCookie('a', 'x;y'),
+                () => serializeCookie('a', 'x', { sameSite: 'sometimes' }),
+                () => serializeCookie('a', 'x', { path: '/;evil' })
+            ].map(f => { try { f(); return 'none'; } catch (e) { return e.name; } });
+
+            const headers = new Headers();
+            headers.append('Set-Cookie', cookie);
+
+            const ok = cookie === expected
+                && expires === 'a=1; Expires=Thu, 01 Jan 1970 00:00:00 GMT'
+                && errors.every(name => name === 'TypeError')
+                && headers.get('set-cookie') === expected;
+            event.respondWith(new Response(ok ? 'OK' : `FAIL: ${cookie} | ${expires} | ${errors}`));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
@@ -1,5 +1,5 @@
 use openworkers_core::{Event, HttpMethod, HttpRequest, RequestBody, Script};
-use openworkers_runtime_jsc::Worker;
+use openworkers_runtime_jsc::{Runtime, Worker};
 use std::collections::HashMap;
 
 /// Test crypto.getRandomValues
@@ -113,6 +113,117 @@ async fn test_digest_sha256() {
     assert_eq!(String::from_utf8_lossy(&body), "OK");
 }
 
+/// Test that byte views only contribute the bytes between their offset and length
+#[tokio::test]
+async fn test_digest_subarray_view() {
+    let script = r#"
+        addEventListener('fetch', async (event) => {
+            const toHex = (hash) => Array.from(new Uint8Array(hash))
+                .map(b => b.toString(16).padStart(2, '0'))
+                .join('');
+
+            const padded = new TextEncoder().encode('xxhello worldyy');
+            const view = padded.subarray(2, 13);
+            const dataView = new DataView(padded.buffer, 2, 11);
+
+            const viewHash = toHex(await crypto.subtle.digest('SHA-256', view));
+            const dataViewHash = toHex(await crypto.subtle.digest('SHA-256', dataView));
+
+            // getRandomValues only fills the view
+            const random = new Uint8Array(16);
+            crypto.getRandomValues(random.subarray(4, 8));
+            const outside = [...random.slice(0, 4), ...random.slice(8)];
+
+            // SHA-256 of "hello world"
+            const expected = 'b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9';
+
+            const result = viewHash === expected
+                && dataViewHash === expected
+                && outside.every(b => b === 0)
+                ? 'OK' : `FAIL: ${viewHash} ${dataViewHash} ${outside}`;
+
+            event.respondWith(new Response(result));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+/// Test that DataView and non-byte typed arrays are read as their underlying bytes
+#[tokio::test]
+async fn test_crypto_data_view_and_typed_array_views() {
+    let script = r#"
+        addEventListener('fetch', async (event) => {
+            const toHex = (buffer) => Array.from(new Uint8Array(buffer))
+                .map(b => b.toString(16).padStart(2, '0'))
+                .join('');
+
+            const buffer = new ArrayBuffer(32);
+            new Uint8Array(buffer).forEach((_, i, bytes) => { bytes[i] = i * 7; });
+
+            // The same 16 bytes, seen through three kinds of view
+            const bytes = new Uint8Array(buffer.slice(8, 24));
+            const dataView = new DataView(buffer.slice(4, 28), 4, 16);
+            const int16 = new Int16Array(buffer, 8, 8);
+
+            const expected = toHex(await crypto.subtle.digest('SHA-256', bytes));
+            const fromDataView = toHex(await crypto.subtle.digest('SHA-256', dataView));
+            const fromInt16 = toHex(await crypto.subtle.digest('SHA-256', int16));
+
+            const key = await crypto.subtle.importKey(
+                'raw',
+                new DataView(new TextEncoder().encode('secret').buffer),
+                { name: 'HMAC', hash: 'SHA-256' },
+                false,
+                ['sign', 'verify']
+            );
+            const signature = await crypto.subtle.sign('HMAC', key, dataView);
+            const verified = await crypto.subtle.verify('HMAC', key, new DataView(signature), bytes);
+
+            const result = fromDataView === expected && fromInt16 === expected && verified
+                ? 'OK' : `FAIL: ${expected} ${fromDataView} ${fromInt16} ${verified}`;
+
+            event.respondWith(new Response(result));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
 /// Test crypto.subtle.digest with SHA-512
 #[tokio::test]
 async fn test_digest_sha512() {
@@ -484,3 +595,852 @@ async fn test_hmac_different_algorithms() {
     let body = response.body.collect().await.expect("Should have body");
     assert_eq!(String::from_utf8_lossy(&body), "OK");
 }
+
+/// Test wrapping an HMAC key with AES-GCM and verifying with the unwrapped key
+#[tokio::test]
+async fn test_wrap_unwrap_hmac_key_with_aes_gcm() {
+    let script = r#"
+        addEventListener('fetch', async (event) => {
+            const hmacKey = await crypto.subtle.importKey(
+                'raw',
+                new TextEncoder().encode('my-secret-key'),
+                { name: 'HMAC', hash: 'SHA-256' },
+                true,
+                ['sign', 'verify']
+            );
+            const wrappingKey = await crypto.subtle.generateKey(
+                { name: 'AES-GCM', length: 256 },
+                false,
+                ['wrapKey', 'unwrapKey']
+            );
+
+            const data = new TextEncoder().encode('hello world');
+            const signature = await crypto.subtle.sign('HMAC', hmacKey, data);
+
+            const iv = crypto.getRandomValues(new Uint8Array(12));
+            const wrapped = await crypto.subtle.wrapKey('raw', hmacKey, wrappingKey, { name: 'AES-GCM', iv });
+
+            const unwrapped = await crypto.subtle.unwrapKey(
+                'raw',
+                wrapped,
+                wrappingKey,
+                { name: 'AES-GCM', iv },
+                { name: 'HMAC', hash: 'SHA-256' },
+                false,
+                ['verify']
+            );
+
+            const isValid = await crypto.subtle.verify('HMAC', unwrapped, signature, data);
+
+            // 13 key bytes + 16 tag bytes
+            const result = isValid && wrapped.byteLength === 29 && !unwrapped.extractable
+                ? 'OK' : `FAIL: ${isValid} ${wrapped.byteLength}`;
+            event.respondWith(new Response(result));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+/// Test wrapKey rejects non-extractable keys, AES-KW and tampered data
+#[tokio::test]
+async fn test_wrap_key_errors() {
+    let script = r#"
+        addEventListener('fetch', async (event) => {
+            const errorName = (promise) => promise.then(() => 'resolved', (e) => e.name);
+
+            const hiddenKey = await crypto.subtle.importKey(
+                'raw',
+                new TextEncoder().encode('secret'),
+                { name: 'HMAC', hash: 'SHA-256' },
+                false,
+                ['sign']
+            );
+            const wrappingKey = await crypto.subtle.importKey(
+                'raw',
+                new Uint8Array(16),
+                'AES-GCM',
+                false,
+                ['wrapKey', 'unwrapKey']
+            );
+            const iv = new Uint8Array(12);
+
+            const nonExtractable = await errorName(
+                crypto.subtle.wrapKey('raw', hiddenKey, wrappingKey, { name: 'AES-GCM', iv })
+            );
+            const wrongAlgorithm = await errorName(
+                crypto.subtle.wrapKey('raw', hiddenKey, wrappingKey, { name: 'AES-KW' })
+            );
+
+            const tampered = new Uint8Array(29);
+            const badUnwrap = await errorName(crypto.subtle.unwrapKey(
+                'raw', tampered, wrappingKey, { name: 'AES-GCM', iv },
+                { name: 'HMAC', hash: 'SHA-256' }, false, ['verify']
+            ));
+
+            const result = [nonExtractable, wrongAlgorithm, badUnwrap].join(',');
+            event.respondWith(new Response(
+                result === 'InvalidAccessError,InvalidAccessError,OperationError' ? 'OK' : `FAIL: ${result}`
+            ));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+/// Test AES-KW wrapping against the RFC 3394 128-bit vector, and a round trip
+#[tokio::test]
+async fn test_wrap_unwrap_with_aes_kw() {
+    let script = r#"
+        addEventListener('fetch', async (event) => {
+            const hex = (buffer) => Array.from(new Uint8Array(buffer), b => b.toString(16).padStart(2, '0')).join('');
+            const fromHex = (text) => new Uint8Array(text.match(/../g).map(b => parseInt(b, 16)));
+            const errorName = (promise) => promise.then(() => 'resolved', (e) => e.name);
+
+            // RFC 3394 section 4.1
+            const kek = await crypto.subtle.importKey(
+                'raw',
+                fromHex('000102030405060708090a0b0c0d0e0f'),
+                'AES-KW',
+                false,
+                ['wrapKey', 'unwrapKey']
+            );
+            const key = await crypto.subtle.importKey(
+                'raw',
+                fromHex('00112233445566778899aabbccddeeff'),
+                'AES-GCM',
+                true,
+                ['encrypt', 'decrypt']
+            );
+
+            const wrapped = await crypto.subtle.wrapKey('raw', key, kek, 'AES-KW');
+            const unwrapped = await crypto.subtle.unwrapKey(
+                'raw', wrapped, kek, 'AES-KW', 'AES-GCM', true, ['encrypt', 'decrypt']
+            );
+            const exported = await crypto.subtle.exportKey('raw', unwrapped);
+
+            // A generated 256-bit wrapping key round-trips too
+            const generated = await crypto.subtle.generateKey(
+                { name: 'AES-KW', length: 256 }, false, ['wrapKey', 'unwrapKey']
+            );
+            const rewrapped = await crypto.subtle.wrapKey('raw', key, generated, { name: 'AES-KW' });
+            const roundTrip = await crypto.subtle.unwrapKey(
+                'raw', rewrapped, generated, { name: 'AES-KW' }, 'AES-GCM', true, ['encrypt']
+            );
+
+            const tampered = new Uint8Array(wrapped);
+            tampered[0] ^= 1;
+            const badUnwrap = await errorName(crypto.subtle.unwrapKey(
+                'raw', tampered, kek, 'AES-KW', 'AES-GCM', true, ['encrypt']
+            ));
+
+            const result = [
+                hex(wrapped),
+                hex(exported),
+                hex(await crypto.subtle.exportKey('raw', roundTrip)),
+                badUnwrap
+            ].join(',');
+            event.respondWith(new Response(result));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(
+        String::from_utf8_lossy(&body),
+        "1fa68b0a8112b447aef34bd8fb5a7b829d3e862371d2cfe5,\
+         00112233445566778899aabbccddeeff,\
+         00112233445566778899aabbccddeeff,\
+         OperationError"
+    );
+}
+
+/// Test AES-GCM encrypt and decrypt
+#[tokio::test]
+async fn test_aes_gcm_encrypt_decrypt() {
+    let script = r#"
+        addEventListener('fetch', async (event) => {
+            const key = await crypto.subtle.generateKey(
+                { name: 'AES-GCM', length: 128 },
+                true,
+                ['encrypt', 'decrypt']
+            );
+            const iv = crypto.getRandomValues(new Uint8Array(12));
+            const additionalData = new TextEncoder().encode('header');
+
+            const ciphertext = await crypto.subtle.encrypt(
+                { name: 'AES-GCM', iv, additionalData },
+                key,
+                new TextEncoder().encode('hello world')
+            );
+            const plaintext = await crypto.subtle.decrypt(
+                { name: 'AES-GCM', iv, additionalData },
+                key,
+                ciphertext
+            );
+            const raw = await crypto.subtle.exportKey('raw', key);
+
+            const text = new TextDecoder().decode(plaintext);
+            const result = text === 'hello world' && ciphertext.byteLength === 27 && raw.byteLength === 16
+                ? 'OK' : `FAIL: ${text}`;
+            event.respondWith(new Response(result));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+/// Test RSA-PSS SHA-256 sign and verify
+#[tokio::test]
+async fn test_rsa_pss_sign_verify() {
+    let script = r#"
+        addEventListener('fetch', async (event) => {
+            try {
+                // Same 2048-bit RSA key pair as test_rsa_sign_verify
+                const privateKeyData = Uint8Array.fromBase64('MIIEpAIBAAKCAQEA5EmDGTHoMj6bosn6lbZMJkZNnDlfoon7eMBrVQYSkQDLZCnJHDAxAD8ODlIWlRHDD9NWqyEBdTGqlUDTrjKvLBzktSMWeIG0TrXVQ0Yw3Ibu8EvSn8tGVEq/Epa05uNh7JGVjxmIRVyGn6ic9b1S85JzfcSJgUoxSvW0KmTOh/TaaHdAkGS/4wpdfjSexogWapyKNms17jHehmtkUq0Vhh4YYr8t72bb+FJtHqwsEYbC3jXXEQ+u6zCmc9fDuAvbv5kvjglBZu0aEGap5fmbqSWexWqJcdvln7TMQ2A6b1fmZ1t76+WtKH7WwGf4SGkJ2PLFxCZaJ8oE0Ci+Rm/amwIDAQABAoIBABBogj5A2o4l9tzMBLFXEYEcw35Ll2ag4UzME8rgLVxzwKq54CUhB5yba6C24L2lMa6FA7E4JZktUTP6HVzjcrjKeNvWIkrWE8YmhqYXuPJY1nq6EHEA1NTBLJui7my8AjFVQ3kuHh/SJzD5lxKIoZo1OAzdn/6FfSaEo4b6iOe3nGj2q00WUf4t5OjQyWkgZHb3D+QFimnrw0q0ct/N28MxiHohJs+8NgDhDnjthF1fwi5mpso9mm+ysw2/ss5W1y6mczWcEwXvTh0svD6BkdGHfdpbkaXguHFCyFk1WG80MYiq61yZOwPMj2GFh/o3dPdIo5x3ScKBzDen2zuevLkCgYEA+N30zLXWM67jpqxFTokbEiImmUiLHGPx4CtCu1Cf3MNWz7W2p8/eCi3vtL8dCeD687yuHDPcft6KH88jXHriyTaK3zez8BTetmGNM+3YM1QmFynD1qYuqaDyobZBFwpxka902SQFcWAIDsimaJeNsVd2Kxr2lb3AYZyu66vQAtkCgYEA6tSNeHWS+PqF0OUivYI2Vsn+moplxNfEElSK6ifrK39YaEv9hZzwLIR3Iq0cxbKQWBNvssWzaLd0Z5ZKBEWFmLDNph7Giq7V1spUc6V6tWrbGL+92Yw0+ZWjx83InFAT+B6Cjgvptfrd0AipphhrAFC0c3iiIKbSPv0EOPec+JMCgYEAx0rneO+9A1JwV87pCYVeOl1Cz8l6LVgUIFJEdECSZHXBlUCNb0FVLI2wweux03FpRbq5KziUwLxxnBuC09JMvpmBCFRRMldkKmVgcE9trV0by7zUaZZXE9whsUKESXFBlUsOpbzk5u/iRASGzodfHr9NkCNdiHiWERUqNuw1/bECgYASDVDqx68KsMeErXikNNRUi6ak3qrAHQ4XkqQzJ+puJ5X2PpE4qj3UTkKSSdiCYh2yh5v4lDYcgK3UILuD5IxGlqDYelks5A/QOTGQylHKjHJXTrYbeSnBXf1/KJSZX5aJZl8G6GeI88YFbgUMnafsGEgm8EkWVXyoFu8yKebJPQKBgQDsltWFU9zmXXA6mMaKi5A7J7Va3s74pEqlyQk+Xb0iRcZLKCIdB3MepaIPXi0QPjRwXY6vIVIV2AvTToup1c4pZKH98YM/HFZfLgQsNw0YGW39VzyR4i39j44AvAmLB0y8x8GKD7NUk8cVJGLL+R5qyRe2LGOJtHb4UoBsmTCIWg==');
+                const publicKeyData = Uint8Array.fromBase64('MIIBCgKCAQEA5EmDGTHoMj6bosn6lbZMJkZNnDlfoon7eMBrVQYSkQDLZCnJHDAxAD8ODlIWlRHDD9NWqyEBdTGqlUDTrjKvLBzktSMWeIG0TrXVQ0Yw3Ibu8EvSn8tGVEq/Epa05uNh7JGVjxmIRVyGn6ic9b1S85JzfcSJgUoxSvW0KmTOh/TaaHdAkGS/4wpdfjSexogWapyKNms17jHehmtkUq0Vhh4YYr8t72bb+FJtHqwsEYbC3jXXEQ+u6zCmc9fDuAvbv5kvjglBZu0aEGap5fmbqSWexWqJcdvln7TMQ2A6b1fmZ1t76+WtKH7WwGf4SGkJ2PLFxCZaJ8oE0Ci+Rm/amwIDAQAB');
+
+                const algorithm = { name: 'RSA-PSS', hash: 'SHA-256' };
+                const privateKey = await crypto.subtle.importKey('pkcs8', privateKeyData, algorithm, false, ['sign']);
+                const publicKey = await crypto.subtle.importKey('spki', publicKeyData, algorithm, false, ['verify']);
+
+                const params = { name: 'RSA-PSS', saltLength: 32 };
+                const data = new TextEncoder().encode('hello world');
+                const signature = await crypto.subtle.sign(params, privateKey, data);
+
+                const isValid = await crypto.subtle.verify(params, publicKey, signature, data);
+
+                // Tampered data and signature must not verify
+                const isInvalid = await crypto.subtle.verify(params, publicKey, signature, new TextEncoder().encode('hello worle'));
+                const tampered = new Uint8Array(signature);
+                tampered[0] ^= 1;
+                const tamperedValid = await crypto.subtle.verify(params, publicKey, tampered, data);
+
+                // PSS is not interchangeable with PKCS#1 v1.5
+                const pkcs1Key = await crypto.subtle.importKey('spki', publicKeyData, { name: 'RSASSA-PKCS1-v1_5', hash: 'SHA-256' }, false, ['verify']);
+                const crossValid = await crypto.subtle.verify('RSASSA-PKCS1-v1_5', pkcs1Key, signature, data);
+
+                // Salt lengths other than the digest length are unsupported
+                const badSalt = await crypto.subtle.sign({ name: 'RSA-PSS', saltLength: 20 }, privateKey, data)
+                    .then(() => 'resolved', e => e.name);
+
+                const ok = isValid && !isInvalid && !tamperedValid && !crossValid
+                    && signature.byteLength === 256 && badSalt === 'NotSupportedError';
+                const result = ok ? 'OK' : `FAIL: ${isValid} ${isInvalid} ${tamperedValid} ${crossValid} ${badSalt}`;
+                event.respondWith(new Response(result));
+            } catch (e) {
+                event.respondWith(new Response('ERROR: ' + e.message));
+            }
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+/// Test RSA-OAEP SHA-256 round trip of a 32-byte payload
+#[tokio::test]
+async fn test_rsa_oaep_encrypt_decrypt() {
+    let script = r#"
+        addEventListener('fetch', async (event) => {
+            try {
+                // Same 2048-bit RSA key pair as test_rsa_sign_verify
+                const algorithm = { name: 'RSA-OAEP', hash: 'SHA-256' };
+                const privateKey = await crypto.subtle.importKey(
+                    'pkcs8', Uint8Array.fromBase64('MIIEpAIBAAKCAQEA5EmDGTHoMj6bosn6lbZMJkZNnDlfoon7eMBrVQYSkQDLZCnJHDAxAD8ODlIWlRHDD9NWqyEBdTGqlUDTrjKvLBzktSMWeIG0TrXVQ0Yw3Ibu8EvSn8tGVEq/Epa05uNh7JGVjxmIRVyGn6ic9b1S85JzfcSJgUoxSvW0KmTOh/TaaHdAkGS/4wpdfjSexogWapyKNms17jHehmtkUq0Vhh4YYr8t72bb+FJtHqwsEYbC3jXXEQ+u6zCmc9fDuAvbv5kvjglBZu0aEGap5fmbqSWexWqJcdvln7TMQ2A6b1fmZ1t76+WtKH7WwGf4SGkJ2PLFxCZaJ8oE0Ci+Rm/amwIDAQABAoIBABBogj5A2o4l9tzMBLFXEYEcw35Ll2ag4UzME8rgLVxzwKq54CUhB5yba6C24L2lMa6FA7E4JZktUTP6HVzjcrjKeNvWIkrWE8YmhqYXuPJY1nq6EHEA1NTBLJui7my8AjFVQ3kuHh/SJzD5lxKIoZo1OAzdn/6FfSaEo4b6iOe3nGj2q00WUf4t5OjQyWkgZHb3D+QFimnrw0q0ct/N28MxiHohJs+8NgDhDnjthF1fwi5mpso9mm+ysw2/ss5W1y6mczWcEwXvTh0svD6BkdGHfdpbkaXguHFCyFk1WG80MYiq61yZOwPMj2GFh/o3dPdIo5x3ScKBzDen2zuevLkCgYEA+N30zLXWM67jpqxFTokbEiImmUiLHGPx4CtCu1Cf3MNWz7W2p8/eCi3vtL8dCeD687yuHDPcft6KH88jXHriyTaK3zez8BTetmGNM+3YM1QmFynD1qYuqaDyobZBFwpxka902SQFcWAIDsimaJeNsVd2Kxr2lb3AYZyu66vQAtkCgYEA6tSNeHWS+PqF0OUivYI2Vsn+moplxNfEElSK6ifrK39YaEv9hZzwLIR3Iq0cxbKQWBNvssWzaLd0Z5ZKBEWFmLDNph7Giq7V1spUc6V6tWrbGL+92Yw0+ZWjx83InFAT+B6Cjgvptfrd0AipphhrAFC0c3iiIKbSPv0EOPec+JMCgYEAx0rneO+9A1JwV87pCYVeOl1Cz8l6LVgUIFJEdECSZHXBlUCNb0FVLI2wweux03FpRbq5KziUwLxxnBuC09JMvpmBCFRRMldkKmVgcE9trV0by7zUaZZXE9whsUKESXFBlUsOpbzk5u/iRASGzodfHr9NkCNdiHiWERUqNuw1/bECgYASDVDqx68KsMeErXikNNRUi6ak3qrAHQ4XkqQzJ+puJ5X2PpE4qj3UTkKSSdiCYh2yh5v4lDYcgK3UILuD5IxGlqDYelks5A/QOTGQylHKjHJXTrYbeSnBXf1/KJSZX5aJZl8G6GeI88YFbgUMnafsGEgm8EkWVXyoFu8yKebJPQKBgQDsltWFU9zmXXA6mMaKi5A7J7Va3s74pEqlyQk+Xb0iRcZLKCIdB3MepaIPXi0QPjRwXY6vIVIV2AvTToup1c4pZKH98YM/HFZfLgQsNw0YGW39VzyR4i39j44AvAmLB0y8x8GKD7NUk8cVJGLL+R5qyRe2LGOJtHb4UoBsmTCIWg=='), algorithm, false, ['decrypt']
+                );
+                const publicKey = await crypto.subtle.importKey(
+                    'spki', Uint8Array.fromBase64('MIIBCgKCAQEA5EmDGTHoMj6bosn6lbZMJkZNnDlfoon7eMBrVQYSkQDLZCnJHDAxAD8ODlIWlRHDD9NWqyEBdTGqlUDTrjKvLBzktSMWeIG0TrXVQ0Yw3Ibu8EvSn8tGVEq/Epa05uNh7JGVjxmIRVyGn6ic9b1S85JzfcSJgUoxSvW0KmTOh/TaaHdAkGS/4wpdfjSexogWapyKNms17jHehmtkUq0Vhh4YYr8t72bb+FJtHqwsEYbC3jXXEQ+u6zCmc9fDuAvbv5kvjglBZu0aEGap5fmbqSWexWqJcdvln7TMQ2A6b1fmZ1t76+WtKH7WwGf4SGkJ2PLFxCZaJ8oE0Ci+Rm/amwIDAQAB'), algorithm, false, ['encrypt']
+                );
+
+                const payload = crypto.getRandomValues(new Uint8Array(32));
+                const ciphertext = await crypto.subtle.encrypt({ name: 'RSA-OAEP' }, publicKey, payload);
+                const decrypted = new Uint8Array(
+                    await crypto.subtle.decrypt({ name: 'RSA-OAEP' }, privateKey, ciphertext)
+                );
+
+                const roundTrip = decrypted.length === 32 && decrypted.every((b, i) => b === payload[i]);
+
+                // 256 - 2 * 32 - 2 = 190 bytes max with SHA-256
+                const tooLong = await crypto.subtle.encrypt({ name: 'RSA-OAEP' }, publicKey, new Uint8Array(191))
+                    .then(() => 'resolved', e => e.name);
+                const maxLen = await crypto.subtle.encrypt({ name: 'RSA-OAEP' }, publicKey, new Uint8Array(190))
+                    .then(() => 'resolved', e => e.name);
+
+                const ok = roundTrip && ciphertext.byteLength === 256
+                    && tooLong === 'OperationError' && maxLen === 'resolved';
+                event.respondWith(new Response(ok ? 'OK' : `FAIL: ${roundTrip} ${tooLong} ${maxLen}`));
+            } catch (e) {
+                event.respondWith(new Response('ERROR: ' + e.message));
+            }
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+/// Test ECDH P-256 and P-384 key exchange derives the same secret on both sides
+#[tokio::test]
+async fn test_ecdh_derive_bits() {
+    let script = r#"
+        addEventListener('fetch', async (event) => {
+            try {
+                const equal = (a, b) => a.length === b.length && a.every((x, i) => x === b[i]);
+                const results = [];
+
+                for (const [namedCurve, bits] of [['P-256', 256], ['P-384', 384]]) {
+                    const algorithm = { name: 'ECDH', namedCurve };
+                    const alice = await crypto.subtle.generateKey(algorithm, false, ['deriveBits', 'deriveKey']);
+                    const bob = await crypto.subtle.generateKey(algorithm, false, ['deriveBits', 'deriveKey']);
+
+                    // Bob's public key goes through a raw export/import like over the wire
+                    const bobRaw = await crypto.subtle.exportKey('raw', bob.publicKey);
+                    const bobPublic = await crypto.subtle.importKey('raw', bobRaw, algorithm, true, []);
+
+                    const aliceBits = new Uint8Array(
+                        await crypto.subtle.deriveBits({ name: 'ECDH', public: bobPublic }, alice.privateKey, bits)
+                    );
+                    const bobBits = new Uint8Array(
+                        await crypto.subtle.deriveBits({ name: 'ECDH', public: alice.publicKey }, bob.privateKey, bits)
+                    );
+
+                    // Same peer again, truncated to 128 bits
+                    const truncated = new Uint8Array(
+                        await crypto.subtle.deriveBits({ name: 'ECDH', public: bobPublic }, alice.privateKey, 128)
+                    );
+
+                    results.push(aliceBits.length === bits / 8 && equal(aliceBits, bobBits)
+                        && equal(truncated, aliceBits.slice(0, 16)));
+                }
+
+                // deriveKey into AES-GCM on both sides, then decrypt across
+                const algorithm = { name: 'ECDH', namedCurve: 'P-256' };
+                const alice = await crypto.subtle.generateKey(algorithm, false, ['deriveKey']);
+                const bob = await crypto.subtle.generateKey(algorithm, false, ['deriveKey']);
+                const aesAlgorithm = { name: 'AES-GCM', length: 256 };
+                const aliceKey = await crypto.subtle.deriveKey(
+                    { name: 'ECDH', public: bob.publicKey }, alice.privateKey, aesAlgorithm, false, ['encrypt']
+                );
+                const bobKey = await crypto.subtle.deriveKey(
+                    { name: 'ECDH', public: alice.publicKey }, bob.privateKey, aesAlgorithm, false, ['decrypt']
+                );
+
+                const iv = crypto.getRandomValues(new Uint8Array(12));
+                const ciphertext = await crypto.subtle.encrypt({ name: 'AES-GCM', iv }, aliceKey, new TextEncoder().encode('hello'));
+                const plaintext = new TextDecoder().decode(await crypto.subtle.decrypt({ name: 'AES-GCM', iv }, bobKey, ciphertext));
+                results.push(plaintext === 'hello');
+
+                // Malformed private keys are rejected
+                const imported = await crypto.subtle.importKey('pkcs8', new Uint8Array(32), algorithm, false, ['deriveBits'])
+                    .then(() => 'resolved', e => e.name);
+                results.push(imported === 'DataError');
+
+                const ok = results.every(Boolean);
+                event.respondWith(new Response(ok ? 'OK' : `FAIL: ${results}`));
+            } catch (e) {
+                event.respondWith(new Response('ERROR: ' + e.message));
+            }
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+/// Test ECDH private keys survive pkcs8 and JWK round trips and derive against several peers
+#[tokio::test]
+async fn test_ecdh_private_key_import_export() {
+    let script = r#"
+        addEventListener('fetch', async (event) => {
+            try {
+                const equal = (a, b) => a.length === b.length && a.every((x, i) => x === b[i]);
+                const results = [];
+
+                for (const namedCurve of ['P-256', 'P-384']) {
+                    const algorithm = { name: 'ECDH', namedCurve };
+                    const alice = await crypto.subtle.generateKey(algorithm, true, ['deriveBits']);
+                    const bob = await crypto.subtle.generateKey(algorithm, false, ['deriveBits']);
+                    const carol = await crypto.subtle.generateKey(algorithm, false, ['deriveBits']);
+
+                    // One private key agrees with several peers
+                    const derive = async (privateKey, publicKey) => new Uint8Array(
+                        await crypto.subtle.deriveBits({ name: 'ECDH', public: publicKey }, privateKey, null)
+                    );
+                    const withBob = await derive(alice.privateKey, bob.publicKey);
+                    const withCarol = await derive(alice.privateKey, carol.publicKey);
+                    results.push(equal(withBob, await derive(bob.privateKey, alice.publicKey)));
+                    results.push(equal(withCarol, await derive(carol.privateKey, alice.publicKey)));
+                    results.push(!equal(withBob, withCarol));
+
+                    // pkcs8 round trip keeps the same secret
+                    const pkcs8 = await crypto.subtle.exportKey('pkcs8', alice.privateKey);
+                    const fromPkcs8 = await crypto.subtle.importKey('pkcs8', pkcs8, algorithm, false, ['deriveBits']);
+                    results.push(equal(withBob, await derive(fromPkcs8, bob.publicKey)));
+
+                    // JWK round trip too, with d, x and y checked against each other
+                    const jwk = await crypto.subtle.exportKey('jwk', alice.privateKey);
+                    results.push(jwk.kty === 'EC' && jwk.crv === namedCurve && typeof jwk.d === 'string');
+                    const fromJwk = await crypto.subtle.importKey('jwk', jwk, algorithm, false, ['deriveBits']);
+                    results.push(equal(withCarol, await derive(fromJwk, carol.publicKey)));
+
+                    const bobJwk = await crypto.subtle.exportKey('jwk', bob.publicKey);
+                    const mismatched = await crypto.subtle.importKey('jwk', { ...jwk, x: bobJwk.x, y: bobJwk.y }, algorithm, false, ['deriveBits'])
+                        .then(() => 'resolved', e => e.name);
+                    results.push(mismatched === 'DataError');
+                }
+
+                // Non-extractable generated keys stay non-extractable
+                const algorithm = { name: 'ECDH', namedCurve: 'P-256' };
+                const locked = await crypto.subtle.generateKey(algorithm, false, ['deriveBits']);
+                const exported = await crypto.subtle.exportKey('pkcs8', locked.privateKey)
+                    .then(() => 'resolved', e => e.name);
+                results.push(exported === 'InvalidAccessError');
+
+                const ok = results.every(Boolean);
+                event.respondWith(new Response(ok ? 'OK' : `FAIL: ${results}`));
+            } catch (e) {
+                event.respondWith(new Response('ERROR: ' + e.message));
+            }
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+/// Test importing an HMAC key from a JWK, signing, and exporting it back
+#[tokio::test]
+async fn test_jwk_hmac_import_export() {
+    let script = r#"
+        addEventListener('fetch', async (event) => {
+            try {
+                // k is "0123456789abcdef0123456789abcdef"
+                const jwk = { kty: 'oct', k: 'MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY', alg: 'HS256', key_ops: ['sign', 'verify'], ext: true };
+                const key = await crypto.subtle.importKey('jwk', jwk, { name: 'HMAC' }, true, []);
+
+                const signature = new Uint8Array(
+                    await crypto.subtle.sign('HMAC', key, new TextEncoder().encode('known message'))
+                );
+                const signed = signature.toBase64({ alphabet: 'base64url', omitPadding: true })
+                    === 'ALw-S4UaQZrFeiOKpME3KPdqdMWpbVu70QJE6KpNcro';
+
+                const exported = await crypto.subtle.exportKey('jwk', key);
+                const roundTrip = exported.kty === 'oct' && exported.k === jwk.k && exported.alg === 'HS256'
+                    && key.algorithm.hash.name === 'SHA-256' && key.usages.join() === 'sign,verify';
+
+                const mismatch = await crypto.subtle.importKey('jwk', jwk, { name: 'HMAC', hash: 'SHA-512' }, false, ['sign'])
+                    .then(() => 'resolved', e => e.name);
+                const notAllowed = await crypto.subtle.importKey('jwk', { ...jwk, key_ops: ['verify'] }, { name: 'HMAC' }, false, ['sign'])
+                    .then(() => 'resolved', e => e.name);
+
+                const ok = signed && roundTrip && mismatch === 'DataError' && notAllowed === 'DataError';
+                event.respondWith(new Response(ok ? 'OK' : `FAIL: ${signed} ${roundTrip} ${mismatch} ${notAllowed}`));
+            } catch (e) {
+                event.respondWith(new Response('ERROR: ' + e.message));
+            }
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+/// Test importing EC P-256 keys from JWKs and verifying a known signature
+#[tokio::test]
+async fn test_jwk_ec_import_export() {
+    let script = r#"
+        addEventListener('fetch', async (event) => {
+            try {
+                const algorithm = { name: 'ECDSA', namedCurve: 'P-256' };
+                const sign = { name: 'ECDSA', hash: 'SHA-256' };
+                const data = new TextEncoder().encode('known message');
+                const jwk = {
+                    kty: 'EC',
+                    crv: 'P-256',
+                    x: 'YEFXa4X9T5i4e1RGmtxaQzsYB3n3XUC8yp6BOMvjF6E',
+                    y: 'dxs3cGSt8TPUxy5U_Xf3ciYfSm9RzdcxffVsnh_q2zo'
+                };
+                const knownSignature = Uint8Array.fromBase64(
+                    'mRRedmiJ8KPheYulmrg56YbesHKDoSaIoKgDo78cVR6DaXmAOiftfVbGKveENYZbtyHYCU1UU2AEieH88OC4sQ',
+                    { alphabet: 'base64url' }
+                );
+
+                const publicKey = await crypto.subtle.importKey('jwk', jwk, algorithm, true, ['verify']);
+                const verified = await crypto.subtle.verify(sign, publicKey, knownSignature, data);
+                const tampered = await crypto.subtle.verify(sign, publicKey, knownSignature, new TextEncoder().encode('other'));
+
+                // The private key signs something the public key verifies
+                const privateKey = await crypto.subtle.importKey(
+                    'jwk', { ...jwk, d: 'yhx8FF3MmYirOb2Cmq6QeyGt4ZAvf5dTwNePWDTZg38' }, algorithm, true, ['sign']
+                );
+                const signature = await crypto.subtle.sign(sign, privateKey, data);
+                const selfSigned = await crypto.subtle.verify(sign, publicKey, signature, data);
+
+                const exportedPublic = await crypto.subtle.exportKey('jwk', publicKey);
+                const exportedPrivate = await crypto.subtle.exportKey('jwk', privateKey);
+                const exported = exportedPublic.x === jwk.x && exportedPublic.y === jwk.y && !('d' in exportedPublic)
+                    && exportedPrivate.d === 'yhx8FF3MmYirOb2Cmq6QeyGt4ZAvf5dTwNePWDTZg38' && exportedPrivate.crv === 'P-256';
+
+                // Generated keys export too
+                const generated = await crypto.subtle.generateKey(algorithm, true, ['sign', 'verify']);
+                const generatedJwk = await crypto.subtle.exportKey('jwk', generated.privateKey);
+                const reimported = await crypto.subtle.importKey('jwk', generatedJwk, algorithm, false, ['sign']);
+                const reimportedSignature = await crypto.subtle.sign(sign, reimported, data);
+                const generatedOk = await crypto.subtle.verify(sign, generated.publicKey, reimportedSignature, data);
+
+                const wrongCurve = await crypto.subtle.importKey('jwk', { ...jwk, crv: 'P-384' }, algorithm, false, ['verify'])
+                    .then(() => 'resolved', e => e.name);
+
+                const ok = verified && !tampered && selfSigned && exported && generatedOk && wrongCurve === 'DataError';
+                event.respondWith(new Response(ok
+                    ? 'OK'
+                    : `FAIL: ${verified} ${tampered} ${selfSigned} ${exported} ${generatedOk} ${wrongCurve}`));
+            } catch (e) {
+                event.respondWith(new Response('ERROR: ' + e.message));
+            }
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+/// Test crypto.subtle.digestStream over a multi-chunk stream matches the one-shot digest
+#[tokio::test]
+async fn test_digest_stream() {
+    let script = r#"
+        addEventListener('fetch', async (event) => {
+            try {
+                const chunks = [
+                    new TextEncoder().encode('hello '),
+                    new Uint8Array(0),
+                    crypto.getRandomValues(new Uint8Array(60000)),
+                    new Uint8Array([1, 2, 3]).buffer,
+                    new TextEncoder().encode('world')
+                ];
+
+                const toHex = (buffer) => new Uint8Array(buffer).toHex();
+                const concatenated = new Uint8Array(await new Blob(chunks).arrayBuffer());
+
+                const results = [];
+                for (const algorithm of ['SHA-1', 'SHA-256', 'SHA-384', { name: 'SHA-512' }]) {
+                    const stream = new ReadableStream({
+                        start(controller) {
+                            for (const chunk of chunks) {
+                                controller.enqueue(chunk);
+                            }
+                            controller.close();
+                        }
+                    });
+
+                    const streamed = await crypto.subtle.digestStream(algorithm, stream);
+                    const oneShot = await crypto.subtle.digest(algorithm, concatenated);
+                    results.push(toHex(streamed) === toHex(oneShot));
+                }
+
+                const unsupported = await crypto.subtle.digestStream('MD5', new ReadableStream())
+                    .then(() => 'resolved', () => 'rejected');
+
+                const ok = results.every(Boolean) && unsupported === 'rejected';
+                event.respondWith(new Response(ok ? 'OK' : `FAIL: ${results} ${unsupported}`));
+            } catch (e) {
+                event.respondWith(new Response('ERROR: ' + e.message));
+            }
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+/// Test digest and HMAC sign over a multi-MB body stream match the one-shot results
+#[tokio::test]
+async fn test_digest_and_hmac_over_body_stream() {
+    let script = r#"
+        addEventListener('fetch', async (event) => {
+            try {
+                // 4 MiB in 64 KiB chunks
+                const chunkSize = 64 * 1024;
+                const chunkCount = 64;
+                const all = new Uint8Array(chunkSize * chunkCount);
+                for (let i = 0; i < all.length; i++) {
+                    all[i] = (i * 31 + (i >> 8)) & 0xff;
+                }
+
+                const body = () => new Response(new ReadableStream({
+                    start(controller) {
+                        for (let i = 0; i < chunkCount; i++) {
+                            controller.enqueue(all.slice(i * chunkSize, (i + 1) * chunkSize));
+                        }
+                        controller.close();
+                    }
+                })).body;
+
+                const toHex = (buffer) => new Uint8Array(buffer).toHex();
+
+                const streamedDigest = await crypto.subtle.digest('SHA-256', body());
+                const oneShotDigest = await crypto.subtle.digest('SHA-256', all);
+
+                const key = await crypto.subtle.importKey(
+                    'raw',
+                    new TextEncoder().encode('upload-secret'),
+                    { name: 'HMAC', hash: 'SHA-384' },
+                    false,
+                    ['sign', 'verify']
+                );
+                const streamedMac = await crypto.subtle.sign('HMAC', key, body());
+                const oneShotMac = await crypto.subtle.sign('HMAC', key, all);
+
+                const notHmac = await crypto.subtle.sign({ name: 'ECDSA' }, key, body())
+                    .then(() => 'resolved', (e) => e.name);
+
+                const result = [
+                    toHex(streamedDigest) === toHex(oneShotDigest),
+                    streamedDigest.byteLength,
+                    toHex(streamedMac) === toHex(oneShotMac),
+                    streamedMac.byteLength,
+                    notHmac
+                ].join(',');
+                event.respondWith(new Response(result));
+            } catch (e) {
+                event.respondWith(new Response('ERROR: ' + e.message));
+            }
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "true,32,true,48,TypeError");
+}
+
+/// Test that a seeded runtime RNG makes getRandomValues and randomUUID reproducible
+#[tokio::test]
+async fn test_seeded_rng_is_reproducible() {
+    fn sample(seed: u64) -> String {
+        let (runtime, _scheduler_rx, _callback_tx, _stream_manager) = Runtime::new();
+        let mut runtime = runtime.with_rng(seed);
+
+        runtime
+            .evaluate(
+                r#"
+                JSON.stringify([
+                    Array.from(crypto.getRandomValues(new Uint8Array(13))),
+                    Array.from(crypto.getRandomValues(new Uint32Array(2))),
+                    crypto.randomUUID()
+                ])
+                "#,
+            )
+            .unwrap()
+            .to_js_string(&runtime.context)
+            .unwrap()
+            .to_string()
+    }
+
+    let first = sample(42);
+    assert_eq!(first, sample(42));
+    assert_ne!(first, sample(7));
+
+    let values: serde_json::Value = serde_json::from_str(&first).unwrap();
+    let uuid = values[2].as_str().unwrap();
+    assert_eq!(uuid.len(), 36);
+    assert_eq!(
+        &uuid[14..15],
+        "4",
+        "seeded UUIDs are still version 4: {}",
+        uuid
+    );
+}
@@ -0,0 +1,98 @@
+use openworkers_core::{Event, HttpMethod, HttpRequest, RequestBody, Script};
+use openworkers_runtime_jsc::Worker;
+use std::collections::HashMap;
+
+/// Test a CustomEvent dispatched through the global addEventListener
+#[tokio::test]
+async fn test_custom_event_dispatch() {
+    let script = r#"
+        const received = [];
+        addEventListener('greeting', (event) => {
+            received.push([event.type, event.detail.name, event instanceof CustomEvent, event instanceof Event]);
+        });
+        addEventListener('greeting', { handleEvent: (event) => received.push(['object', event.detail.name]) });
+
+        addEventListener('fetch', (event) => {
+            const dispatched = dispatchEvent(new CustomEvent('greeting', { detail: { name: 'worker' } }));
+            const ok = dispatched
+                && JSON.stringify(received) === JSON.stringify([['greeting', 'worker', true, true], ['object', 'worker']]);
+            event.respondWith(new Response(ok ? 'OK' : `FAIL: ${JSON.stringify(received)}`));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+/// Test once listeners, removal, preventDefault and stopImmediatePropagation
+#[tokio::test]
+async fn test_event_target_listeners() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const target = new EventTarget();
+            const calls = [];
+            const counter = () => calls.push('counter');
+
+            target.addEventListener('ping', counter);
+            target.addEventListener('ping', counter);
+            target.addEventListener('ping', () => calls.push('once'), { once: true });
+            target.dispatchEvent(new Event('ping'));
+            target.dispatchEvent(new Event('ping'));
+            target.removeEventListener('ping', counter);
+            target.dispatchEvent(new Event('ping'));
+
+            target.addEventListener('cancel', (e) => {
+                e.preventDefault();
+                e.stopImmediatePropagation();
+                calls.push(e.target === target);
+            });
+            target.addEventListener('cancel', () => calls.push('skipped'));
+            const notCancelable = target.dispatchEvent(new Event('cancel'));
+            const cancelable = target.dispatchEvent(new Event('cancel', { cancelable: true }));
+
+            const custom = new CustomEvent('plain');
+            let threw = false;
+            try { target.dispatchEvent({ type: 'ping' }); } catch (e) { threw = e instanceof TypeError; }
+
+            const ok = JSON.stringify(calls) === JSON.stringify(['counter', 'once', 'counter', true, true])
+                && notCancelable === true && cancelable === false
+                && custom.detail === null && custom.defaultPrevented === false && threw;
+            event.respondWith(new Response(ok ? 'OK' : `FAIL: ${JSON.stringify(calls)} ${notCancelable} ${cancelable}`));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
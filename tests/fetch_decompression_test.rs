@@ -0,0 +1,285 @@
+mod common;
+
+use common::RecordingOps;
+use openworkers_runtime_jsc::runtime::execute_fetch_streaming;
+use openworkers_runtime_jsc::{
+    Event, HttpMethod, HttpRequest, HttpResponse, OpFuture, OperationsHandler, RequestBody,
+    ResponseBody, Script, StreamChunk, StreamManager, Worker,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+
+/// "Hello, decompressed world!" gzip-encoded
+const GZIP_BODY: &[u8] = &[
+    31, 139, 8, 0, 0, 0, 0, 0, 2, 3, 243, 72, 205, 201, 201, 215, 81, 72, 73, 77, 206, 207, 45, 40,
+    74, 45, 46, 78, 77, 81, 40, 207, 47, 202, 73, 81, 4, 0, 35, 149, 130, 52, 26, 0, 0, 0,
+];
+
+/// "Hello, brotli world!" brotli-encoded (a single uncompressed meta-block)
+const BROTLI_BODY: &[u8] = &[
+    48, 1, 16, 72, 101, 108, 108, 111, 44, 32, 98, 114, 111, 116, 108, 105, 32, 119, 111, 114, 108,
+    100, 33, 3,
+];
+
+/// Serve a single encoded response on a local port; the receiver yields
+/// the request head
+async fn serve_encoded_once(
+    encoding: &'static str,
+    body: &'static [u8],
+) -> (String, oneshot::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (head_tx, head_rx) = oneshot::channel();
+
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+
+        let mut buf = [0u8; 4096];
+        let n = socket.read(&mut buf).await.unwrap_or(0);
+        let _ = head_tx.send(String::from_utf8_lossy(&buf[..n]).to_string());
+
+        let head = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Encoding: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            encoding,
+            body.len()
+        );
+        socket.write_all(head.as_bytes()).await.unwrap();
+        socket.write_all(body).await.unwrap();
+        socket.shutdown().await.unwrap();
+    });
+
+    (format!("http://{}/{}", addr, encoding), head_rx)
+}
+
+/// Fetch `url` with the built-in client, returning the response headers and body
+async fn fetch_text(
+    url: String,
+    headers: HashMap<String, String>,
+) -> (Vec<(String, String)>, String) {
+    let manager = Arc::new(StreamManager::new());
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url,
+        headers,
+        body: RequestBody::None,
+    };
+
+    let (meta, stream_id) = execute_fetch_streaming(request, manager.clone())
+        .await
+        .expect("Fetch should succeed");
+    assert_eq!(meta.status, 200);
+
+    let mut body = Vec::new();
+    loop {
+        match manager
+            .read_chunk(stream_id)
+            .await
+            .expect("Should read chunk")
+        {
+            StreamChunk::Data(bytes) => body.extend_from_slice(&bytes),
+            StreamChunk::Done => break,
+            StreamChunk::Error(e) => panic!("Stream error: {}", e),
+        }
+    }
+
+    (meta.headers, String::from_utf8_lossy(&body).to_string())
+}
+
+/// Value of a header in a raw HTTP request head
+fn head_header(head: &str, name: &str) -> Option<String> {
+    head.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.trim()
+            .eq_ignore_ascii_case(name)
+            .then(|| value.trim().to_string())
+    })
+}
+
+#[tokio::test]
+async fn test_fetch_gzip_response_is_decoded() {
+    let (url, _) = serve_encoded_once("gzip", GZIP_BODY).await;
+
+    let (headers, body) = fetch_text(url, HashMap::new()).await;
+
+    assert!(
+        !headers
+            .iter()
+            .any(|(k, _)| k.eq_ignore_ascii_case("content-encoding")
+                || k.eq_ignore_ascii_case("content-length")),
+        "Encoding headers should be stripped, got: {:?}",
+        headers
+    );
+    assert_eq!(body, "Hello, decompressed world!");
+}
+
+#[tokio::test]
+async fn test_fetch_brotli_response_is_decoded() {
+    let (url, head_rx) = serve_encoded_once("br", BROTLI_BODY).await;
+
+    let (headers, body) = fetch_text(url, HashMap::new()).await;
+    let head = head_rx.await.expect("Server should see the request");
+
+    assert_eq!(
+        head_header(&head, "accept-encoding").as_deref(),
+        Some("gzip, deflate, br")
+    );
+    assert!(
+        !headers
+            .iter()
+            .any(|(k, _)| k.eq_ignore_ascii_case("content-encoding")
+                || k.eq_ignore_ascii_case("content-length")),
+        "Encoding headers should be stripped, got: {:?}",
+        headers
+    );
+    assert_eq!(body, "Hello, brotli world!");
+}
+
+#[tokio::test]
+async fn test_fetch_accept_encoding_can_be_overridden() {
+    let (url, head_rx) = serve_encoded_once("br", BROTLI_BODY).await;
+
+    let mut headers = HashMap::new();
+    headers.insert("Accept-Encoding".to_string(), "br".to_string());
+    let (_, body) = fetch_text(url, headers).await;
+    let head = head_rx.await.expect("Server should see the request");
+
+    assert_eq!(head_header(&head, "accept-encoding").as_deref(), Some("br"));
+    assert_eq!(body, "Hello, brotli world!");
+}
+
+/// Transport returning encoded bodies as-is, like an embedder that doesn't
+/// decode: `/gzip` and `/br` in 5-byte chunks
+struct EncodedOps;
+
+impl OperationsHandler for EncodedOps {
+    fn handle_fetch(&self, request: HttpRequest) -> OpFuture<'_, Result<HttpResponse, String>> {
+        Box::pin(async move {
+            let (encoding, body) = if request.url.ends_with("/gzip") {
+                ("gzip", GZIP_BODY)
+            } else {
+                ("br", BROTLI_BODY)
+            };
+
+            let (tx, rx) = tokio::sync::mpsc::channel(4);
+            tokio::spawn(async move {
+                for chunk in body.chunks(5) {
+                    if tx.send(Ok(bytes::Bytes::from_static(chunk))).await.is_err() {
+                        break;
+                    }
+                }
+            });
+
+            Ok(HttpResponse {
+                status: 200,
+                headers: vec![
+                    ("content-type".to_string(), "text/plain".to_string()),
+                    ("content-encoding".to_string(), encoding.to_string()),
+                    ("content-length".to_string(), body.len().to_string()),
+                ],
+                body: ResponseBody::Stream(rx),
+            })
+        })
+    }
+}
+
+/// Test that fetch() from a worker decodes bodies its transport left encoded
+#[tokio::test]
+async fn test_worker_fetch_decodes_transport_response() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            event.respondWith((async () => {
+                const response = await fetch('https://api.example.com/gzip');
+                return new Response(JSON.stringify({
+                    text: await response.text(),
+                    encoding: response.headers.get('content-encoding'),
+                    length: response.headers.get('content-length')
+                }));
+            })());
+        });
+    "#;
+
+    let mut worker = Worker::new_with_ops(Script::new(script), None, Arc::new(EncodedOps))
+        .await
+        .expect("Worker should initialize");
+
+    let (task, rx) = Event::fetch(HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    });
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    let result: serde_json::Value = serde_json::from_slice(&body).expect("Valid JSON");
+
+    assert_eq!(result["text"], "Hello, decompressed world!");
+    assert_eq!(result["encoding"], serde_json::Value::Null);
+    assert_eq!(result["length"], serde_json::Value::Null);
+}
+
+/// Test that worker fetches ask for compressed bodies and get brotli decoded
+#[tokio::test]
+async fn test_worker_fetch_sends_accept_encoding_and_decodes_brotli() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            event.respondWith((async () => {
+                const plain = await fetch('https://api.example.com/br');
+                const custom = await fetch('https://api.example.com/br', {
+                    headers: { 'Accept-Encoding': 'br' }
+                });
+                return new Response(JSON.stringify({
+                    text: await plain.text(),
+                    custom: await custom.text(),
+                    encoding: plain.headers.get('content-encoding')
+                }));
+            })());
+        });
+    "#;
+
+    let ops = RecordingOps::new(Arc::new(EncodedOps));
+
+    let mut worker = Worker::new_with_ops(Script::new(script), None, ops.clone())
+        .await
+        .expect("Worker should initialize");
+
+    let (task, rx) = Event::fetch(HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    });
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    let result: serde_json::Value = serde_json::from_slice(&body).expect("Valid JSON");
+
+    assert_eq!(result["text"], "Hello, brotli world!");
+    assert_eq!(result["custom"], "Hello, brotli world!");
+    assert_eq!(result["encoding"], serde_json::Value::Null);
+
+    let accept_encoding: Vec<Option<String>> = ops
+        .recorded_requests()
+        .iter()
+        .map(|request| {
+            request
+                .headers
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case("accept-encoding"))
+                .map(|(_, value)| value.clone())
+        })
+        .collect();
+    assert_eq!(
+        accept_encoding,
+        vec![
+            Some("gzip, deflate, br".to_string()),
+            Some("br".to_string())
+        ]
+    );
+}
@@ -0,0 +1,204 @@
+mod common;
+
+use common::{EchoOps, RecordingOps, TestRunner};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_fetch_stream_upload_echoed_as_stream() {
+    let mut runner = TestRunner::new_with_ops(Arc::new(EchoOps { chunk_size: 7 }));
+
+    let script = r#"
+        globalThis.result = null;
+
+        // 3 upload chunks with every byte value, read back in 7-byte pieces
+        const chunks = [
+            new Uint8Array(256).map((_, i) => i),
+            new Uint8Array(100).map((_, i) => 255 - i),
+            new Uint8Array([0, 1, 2])
+        ];
+        const expected = new Uint8Array(chunks.reduce((sum, c) => sum + c.length, 0));
+        let offset = 0;
+        for (const chunk of chunks) {
+            expected.set(chunk, offset);
+            offset += chunk.length;
+        }
+
+        const upload = new ReadableStream({
+            start(controller) {
+                for (const chunk of chunks) {
+                    controller.enqueue(chunk);
+                }
+                controller.close();
+            }
+        });
+
+        (async () => {
+            const response = await fetch('https://echo.local/', { method: 'POST', body: upload });
+            const reader = response.body.getReader();
+
+            const received = [];
+            let reads = 0;
+            while (true) {
+                const { done, value } = await reader.read();
+                if (done) break;
+                reads++;
+                received.push(...value);
+            }
+
+            const equal = received.length === expected.length
+                && received.every((b, i) => b === expected[i]);
+            globalThis.result = equal && reads > 1 ? 'OK' : `FAIL: ${received.length} bytes in ${reads} reads`;
+        })().catch(error => { globalThis.result = 'error: ' + error.message; });
+    "#;
+
+    runner.execute(script).expect("Script should execute");
+    runner.process_for(Duration::from_millis(300)).await;
+
+    let result = runner
+        .runtime
+        .evaluate("globalThis.result")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    assert_eq!(result, "OK");
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_cloned_stream_request_forwarded_and_read() {
+    let mut runner = TestRunner::new_with_ops(Arc::new(EchoOps { chunk_size: 16 }));
+
+    let script = r#"
+        globalThis.result = null;
+
+        const parts = ['first chunk|', 'second chunk|', 'third chunk'];
+        const body = new ReadableStream({
+            start(controller) {
+                const encoder = new TextEncoder();
+                for (const part of parts) {
+                    controller.enqueue(encoder.encode(part));
+                }
+                controller.close();
+            }
+        });
+
+        const request = new Request('https://echo.local/upload', {
+            method: 'POST',
+            headers: { 'x-forwarded': 'yes' },
+            body
+        });
+        const copy = request.clone();
+
+        (async () => {
+            // Forward the original, read the clone
+            const [forwarded, read] = await Promise.all([
+                fetch(request).then(response => response.text()),
+                copy.text()
+            ]);
+
+            let refetch;
+            try {
+                await fetch(request);
+                refetch = 'no error';
+            } catch (e) {
+                refetch = e.name;
+            }
+
+            const expected = parts.join('');
+            globalThis.result = [
+                forwarded === expected,
+                read === expected,
+                request.bodyUsed,
+                copy.bodyUsed,
+                refetch
+            ].join(',');
+        })().catch(error => { globalThis.result = 'error: ' + error.message; });
+    "#;
+
+    runner.execute(script).expect("Script should execute");
+    runner.process_for(Duration::from_millis(300)).await;
+
+    let result = runner
+        .runtime
+        .evaluate("globalThis.result")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    assert_eq!(result, "true,true,true,true,TypeError");
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_forwarded_request_keeps_repeated_headers() {
+    let ops = RecordingOps::new(Arc::new(EchoOps { chunk_size: 16 }));
+    let mut runner = TestRunner::new_with_ops(ops.clone());
+
+    let script = r#"
+        globalThis.result = null;
+
+        const headers = new Headers();
+        headers.append('X-Tag', 'first');
+        headers.append('X-Tag', 'second');
+        headers.append('Set-Cookie', 'a=1');
+        headers.append('Set-Cookie', 'b=2');
+
+        const request = new Request('https://echo.local/forward', {
+            method: 'POST',
+            headers,
+            body: 'payload'
+        });
+
+        (async () => {
+            const [forwarded, pairs] = await Promise.all([
+                fetch(request).then(response => response.text()),
+                fetch('https://echo.local/pairs', {
+                    headers: [['x-tag', 'one'], ['x-tag', 'two']]
+                }).then(response => response.text())
+            ]);
+            globalThis.result = forwarded + '|' + pairs;
+        })().catch(error => { globalThis.result = 'error: ' + error.message; });
+    "#;
+
+    runner.execute(script).expect("Script should execute");
+    runner.process_for(Duration::from_millis(300)).await;
+
+    let result = runner
+        .runtime
+        .evaluate("globalThis.result")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    assert_eq!(result, "payload|");
+
+    let requests = ops.recorded_requests();
+    let header = |url: &str, name: &str| {
+        requests
+            .iter()
+            .find(|request| request.url.ends_with(url))
+            .and_then(|request| {
+                request
+                    .headers
+                    .iter()
+                    .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                    .map(|(_, value)| value.clone())
+            })
+    };
+
+    assert_eq!(
+        header("/forward", "x-tag").as_deref(),
+        Some("first, second")
+    );
+    assert_eq!(
+        header("/forward", "set-cookie").as_deref(),
+        Some("a=1, b=2")
+    );
+    assert_eq!(header("/pairs", "x-tag").as_deref(), Some("one, two"));
+
+    runner.shutdown().await;
+}
@@ -0,0 +1,253 @@
+mod common;
+
+use common::TestRunner;
+use openworkers_runtime_jsc::{
+    FetchPolicy, HttpRequest, HttpResponse, OpFuture, OperationsHandle, OperationsHandler,
+    ResponseBody,
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Mock operations handler that answers every fetch with 200
+struct MockOps;
+
+impl OperationsHandler for MockOps {
+    fn handle_fetch(&self, request: HttpRequest) -> OpFuture<'_, Result<HttpResponse, String>> {
+        Box::pin(async move {
+            if request.url.contains("/many-headers") {
+                let headers = (0..500)
+                    .map(|i| (format!("x-upstream-{}", i), "value".to_string()))
+                    .collect();
+
+                return Ok(HttpResponse {
+                    status: 200,
+                    headers,
+                    body: ResponseBody::None,
+                });
+            }
+
+            Ok(HttpResponse {
+                status: 200,
+                headers: vec![("content-type".to_string(), "text/plain".to_string())],
+                body: ResponseBody::Bytes(format!("fetched {}", request.url).into()),
+            })
+        })
+    }
+}
+
+fn ops() -> OperationsHandle {
+    Arc::new(MockOps)
+}
+
+#[tokio::test]
+async fn test_fetch_denied_host_rejects() {
+    let policy = FetchPolicy::new().deny_host("evil.example");
+    let mut runner = TestRunner::new_with_policy(ops(), policy);
+
+    let script = r#"
+        globalThis.denied = null;
+        globalThis.allowed = null;
+
+        fetch('https://evil.example/steal')
+            .then(() => { globalThis.denied = 'resolved'; })
+            .catch(error => { globalThis.denied = String(error.message); });
+
+        fetch('https://good.example/ok')
+            .then(response => response.text())
+            .then(text => { globalThis.allowed = text; })
+            .catch(error => { globalThis.allowed = 'error: ' + error.message; });
+    "#;
+
+    runner.execute(script).expect("Script should execute");
+    runner.process_for(Duration::from_millis(200)).await;
+
+    let denied = runner
+        .runtime
+        .evaluate("globalThis.denied")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    assert!(
+        denied.contains("evil.example") && denied.contains("denied"),
+        "Denied host should reject, got: {}",
+        denied
+    );
+
+    let allowed = runner
+        .runtime
+        .evaluate("globalThis.allowed")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    assert_eq!(allowed, "fetched https://good.example/ok");
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_fetch_allowlist_rejects_other_hosts() {
+    let policy = FetchPolicy::new().allow_host("api.example.com");
+    let mut runner = TestRunner::new_with_policy(ops(), policy);
+
+    let script = r#"
+        globalThis.result = null;
+
+        fetch('https://other.example.com/')
+            .then(() => { globalThis.result = 'resolved'; })
+            .catch(error => { globalThis.result = error instanceof TypeError ? 'rejected' : 'wrong'; });
+    "#;
+
+    runner.execute(script).expect("Script should execute");
+    runner.process_for(Duration::from_millis(200)).await;
+
+    let result = runner
+        .runtime
+        .evaluate("globalThis.result")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    assert_eq!(result, "rejected");
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_fetch_private_ip_blocked() {
+    let policy = FetchPolicy::new().block_private_ips();
+    let mut runner = TestRunner::new_with_policy(ops(), policy);
+
+    let script = r#"
+        globalThis.result = null;
+
+        fetch('http://127.0.0.1/')
+            .then(() => { globalThis.result = 'resolved'; })
+            .catch(error => { globalThis.result = error.message; });
+    "#;
+
+    runner.execute(script).expect("Script should execute");
+    runner.process_for(Duration::from_millis(200)).await;
+
+    let result = runner
+        .runtime
+        .evaluate("globalThis.result")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    assert!(
+        result.contains("private address"),
+        "Loopback fetch should be blocked, got: {}",
+        result
+    );
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_fetch_private_ip_allowed_by_default() {
+    let mut runner = TestRunner::new_with_ops(ops());
+
+    let script = r#"
+        globalThis.result = null;
+
+        fetch('http://127.0.0.1/')
+            .then(response => { globalThis.result = response.status; })
+            .catch(error => { globalThis.result = error.message; });
+    "#;
+
+    runner.execute(script).expect("Script should execute");
+    runner.process_for(Duration::from_millis(200)).await;
+
+    let result = runner.runtime.evaluate("globalThis.result").unwrap();
+    assert_eq!(result.to_number(&runner.runtime.context).unwrap(), 200.0);
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_fetch_too_many_request_headers_rejected() {
+    let policy = FetchPolicy::new()
+        .max_header_count(100)
+        .max_header_bytes(8 * 1024);
+    let mut runner = TestRunner::new_with_policy(ops(), policy);
+
+    let script = r#"
+        globalThis.bomb = null;
+        globalThis.small = null;
+
+        const headers = {};
+        for (let i = 0; i < 5000; i++) {
+            headers['x-bomb-' + i] = 'value';
+        }
+
+        fetch('https://example.com/', { headers })
+            .then(() => { globalThis.bomb = 'resolved'; })
+            .catch(error => { globalThis.bomb = error.message; });
+
+        fetch('https://example.com/', { headers: { 'x-small': '1' } })
+            .then(response => { globalThis.small = String(response.status); })
+            .catch(error => { globalThis.small = error.message; });
+    "#;
+
+    runner.execute(script).expect("Script should execute");
+    runner.process_for(Duration::from_millis(200)).await;
+
+    let bomb = runner
+        .runtime
+        .evaluate("globalThis.bomb")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    assert!(
+        bomb.contains("Too many headers"),
+        "Header bomb should be rejected, got: {}",
+        bomb
+    );
+
+    let small = runner
+        .runtime
+        .evaluate("globalThis.small")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    assert_eq!(small, "200");
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_fetch_too_many_response_headers_rejected() {
+    let policy = FetchPolicy::new().max_header_count(100);
+    let mut runner = TestRunner::new_with_policy(ops(), policy);
+
+    let script = r#"
+        globalThis.result = null;
+
+        fetch('https://example.com/many-headers')
+            .then(() => { globalThis.result = 'resolved'; })
+            .catch(error => { globalThis.result = error.message; });
+    "#;
+
+    runner.execute(script).expect("Script should execute");
+    runner.process_for(Duration::from_millis(200)).await;
+
+    let result = runner
+        .runtime
+        .evaluate("globalThis.result")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    assert!(
+        result.contains("Upstream response rejected"),
+        "Upstream header bomb should be rejected, got: {}",
+        result
+    );
+
+    runner.shutdown().await;
+}
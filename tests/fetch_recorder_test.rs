@@ -0,0 +1,101 @@
+mod common;
+
+use common::TestRunner;
+use openworkers_runtime_jsc::{
+    FetchRecorder, FetchReplayer, HttpRequest, HttpResponse, OpFuture, OperationsHandler,
+    ResponseBody,
+};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Mock "network" that counts how many fetches reach it
+struct CountingOps {
+    calls: AtomicUsize,
+}
+
+impl OperationsHandler for CountingOps {
+    fn handle_fetch(&self, request: HttpRequest) -> OpFuture<'_, Result<HttpResponse, String>> {
+        Box::pin(async move {
+            let n = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+
+            Ok(HttpResponse {
+                status: 201,
+                headers: vec![("x-call".to_string(), n.to_string())],
+                body: ResponseBody::Bytes(format!("response {} for {}", n, request.url).into()),
+            })
+        })
+    }
+}
+
+const SCRIPT: &str = r#"
+    globalThis.output = null;
+
+    (async () => {
+        const a = await fetch('https://api.example.com/a');
+        const b = await fetch('https://api.example.com/b');
+        globalThis.output = [
+            a.status, a.headers.get('x-call'), await a.text(),
+            b.status, b.headers.get('x-call'), await b.text()
+        ].join('|');
+    })().catch(error => { globalThis.output = 'error: ' + error.message; });
+"#;
+
+async fn run_script(runner: &mut TestRunner) -> String {
+    runner.execute(SCRIPT).expect("Script should execute");
+    runner.process_for(Duration::from_millis(200)).await;
+
+    runner
+        .runtime
+        .evaluate("globalThis.output")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string()
+}
+
+#[tokio::test]
+async fn test_record_and_replay_fetches() {
+    let path = std::env::temp_dir().join(format!("fetch-recording-{}.json", uuid::Uuid::new_v4()));
+
+    // Record against the mock network
+    let network = Arc::new(CountingOps {
+        calls: AtomicUsize::new(0),
+    });
+    let recorder = Arc::new(FetchRecorder::new(network.clone()));
+    let mut runner = TestRunner::new_with_ops(recorder.clone());
+    let recorded = run_script(&mut runner).await;
+    runner.shutdown().await;
+
+    assert_eq!(
+        recorded,
+        "201|1|response 1 for https://api.example.com/a|201|2|response 2 for https://api.example.com/b"
+    );
+    assert_eq!(recorder.entries().len(), 2);
+    recorder.save(&path).expect("Recording should be saved");
+
+    // Replay offline: the network is never reached
+    let replayer = Arc::new(FetchReplayer::load(&path).expect("Recording should load"));
+    let mut runner = TestRunner::new_with_ops(replayer);
+    let replayed = run_script(&mut runner).await;
+    runner.shutdown().await;
+
+    assert_eq!(replayed, recorded);
+    assert_eq!(network.calls.load(Ordering::SeqCst), 2);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn test_replay_unrecorded_fetch_rejects() {
+    let mut runner = TestRunner::new_with_ops(Arc::new(FetchReplayer::new(Vec::new())));
+
+    let output = run_script(&mut runner).await;
+    assert!(
+        output.contains("No recorded response") && output.contains("https://api.example.com/a"),
+        "Unrecorded fetch should reject, got: {}",
+        output
+    );
+
+    runner.shutdown().await;
+}
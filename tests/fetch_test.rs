@@ -2,7 +2,8 @@ mod common;
 
 use common::TestRunner;
 use openworkers_runtime_jsc::{
-    HttpRequest, HttpResponse, OpFuture, OperationsHandle, OperationsHandler, ResponseBody,
+    HttpRequest, HttpResponse, OpFuture, OperationsHandle, OperationsHandler, RequestBody,
+    ResponseBody,
 };
 use std::sync::Arc;
 use std::time::Duration;
@@ -32,6 +33,130 @@ impl OperationsHandler for MockOps {
                 });
             }
 
+            if url.contains("/redirect") {
+                return Ok(HttpResponse {
+                    status: 302,
+                    headers: vec![("location".to_string(), "/final".to_string())],
+                    body: ResponseBody::None,
+                });
+            }
+
+            if url.contains("/final") {
+                return Ok(HttpResponse {
+                    status: 200,
+                    headers: vec![("content-type".to_string(), "text/plain".to_string())],
+                    body: ResponseBody::Bytes("final".into()),
+                });
+            }
+
+            if url.contains("/echo-bytes") {
+                // Echo the raw request body bytes as a comma-separated list
+                let bytes = match &request.body {
+                    RequestBody::Bytes(bytes) => bytes.to_vec(),
+                    _ => Vec::new(),
+                };
+                let body = bytes
+                    .iter()
+                    .map(|b| b.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",");
+
+                return Ok(HttpResponse {
+                    status: 200,
+                    headers: vec![("content-type".to_string(), "text/plain".to_string())],
+                    body: ResponseBody::Bytes(body.into()),
+                });
+            }
+
+            if url.contains("/cookies") {
+                return Ok(HttpResponse {
+                    status: 200,
+                    headers: vec![
+                        ("set-cookie".to_string(), "a=1; Path=/".to_string()),
+                        ("content-type".to_string(), "text/plain".to_string()),
+                        ("set-cookie".to_string(), "b=2; HttpOnly".to_string()),
+                    ],
+                    body: ResponseBody::None,
+                });
+            }
+
+            if url.contains("/empty") {
+                return Ok(HttpResponse {
+                    status: 200,
+                    headers: vec![("content-length".to_string(), "0".to_string())],
+                    body: ResponseBody::Bytes(Vec::new().into()),
+                });
+            }
+
+            if url.contains("/utf8-stream") {
+                // Multibyte UTF-8 text in 3-byte chunks, splitting characters across chunks
+                let (tx, rx) = tokio::sync::mpsc::channel(1);
+                tokio::spawn(async move {
+                    for chunk in "h\u{e9}llo w\u{f6}rld \u{2713} \u{1f600}!"
+                        .as_bytes()
+                        .chunks(3)
+                    {
+                        if tx.send(Ok(chunk.to_vec().into())).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+
+                return Ok(HttpResponse {
+                    status: 200,
+                    headers: vec![(
+                        "content-type".to_string(),
+                        "text/plain; charset=utf-8".to_string(),
+                    )],
+                    body: ResponseBody::Stream(rx),
+                });
+            }
+
+            if url.contains("/slow-stream") {
+                // 4 chunks of 256 bytes, 50ms apart
+                let (tx, rx) = tokio::sync::mpsc::channel(1);
+                tokio::spawn(async move {
+                    for i in 0..4u8 {
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        if tx.send(Ok(vec![i; 256].into())).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+
+                return Ok(HttpResponse {
+                    status: 200,
+                    headers: vec![],
+                    body: ResponseBody::Stream(rx),
+                });
+            }
+
+            if url.contains("/stream") {
+                // 4 chunks of 256 bytes with a matching Content-Length
+                let (tx, rx) = tokio::sync::mpsc::channel(1);
+                tokio::spawn(async move {
+                    for i in 0..4u8 {
+                        if tx.send(Ok(vec![i; 256].into())).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+
+                return Ok(HttpResponse {
+                    status: 200,
+                    headers: vec![("content-length".to_string(), "1024".to_string())],
+                    body: ResponseBody::Stream(rx),
+                });
+            }
+
+            if url.contains("/custom-header") {
+                return Ok(HttpResponse {
+                    status: 200,
+                    headers: vec![("X-Custom-Header".to_string(), "value".to_string())],
+                    body: ResponseBody::None,
+                });
+            }
+
             if url.contains("/post") {
                 return Ok(HttpResponse {
                     status: 200,
@@ -400,3 +525,721 @@ async fn test_response_headers_api() {
 
     runner.shutdown().await;
 }
+
+#[tokio::test]
+async fn test_fetch_headers_raw_preserves_casing() {
+    let mut runner = TestRunner::new_with_ops(ops());
+
+    let script = r#"
+        globalThis.rawResult = null;
+
+        fetch('https://echo.workers.rocks/custom-header')
+            .then(response => {
+                const raw = response.headers.raw();
+                globalThis.rawResult = JSON.stringify({
+                    raw: raw,
+                    lower: response.headers.get('x-custom-header'),
+                    upper: response.headers.get('X-CUSTOM-HEADER')
+                });
+            })
+            .catch(error => {
+                globalThis.rawResult = String(error);
+            });
+    "#;
+
+    runner.execute(script).expect("fetch should execute");
+    runner.process_for(Duration::from_millis(200)).await;
+
+    let result = runner
+        .runtime
+        .evaluate("globalThis.rawResult")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    let result: serde_json::Value = serde_json::from_str(&result).expect("Valid JSON");
+
+    assert_eq!(
+        result["raw"],
+        serde_json::json!([["X-Custom-Header", "value"]])
+    );
+    assert_eq!(result["lower"], "value");
+    assert_eq!(result["upper"], "value");
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_fetch_binary_body() {
+    let mut runner = TestRunner::new_with_ops(ops());
+
+    let script = r#"
+        globalThis.uint8Result = null;
+        globalThis.bufferResult = null;
+
+        fetch('https://echo.workers.rocks/echo-bytes', {
+            method: 'POST',
+            body: new Uint8Array([0, 159, 146, 150])
+        })
+            .then(response => response.text())
+            .then(text => { globalThis.uint8Result = text; })
+            .catch(error => { globalThis.uint8Result = String(error); });
+
+        fetch('https://echo.workers.rocks/echo-bytes', {
+            method: 'POST',
+            body: new Uint8Array([255, 0, 128]).buffer
+        })
+            .then(response => response.text())
+            .then(text => { globalThis.bufferResult = text; })
+            .catch(error => { globalThis.bufferResult = String(error); });
+    "#;
+
+    runner.execute(script).expect("fetch should execute");
+    runner.process_for(Duration::from_millis(200)).await;
+
+    let uint8_result = runner
+        .runtime
+        .evaluate("globalThis.uint8Result")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    assert_eq!(uint8_result, "0,159,146,150");
+
+    let buffer_result = runner
+        .runtime
+        .evaluate("globalThis.bufferResult")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    assert_eq!(buffer_result, "255,0,128");
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_fetch_redirect_modes() {
+    let mut runner = TestRunner::new_with_ops(ops());
+
+    let script = r#"
+        globalThis.follow = null;
+        globalThis.manual = null;
+        globalThis.error = null;
+
+        fetch('https://echo.workers.rocks/redirect')
+            .then(async response => {
+                globalThis.follow = JSON.stringify({
+                    status: response.status,
+                    redirected: response.redirected,
+                    url: response.url,
+                    body: await response.text()
+                });
+            })
+            .catch(error => { globalThis.follow = String(error); });
+
+        fetch('https://echo.workers.rocks/redirect', { redirect: 'manual' })
+            .then(response => {
+                globalThis.manual = JSON.stringify({
+                    status: response.status,
+                    redirected: response.redirected,
+                    location: response.headers.get('location')
+                });
+            })
+            .catch(error => { globalThis.manual = String(error); });
+
+        fetch('https://echo.workers.rocks/redirect', { redirect: 'error' })
+            .then(() => { globalThis.error = 'resolved'; })
+            .catch(error => { globalThis.error = error instanceof TypeError ? 'rejected' : String(error); });
+    "#;
+
+    runner.execute(script).expect("fetch should execute");
+    runner.process_for(Duration::from_millis(200)).await;
+
+    let get = |runner: &mut TestRunner, name: &str| {
+        runner
+            .runtime
+            .evaluate(name)
+            .unwrap()
+            .to_js_string(&runner.runtime.context)
+            .unwrap()
+            .to_string()
+    };
+
+    let follow: serde_json::Value =
+        serde_json::from_str(&get(&mut runner, "globalThis.follow")).expect("Valid JSON");
+    assert_eq!(follow["status"], 200);
+    assert_eq!(follow["redirected"], true);
+    assert_eq!(follow["url"], "https://echo.workers.rocks/final");
+    assert_eq!(follow["body"], "final");
+
+    let manual: serde_json::Value =
+        serde_json::from_str(&get(&mut runner, "globalThis.manual")).expect("Valid JSON");
+    assert_eq!(manual["status"], 302);
+    assert_eq!(manual["redirected"], false);
+    assert_eq!(manual["location"], "/final");
+
+    assert_eq!(get(&mut runner, "globalThis.error"), "rejected");
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_fetch_response_url() {
+    let mut runner = TestRunner::new_with_ops(ops());
+
+    let script = r#"
+        globalThis.urlResult = null;
+
+        fetch('https://echo.workers.rocks/get?x=1')
+            .then(response => {
+                globalThis.urlResult = JSON.stringify({
+                    url: response.url,
+                    redirected: response.redirected
+                });
+            })
+            .catch(error => { globalThis.urlResult = String(error); });
+    "#;
+
+    runner.execute(script).expect("fetch should execute");
+    runner.process_for(Duration::from_millis(200)).await;
+
+    let result = runner
+        .runtime
+        .evaluate("globalThis.urlResult")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    let result: serde_json::Value = serde_json::from_str(&result).expect("Valid JSON");
+
+    assert_eq!(result["url"], "https://echo.workers.rocks/get?x=1");
+    assert_eq!(result["redirected"], false);
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_fetch_abort_with_custom_reason() {
+    let mut runner = TestRunner::new_with_ops(ops());
+
+    let script = r#"
+        globalThis.abortResult = null;
+
+        const controller = new AbortController();
+        const reason = { code: 'cancelled' };
+
+        fetch('https://echo.workers.rocks/json', { signal: controller.signal })
+            .then(() => { globalThis.abortResult = 'resolved'; })
+            .catch(error => {
+                globalThis.abortResult = JSON.stringify({
+                    same: error === reason,
+                    aborted: controller.signal.aborted
+                });
+            });
+
+        controller.abort(reason);
+    "#;
+
+    runner.execute(script).expect("fetch should execute");
+    runner.process_for(Duration::from_millis(200)).await;
+
+    let result = runner
+        .runtime
+        .evaluate("globalThis.abortResult")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    let result: serde_json::Value = serde_json::from_str(&result).expect("Valid JSON");
+
+    assert_eq!(result["same"], true);
+    assert_eq!(result["aborted"], true);
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_fetch_already_aborted_signal() {
+    let mut runner = TestRunner::new_with_ops(ops());
+
+    let script = r#"
+        globalThis.abortResult = null;
+
+        const signal = AbortSignal.abort();
+
+        fetch('https://echo.workers.rocks/json', { signal })
+            .then(() => { globalThis.abortResult = 'resolved'; })
+            .catch(error => { globalThis.abortResult = error.name; });
+    "#;
+
+    runner.execute(script).expect("fetch should execute");
+    runner.process_for(Duration::from_millis(100)).await;
+
+    let result = runner
+        .runtime
+        .evaluate("globalThis.abortResult")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    assert_eq!(result, "AbortError");
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_clear_timeout_ignores_fetch_ids() {
+    let mut runner = TestRunner::new_with_ops(ops());
+
+    let script = r#"
+        globalThis.fetchResult = null;
+
+        fetch('https://echo.workers.rocks/json')
+            .then(response => response.json())
+            .then(data => { globalThis.fetchResult = data.message; })
+            .catch(error => { globalThis.fetchResult = 'error: ' + error; });
+
+        // Timer and fetch IDs come from the same counter: clearing every ID
+        // around the pending fetch must only affect timers
+        const timer = setTimeout(() => {}, 1000);
+        for (let id = 0; id < timer + 1000; id++) {
+            clearTimeout(id);
+            clearInterval(id);
+            clearImmediate(id);
+        }
+    "#;
+
+    runner.execute(script).expect("fetch should execute");
+    runner.process_for(Duration::from_millis(200)).await;
+
+    let result = runner
+        .runtime
+        .evaluate("globalThis.fetchResult")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    assert_eq!(result, "hello");
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_fetch_buffered_many_small_responses() {
+    let mut runner = TestRunner::new_with_ops(ops());
+
+    let script = r#"
+        globalThis.bufferedResult = null;
+
+        const requests = [];
+        for (let i = 0; i < 50; i++) {
+            requests.push(
+                fetch('https://echo.workers.rocks/json?i=' + i, { buffer: true })
+                    .then(async response => ({
+                        native: response._nativeStreamId !== null || response._isStreaming === true,
+                        status: response.status,
+                        type: response.headers.get('content-type'),
+                        data: await response.json()
+                    }))
+            );
+        }
+
+        Promise.all(requests)
+            .then(results => {
+                globalThis.bufferedResult = JSON.stringify({
+                    count: results.length,
+                    allOk: results.every(r => r.status === 200 && r.data.value === 42),
+                    native: results.filter(r => r.native).length,
+                    type: results[0].type
+                });
+            })
+            .catch(error => { globalThis.bufferedResult = String(error); });
+    "#;
+
+    runner.execute(script).expect("fetch should execute");
+    runner.process_for(Duration::from_millis(300)).await;
+
+    let result = runner
+        .runtime
+        .evaluate("globalThis.bufferedResult")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    let result: serde_json::Value = serde_json::from_str(&result).expect("Valid JSON");
+
+    assert_eq!(result["count"], 50);
+    assert_eq!(result["allOk"], true);
+    assert_eq!(result["type"], "application/json");
+    // Buffered responses never go through a native stream (no per-chunk reads)
+    assert_eq!(result["native"], 0);
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_fetch_no_cors_opaque_response() {
+    let mut runner = TestRunner::new_with_ops(ops());
+
+    let script = r#"
+        globalThis.modeResult = null;
+
+        (async () => {
+            const opaque = await fetch('https://echo.workers.rocks/custom-header', { mode: 'no-cors' });
+            const body = await opaque.text();
+            const clone = opaque.clone();
+
+            const cors = await fetch('https://echo.workers.rocks/custom-header', { mode: 'cors' });
+
+            const sameOrigin = await fetch('https://echo.workers.rocks/get', { mode: 'same-origin' })
+                .then(() => 'resolved', e => e.name);
+
+            globalThis.modeResult = JSON.stringify({
+                type: opaque.type,
+                status: opaque.status,
+                ok: opaque.ok,
+                headers: [...opaque.headers].length,
+                body: opaque.body,
+                text: body,
+                cloneType: clone.type,
+                cloneStatus: clone.status,
+                corsType: cors.type,
+                corsHeader: cors.headers.get('x-custom-header'),
+                sameOrigin
+            });
+        })().catch(error => { globalThis.modeResult = String(error); });
+    "#;
+
+    runner.execute(script).expect("fetch should execute");
+    runner.process_for(Duration::from_millis(200)).await;
+
+    let result = runner
+        .runtime
+        .evaluate("globalThis.modeResult")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    let result: serde_json::Value = serde_json::from_str(&result).expect("Valid JSON");
+
+    assert_eq!(result["type"], "opaque");
+    assert_eq!(result["status"], 0);
+    assert_eq!(result["ok"], false);
+    assert_eq!(result["headers"], 0);
+    assert_eq!(result["body"], serde_json::Value::Null);
+    assert_eq!(result["text"], "");
+    assert_eq!(result["cloneType"], "opaque");
+    assert_eq!(result["cloneStatus"], 0);
+    assert_eq!(result["corsType"], "cors");
+    assert_eq!(result["corsHeader"], "value");
+    assert_eq!(result["sameOrigin"], "TypeError");
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_fetch_preserves_multiple_set_cookie_headers() {
+    let mut runner = TestRunner::new_with_ops(ops());
+
+    let script = r#"
+        globalThis.cookieResult = null;
+
+        fetch('https://echo.workers.rocks/cookies')
+            .then(response => {
+                globalThis.cookieResult = JSON.stringify({
+                    cookies: response.headers.getSetCookie(),
+                    joined: response.headers.get('set-cookie'),
+                    entries: [...response.headers].filter(([name]) => name === 'set-cookie').length,
+                    contentType: response.headers.get('content-type')
+                });
+            })
+            .catch(error => { globalThis.cookieResult = String(error); });
+    "#;
+
+    runner.execute(script).expect("fetch should execute");
+    runner.process_for(Duration::from_millis(200)).await;
+
+    let result = runner
+        .runtime
+        .evaluate("globalThis.cookieResult")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    let result: serde_json::Value = serde_json::from_str(&result).expect("Valid JSON");
+
+    assert_eq!(
+        result["cookies"],
+        serde_json::json!(["a=1; Path=/", "b=2; HttpOnly"])
+    );
+    assert_eq!(result["joined"], "a=1; Path=/, b=2; HttpOnly");
+    assert_eq!(result["entries"], 2);
+    assert_eq!(result["contentType"], "text/plain");
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_fetch_progress_reaches_total() {
+    let mut runner = TestRunner::new_with_ops(ops());
+
+    let script = r#"
+        globalThis.result = null;
+        const events = [];
+
+        fetch('https://echo.workers.rocks/stream', {
+            method: 'POST',
+            body: 'x'.repeat(100),
+            onprogress: (event) => events.push(event)
+        })
+            .then(response => response.arrayBuffer())
+            .then(body => {
+                const downloads = events.filter(e => e.type === 'download');
+                const loaded = downloads.map(e => e.loaded);
+                const last = downloads[downloads.length - 1];
+                globalThis.result = {
+                    upload: events.filter(e => e.type === 'upload').map(e => [e.loaded, e.total]),
+                    increasing: loaded.every((n, i) => i === 0 || n >= loaded[i - 1]),
+                    last: [last.loaded, last.total, last.lengthComputable],
+                    bodyLength: body.byteLength
+                };
+            })
+            .catch(error => {
+                globalThis.result = { error: String(error) };
+            });
+    "#;
+
+    runner.execute(script).expect("fetch should execute");
+    runner.process_for(Duration::from_millis(500)).await;
+
+    let result = runner
+        .runtime
+        .evaluate("JSON.stringify(globalThis.result)")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+
+    assert_eq!(
+        result,
+        r#"{"upload":[[100,100]],"increasing":true,"last":[1024,1024,true],"bodyLength":1024}"#
+    );
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_fetch_body_pipe_through_text_decoder_stream() {
+    let mut runner = TestRunner::new_with_ops(ops());
+
+    let script = r#"
+        globalThis.result = null;
+
+        fetch('https://echo.workers.rocks/utf8-stream')
+            .then(async response => {
+                const chunks = [];
+                for await (const chunk of response.body.pipeThrough(new TextDecoderStream())) {
+                    chunks.push(chunk);
+                }
+                globalThis.result = {
+                    text: chunks.join(''),
+                    allStrings: chunks.every(chunk => typeof chunk === 'string'),
+                    chunked: chunks.length > 1
+                };
+            })
+            .catch(error => {
+                globalThis.result = { error: String(error) };
+            });
+    "#;
+
+    runner.execute(script).expect("fetch should execute");
+    runner.process_for(Duration::from_millis(500)).await;
+
+    let result = runner
+        .runtime
+        .evaluate("JSON.stringify(globalThis.result)")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+
+    assert_eq!(
+        result,
+        "{\"text\":\"h\u{e9}llo w\u{f6}rld \u{2713} \u{1f600}!\",\"allStrings\":true,\"chunked\":true}"
+    );
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_fetch_empty_body() {
+    let mut runner = TestRunner::new_with_ops(ops());
+
+    let script = r#"
+        globalThis.result = null;
+
+        (async () => {
+            const start = performance.now();
+
+            // The body stream closes on the first read
+            const streamed = await fetch('https://echo.workers.rocks/empty');
+            const first = await streamed.body.getReader().read();
+
+            const response = await fetch('https://echo.workers.rocks/empty');
+            const text = await response.text();
+
+            globalThis.result = {
+                status: response.status,
+                text,
+                firstDone: first.done,
+                elapsed: performance.now() - start
+            };
+        })().catch(error => {
+            globalThis.result = { error: String(error) };
+        });
+    "#;
+
+    runner.execute(script).expect("fetch should execute");
+    runner.process_for(Duration::from_millis(300)).await;
+
+    let result = runner
+        .runtime
+        .evaluate("JSON.stringify(globalThis.result)")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    let result: serde_json::Value = serde_json::from_str(&result).expect("Valid JSON");
+
+    assert_eq!(result["status"], 200, "{}", result);
+    assert_eq!(result["text"], "");
+    assert_eq!(result["firstDone"], true);
+    assert!(
+        result["elapsed"].as_f64().unwrap() < 100.0,
+        "empty body took {}ms",
+        result["elapsed"]
+    );
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_drain_completes_started_stream() {
+    let mut runner = TestRunner::new_with_ops(ops());
+
+    let script = r#"
+        globalThis.result = { bytes: 0, done: false };
+
+        fetch('https://echo.workers.rocks/slow-stream')
+            .then(async response => {
+                const reader = response.body.getReader();
+                while (true) {
+                    const { done, value } = await reader.read();
+                    if (done) break;
+                    globalThis.result.bytes += value.length;
+                }
+                globalThis.result.done = true;
+            })
+            .catch(error => {
+                globalThis.result.error = String(error);
+            });
+    "#;
+
+    runner.execute(script).expect("fetch should execute");
+    runner.process_for(Duration::from_millis(80)).await;
+
+    runner.runtime.drain(Duration::from_secs(2));
+
+    // New work is refused once draining
+    runner
+        .execute(
+            r#"
+            fetch('https://echo.workers.rocks/json')
+                .then(() => { globalThis.result.late = 'resolved'; })
+                .catch(error => { globalThis.result.late = String(error); });
+            "#,
+        )
+        .expect("late fetch should execute");
+
+    runner.process_for(Duration::from_millis(500)).await;
+
+    let result = runner
+        .runtime
+        .evaluate("JSON.stringify(globalThis.result)")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    let result: serde_json::Value = serde_json::from_str(&result).expect("Valid JSON");
+
+    assert_eq!(result["bytes"], 1024, "{}", result);
+    assert_eq!(result["done"], true, "{}", result);
+    assert!(
+        result["late"]
+            .as_str()
+            .is_some_and(|late| late.contains("shutting down")),
+        "{}",
+        result
+    );
+    assert!(runner.runtime.is_shut_down());
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_fetch_body_tee_branches_match() {
+    let mut runner = TestRunner::new_with_ops(ops());
+
+    let script = r#"
+        globalThis.result = null;
+
+        const readAll = async (stream) => {
+            const bytes = [];
+            const reader = stream.getReader();
+            while (true) {
+                const { done, value } = await reader.read();
+                if (done) break;
+                bytes.push(...value);
+            }
+            return bytes;
+        };
+
+        fetch('https://echo.workers.rocks/stream')
+            .then(async response => {
+                const [left, right] = response.body.tee();
+
+                // Drain one branch first so chunks must be buffered for the other
+                const first = await readAll(left);
+                const second = await readAll(right);
+
+                globalThis.result = {
+                    left: first.length,
+                    right: second.length,
+                    identical: first.every((byte, i) => byte === second[i]),
+                    lastByte: second[second.length - 1]
+                };
+            })
+            .catch(error => {
+                globalThis.result = { error: String(error) };
+            });
+    "#;
+
+    runner.execute(script).expect("fetch should execute");
+    runner.process_for(Duration::from_millis(300)).await;
+
+    let result = runner
+        .runtime
+        .evaluate("JSON.stringify(globalThis.result)")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+
+    assert_eq!(
+        result,
+        r#"{"left":1024,"right":1024,"identical":true,"lastByte":3}"#
+    );
+
+    runner.shutdown().await;
+}
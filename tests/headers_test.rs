@@ -277,3 +277,115 @@ async fn test_headers_clone_from_headers() {
     let body = response.body.collect().await.expect("Should have body");
     assert_eq!(String::from_utf8_lossy(&body), "OK");
 }
+
+#[tokio::test]
+async fn test_headers_set_cookie_values_kept_apart() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const headers = new Headers();
+            headers.append('Set-Cookie', 'a=1');
+            headers.append('Set-Cookie', 'b=2');
+            headers.append('X-Custom', 'x');
+
+            const copy = new Headers(headers);
+            const replaced = new Headers(headers);
+            replaced.set('set-cookie', 'c=3');
+
+            const result = headers.get('set-cookie') === 'a=1, b=2'
+                && headers.getSetCookie().join('|') === 'a=1|b=2'
+                && copy.getSetCookie().length === 2
+                && replaced.getSetCookie().join('|') === 'c=3'
+                && [...headers.keys()].join() === 'set-cookie,set-cookie,x-custom';
+
+            event.respondWith(new Response(result ? 'OK' : 'FAIL', { headers }));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+
+    // Each Set-Cookie value reaches the embedder as its own header
+    let cookies: Vec<&str> = response
+        .headers
+        .iter()
+        .filter(|(name, _)| name == "set-cookie")
+        .map(|(_, value)| value.as_str())
+        .collect();
+    assert_eq!(cookies, vec!["a=1", "b=2"]);
+
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+/// Test Headers rejects invalid names and values and normalizes value whitespace
+#[tokio::test]
+async fn test_headers_validation() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const throwsTypeError = (f) => {
+                try {
+                    f();
+                    return false;
+                } catch (e) {
+                    return e instanceof TypeError;
+                }
+            };
+
+            const headers = new Headers();
+            const invalid = [
+                () => headers.set('bad name', 'x'),
+                () => headers.set('X', 'a\r\nInjected: 1'),
+                () => headers.append('X', 'a\nb'),
+                () => headers.append('', 'x'),
+                () => headers.append('X-\u00e9', 'x'),
+                () => new Headers({ 'bad:name': 'x' }),
+                () => new Headers([['X', 'a\0b']])
+            ].every(throwsTypeError);
+
+            headers.set('X-Custom', '  padded value\t');
+            headers.append('Accept', 'text/html');
+            headers.append('accept', '\r\n application/json ');
+            const fromObject = new Headers({ 'Content-Type': ' text/plain ' });
+
+            const valid = headers.get('x-custom') === 'padded value'
+                && headers.get('accept') === 'text/html, application/json'
+                && fromObject.get('content-type') === 'text/plain'
+                && !headers.has('injected');
+
+            event.respondWith(new Response(invalid && valid ? 'OK' : `FAIL: ${invalid} ${valid}`));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use common::TestRunner;
+use openworkers_core::{Event, HttpMethod, HttpRequest, RequestBody, RuntimeLimits, Script};
+use openworkers_runtime_jsc::Worker;
+use std::collections::HashMap;
+
+#[tokio::test]
+async fn test_heap_used_grows_with_allocations() {
+    let mut runner = TestRunner::new();
+
+    let script = r#"
+        const before = __heapUsed();
+        globalThis.retained = Array.from({ length: 200000 }, (_, i) => ({ i, label: 'item-' + i }));
+        const after = __heapUsed();
+        const memory = performance.memory;
+
+        JSON.stringify({
+            before,
+            after,
+            used: memory.usedJSHeapSize,
+            total: memory.totalJSHeapSize
+        })
+    "#;
+
+    let result = runner
+        .runtime
+        .evaluate(script)
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    let result: serde_json::Value = serde_json::from_str(&result).expect("Valid JSON");
+
+    let before = result["before"].as_f64().expect("before is a number");
+    let after = result["after"].as_f64().expect("after is a number");
+    let used = result["used"].as_f64().expect("usedJSHeapSize is a number");
+    let total = result["total"]
+        .as_f64()
+        .expect("totalJSHeapSize is a number");
+
+    assert!(before > 0.0);
+    assert!(
+        after > before,
+        "heap should grow: {} -> {} bytes",
+        before,
+        after
+    );
+    assert!(used > 0.0);
+    assert!(total >= used);
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_performance_memory_reports_heap_limit() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const memory = performance.memory;
+            event.respondWith(new Response(JSON.stringify({
+                limit: memory.jsHeapSizeLimit,
+                used: memory.usedJSHeapSize
+            })));
+        });
+    "#;
+
+    let limits = RuntimeLimits {
+        heap_max_mb: 64,
+        ..Default::default()
+    };
+    let mut worker = Worker::new(Script::new(script), Some(limits))
+        .await
+        .expect("Worker should initialize");
+
+    let (task, rx) = Event::fetch(HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    });
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    let result: serde_json::Value = serde_json::from_slice(&body).expect("Valid JSON");
+
+    assert_eq!(result["limit"].as_u64(), Some(64 * 1024 * 1024));
+    assert!(result["used"].as_f64().expect("usedJSHeapSize is a number") > 0.0);
+
+    // Without limits there is no bound to report
+    let mut runner = TestRunner::new();
+    let unbounded = runner
+        .runtime
+        .evaluate("performance.memory.jsHeapSizeLimit === Infinity")
+        .unwrap()
+        .to_bool(&runner.runtime.context);
+    assert!(unbounded);
+
+    runner.shutdown().await;
+}
@@ -0,0 +1,98 @@
+use openworkers_runtime_jsc::{DefaultOps, OperationsHandle, Runtime, run_event_loop};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn spawn_runtime() -> (Runtime, tokio::task::JoinHandle<()>) {
+    let (runtime, scheduler_rx, callback_tx, stream_manager) = Runtime::new();
+    let ops: OperationsHandle = Arc::new(DefaultOps);
+
+    let handle = tokio::spawn(async move {
+        run_event_loop(scheduler_rx, callback_tx, stream_manager, ops).await;
+    });
+
+    (runtime, handle)
+}
+
+#[tokio::test]
+async fn test_idle_timeout_stops_event_loop() {
+    let (runtime, handle) = spawn_runtime();
+    runtime.set_idle_timeout(Some(Duration::from_millis(50)));
+
+    // Activity keeps the loop alive past the timeout
+    for _ in 0..5 {
+        tokio::time::sleep(Duration::from_millis(25)).await;
+        runtime.record_activity();
+    }
+    assert!(!handle.is_finished(), "Activity should restart the timeout");
+    assert!(!runtime.is_shut_down());
+
+    // Then it exits once idle
+    tokio::time::timeout(Duration::from_secs(1), handle)
+        .await
+        .expect("Event loop should exit after inactivity")
+        .unwrap();
+    assert!(runtime.is_shut_down());
+    drop(runtime);
+
+    // A fresh runtime can be created afterwards
+    let (mut runtime, handle) = spawn_runtime();
+    let value = runtime
+        .evaluate("1 + 1")
+        .unwrap()
+        .to_number(&runtime.context)
+        .unwrap();
+    assert_eq!(value, 2.0);
+    assert!(!runtime.is_shut_down());
+
+    drop(runtime);
+    let _ = tokio::time::timeout(Duration::from_secs(1), handle).await;
+}
+
+#[tokio::test]
+async fn test_idle_timeout_waits_for_pending_timers() {
+    let (mut runtime, handle) = spawn_runtime();
+    runtime.set_idle_timeout(Some(Duration::from_millis(20)));
+
+    runtime
+        .evaluate("setTimeout(() => { globalThis.fired = true; }, 100)")
+        .unwrap();
+
+    // The pending timer is in-flight work: the loop stays up
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert!(
+        !handle.is_finished(),
+        "Pending timers should defer shutdown"
+    );
+
+    tokio::time::timeout(Duration::from_secs(1), handle)
+        .await
+        .expect("Event loop should exit once the timer has fired")
+        .unwrap();
+
+    runtime.process_callbacks();
+    let fired = runtime
+        .evaluate("globalThis.fired === true")
+        .unwrap()
+        .to_bool(&runtime.context);
+    assert!(fired, "Timer should fire before the idle shutdown");
+}
+
+#[tokio::test]
+async fn test_idle_timeout_disabled_by_default() {
+    let (runtime, handle) = spawn_runtime();
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!handle.is_finished());
+
+    runtime.set_idle_timeout(Some(Duration::from_millis(10)));
+    runtime.set_idle_timeout(None);
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(
+        !handle.is_finished(),
+        "None should disable the idle timeout"
+    );
+
+    drop(runtime);
+    let _ = tokio::time::timeout(Duration::from_secs(1), handle).await;
+}
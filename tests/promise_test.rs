@@ -376,3 +376,40 @@ async fn test_microtask_vs_timeout() {
 
     runner.shutdown().await;
 }
+
+#[tokio::test]
+async fn test_poll_once_drives_promise_chain() {
+    let mut runner = TestRunner::new();
+
+    let script = r#"
+        globalThis.chainResult = null;
+
+        new Promise(resolve => setTimeout(() => resolve(1), 10))
+            .then(value => new Promise(resolve => setTimeout(() => resolve(value + 1), 10)))
+            .then(value => value * 10)
+            .then(value => { globalThis.chainResult = value; });
+    "#;
+
+    runner.execute(script).expect("Script should execute");
+
+    // Drive the runtime manually until no work remains
+    let mut iterations = 0;
+    while runner.runtime.poll_once() {
+        iterations += 1;
+        assert!(
+            iterations < 1000,
+            "poll_once should eventually report no work"
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+    }
+
+    let value = runner
+        .runtime
+        .evaluate("globalThis.chainResult")
+        .unwrap()
+        .to_number(&runner.runtime.context)
+        .unwrap();
+    assert_eq!(value, 20.0, "Promise chain should complete");
+
+    runner.shutdown().await;
+}
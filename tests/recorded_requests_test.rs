@@ -0,0 +1,308 @@
+mod common;
+
+use common::{EchoOps, RecordingOps};
+use openworkers_core::{Event, HttpMethod, HttpRequest, RequestBody, Script};
+use openworkers_runtime_jsc::{
+    FetchPolicy, HttpResponse, OpFuture, OperationsHandler, ResponseBody, Worker, WorkerOptions,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Test that the outbound requests made by a handler can be asserted on
+#[tokio::test]
+async fn test_handler_outbound_requests_are_recorded() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            event.respondWith((async () => {
+                const user = await fetch('https://api.example.com/users/1');
+                await user.text();
+
+                const log = await fetch('https://logs.example.com/ingest', {
+                    method: 'POST',
+                    headers: { 'content-type': 'application/json' },
+                    body: JSON.stringify({ event: 'lookup' })
+                });
+
+                return new Response(await log.text());
+            })());
+        });
+    "#;
+
+    let ops = RecordingOps::new(Arc::new(EchoOps { chunk_size: 64 }));
+
+    let mut worker = Worker::new_with_ops(Script::new(script), None, ops.clone())
+        .await
+        .expect("Worker should initialize");
+
+    let (task, rx) = Event::fetch(HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    });
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), r#"{"event":"lookup"}"#);
+
+    let requests = ops.recorded_requests();
+    let calls: Vec<(&str, &str)> = requests
+        .iter()
+        .map(|r| (r.method.as_str(), r.url.as_str()))
+        .collect();
+    assert_eq!(
+        calls,
+        vec![
+            ("GET", "https://api.example.com/users/1"),
+            ("POST", "https://logs.example.com/ingest"),
+        ]
+    );
+
+    assert_eq!(
+        requests[1].headers.get("content-type").map(String::as_str),
+        Some("application/json")
+    );
+    assert_eq!(
+        requests[1].body.as_deref(),
+        Some(br#"{"event":"lookup"}"#.as_slice())
+    );
+}
+
+/// Test that fetches without an Accept header get the default one
+#[tokio::test]
+async fn test_outbound_fetch_gets_default_accept() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            event.respondWith((async () => {
+                await (await fetch('https://api.example.com/plain')).text();
+                await (await fetch('https://api.example.com/json', {
+                    headers: { 'Accept': 'application/json' }
+                })).text();
+                return new Response('ok');
+            })());
+        });
+    "#;
+
+    let accept = |requests: &[common::RecordedRequest], i: usize| {
+        requests[i]
+            .headers
+            .iter()
+            .filter(|(k, _)| k.eq_ignore_ascii_case("accept"))
+            .map(|(_, v)| v.clone())
+            .collect::<Vec<_>>()
+    };
+
+    let ops = RecordingOps::new(Arc::new(EchoOps { chunk_size: 64 }));
+    let mut worker = Worker::new_with_ops(Script::new(script), None, ops.clone())
+        .await
+        .expect("Worker should initialize");
+
+    let (task, rx) = Event::fetch(HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    });
+    worker.exec(task).await.expect("Task should execute");
+    rx.await.expect("Should receive response");
+
+    let requests = ops.recorded_requests();
+    assert_eq!(accept(&requests, 0), vec!["*/*"]);
+    assert_eq!(accept(&requests, 1), vec!["application/json"]);
+
+    // The default is configurable through the fetch policy
+    let ops = RecordingOps::new(Arc::new(EchoOps { chunk_size: 64 }));
+    let policy = FetchPolicy::new().default_accept("text/html");
+    let mut worker = Worker::new_with_policy(Script::new(script), None, ops.clone(), policy)
+        .await
+        .expect("Worker should initialize");
+
+    let (task, rx) = Event::fetch(HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    });
+    worker.exec(task).await.expect("Task should execute");
+    rx.await.expect("Should receive response");
+
+    let requests = ops.recorded_requests();
+    assert_eq!(accept(&requests, 0), vec!["text/html"]);
+    assert_eq!(accept(&requests, 1), vec!["application/json"]);
+}
+
+/// Test that configured trace headers are copied onto subrequests
+#[tokio::test]
+async fn test_trace_headers_propagate_to_subrequests() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            event.respondWith((async () => {
+                await (await fetch('https://api.example.com/users/1')).text();
+                await (await fetch('https://api.example.com/users/2', {
+                    headers: { 'X-Request-Id': 'overridden' }
+                })).text();
+                return new Response('ok');
+            })());
+        });
+    "#;
+
+    let ops = RecordingOps::new(Arc::new(EchoOps { chunk_size: 64 }));
+    let options = WorkerOptions::new()
+        .propagate_header("traceparent")
+        .propagate_header("x-request-id");
+    let mut worker = Worker::new_with_options(Script::new(script), None, ops.clone(), options)
+        .await
+        .expect("Worker should initialize");
+
+    let traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+    let mut headers = HashMap::new();
+    headers.insert("traceparent".to_string(), traceparent.to_string());
+    headers.insert("x-request-id".to_string(), "req-42".to_string());
+    headers.insert("authorization".to_string(), "Bearer secret".to_string());
+
+    let (task, rx) = Event::fetch(HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers,
+        body: RequestBody::None,
+    });
+    worker.exec(task).await.expect("Task should execute");
+    rx.await.expect("Should receive response");
+
+    let requests = ops.recorded_requests();
+    let header = |i: usize, name: &str| {
+        requests[i]
+            .headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.clone())
+    };
+
+    assert_eq!(header(0, "traceparent").as_deref(), Some(traceparent));
+    assert_eq!(header(0, "x-request-id").as_deref(), Some("req-42"));
+    assert_eq!(header(0, "authorization"), None);
+
+    // Explicit headers win over propagated ones
+    assert_eq!(header(1, "traceparent").as_deref(), Some(traceparent));
+    assert_eq!(header(1, "x-request-id").as_deref(), Some("overridden"));
+}
+
+/// Upstream redirecting /login across origins and /account within its origin
+struct RedirectOps;
+
+impl OperationsHandler for RedirectOps {
+    fn handle_fetch(&self, request: HttpRequest) -> OpFuture<'_, Result<HttpResponse, String>> {
+        Box::pin(async move {
+            let location = match request.url.as_str() {
+                "https://a.example.com/login" => Some("https://b.example.com/welcome"),
+                "https://a.example.com/account" => Some("/profile"),
+                _ => None,
+            };
+
+            Ok(match location {
+                Some(location) => HttpResponse {
+                    status: 302,
+                    headers: vec![("location".to_string(), location.to_string())],
+                    body: ResponseBody::None,
+                },
+                None => HttpResponse {
+                    status: 200,
+                    headers: vec![],
+                    body: ResponseBody::Bytes("ok".into()),
+                },
+            })
+        })
+    }
+}
+
+/// Test that redirects drop credentials across origins and body headers on POST to GET
+#[tokio::test]
+async fn test_redirect_strips_credentials_and_body_headers() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            event.respondWith((async () => {
+                const headers = {
+                    'Authorization': 'Bearer secret',
+                    'Cookie': 'session=1',
+                    'Proxy-Authorization': 'Basic cHJveHk=',
+                    'Content-Type': 'application/json',
+                    'X-Trace': 'abc'
+                };
+
+                await (await fetch('https://a.example.com/login', {
+                    method: 'POST',
+                    headers,
+                    body: '{"user":"me"}'
+                })).text();
+                await (await fetch('https://a.example.com/account', { headers })).text();
+
+                return new Response('done');
+            })());
+        });
+    "#;
+
+    let ops = RecordingOps::new(Arc::new(RedirectOps));
+
+    let mut worker = Worker::new_with_ops(Script::new(script), None, ops.clone())
+        .await
+        .expect("Worker should initialize");
+
+    let (task, rx) = Event::fetch(HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    });
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "done");
+
+    let requests = ops.recorded_requests();
+    let calls: Vec<(&str, &str)> = requests
+        .iter()
+        .map(|r| (r.method.as_str(), r.url.as_str()))
+        .collect();
+    assert_eq!(
+        calls,
+        vec![
+            ("POST", "https://a.example.com/login"),
+            ("GET", "https://b.example.com/welcome"),
+            ("GET", "https://a.example.com/account"),
+            ("GET", "https://a.example.com/profile"),
+        ]
+    );
+
+    let has = |request: &common::RecordedRequest, name: &str| {
+        request
+            .headers
+            .keys()
+            .any(|key| key.eq_ignore_ascii_case(name))
+    };
+
+    // Cross-origin POST -> GET: no credentials, no body headers
+    let welcome = &requests[1];
+    for name in [
+        "authorization",
+        "cookie",
+        "proxy-authorization",
+        "content-type",
+        "content-length",
+    ] {
+        assert!(
+            !has(welcome, name),
+            "{} leaked: {:?}",
+            name,
+            welcome.headers
+        );
+    }
+    assert!(has(welcome, "x-trace"), "{:?}", welcome.headers);
+    assert_eq!(welcome.body, None);
+
+    // Same-origin redirect keeps the credentials
+    let profile = &requests[3];
+    assert!(has(profile, "authorization"), "{:?}", profile.headers);
+    assert!(has(profile, "cookie"), "{:?}", profile.headers);
+}
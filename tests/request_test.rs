@@ -285,3 +285,181 @@ async fn test_request_arraybuffer() {
     assert_eq!(result["first"], 1);
     assert_eq!(result["last"], 5);
 }
+
+/// Test Request.formData() with an urlencoded body
+#[tokio::test]
+async fn test_request_form_data_urlencoded() {
+    let script = r#"
+        addEventListener('fetch', async (event) => {
+            const req = new Request('https://example.com/form', {
+                method: 'POST',
+                body: 'name=Jane+Doe&tag=a&tag=b&empty=',
+                headers: { 'Content-Type': 'application/x-www-form-urlencoded' }
+            });
+
+            const form = await req.formData();
+            const ok = form instanceof FormData
+                && form.get('name') === 'Jane Doe'
+                && form.getAll('tag').join(',') === 'a,b'
+                && form.get('empty') === ''
+                && form.has('tag')
+                && !form.has('missing');
+
+            event.respondWith(new Response(ok ? 'OK' : `FAIL: ${JSON.stringify([...form])}`));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+/// Test Request.formData() with a multipart body
+#[tokio::test]
+async fn test_request_form_data_multipart() {
+    let script = r#"
+        addEventListener('fetch', async (event) => {
+            const body = [
+                '--XyZ',
+                'Content-Disposition: form-data; name="first"',
+                '',
+                'hello',
+                '--XyZ',
+                'Content-Disposition: form-data; name="second"',
+                '',
+                'multi',
+                'line',
+                '--XyZ--',
+                ''
+            ].join('\r\n');
+
+            const req = new Request('https://example.com/upload', {
+                method: 'POST',
+                body,
+                headers: { 'Content-Type': 'multipart/form-data; boundary=XyZ' }
+            });
+
+            const form = await req.formData();
+            const ok = form.get('first') === 'hello'
+                && form.get('second') === 'multi\r\nline'
+                && [...form.keys()].join(',') === 'first,second';
+
+            event.respondWith(new Response(ok ? 'OK' : `FAIL: ${JSON.stringify([...form])}`));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+/// Test FormData append/set/delete
+#[tokio::test]
+async fn test_form_data_methods() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const form = new FormData();
+            form.append('a', '1');
+            form.append('a', '2');
+            form.append('b', 3);
+            form.set('a', 'x');
+            form.delete('missing');
+
+            const ok = form.getAll('a').join(',') === 'x'
+                && form.get('b') === '3'
+                && form.get('missing') === null
+                && JSON.stringify([...form.entries()]) === '[["a","x"],["b","3"]]';
+
+            form.delete('b');
+
+            event.respondWith(new Response(ok && !form.has('b') ? 'OK' : 'FAIL'));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+/// Test normalizedPathname on the incoming request and on Request objects
+#[tokio::test]
+async fn test_request_normalized_pathname() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const local = new Request('https://example.com/x//y/./z/..');
+
+            event.respondWith(new Response(JSON.stringify({
+                incoming: event.request.normalizedPathname,
+                local: local.normalizedPathname
+            })));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/a/./b/../c".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    let result: serde_json::Value =
+        serde_json::from_str(&String::from_utf8_lossy(&body)).expect("Valid JSON");
+
+    assert_eq!(result["incoming"], "/a/c");
+    assert_eq!(result["local"], "/x/y/");
+}
@@ -1,6 +1,9 @@
-use openworkers_core::{Event, HttpMethod, HttpRequest, RequestBody, ResponseBody, Script};
-use openworkers_runtime_jsc::Worker;
+use openworkers_core::{
+    DefaultOps, Event, HttpMethod, HttpRequest, RequestBody, ResponseBody, Script,
+};
+use openworkers_runtime_jsc::{Worker, WorkerOptions};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 #[tokio::test]
 async fn test_response_body_is_readable_stream() {
@@ -341,3 +344,391 @@ async fn test_response_read_body_stream() {
     let body = response.body.collect().await.expect("Should have body");
     assert_eq!(String::from_utf8_lossy(&body), "OK");
 }
+
+/// Test that Response.redirect validates the URL and keeps path-absolute locations
+#[tokio::test]
+async fn test_response_redirect_validates_url() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            let invalid = null;
+            try {
+                Response.redirect('not a url');
+            } catch (e) {
+                invalid = e instanceof TypeError ? 'TypeError' : String(e);
+            }
+
+            const relative = Response.redirect('/path');
+            const absolute = Response.redirect('https://example.com/new', 301);
+
+            const result = invalid === 'TypeError'
+                && relative.status === 302
+                && relative.headers.get('location') === '/path'
+                && absolute.status === 301
+                && absolute.headers.get('location') === 'https://example.com/new'
+                ? 'OK' : `FAIL: invalid=${invalid} relative=${relative.headers.get('location')}`;
+
+            event.respondWith(new Response(result));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+#[tokio::test]
+async fn test_response_promise_body() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            event.respondWith((async () => {
+                const response = new Response(Promise.resolve('hi'));
+                const request = new Request('https://example.com/', {
+                    method: 'POST',
+                    body: Promise.resolve('there')
+                });
+                return new Response(await response.text() + ' ' + await request.text());
+            })());
+        });
+    "#;
+
+    let options = WorkerOptions::new().promise_bodies(true);
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new_with_options(script_obj, None, Arc::new(DefaultOps), options)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "hi there");
+}
+
+/// Test that reading a clone leaves the original unused and readable
+#[tokio::test]
+async fn test_response_clone_body_used_is_per_instance() {
+    let script = r#"
+        addEventListener('fetch', async (event) => {
+            const original = new Response('Hello', { headers: { 'X-Test': 'a' } });
+            const clone = original.clone();
+
+            const cloneText = await clone.text();
+            const afterClone = [clone.bodyUsed, original.bodyUsed];
+
+            clone.headers.set('X-Test', 'b');
+            const originalText = await original.text();
+
+            const request = new Request('https://example.com/', { method: 'POST', body: 'payload' });
+            const requestClone = request.clone();
+            const requestCloneText = await requestClone.text();
+            const requestUnused = !request.bodyUsed;
+            const requestText = await request.text();
+
+            const ok = cloneText === 'Hello' && afterClone[0] === true && afterClone[1] === false
+                && originalText === 'Hello' && original.bodyUsed && original.headers.get('X-Test') === 'a'
+                && requestCloneText === 'payload' && requestUnused && requestText === 'payload';
+            event.respondWith(new Response(ok ? 'OK' : `FAIL: ${cloneText} ${afterClone} ${originalText} ${requestCloneText} ${requestText}`));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+/// Test a 5MB string body against the JS TextEncoder path
+#[tokio::test]
+async fn test_response_large_string_body_fast_path() {
+    let script = r#"
+        addEventListener('fetch', async (event) => {
+            const big = 'a'.repeat(5 * 1024 * 1024 - 3) + '\u00e9!';
+
+            const encoded = await new Response(new TextEncoder().encode(big)).arrayBuffer();
+            const text = await new Response(big).text();
+            const bytes = await new Response(big).arrayBuffer();
+
+            const ok = text === big && bytes.byteLength === encoded.byteLength
+                && new Uint8Array(bytes)[bytes.byteLength - 2] === 0xA9;
+
+            event.respondWith(new Response(ok ? big : 'FAIL'));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(body.len(), 5 * 1024 * 1024);
+    assert!(body.ends_with("\u{e9}!".as_bytes()));
+}
+
+/// Test that null body statuses drop the body and out-of-range statuses throw
+#[tokio::test]
+async fn test_response_null_body_status() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const describe = (body, status) => {
+                try {
+                    const response = new Response(body, { status });
+                    return `${response.status}/${response.body}`;
+                } catch (e) {
+                    return e.name;
+                }
+            };
+
+            const results = [204, 304, 205, 101].map((status) =>
+                `${status}:${describe('body', status)},${describe('', status)},${describe(null, status)}`
+            );
+
+            event.respondWith(new Response(results.join(' ')));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(
+        String::from_utf8_lossy(&body),
+        "204:204/null,204/null,204/null 304:304/null,304/null,304/null \
+         205:205/null,205/null,205/null 101:RangeError,RangeError,RangeError"
+    );
+}
+
+/// Test Response status validation, statusText and ok
+#[tokio::test]
+async fn test_response_status_validation() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const attempt = (init) => {
+                try {
+                    const response = new Response('x', init);
+                    return `${response.status}:${JSON.stringify(response.statusText)}:${response.ok}`;
+                } catch (e) {
+                    return e.name;
+                }
+            };
+
+            const results = [
+                attempt({ status: 999 }),
+                attempt({ status: 199 }),
+                attempt({ status: 600 }),
+                attempt({ status: 0 }),
+                attempt({ status: 'abc' }),
+                attempt({ status: 404 }),
+                attempt({ status: '201' }),
+                attempt({ status: 299, statusText: 'Fine' }),
+                attempt({ statusText: 'bad\r\nheader' }),
+                attempt(undefined),
+                Response.error().status,
+                Response.error().clone().status
+            ];
+
+            event.respondWith(new Response(results.join(' ')));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(
+        String::from_utf8_lossy(&body),
+        "RangeError RangeError RangeError RangeError RangeError 404:\"\":false \
+         201:\"\":true 299:\"Fine\":true TypeError 200:\"\":true 0 0"
+    );
+}
+
+/// Test Response.blob() and Response.formData()
+#[tokio::test]
+async fn test_response_blob_and_form_data() {
+    let script = r#"
+        addEventListener('fetch', async (event) => {
+            const blobResponse = new Response('héllo', {
+                headers: { 'content-type': 'text/plain;charset=utf-8' }
+            });
+            const blob = await blobResponse.blob();
+            const blobText = await blob.text();
+
+            let reused;
+            try {
+                await blobResponse.blob();
+                reused = 'no error';
+            } catch (e) {
+                reused = e.name;
+            }
+
+            const formResponse = new Response('name=Ada+Lovelace&lang=en&lang=fr', {
+                headers: { 'content-type': 'application/x-www-form-urlencoded' }
+            });
+            const form = await formResponse.formData();
+
+            const result = [
+                blob instanceof Blob,
+                blob.size,
+                blob.type,
+                blobText,
+                reused,
+                form instanceof FormData,
+                form.get('name'),
+                form.getAll('lang').join(','),
+                formResponse.bodyUsed
+            ].join('|');
+
+            event.respondWith(new Response(result));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(
+        String::from_utf8_lossy(&body),
+        "true|6|text/plain;charset=utf-8|héllo|TypeError|true|Ada Lovelace|en,fr|true"
+    );
+}
+
+#[tokio::test]
+async fn test_response_decompress_gzip_body() {
+    let script = r#"
+        addEventListener('fetch', async (event) => {
+            // gzip of 'Hello, decompressed world!'
+            const gzipped = new Uint8Array([
+                31, 139, 8, 0, 0, 0, 0, 0, 2, 3, 243, 72, 205, 201, 201, 215, 81, 72,
+                73, 77, 206, 207, 45, 40, 74, 45, 46, 78, 77, 81, 40, 207, 47, 202,
+                73, 81, 4, 0, 35, 149, 130, 52, 26, 0, 0, 0
+            ]);
+            const headers = { 'content-encoding': 'gzip', 'content-length': '46' };
+
+            const decoded = new Response(gzipped, { headers, decompress: true });
+            const text = await decoded.text();
+
+            const raw = new Response(gzipped, { headers });
+            const rawBytes = new Uint8Array(await raw.arrayBuffer());
+
+            const result = [
+                text,
+                decoded.headers.has('content-encoding'),
+                decoded.headers.has('content-length'),
+                rawBytes.length,
+                raw.headers.get('content-encoding')
+            ].join('|');
+
+            event.respondWith(new Response(result));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(
+        String::from_utf8_lossy(&body),
+        "Hello, decompressed world!|false|false|46|gzip"
+    );
+}
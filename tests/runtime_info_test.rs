@@ -0,0 +1,35 @@
+mod common;
+
+use common::TestRunner;
+
+#[tokio::test]
+async fn test_runtime_info() {
+    let mut runner = TestRunner::new();
+
+    let script = r#"
+        JSON.stringify({
+            name: __runtime.name,
+            version: __runtime.version,
+            engine: __runtime.engine,
+            crypto: __runtime.features.includes('crypto'),
+            frozen: Object.isFrozen(__runtime) && Object.isFrozen(__runtime.features)
+        })
+    "#;
+
+    let result = runner
+        .runtime
+        .evaluate(script)
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    let result: serde_json::Value = serde_json::from_str(&result).expect("Valid JSON");
+
+    assert_eq!(result["name"], env!("CARGO_PKG_NAME"));
+    assert_eq!(result["version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(result["engine"], "JavaScriptCore");
+    assert_eq!(result["crypto"], true);
+    assert_eq!(result["frozen"], true);
+
+    runner.shutdown().await;
+}
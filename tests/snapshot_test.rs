@@ -0,0 +1,62 @@
+use openworkers_runtime_jsc::Runtime;
+use openworkers_runtime_jsc::snapshot::{Snapshot, create_runtime_snapshot};
+
+#[tokio::test]
+async fn test_runtime_from_snapshot() {
+    let output = create_runtime_snapshot().expect("Snapshot should be created");
+    let snapshot = Snapshot::from_bytes(&output.output).expect("Snapshot should load");
+
+    assert!(
+        snapshot
+            .scripts
+            .iter()
+            .any(|script| script.name == "response"),
+        "Snapshot should capture the Response prelude"
+    );
+
+    let (mut runtime, _scheduler_rx, _callback_tx, _stream_manager) =
+        Runtime::from_snapshot(&snapshot).expect("Runtime should restore");
+
+    let script = r#"
+        JSON.stringify({
+            crypto: typeof crypto.getRandomValues,
+            response: typeof Response,
+            fetch: typeof fetch,
+            headers: new Response('hi').headers instanceof Headers
+        })
+    "#;
+
+    let result = runtime
+        .evaluate(script)
+        .unwrap()
+        .to_js_string(&runtime.context)
+        .unwrap()
+        .to_string();
+    let result: serde_json::Value = serde_json::from_str(&result).expect("Valid JSON");
+
+    assert_eq!(result["crypto"], "function");
+    assert_eq!(result["response"], "function");
+    assert_eq!(result["fetch"], "function");
+    assert_eq!(result["headers"], true);
+}
+
+#[test]
+fn test_runtime_from_corrupt_snapshot() {
+    let mut snapshot = Snapshot::capture();
+    snapshot.scripts[0].source = "throw new Error('corrupt prelude');".to_string();
+
+    let err = match Runtime::from_snapshot(&snapshot) {
+        Ok(_) => panic!("Corrupt snapshot should not restore"),
+        Err(err) => err,
+    };
+    assert!(err.contains("corrupt prelude"), "Unexpected error: {}", err);
+}
+
+#[test]
+fn test_snapshot_version_mismatch() {
+    let mut snapshot = Snapshot::capture();
+    snapshot.version = "0.0.0-other".to_string();
+
+    let err = Snapshot::from_bytes(&snapshot.to_bytes()).unwrap_err();
+    assert!(err.contains("does not match"), "Unexpected error: {}", err);
+}
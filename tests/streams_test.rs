@@ -1,5 +1,5 @@
 use openworkers_core::{Event, HttpMethod, HttpRequest, RequestBody, ResponseBody, Script};
-use openworkers_runtime_jsc::Worker;
+use openworkers_runtime_jsc::{Runtime, Worker};
 use std::collections::HashMap;
 
 #[tokio::test]
@@ -124,3 +124,354 @@ async fn test_readable_stream_with_then() {
     let body = response.body.collect().await.expect("Should have body");
     assert_eq!(String::from_utf8_lossy(&body), "OK");
 }
+
+#[tokio::test]
+async fn test_byte_stream_default_reader() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const stream = new ReadableStream({
+                type: 'bytes',
+                start(controller) {
+                    controller.enqueue(new Uint8Array([1, 2, 3]));
+                    controller.close();
+                }
+            });
+
+            const reader = stream.getReader();
+
+            reader.read().then(r1 => {
+                reader.read().then(r2 => {
+                    const ok = r1.value instanceof Uint8Array
+                        && r1.value.join(',') === '1,2,3'
+                        && r2.done;
+                    event.respondWith(new Response(ok ? 'OK' : `FAIL: ${r1.value}, done=${r2.done}`));
+                });
+            });
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+#[tokio::test]
+async fn test_byte_stream_byob_reader() {
+    let script = r#"
+        addEventListener('fetch', async (event) => {
+            const stream = new ReadableStream({
+                type: 'bytes',
+                start(controller) {
+                    controller.enqueue(new Uint8Array([1, 2, 3, 4, 5]));
+                    controller.close();
+                }
+            });
+
+            const reader = stream.getReader({ mode: 'byob' });
+
+            // Buffer smaller than the chunk: the rest stays queued
+            const r1 = await reader.read(new Uint8Array(3));
+            const r2 = await reader.read(new Uint8Array(8));
+            const r3 = await reader.read(new Uint8Array(8));
+
+            const ok = r1.value.join(',') === '1,2,3'
+                && r2.value.join(',') === '4,5'
+                && r3.done
+                && reader instanceof ReadableStreamBYOBReader;
+            event.respondWith(new Response(ok ? 'OK' : `FAIL: ${r1.value} / ${r2.value} / ${r3.done}`));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+#[tokio::test]
+async fn test_byte_stream_byob_request() {
+    let script = r#"
+        addEventListener('fetch', async (event) => {
+            const stream = new ReadableStream({
+                type: 'bytes',
+                pull(controller) {
+                    // Fill the reader's buffer in place
+                    const request = controller.byobRequest;
+                    request.view[0] = 42;
+                    request.view[1] = 43;
+                    request.respond(2);
+                }
+            });
+
+            const reader = stream.getReader({ mode: 'byob' });
+            const { value } = await reader.read(new Uint8Array(4));
+
+            event.respondWith(new Response(value.join(',') === '42,43' ? 'OK' : `FAIL: ${value}`));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+/// Test a Response body whose chunks are enqueued after construction
+#[tokio::test]
+async fn test_response_from_async_stream() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const encoder = new TextEncoder();
+            const stream = new ReadableStream({
+                start(controller) {
+                    setTimeout(() => {
+                        controller.enqueue(encoder.encode('O'));
+                        controller.enqueue('K');
+                        controller.close();
+                    }, 10);
+                }
+            });
+
+            event.respondWith(new Response(stream));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+/// Test that clone() sees chunks enqueued after the clone was made
+#[tokio::test]
+async fn test_response_clone_async_stream() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const encoder = new TextEncoder();
+            const stream = new ReadableStream({
+                start(controller) {
+                    setTimeout(() => {
+                        controller.enqueue(encoder.encode('O'));
+                        controller.enqueue(encoder.encode('K'));
+                        controller.close();
+                    }, 10);
+                }
+            });
+
+            const original = new Response(stream);
+            const copy = original.clone();
+
+            event.respondWith(original.text().then(text => {
+                return text === 'OK' ? copy : new Response(`FAIL: ${text}`);
+            }));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+#[tokio::test]
+async fn test_response_stream_write_rejects_oversized_chunk() {
+    let (mut runtime, _scheduler_rx, _callback_tx, stream_manager) = Runtime::new();
+    stream_manager.set_max_chunk_size(Some(1024));
+
+    let script = r#"
+        const streamId = __responseStreamCreate();
+        JSON.stringify({
+            small: __responseStreamWrite(streamId, new Uint8Array(1024)),
+            oversized: __responseStreamWrite(streamId, new Uint8Array(1025))
+        })
+    "#;
+
+    let result = runtime
+        .evaluate(script)
+        .unwrap()
+        .to_js_string(&runtime.context)
+        .unwrap()
+        .to_string();
+    let result: serde_json::Value = serde_json::from_str(&result).expect("Valid JSON");
+
+    assert_eq!(result["small"], true);
+    assert_eq!(result["oversized"], false);
+}
+
+/// Test that a 64 MiB response streamed from JS in 1 MiB chunks arrives
+/// complete and in order
+///
+/// The body is consumed while the worker runs, so the writer is paced by
+/// backpressure instead of overflowing the response buffer.
+#[tokio::test]
+async fn test_response_stream_64mb_with_backpressure() {
+    const CHUNK_SIZE: usize = 1024 * 1024;
+    const CHUNKS: usize = 64;
+
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const CHUNK_SIZE = 1024 * 1024;
+            const CHUNKS = 64;
+
+            const base = new Uint8Array(CHUNK_SIZE);
+            for (let i = 0; i < CHUNK_SIZE; i++) base[i] = i & 0xff;
+
+            let index = 0;
+            const stream = new ReadableStream({
+                pull(controller) {
+                    if (index === CHUNKS) {
+                        controller.close();
+                        return;
+                    }
+                    const chunk = base.slice();
+                    chunk[0] = index++;
+                    controller.enqueue(chunk);
+                }
+            });
+
+            event.respondWith(new Response(stream));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+
+    let (exec_result, body) = tokio::join!(worker.exec(task), async {
+        let response = rx.await.expect("Should receive response");
+        response.body.collect().await.expect("Should have body")
+    });
+    exec_result.expect("Task should execute");
+
+    assert_eq!(body.len(), CHUNK_SIZE * CHUNKS);
+    for (index, chunk) in body.chunks(CHUNK_SIZE).enumerate() {
+        assert_eq!(chunk[0] as usize, index, "chunk {} out of order", index);
+        assert!(
+            chunk[1..]
+                .iter()
+                .enumerate()
+                .all(|(i, &b)| b == ((i + 1) & 0xff) as u8),
+            "chunk {} corrupted",
+            index
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_response_stream_write_accepts_strings_and_views() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const stream = new ReadableStream({
+                start(controller) {
+                    controller.enqueue('a');
+                    controller.enqueue(new Uint8Array([98]).buffer);
+                    controller.enqueue(new DataView(new Uint8Array([0, 99, 0]).buffer, 1, 1));
+                    controller.enqueue(new Uint8Array([0, 100]).subarray(1));
+                    controller.close();
+                }
+            });
+
+            event.respondWith(new Response(stream));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "abcd");
+}
@@ -173,3 +173,532 @@ async fn test_text_encoder_roundtrip() {
     let body = response.body.collect().await.expect("Should have body");
     assert_eq!(String::from_utf8_lossy(&body), "OK");
 }
+
+#[tokio::test]
+async fn test_text_decoder_utf16le() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const decoder = new TextDecoder('utf-16le');
+            // BOM + "Hi€" + U+1F600 as a surrogate pair
+            const bytes = new Uint8Array([
+                0xFF, 0xFE, 0x48, 0x00, 0x69, 0x00, 0xAC, 0x20, 0x3D, 0xD8, 0x00, 0xDE
+            ]);
+            const text = decoder.decode(bytes);
+
+            const result = text === 'Hi€😀' && decoder.encoding === 'utf-16le'
+                ? 'OK' : `FAIL: ${text}`;
+
+            event.respondWith(new Response(result));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+#[tokio::test]
+async fn test_text_decoder_utf16be() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const decoder = new TextDecoder('utf-16be');
+            const bytes = new Uint8Array([0x00, 0x48, 0x00, 0x69]);
+            const text = decoder.decode(bytes);
+
+            const result = text === 'Hi' ? 'OK' : `FAIL: ${text}`;
+
+            event.respondWith(new Response(result));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+#[tokio::test]
+async fn test_text_decoder_latin1() {
+    let script = r#"
+        // The decoder's lookup table must not claim global names
+        const WINDOWS_1252_HIGH = 'user binding';
+
+        addEventListener('fetch', (event) => {
+            const decoder = new TextDecoder('latin1');
+            // "café" in latin1, then 0x80 which windows-1252 maps to €
+            const bytes = new Uint8Array([0x63, 0x61, 0x66, 0xE9, 0x80]);
+            const text = decoder.decode(bytes);
+
+            const result = text === 'café€' ? 'OK' : `FAIL: ${text}`;
+
+            event.respondWith(new Response(result));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+#[tokio::test]
+async fn test_text_decoder_fatal() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const invalid = new Uint8Array([0x48, 0xC3, 0x28]); // Truncated 2-byte sequence
+
+            // Non-fatal: replacement character
+            const lenient = new TextDecoder().decode(invalid);
+
+            // Fatal: TypeError
+            let fatalError = null;
+            try {
+                new TextDecoder('utf-8', { fatal: true }).decode(invalid);
+            } catch (e) {
+                fatalError = e;
+            }
+
+            const result = lenient === 'H\uFFFD(' && fatalError instanceof TypeError
+                ? 'OK' : `FAIL: ${lenient} ${fatalError}`;
+
+            event.respondWith(new Response(result));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+#[tokio::test]
+async fn test_text_decoder_invalid_label() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            let error = null;
+            try {
+                new TextDecoder('not-an-encoding');
+            } catch (e) {
+                error = e;
+            }
+
+            const result = error instanceof RangeError ? 'OK' : `FAIL: ${error}`;
+
+            event.respondWith(new Response(result));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+#[tokio::test]
+async fn test_text_decoder_ignore_bom() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const bytes = new Uint8Array([0xEF, 0xBB, 0xBF, 0x41]);
+
+            const stripped = new TextDecoder().decode(bytes);
+            const kept = new TextDecoder('utf-8', { ignoreBOM: true }).decode(bytes);
+
+            const result = stripped === 'A' && kept === '\uFEFFA'
+                ? 'OK' : `FAIL: ${stripped.length} ${kept.length}`;
+
+            event.respondWith(new Response(result));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+#[tokio::test]
+async fn test_text_encoder_encode_into_full() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const buffer = new Uint8Array(16);
+            const { read, written } = new TextEncoder().encodeInto('Hello', buffer);
+
+            const result = read === 5 && written === 5
+                && buffer[0] === 72 && buffer[4] === 111 && buffer[5] === 0
+                ? 'OK' : `FAIL: read=${read} written=${written}`;
+
+            event.respondWith(new Response(result));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+#[tokio::test]
+async fn test_text_encoder_encode_into_partial() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const buffer = new Uint8Array(3);
+            const { read, written } = new TextEncoder().encodeInto('Hello', buffer);
+
+            const result = read === 3 && written === 3
+                && new TextDecoder().decode(buffer) === 'Hel'
+                ? 'OK' : `FAIL: read=${read} written=${written}`;
+
+            event.respondWith(new Response(result));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+#[tokio::test]
+async fn test_text_encoder_encode_into_multibyte_boundary() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            // 'a' (1 byte) + '€' (3 bytes) + '😀' (4 bytes, 2 UTF-16 units)
+            const encoder = new TextEncoder();
+
+            // Room for 'a' and 2 of the 3 bytes of '€': stop before '€'
+            const small = new Uint8Array(3);
+            const r1 = encoder.encodeInto('a€😀', small);
+
+            // Room for 'a€' and 3 of the 4 bytes of '😀': stop before '😀'
+            const medium = new Uint8Array(7);
+            const r2 = encoder.encodeInto('a€😀', medium);
+
+            // Exactly enough
+            const exact = new Uint8Array(8);
+            const r3 = encoder.encodeInto('a€😀', exact);
+
+            const result = r1.read === 1 && r1.written === 1
+                && r2.read === 2 && r2.written === 4
+                && r3.read === 4 && r3.written === 8
+                && new TextDecoder().decode(exact) === 'a€😀'
+                ? 'OK' : `FAIL: ${JSON.stringify([r1, r2, r3])}`;
+
+            event.respondWith(new Response(result));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+#[tokio::test]
+async fn test_text_encoder_lone_surrogate() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const encoder = new TextEncoder();
+            const lone = encoder.encode('a\uD800b');
+            const lowOnly = encoder.encode('\uDC00');
+            const pair = encoder.encode('\uD83D\uDE00');
+
+            const result = lone.join(',') === '97,239,191,189,98'
+                && lowOnly.join(',') === '239,191,189'
+                && pair.join(',') === '240,159,152,128'
+                ? 'OK' : `FAIL: ${lone.join(',')} / ${lowOnly.join(',')} / ${pair.join(',')}`;
+
+            event.respondWith(new Response(result));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+#[tokio::test]
+async fn test_text_encoder_bom() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const encoder = new TextEncoder();
+            const plain = encoder.encode('hi');
+            const withBom = encoder.encodeWithBom('hi');
+
+            const result = plain.join(',') === '104,105'
+                && withBom.join(',') === '239,187,191,104,105'
+                && new TextDecoder().decode(withBom) === 'hi'
+                ? 'OK' : `FAIL: ${plain.join(',')} / ${withBom.join(',')}`;
+
+            event.respondWith(new Response(result));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+/// Test TextDecoder normalizes labels and exposes read-only attributes
+#[tokio::test]
+async fn test_text_decoder_label_normalization() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const decoder = new TextDecoder('UTF8');
+            const padded = new TextDecoder('  Latin1\n');
+            const options = new TextDecoder('utf-16', { fatal: true, ignoreBOM: true });
+
+            // Assignments to read-only attributes are ignored
+            decoder.encoding = 'utf-16le';
+            decoder.fatal = true;
+            const encoder = new TextEncoder();
+            encoder.encoding = 'latin1';
+
+            const invalid = ['invalid-label', 'utf-8x', '\u00a0utf-8'].map((label) => {
+                try {
+                    new TextDecoder(label);
+                    return 'none';
+                } catch (e) {
+                    return e.name;
+                }
+            });
+
+            const ok = decoder.encoding === 'utf-8' && decoder.fatal === false && decoder.ignoreBOM === false
+                && padded.encoding === 'windows-1252'
+                && options.encoding === 'utf-16le' && options.fatal === true && options.ignoreBOM === true
+                && encoder.encoding === 'utf-8'
+                && invalid.every((name) => name === 'RangeError');
+            event.respondWith(new Response(ok ? 'OK' : `FAIL: ${decoder.encoding} ${padded.encoding} ${invalid}`));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+/// Test that encode() stringifies non-string input like the USVString conversion
+#[tokio::test]
+async fn test_text_encoder_non_string_input() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const encoder = new TextEncoder();
+            const decode = (bytes) => new TextDecoder().decode(bytes);
+
+            let symbol;
+            try {
+                encoder.encode(Symbol('x'));
+                symbol = 'no error';
+            } catch (e) {
+                symbol = e.name;
+            }
+
+            const results = [
+                encoder.encode().length,
+                encoder.encode(undefined).length,
+                decode(encoder.encode(null)),
+                decode(encoder.encode(0)),
+                decode(encoder.encode(false)),
+                decode(encoder.encode(NaN)),
+                decode(encoder.encode({ toString: () => 'custom' })),
+                decode(encoder.encode([1, 2])),
+                symbol
+            ];
+
+            event.respondWith(new Response(results.join('|')));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let request = HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    };
+
+    let (task, rx) = Event::fetch(request);
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(
+        String::from_utf8_lossy(&body),
+        "0|0|null|0|false|NaN|custom|1,2|TypeError"
+    );
+}
@@ -262,3 +262,300 @@ async fn test_nested_timers() {
 
     runner.shutdown().await;
 }
+
+#[tokio::test(start_paused = true)]
+async fn test_date_now_follows_virtual_time() {
+    let mut runner = TestRunner::new();
+
+    let script = r#"
+        globalThis.start = Date.now();
+        globalThis.observedDelay = null;
+
+        setTimeout(() => {
+            globalThis.observedDelay = Date.now() - globalThis.start;
+        }, 1000);
+    "#;
+
+    runner.execute(script).expect("Script should execute");
+
+    // Let the event loop register the timer, then jump virtual time forward
+    for _ in 0..10 {
+        tokio::task::yield_now().await;
+    }
+    tokio::time::advance(Duration::from_millis(1000)).await;
+
+    let elapsed = runner
+        .runtime
+        .evaluate("Date.now() - globalThis.start")
+        .unwrap()
+        .to_number(&runner.runtime.context)
+        .unwrap();
+    assert_eq!(
+        elapsed, 1000.0,
+        "Date.now() should advance with virtual time"
+    );
+
+    // Let the timer task deliver its callback
+    for _ in 0..10 {
+        tokio::task::yield_now().await;
+    }
+    runner.runtime.process_callbacks();
+
+    let delay = runner
+        .runtime
+        .evaluate("globalThis.observedDelay")
+        .unwrap()
+        .to_number(&runner.runtime.context)
+        .unwrap();
+    assert_eq!(delay, 1000.0, "Timeout should observe exactly its delay");
+
+    runner.shutdown().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_date_constructor_follows_virtual_time() {
+    let mut runner = TestRunner::new();
+
+    runner
+        .execute("globalThis.start = new Date();")
+        .expect("Script should execute");
+
+    tokio::time::advance(Duration::from_millis(1000)).await;
+
+    let result = runner
+        .runtime
+        .evaluate(
+            r#"
+            class Stamp extends Date {}
+            [
+                new Date() - globalThis.start,
+                Date() === new Date(Date.now()).toString(),
+                new Date(0).getTime(),
+                new Date(2020, 0, 1).getFullYear(),
+                globalThis.start instanceof Date,
+                new Stamp() - globalThis.start,
+                Date.prototype.constructor === Date,
+                typeof Date.UTC(2020, 0)
+            ].join(',')
+        "#,
+        )
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    assert_eq!(result, "1000,true,0,2020,true,1000,true,number");
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_setimmediate_runs_after_microtasks() {
+    let mut runner = TestRunner::new();
+
+    let script = r#"
+        globalThis.order = [];
+        setImmediate(() => globalThis.order.push('immediate'));
+        queueMicrotask(() => globalThis.order.push('microtask'));
+        globalThis.order.push('sync');
+    "#;
+
+    runner.execute(script).expect("Script should execute");
+    runner.process_for(Duration::from_millis(50)).await;
+
+    let result = runner
+        .runtime
+        .evaluate("JSON.stringify(globalThis.order)")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    assert_eq!(result, r#"["sync","microtask","immediate"]"#);
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_setimmediate_forwards_arguments() {
+    let mut runner = TestRunner::new();
+
+    let script = r#"
+        globalThis.received = null;
+        setImmediate((a, b) => { globalThis.received = a + b; }, 40, 2);
+    "#;
+
+    runner.execute(script).expect("Script should execute");
+    runner.process_for(Duration::from_millis(50)).await;
+
+    let result = runner
+        .runtime
+        .evaluate("globalThis.received")
+        .unwrap()
+        .to_number(&runner.runtime.context)
+        .unwrap();
+    assert_eq!(result, 42.0);
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_clearimmediate() {
+    let mut runner = TestRunner::new();
+
+    let script = r#"
+        globalThis.fired = false;
+        const id = setImmediate(() => { globalThis.fired = true; });
+        clearImmediate(id);
+    "#;
+
+    runner.execute(script).expect("Script should execute");
+    runner.process_for(Duration::from_millis(50)).await;
+
+    let fired = runner.runtime.evaluate("globalThis.fired").unwrap();
+    assert!(
+        !fired.to_bool(&runner.runtime.context),
+        "Cleared immediate should not fire"
+    );
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_settimeout_forwards_arguments() {
+    let mut runner = TestRunner::new();
+
+    let script = r#"
+        globalThis.sum = 0;
+        setTimeout((a, b) => globalThis.sum = a + b, 10, 2, 3);
+    "#;
+
+    runner.execute(script).expect("Script should execute");
+    runner.process_for(Duration::from_millis(50)).await;
+
+    let sum = runner
+        .runtime
+        .evaluate("globalThis.sum")
+        .unwrap()
+        .to_number(&runner.runtime.context)
+        .unwrap();
+    assert_eq!(sum, 5.0);
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_setinterval_forwards_arguments() {
+    let mut runner = TestRunner::new();
+
+    let script = r#"
+        globalThis.received = [];
+        const id = setInterval((label) => {
+            globalThis.received.push(label);
+            if (globalThis.received.length === 2) {
+                clearInterval(id);
+            }
+        }, 10, 'tick');
+    "#;
+
+    runner.execute(script).expect("Script should execute");
+    runner.process_for(Duration::from_millis(100)).await;
+
+    let result = runner
+        .runtime
+        .evaluate("JSON.stringify(globalThis.received)")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    assert_eq!(result, r#"["tick","tick"]"#);
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_settimeout_invalid_delays_fire_immediately() {
+    let mut runner = TestRunner::new();
+
+    let script = r#"
+        globalThis.fired = [];
+        setTimeout(() => globalThis.fired.push('negative'), -100);
+        setTimeout(() => globalThis.fired.push('nan'), NaN);
+        setTimeout(() => globalThis.fired.push('infinity'), Infinity);
+    "#;
+
+    runner.execute(script).expect("Script should execute");
+    runner.process_for(Duration::from_millis(50)).await;
+
+    let result = runner
+        .runtime
+        .evaluate("JSON.stringify(globalThis.fired.sort())")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+    assert_eq!(result, r#"["infinity","nan","negative"]"#);
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_setinterval_zero_delay() {
+    let mut runner = TestRunner::new();
+
+    let script = r#"
+        globalThis.ticks = 0;
+        const id = setInterval(() => {
+            if (++globalThis.ticks === 3) {
+                clearInterval(id);
+            }
+        }, -1);
+    "#;
+
+    runner.execute(script).expect("Script should execute");
+    runner.process_for(Duration::from_millis(100)).await;
+
+    let ticks = runner
+        .runtime
+        .evaluate("globalThis.ticks")
+        .unwrap()
+        .to_number(&runner.runtime.context)
+        .unwrap();
+    assert_eq!(ticks, 3.0);
+
+    runner.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_reset_cancels_intervals() {
+    let mut runner = TestRunner::new();
+
+    let script = r#"
+        globalThis.ticks = 0;
+        setInterval(() => globalThis.ticks++, 10);
+    "#;
+
+    runner.execute(script).expect("Script should execute");
+    runner.process_for(Duration::from_millis(50)).await;
+
+    let ticks = |runner: &mut TestRunner| {
+        runner
+            .runtime
+            .evaluate("globalThis.ticks")
+            .unwrap()
+            .to_number(&runner.runtime.context)
+            .unwrap()
+    };
+
+    assert!(ticks(&mut runner) > 0.0, "Interval should have ticked");
+
+    runner.runtime.reset();
+    let after_reset = ticks(&mut runner);
+
+    runner.process_for(Duration::from_millis(50)).await;
+    assert_eq!(
+        ticks(&mut runner),
+        after_reset,
+        "Interval should stop after reset"
+    );
+
+    runner.shutdown().await;
+}
@@ -0,0 +1,132 @@
+mod common;
+
+use common::TestRunner;
+use futures::{SinkExt, StreamExt};
+use openworkers_runtime_jsc::DefaultOps;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Start a WebSocket server echoing every text and binary frame back
+async fn spawn_echo_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let Ok(mut ws) = tokio_tungstenite::accept_async(stream).await else {
+                    return;
+                };
+
+                while let Some(Ok(message)) = ws.next().await {
+                    if (message.is_text() || message.is_binary()) && ws.send(message).await.is_err()
+                    {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+
+    format!("ws://{}", addr)
+}
+
+/// Test a text and a binary message echoed back through onmessage
+#[tokio::test]
+async fn test_websocket_echo() {
+    let url = spawn_echo_server().await;
+    let mut runner = TestRunner::new_with_ops(Arc::new(DefaultOps));
+
+    let script = format!(
+        r#"
+        globalThis.result = {{ events: [] }};
+
+        const ws = new WebSocket('{}');
+        ws.binaryType = 'arraybuffer';
+        result.initialState = ws.readyState;
+
+        ws.onopen = () => {{
+            result.events.push('open');
+            ws.send('hello');
+            ws.send(new Uint8Array([1, 2, 3]));
+        }};
+        ws.addEventListener('message', (event) => {{
+            if (typeof event.data === 'string') {{
+                result.text = event.data;
+            }} else {{
+                result.binary = Array.from(new Uint8Array(event.data));
+                ws.close(1000, 'done');
+            }}
+        }});
+        ws.onerror = (event) => result.events.push('error: ' + event.message);
+        ws.onclose = (event) => {{
+            result.events.push('close');
+            result.code = event.code;
+            result.wasClean = event.wasClean;
+            result.finalState = ws.readyState;
+        }};
+    "#,
+        url
+    );
+
+    runner.execute(&script).expect("script should execute");
+    runner.process_for(Duration::from_secs(2)).await;
+
+    let result = runner
+        .runtime
+        .evaluate("JSON.stringify(globalThis.result)")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+
+    assert_eq!(
+        result,
+        r#"{"events":["open","close"],"initialState":0,"text":"hello","binary":[1,2,3],"code":1000,"wasClean":true,"finalState":3}"#
+    );
+
+    runner.shutdown().await;
+}
+
+/// Test invalid URLs and sending before the connection is open
+#[tokio::test]
+async fn test_websocket_invalid_usage() {
+    let mut runner = TestRunner::new();
+
+    let script = r#"
+        const errorName = (fn) => {
+            try { fn(); return 'no error'; } catch (e) { return e.name; }
+        };
+
+        const ws = new WebSocket('http://127.0.0.1:9/socket');
+        globalThis.result = [
+            errorName(() => new WebSocket('ftp://example.com/')),
+            errorName(() => new WebSocket('wss://example.com/#fragment')),
+            errorName(() => new WebSocket('wss://example.com/', ['chat', 'chat'])),
+            errorName(() => ws.send('too early')),
+            errorName(() => ws.close(1001)),
+            ws.url,
+            WebSocket.OPEN,
+            ws instanceof EventTarget,
+        ].join(',');
+    "#;
+
+    runner.execute(script).expect("script should execute");
+
+    let result = runner
+        .runtime
+        .evaluate("globalThis.result")
+        .unwrap()
+        .to_js_string(&runner.runtime.context)
+        .unwrap()
+        .to_string();
+
+    assert_eq!(
+        result,
+        "SyntaxError,SyntaxError,SyntaxError,InvalidStateError,InvalidAccessError,ws://127.0.0.1:9/socket,1,true"
+    );
+
+    runner.shutdown().await;
+}
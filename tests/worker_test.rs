@@ -0,0 +1,1074 @@
+use openworkers_core::{
+    DefaultOps, Event, HttpMethod, HttpRequest, HttpResponse, RequestBody, ResponseBody, Script,
+    TaskInit, TaskSource, TerminationReason,
+};
+use openworkers_runtime_jsc::{Worker, WorkerOptions};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot};
+
+fn get_request() -> HttpRequest {
+    HttpRequest {
+        method: HttpMethod::Get,
+        url: "https://example.com/".to_string(),
+        headers: HashMap::new(),
+        body: RequestBody::None,
+    }
+}
+
+/// Test that event.waitUntil keeps the worker running after the response
+#[tokio::test]
+async fn test_fetch_wait_until() {
+    let script = r#"
+        globalThis.backgroundDone = false;
+
+        addEventListener('fetch', (event) => {
+            event.waitUntil(new Promise((resolve) => {
+                setTimeout(() => {
+                    globalThis.backgroundDone = true;
+                    resolve();
+                }, 50);
+            }));
+
+            event.respondWith(new Response('OK'));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let (task, rx) = Event::fetch(get_request());
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+
+    let done = worker
+        .evaluate("globalThis.backgroundDone")
+        .expect("Should evaluate");
+    assert!(
+        done.to_bool(worker.context()),
+        "waitUntil work should complete before exec returns"
+    );
+}
+
+/// Test that waitUntil work runs until the wall time, not a fixed polling budget
+#[tokio::test]
+async fn test_fetch_wait_until_bounded_by_wall_time() {
+    let script = r#"
+        globalThis.backgroundDone = false;
+
+        addEventListener('fetch', (event) => {
+            event.waitUntil(new Promise((resolve) => {
+                setTimeout(() => {
+                    globalThis.backgroundDone = true;
+                    resolve();
+                }, 6000);
+            }));
+
+            event.respondWith(new Response('OK'));
+        });
+    "#;
+
+    let options = WorkerOptions::new().wall_time(std::time::Duration::from_secs(10));
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new_with_options(script_obj, None, Arc::new(DefaultOps), options)
+        .await
+        .expect("Worker should initialize");
+
+    let (task, rx) = Event::fetch(get_request());
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    assert_eq!(response.status, 200);
+
+    let done = worker
+        .evaluate("globalThis.backgroundDone")
+        .expect("Should evaluate");
+    assert!(
+        done.to_bool(worker.context()),
+        "waitUntil work within the wall time should complete before exec returns"
+    );
+}
+
+/// Test that a rejected waitUntil promise does not fail the request
+#[tokio::test]
+async fn test_fetch_wait_until_rejection() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            event.waitUntil(Promise.reject(new Error('background failure')));
+            event.respondWith(new Response('OK'));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let (task, rx) = Event::fetch(get_request());
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    assert_eq!(response.status, 200);
+}
+
+/// Test that console output is capped per request with a single warning
+#[tokio::test]
+async fn test_console_log_cap() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            for (let i = 0; i < 100; i++) {
+                console.log('line', i);
+            }
+            event.respondWith(new Response('OK'));
+        });
+    "#;
+
+    let (log_tx, mut log_rx) = mpsc::unbounded_channel();
+    let options = WorkerOptions::new().max_log_events(10).log_tx(log_tx);
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new_with_options(script_obj, None, Arc::new(DefaultOps), options)
+        .await
+        .expect("Worker should initialize");
+
+    let (task, rx) = Event::fetch(get_request());
+    worker.exec(task).await.expect("Task should execute");
+    rx.await.expect("Should receive response");
+
+    let mut messages = Vec::new();
+    while let Ok(msg) = log_rx.try_recv() {
+        messages.push(msg);
+    }
+
+    assert_eq!(messages.len(), 11, "Expected cap (10) plus one warning");
+    assert_eq!(messages[0].message, "line 0");
+    assert_eq!(messages[9].message, "line 9");
+    assert_eq!(messages[10].message, "log rate exceeded");
+    assert_eq!(messages[10].level, log::Level::Warn);
+
+    // The cap resets for the next request
+    let (task, rx) = Event::fetch(get_request());
+    worker.exec(task).await.expect("Task should execute");
+    rx.await.expect("Should receive response");
+
+    let mut count = 0;
+    while log_rx.try_recv().is_ok() {
+        count += 1;
+    }
+    assert_eq!(count, 11);
+}
+
+/// Test that every fetch listener runs and the first respondWith wins
+#[tokio::test]
+async fn test_multiple_fetch_listeners() {
+    let script = r#"
+        globalThis.calls = [];
+
+        addEventListener('fetch', (event) => {
+            globalThis.calls.push('first');
+            event.respondWith(new Response('OK'));
+        });
+
+        addEventListener('fetch', (event) => {
+            globalThis.calls.push('second');
+            event.respondWith(new Response('SECOND'));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let (task, rx) = Event::fetch(get_request());
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+
+    let calls = worker
+        .evaluate("globalThis.calls.join(',')")
+        .expect("Should evaluate")
+        .to_js_string(worker.context())
+        .unwrap()
+        .to_string();
+    assert_eq!(calls, "first,second");
+}
+
+/// Test that removeEventListener unregisters a handler before dispatch
+#[tokio::test]
+async fn test_remove_event_listener() {
+    let script = r#"
+        const removed = (event) => {
+            event.respondWith(new Response('REMOVED'));
+        };
+
+        addEventListener('fetch', removed);
+        addEventListener('fetch', (event) => {
+            event.respondWith(new Response('OK'));
+        });
+        removeEventListener('fetch', removed);
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let (task, rx) = Event::fetch(get_request());
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+/// Test a module worker (`export default { fetch }`) with env and ctx.waitUntil
+#[tokio::test]
+async fn test_module_worker_fetch() {
+    let script = r#"
+        globalThis.backgroundDone = false;
+
+        export const greeting = 'OK';
+
+        export default {
+            async fetch(request, env, ctx) {
+                ctx.waitUntil(new Promise((resolve) => {
+                    setTimeout(() => {
+                        globalThis.backgroundDone = true;
+                        resolve();
+                    }, 10);
+                }));
+
+                return new Response(greeting, {
+                    headers: { 'x-url': request.url }
+                });
+            }
+        };
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let (task, rx) = Event::fetch(get_request());
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    assert_eq!(response.status, 200);
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+
+    let done = worker
+        .evaluate("globalThis.backgroundDone")
+        .expect("Should evaluate");
+    assert!(done.to_bool(worker.context()));
+}
+
+/// Run a module worker's fetch handler and return the response body
+async fn module_fetch_text(script: &str) -> String {
+    let mut worker = Worker::new(Script::new(script), None)
+        .await
+        .expect("Worker should initialize");
+
+    let (task, rx) = Event::fetch(get_request());
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    String::from_utf8_lossy(&body).to_string()
+}
+
+/// Test that `export default` in strings, templates, regexes and comments is
+/// left alone, with the handler exported as `export { handler as default }`
+#[tokio::test]
+async fn test_module_worker_export_default_in_literals() {
+    let script = r#"
+        // export default { fetch() { return new Response('comment'); } }
+        /* export default {} */
+        const quoted = "export default {}";
+        const template = `
+export default ${'{'} fetch }`;
+        const pattern = /export default/;
+
+        const handler = {
+            fetch() {
+                const text = [quoted, template.trim(), pattern.source].join('|');
+                return new Response(text);
+            }
+        };
+
+        export { handler as default };
+    "#;
+
+    assert_eq!(
+        module_fetch_text(script).await,
+        "export default {}|export default { fetch }|export default"
+    );
+}
+
+/// Test a multi-line `export default async function` declaration
+#[tokio::test]
+async fn test_module_worker_default_async_function() {
+    let script = r#"
+        export default async function
+            handler(request) {
+            return new Response(handler.name);
+        }
+
+        // The exported function is the module object: give it a fetch handler
+        handler.fetch = handler;
+    "#;
+
+    assert_eq!(module_fetch_text(script).await, "handler");
+}
+
+/// Test a multi-line `export default class` declaration
+#[tokio::test]
+async fn test_module_worker_default_class() {
+    let script = r#"
+        export default class
+            Handler {
+            static async fetch(request) {
+                return new Response(`class ${Handler.name}`);
+            }
+        }
+    "#;
+
+    assert_eq!(module_fetch_text(script).await, "class Handler");
+}
+
+/// Test a module scheduled handler (`export default { scheduled }`) with ctx.waitUntil
+#[tokio::test]
+async fn test_module_worker_scheduled() {
+    let script = r#"
+        globalThis.scheduledTime = null;
+        globalThis.deferredDone = false;
+
+        export default {
+            async scheduled(event, env, ctx) {
+                globalThis.scheduledTime = event.scheduledTime;
+
+                ctx.waitUntil(new Promise((resolve) => {
+                    setTimeout(() => {
+                        globalThis.deferredDone = true;
+                        resolve();
+                    }, 10);
+                }));
+            }
+        };
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let (res_tx, res_rx) = oneshot::channel();
+    let event = Event::Task(Some(TaskInit {
+        task_id: "cron-1".to_string(),
+        payload: None,
+        source: Some(TaskSource::Schedule {
+            time: 1_700_000_000_000,
+        }),
+        attempt: 1,
+        res_tx,
+    }));
+    worker.exec(event).await.expect("Task should execute");
+
+    let result = res_rx.await.expect("Should receive task result");
+    assert!(result.success);
+
+    let time = worker
+        .evaluate("globalThis.scheduledTime")
+        .expect("Should evaluate");
+    assert_eq!(
+        time.to_number(worker.context()).unwrap(),
+        1_700_000_000_000.0
+    );
+
+    let done = worker
+        .evaluate("globalThis.deferredDone")
+        .expect("Should evaluate");
+    assert!(
+        done.to_bool(worker.context()),
+        "ctx.waitUntil work should complete before exec returns"
+    );
+}
+
+/// Test that scheduled events expose the cron expression and scheduled time
+#[tokio::test]
+async fn test_scheduled_event_cron() {
+    let script = r#"
+        addEventListener('task', (event) => {
+            const label = event.cron === '*/5 * * * *' ? 'every-five-minutes' : 'unknown';
+            event.respondWith({
+                success: true,
+                data: { label, scheduledTime: event.scheduledTime }
+            });
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let (res_tx, res_rx) = oneshot::channel();
+    let event = Event::Task(Some(TaskInit {
+        task_id: "cron-2".to_string(),
+        payload: Some(serde_json::json!({ "cron": "*/5 * * * *" })),
+        source: Some(TaskSource::Schedule {
+            time: 1_700_000_000_000,
+        }),
+        attempt: 1,
+        res_tx,
+    }));
+    worker.exec(event).await.expect("Task should execute");
+
+    let result = res_rx.await.expect("Should receive task result");
+    assert!(result.success);
+    assert_eq!(
+        result.data,
+        Some(serde_json::json!({
+            "label": "every-five-minutes",
+            "scheduledTime": 1_700_000_000_000u64
+        }))
+    );
+}
+
+/// Test that Response.error() is surfaced as a 502 instead of status 0
+#[tokio::test]
+async fn test_response_error_maps_to_bad_gateway() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            event.respondWith(Response.error());
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let (task, rx) = Event::fetch(get_request());
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    assert_eq!(response.status, 502);
+    assert!(response.headers.is_empty());
+}
+
+/// Test that fetches past the subrequest cap reject and the cap resets per request
+#[tokio::test]
+async fn test_subrequest_limit() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const fetches = [];
+            for (let i = 0; i < 5; i++) {
+                fetches.push(fetch('https://example.com/' + i));
+            }
+
+            event.respondWith(Promise.allSettled(fetches).then((results) => {
+                const overflow = results.filter((r) =>
+                    r.status === 'rejected' && String(r.reason).includes('Too many subrequests')
+                );
+                return new Response(String(overflow.length));
+            }));
+        });
+    "#;
+
+    let options = WorkerOptions::new().max_subrequests(3);
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new_with_options(script_obj, None, Arc::new(DefaultOps), options)
+        .await
+        .expect("Worker should initialize");
+
+    for _ in 0..2 {
+        let (task, rx) = Event::fetch(get_request());
+        worker.exec(task).await.expect("Task should execute");
+
+        let response = rx.await.expect("Should receive response");
+        let body = response.body.collect().await.expect("Should have body");
+        assert_eq!(String::from_utf8_lossy(&body), "2");
+    }
+}
+
+/// Test that recursive timers stop at the nesting limit while sibling timers are unaffected
+#[tokio::test]
+async fn test_timer_nesting_limit() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            event.respondWith(new Promise((resolve) => {
+                // Ten siblings, all at nesting level 1
+                let siblings = 0;
+                for (let i = 0; i < 10; i++) {
+                    setTimeout(() => siblings++, 0);
+                }
+
+                let depth = 0;
+                let error = null;
+                setTimeout(function recurse() {
+                    depth++;
+                    try {
+                        setTimeout(recurse, 0);
+                    } catch (e) {
+                        error = e.name;
+                    }
+                }, 0);
+
+                setTimeout(() => {
+                    resolve(new Response(JSON.stringify({ siblings, depth, error })));
+                }, 100);
+            }));
+        });
+    "#;
+
+    let options = WorkerOptions::new().max_timer_nesting(5);
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new_with_options(script_obj, None, Arc::new(DefaultOps), options)
+        .await
+        .expect("Worker should initialize");
+
+    let (task, rx) = Event::fetch(get_request());
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(
+        String::from_utf8_lossy(&body),
+        r#"{"siblings":10,"depth":5,"error":"RangeError"}"#
+    );
+}
+
+/// Test that a rejected respondWith promise logs the error type, message and stack
+#[tokio::test]
+async fn test_respond_with_rejection_logs_error() {
+    let script = r#"
+        function failDeep() {
+            throw new TypeError('x');
+        }
+
+        addEventListener('fetch', (event) => {
+            event.respondWith(Promise.resolve().then(failDeep));
+        });
+    "#;
+
+    let (log_tx, mut log_rx) = mpsc::unbounded_channel();
+    let options = WorkerOptions::new().log_tx(log_tx);
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new_with_options(script_obj, None, Arc::new(DefaultOps), options)
+        .await
+        .expect("Worker should initialize");
+
+    let (task, rx) = Event::fetch(get_request());
+    let result = worker.exec(task).await;
+    assert!(
+        matches!(&result, Err(TerminationReason::Exception(msg)) if msg.contains("TypeError: x")),
+        "{:?}",
+        result
+    );
+
+    let response = rx.await.expect("Should receive response");
+    assert_eq!(response.status, 500);
+
+    let mut messages = Vec::new();
+    while let Ok(msg) = log_rx.try_recv() {
+        messages.push(msg);
+    }
+
+    let error = messages
+        .iter()
+        .find(|msg| msg.message.contains("[respondWith] Promise rejected"))
+        .expect("Rejection should be logged");
+    assert_eq!(error.level, log::Level::Error);
+    assert!(error.message.contains("TypeError: x"), "{}", error.message);
+    assert!(error.message.contains("failDeep"), "{}", error.message);
+}
+
+/// Test that a throwing fetch handler is reported through exec() with its message and stack
+#[tokio::test]
+async fn test_fetch_handler_exception_is_returned() {
+    let script = r#"
+        function explode() {
+            throw new Error('handler exploded');
+        }
+
+        addEventListener('fetch', (event) => {
+            explode();
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let (task, rx) = Event::fetch(get_request());
+    let result = worker.exec(task).await;
+
+    match result {
+        Err(TerminationReason::Exception(msg)) => {
+            assert!(msg.contains("Error: handler exploded"), "{}", msg);
+            assert!(msg.contains("explode"), "{}", msg);
+        }
+        other => panic!("Expected an exception, got {:?}", other),
+    }
+
+    // The client still gets a response
+    let response = rx.await.expect("Should receive response");
+    assert_eq!(response.status, 500);
+}
+
+/// Test that a throwing queueMicrotask callback is reported to the host
+#[tokio::test]
+async fn test_queue_microtask_error_is_reported() {
+    let script = r#"
+        addEventListener('error', (event) => {
+            if (event.message === 'handled') {
+                event.preventDefault();
+            }
+        });
+
+        addEventListener('fetch', (event) => {
+            const order = [];
+            queueMicrotask(() => { throw new Error('microtask boom'); });
+            queueMicrotask(() => { throw new Error('handled'); });
+            queueMicrotask(() => order.push('after'));
+
+            event.respondWith(new Promise(resolve => setTimeout(resolve, 0))
+                .then(() => new Response(order.join(','))));
+        });
+    "#;
+
+    let (log_tx, mut log_rx) = mpsc::unbounded_channel();
+    let options = WorkerOptions::new().log_tx(log_tx);
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new_with_options(script_obj, None, Arc::new(DefaultOps), options)
+        .await
+        .expect("Worker should initialize");
+
+    let (task, rx) = Event::fetch(get_request());
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "after");
+
+    let mut messages = Vec::new();
+    while let Ok(msg) = log_rx.try_recv() {
+        messages.push(msg);
+    }
+
+    let error = messages
+        .iter()
+        .find(|msg| msg.message.contains("microtask boom"))
+        .expect("Microtask error should be logged");
+    assert_eq!(error.level, log::Level::Error);
+    assert!(
+        error.message.starts_with("Uncaught Error"),
+        "{}",
+        error.message
+    );
+    assert!(
+        !messages.iter().any(|msg| msg.message.contains("handled")),
+        "prevented errors should not be logged"
+    );
+}
+
+/// Test that the wall time also bounds forwarding a slow response stream
+#[tokio::test]
+async fn test_wall_time_terminates_slow_response_stream() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            // Never-ending stream, one chunk every 20ms
+            const stream = new ReadableStream({
+                async pull(controller) {
+                    await new Promise((resolve) => setTimeout(resolve, 20));
+                    controller.enqueue(new TextEncoder().encode('tick'));
+                }
+            });
+
+            event.respondWith(new Response(stream));
+        });
+    "#;
+
+    let wall_time = std::time::Duration::from_millis(300);
+    let options = WorkerOptions::new().wall_time(wall_time);
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new_with_options(script_obj, None, Arc::new(DefaultOps), options)
+        .await
+        .expect("Worker should initialize");
+
+    let start = std::time::Instant::now();
+    let (task, rx) = Event::fetch(get_request());
+
+    let (exec_result, (chunks, error)) = tokio::join!(worker.exec(task), async {
+        let response = rx.await.expect("Should receive response");
+        let ResponseBody::Stream(mut body) = response.body else {
+            panic!("Expected a streamed body");
+        };
+
+        let mut chunks = 0;
+        let mut error = None;
+        while let Some(item) = body.recv().await {
+            match item {
+                Ok(_) => chunks += 1,
+                Err(e) => {
+                    error = Some(e);
+                    break;
+                }
+            }
+        }
+        (chunks, error)
+    });
+    exec_result.expect("Task should execute");
+
+    let elapsed = start.elapsed();
+    assert!(chunks > 0, "Some chunks should arrive before the deadline");
+    assert_eq!(error.as_deref(), Some("Worker wall time exceeded"));
+    assert!(
+        elapsed >= wall_time && elapsed < std::time::Duration::from_secs(2),
+        "Stream should end at the deadline, took {:?}",
+        elapsed
+    );
+}
+
+/// Test that a response stream still written at the default response
+/// timeout ends with an error, even when the body is only read after exec()
+#[tokio::test(start_paused = true)]
+async fn test_response_stream_terminated_at_response_timeout() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            // Never-ending stream, one chunk every 100ms
+            const stream = new ReadableStream({
+                async pull(controller) {
+                    await new Promise((resolve) => setTimeout(resolve, 100));
+                    controller.enqueue(new TextEncoder().encode('tick'));
+                }
+            });
+
+            event.respondWith(new Response(stream));
+        });
+    "#;
+
+    let mut worker = Worker::new(Script::new(script), None)
+        .await
+        .expect("Worker should initialize");
+
+    let (task, rx) = Event::fetch(get_request());
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let ResponseBody::Stream(mut body) = response.body else {
+        panic!("Expected a streamed body");
+    };
+
+    let mut chunks = 0;
+    let mut error = None;
+    while let Some(item) = body.recv().await {
+        match item {
+            Ok(_) => chunks += 1,
+            Err(e) => {
+                error = Some(e);
+                break;
+            }
+        }
+    }
+
+    assert!(
+        chunks > 0,
+        "Chunks written before the timeout should arrive"
+    );
+    assert_eq!(
+        error.as_deref(),
+        Some("Response stream did not end before the deadline")
+    );
+}
+
+/// Test that a response settling after its request timed out doesn't leak
+/// into the next request on the same worker
+#[tokio::test]
+async fn test_late_response_does_not_leak_into_next_request() {
+    let script = r#"
+        let requests = 0;
+
+        addEventListener('fetch', (event) => {
+            const index = ++requests;
+
+            // The first response settles after the wall time, during the second request
+            const delay = index === 1 ? 300 : 150;
+            event.respondWith(new Promise((resolve) => {
+                setTimeout(() => resolve(new Response('response ' + index)), delay);
+            }));
+        });
+    "#;
+
+    let options = WorkerOptions::new().wall_time(std::time::Duration::from_millis(200));
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new_with_options(script_obj, None, Arc::new(DefaultOps), options)
+        .await
+        .expect("Worker should initialize");
+
+    let (task, _rx) = Event::fetch(get_request());
+    assert!(
+        worker.exec(task).await.is_err(),
+        "First request should time out"
+    );
+
+    let (task, rx) = Event::fetch(get_request());
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "response 2");
+}
+
+/// Test that a wall time longer than the default response timeout is honoured
+#[tokio::test]
+async fn test_wall_time_allows_slow_response() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            event.respondWith(new Promise((resolve) => {
+                setTimeout(() => resolve(new Response('slow')), 6000);
+            }));
+        });
+    "#;
+
+    let options = WorkerOptions::new().wall_time(std::time::Duration::from_secs(10));
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new_with_options(script_obj, None, Arc::new(DefaultOps), options)
+        .await
+        .expect("Worker should initialize");
+
+    let (task, rx) = Event::fetch(get_request());
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "slow");
+}
+
+/// Test that self is globalThis in worker code
+#[tokio::test]
+async fn test_self_aliases_global_this() {
+    let script = r#"
+        self.addEventListener('fetch', (event) => {
+            globalThis.marker = 42;
+            const ok = self === globalThis && self.crypto === crypto && typeof self.addEventListener === 'function'
+                && self.marker === 42 && self.self === self;
+            event.respondWith(new Response(ok ? 'OK' : 'FAIL'));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    let (task, rx) = Event::fetch(get_request());
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "OK");
+}
+
+/// Test that a host reader streams into the worker's response
+#[tokio::test]
+async fn test_response_from_host_reader_stream() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const body = __createNativeStream(globalThis.assetStreamId);
+            event.respondWith(new Response(body, {
+                headers: { 'content-type': 'text/plain' }
+            }));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    // Larger than one read so the body arrives in several chunks
+    let contents: Vec<u8> = (0..200_000u32).map(|i| b'a' + (i % 26) as u8).collect();
+    let stream_id = worker.create_reader_stream(std::io::Cursor::new(contents.clone()));
+    worker
+        .evaluate(&format!("globalThis.assetStreamId = {}", stream_id))
+        .expect("Should set stream id");
+
+    let (task, rx) = Event::fetch(get_request());
+    worker.exec(task).await.expect("Task should execute");
+
+    let response = rx.await.expect("Should receive response");
+    assert_eq!(response.status, 200);
+    assert!(matches!(response.body, ResponseBody::Stream(_)));
+
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(body.len(), contents.len());
+    assert!(body[..] == contents[..]);
+}
+
+/// Test that the response of a slow async handler is delivered as soon as it resolves
+#[tokio::test]
+async fn test_slow_handler_response_is_not_polled() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            event.respondWith(new Promise((resolve) => {
+                setTimeout(() => {
+                    globalThis.resolvedAt = performance.now();
+                    resolve(new Response('slow'));
+                }, 200);
+            }));
+        });
+    "#;
+
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new(script_obj, None)
+        .await
+        .expect("Worker should initialize");
+
+    // Align the JS clock with the host clock
+    let started_js = worker
+        .evaluate("performance.now()")
+        .expect("Should read clock")
+        .to_number(worker.context())
+        .unwrap();
+    let started = std::time::Instant::now();
+
+    let (task, rx) = Event::fetch(get_request());
+    let (result, received) = tokio::join!(worker.exec(task), async {
+        let response = rx.await.expect("Should receive response");
+        (response, started.elapsed())
+    });
+    result.expect("Task should execute");
+
+    let (response, received) = received;
+    assert_eq!(response.status, 200);
+
+    let resolved_at = worker
+        .evaluate("globalThis.resolvedAt")
+        .expect("Should read resolution time")
+        .to_number(worker.context())
+        .unwrap();
+    let resolved = resolved_at - started_js;
+    let latency = received.as_secs_f64() * 1000.0 - resolved;
+
+    assert!(resolved >= 199.0, "resolved after {}ms", resolved);
+    assert!(
+        latency < 5.0,
+        "response took {}ms after the handler resolved",
+        latency
+    );
+
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "slow");
+}
+
+/// Test that streaming still works with a single-chunk response buffer
+#[tokio::test]
+async fn test_response_stream_buffer_size_one() {
+    let script = r#"
+        addEventListener('fetch', (event) => {
+            const encoder = new TextEncoder();
+            let i = 0;
+            const body = new ReadableStream({
+                pull(controller) {
+                    if (i < 20) {
+                        controller.enqueue(encoder.encode('chunk' + i + ';'));
+                        i++;
+                    } else {
+                        controller.close();
+                    }
+                }
+            });
+            event.respondWith(new Response(body));
+        });
+    "#;
+
+    let options = WorkerOptions::new().response_stream_buffer_size(1);
+    let script_obj = Script::new(script);
+    let mut worker = Worker::new_with_options(script_obj, None, Arc::new(DefaultOps), options)
+        .await
+        .expect("Worker should initialize");
+
+    // Read the body while the worker writes it: the buffer only holds one chunk
+    let (task, rx) = Event::fetch(get_request());
+    let (result, body) = tokio::join!(worker.exec(task), async {
+        let response = rx.await.expect("Should receive response");
+        response.body.collect().await.expect("Should have body")
+    });
+    result.expect("Task should execute");
+
+    let expected: String = (0..20).map(|i| format!("chunk{};", i)).collect();
+    assert_eq!(String::from_utf8_lossy(&body), expected);
+
+    // A zero-sized buffer is rejected
+    let options = WorkerOptions::new().response_stream_buffer_size(0);
+    let result =
+        Worker::new_with_options(Script::new(script), None, Arc::new(DefaultOps), options).await;
+    assert!(matches!(result, Err(TerminationReason::Other(_))));
+}
+
+/// Test that a response set from Rust answers the next fetch without the JS handler
+#[tokio::test]
+async fn test_respond_with_from_rust() {
+    let script = r#"
+        globalThis.handled = 0;
+
+        addEventListener('fetch', (event) => {
+            globalThis.handled++;
+            event.respondWith(new Response('from js'));
+        });
+    "#;
+
+    let mut worker = Worker::new(Script::new(script), None)
+        .await
+        .expect("Worker should initialize");
+
+    worker.respond_with(HttpResponse {
+        status: 203,
+        headers: vec![("x-answered-by".to_string(), "host".to_string())],
+        body: ResponseBody::Bytes("from rust".into()),
+    });
+
+    let (task, rx) = Event::fetch(get_request());
+    let result = worker.exec_http(task).await.expect("Task should execute");
+    assert_eq!(result.status, 203);
+    assert_eq!(
+        result.headers,
+        vec![("x-answered-by".to_string(), "host".to_string())]
+    );
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "from rust");
+
+    let handled = worker.evaluate("globalThis.handled").unwrap();
+    assert_eq!(handled.to_number(worker.context()).unwrap(), 0.0);
+
+    // Later events go back to the JS handler
+    let (task, rx) = Event::fetch(get_request());
+    let result = worker.exec_http(task).await.expect("Task should execute");
+    assert_eq!(result.status, 200);
+
+    let response = rx.await.expect("Should receive response");
+    let body = response.body.collect().await.expect("Should have body");
+    assert_eq!(String::from_utf8_lossy(&body), "from js");
+}